@@ -14,10 +14,16 @@ fn main() {
                 exts.enable_hand_tracking();
                 exts
             },
+            api_layers: default(),
+            view_configuration: default(),
             blend_modes: default(),
             backends: default(),
             formats: default(),
+            format_preference: default(),
+            view_format: default(),
             resolutions: default(),
+            sample_count: 1,
+            swapchain_layout: default(),
             synchronous_pipeline_compilation: default(),
         }))
         .add_plugins(bevy_xr_utils::hand_gizmos::HandGizmosPlugin)
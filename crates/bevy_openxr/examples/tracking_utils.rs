@@ -4,7 +4,8 @@ use bevy::prelude::*;
 use bevy_mod_openxr::{action_binding::OxrSendActionBindings, add_xr_plugins};
 use bevy_mod_xr::session::XrSessionCreated;
 use bevy_xr_utils::tracking_utils::{
-    suggest_action_bindings, TrackingUtilitiesPlugin, XrTrackedLeftGrip, XrTrackedLocalFloor, XrTrackedRightGrip, XrTrackedStage, XrTrackedView
+    suggest_action_bindings, TrackingUtilitiesPlugin, XrTrackedLeftAim, XrTrackedLeftGrip,
+    XrTrackedLocalFloor, XrTrackedRightAim, XrTrackedRightGrip, XrTrackedStage, XrTrackedView,
 };
 
 fn main() {
@@ -77,6 +78,25 @@ fn spawn_hands(
         XrTrackedRightGrip,
     );
     let right = cmds.spawn(bundle).id();
+    //aim rays, extending forward from the aim poses
+    let ray_mesh = meshes.add(Cuboid::new(0.005, 0.005, 1.0));
+    let ray_material = materials.add(Color::srgb_u8(255, 255, 144));
+    let rays = [
+        cmds.spawn((SpatialBundle::default(), XrTrackedLeftAim))
+            .id(),
+        cmds.spawn((SpatialBundle::default(), XrTrackedRightAim))
+            .id(),
+    ];
+    for ray in rays {
+        cmds.entity(ray).with_children(|parent| {
+            parent.spawn(PbrBundle {
+                mesh: ray_mesh.clone(),
+                material: ray_material.clone(),
+                transform: Transform::from_xyz(0.0, 0.0, -0.5),
+                ..default()
+            });
+        });
+    }
     //head
 
     let head = cmds
@@ -117,4 +137,5 @@ fn spawn_hands(
 
     cmds.entity(stage)
         .push_children(&[left, right, head, local_floor]);
+    cmds.entity(stage).push_children(&rays);
 }
@@ -8,12 +8,15 @@ use bevy::utils::HashMap;
 use bevy_mod_xr::session::XrSessionCreatedEvent;
 use openxr::sys::ActionSuggestedBinding;
 
+use crate::error::OxrError;
 use crate::resources::OxrInstance;
+use crate::types::Result;
 
 impl Plugin for OxrActionBindingPlugin {
     fn build(&self, app: &mut App) {
         app.add_schedule(Schedule::new(OxrSendActionBindings));
         app.add_event::<OxrSuggestActionBinding>();
+        app.add_event::<OxrSuggestDpadBinding>();
         app.add_systems(
             Update,
             run_action_binding_sugestion.run_if(on_event::<XrSessionCreatedEvent>()),
@@ -28,13 +31,24 @@ pub(crate) fn run_action_binding_sugestion(world: &mut World) {
     world.run_system_once(bind_actions);
 }
 
-fn bind_actions(instance: Res<OxrInstance>, mut actions: EventReader<OxrSuggestActionBinding>) {
+fn bind_actions(
+    instance: Res<OxrInstance>,
+    mut actions: EventReader<OxrSuggestActionBinding>,
+    mut dpads: EventReader<OxrSuggestDpadBinding>,
+) {
     let mut bindings: HashMap<&str, Vec<ActionSuggestedBinding>> = HashMap::new();
     for e in actions.read() {
         bindings.entry(&e.interaction_profile).or_default().extend(
             e.bindings
                 .clone()
                 .into_iter()
+                .filter(|b| match validate_binding_path(&e.interaction_profile, b) {
+                    Ok(()) => true,
+                    Err(err) => {
+                        error!("{} for \"{}\", skipping it", err, e.interaction_profile);
+                        false
+                    }
+                })
                 .filter_map(|b| match instance.string_to_path(&b) {
                     Ok(p) => Some(p),
                     Err(err) => {
@@ -53,6 +67,17 @@ fn bind_actions(instance: Res<OxrInstance>, mut actions: EventReader<OxrSuggestA
                 .collect::<Vec<_>>(),
         );
     }
+    let mut dpad_bindings: HashMap<&str, Vec<(openxr::Path, &OxrSuggestDpadBinding)>> =
+        HashMap::new();
+    for e in dpads.read() {
+        match instance.string_to_path(&e.binding) {
+            Ok(path) => dpad_bindings
+                .entry(&e.interaction_profile)
+                .or_default()
+                .push((path, e)),
+            Err(err) => error!("Unable to convert path: \"{}\"; error: {}", e.binding, err),
+        }
+    }
     use openxr::sys;
     for (profile, bindings) in bindings.iter() {
         let interaction_profile = match instance.string_to_path(profile) {
@@ -66,18 +91,87 @@ fn bind_actions(instance: Res<OxrInstance>, mut actions: EventReader<OxrSuggestA
                 continue;
             }
         };
+        // the modifications point to their haptics, so these must not move until the bindings are suggested
+        let dpads = dpad_bindings.get(profile).map_or(&[][..], Vec::as_slice);
+        let haptics = dpads
+            .iter()
+            .map(|(_, dpad)| {
+                [dpad.settings.on_haptic, dpad.settings.off_haptic]
+                    .map(|haptic| haptic.map(OxrDpadHaptic::to_raw))
+            })
+            .collect::<Vec<_>>();
+        let haptic_ptr = |haptic: &Option<sys::HapticVibration>| {
+            haptic
+                .as_ref()
+                .map_or(ptr::null(), |haptic| haptic as *const _ as _)
+        };
+        let modifications = dpads
+            .iter()
+            .zip(&haptics)
+            .map(|((binding, dpad), [on_haptic, off_haptic])| {
+                sys::InteractionProfileDpadBindingEXT {
+                    ty: sys::InteractionProfileDpadBindingEXT::TYPE,
+                    next: ptr::null(),
+                    binding: *binding,
+                    action_set: dpad.action_set,
+                    force_threshold: dpad.settings.force_threshold,
+                    force_threshold_released: dpad.settings.force_threshold_released,
+                    center_region: dpad.settings.center_region,
+                    wedge_angle: dpad.settings.wedge_angle,
+                    is_sticky: dpad.settings.is_sticky.into(),
+                    on_haptic: haptic_ptr(on_haptic),
+                    off_haptic: haptic_ptr(off_haptic),
+                }
+            })
+            .collect::<Vec<_>>();
+        let modification_ptrs = modifications
+            .iter()
+            .map(|m| m as *const _ as *const sys::BindingModificationBaseHeaderKHR)
+            .collect::<Vec<_>>();
+        let binding_modifications = sys::BindingModificationsKHR {
+            ty: sys::BindingModificationsKHR::TYPE,
+            next: ptr::null(),
+            binding_modification_count: modification_ptrs.len() as u32,
+            binding_modifications: modification_ptrs.as_ptr(),
+        };
+        let next = if modifications.is_empty() {
+            ptr::null()
+        } else {
+            &binding_modifications as *const _ as _
+        };
         // Using the raw way since we want all actions through one event and we can't use the
         // Bindings from the openxr crate since they can't be created from raw actions
-        let info = sys::InteractionProfileSuggestedBinding {
-            ty: sys::InteractionProfileSuggestedBinding::TYPE,
-            next: ptr::null(),
-            interaction_profile,
-            count_suggested_bindings: bindings.len() as u32,
-            suggested_bindings: bindings.as_ptr() as *const _ as _,
+        let suggest = |bindings: &[ActionSuggestedBinding]| {
+            let info = sys::InteractionProfileSuggestedBinding {
+                ty: sys::InteractionProfileSuggestedBinding::TYPE,
+                next,
+                interaction_profile,
+                count_suggested_bindings: bindings.len() as u32,
+                suggested_bindings: bindings.as_ptr() as *const _ as _,
+            };
+            unsafe {
+                (instance.fp().suggest_interaction_profile_bindings)(instance.as_raw(), &info)
+            }
         };
-        match unsafe {
-            (instance.fp().suggest_interaction_profile_bindings)(instance.as_raw(), &info)
-        } {
+        let mut result = suggest(bindings);
+        if result == sys::Result::ERROR_PATH_UNSUPPORTED {
+            // every suggestion replaces the previous one for the profile,
+            // so the supported bindings are found one by one and suggested together afterwards
+            let supported = bindings
+                .iter()
+                .copied()
+                .filter(|binding| {
+                    suggest(std::slice::from_ref(binding)) != sys::Result::ERROR_PATH_UNSUPPORTED
+                })
+                .collect::<Vec<_>>();
+            warn!(
+                "{} suggested bindings are unsupported by \"{}\", their actions stay inactive for it",
+                bindings.len() - supported.len(),
+                profile
+            );
+            result = suggest(&supported);
+        }
+        match result {
             openxr::sys::Result::ERROR_ACTIONSETS_ALREADY_ATTACHED => error!(
                 "Binding Suggested for an Action whith an ActionSet that was already attached!"
             ),
@@ -96,7 +190,326 @@ pub struct OxrSuggestActionBinding {
     pub bindings: Vec<Cow<'static, str>>,
 }
 
+/// Configures how the runtime thresholds the dpad regions of a 2D input for the actions of an action set.
+///
+/// Requires [`XR_EXT_dpad_binding`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#XR_EXT_dpad_binding),
+/// usually sent by [`OxrActionSet::create_dpad_action`](crate::actions::OxrActionSet::create_dpad_action).
+/// Only Send this for Action Sets that were not attached yet!
+#[derive(Event, Clone, Debug)]
+pub struct OxrSuggestDpadBinding {
+    pub action_set: openxr::sys::ActionSet,
+    pub interaction_profile: Cow<'static, str>,
+    /// The path of the 2D input, e.g. `/user/hand/left/input/thumbstick`.
+    pub binding: Cow<'static, str>,
+    pub settings: OxrDpadSettings,
+}
+
+/// How the 2D input is split into dpad regions, the defaults are the ones of the runtime.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OxrDpadSettings {
+    /// The force a trackpad has to be pressed with to activate a region, ignored for thumbsticks.
+    pub force_threshold: f32,
+    /// The force below which an active region of a trackpad is released, must not be above the [`force_threshold`](Self::force_threshold).
+    pub force_threshold_released: f32,
+    /// The radius of the center region, values inside it don't activate any of the directional regions.
+    pub center_region: f32,
+    /// The angle in radians each directional region covers, centered on its direction.
+    pub wedge_angle: f32,
+    /// Keeps the region active while the input stays outside of the center region, even if it moves into another region.
+    pub is_sticky: bool,
+    /// Played when a region is activated.
+    pub on_haptic: Option<OxrDpadHaptic>,
+    /// Played when a region is released.
+    pub off_haptic: Option<OxrDpadHaptic>,
+}
+
+impl Default for OxrDpadSettings {
+    fn default() -> Self {
+        Self {
+            force_threshold: 0.5,
+            force_threshold_released: 0.4,
+            center_region: 0.5,
+            wedge_angle: std::f32::consts::FRAC_PI_2,
+            is_sticky: false,
+            on_haptic: None,
+            off_haptic: None,
+        }
+    }
+}
+
+/// A haptic vibration played by the runtime when a dpad region is activated or released.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OxrDpadHaptic {
+    pub amplitude: f32,
+    pub duration: openxr::Duration,
+    /// The frequency in Hz, [`openxr::FREQUENCY_UNSPECIFIED`] lets the runtime pick one.
+    pub frequency: f32,
+}
+
+impl OxrDpadHaptic {
+    fn to_raw(self) -> openxr::sys::HapticVibration {
+        openxr::sys::HapticVibration {
+            ty: openxr::sys::HapticVibration::TYPE,
+            next: ptr::null(),
+            duration: self.duration,
+            frequency: self.frequency,
+            amplitude: self.amplitude.clamp(0.0, 1.0),
+        }
+    }
+}
+
 pub struct OxrActionBindingPlugin;
 // Maybe use a SystemSet in an XrStartup Schedule?
 #[derive(ScheduleLabel, Hash, Debug, Clone, Copy, PartialEq, Eq)]
 pub struct OxrSendActionBindings;
+
+/// Bindings of an action grouped per interaction profile, usually created with [`oxr_bindings!`](crate::oxr_bindings).
+///
+/// Derefs to the `(profile, path)` pairs expected by [`OxrActionSet::create_action`](crate::actions::OxrActionSet::create_action).
+#[derive(Clone, Debug, Default, Deref)]
+pub struct OxrBindings(Vec<(&'static str, &'static str)>);
+
+impl OxrBindings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `paths` as bindings for the interaction `profile`, e.g. [`profiles::oculus_touch`].
+    pub fn profile(
+        mut self,
+        profile: &'static str,
+        paths: impl IntoIterator<Item = &'static str>,
+    ) -> Self {
+        self.0.extend(paths.into_iter().map(|path| (profile, path)));
+        self
+    }
+
+    /// Checks every path against the standard paths of its interaction profile, see [`validate_binding_path`].
+    pub fn validate(&self) -> Result<()> {
+        self.0
+            .iter()
+            .try_for_each(|(profile, path)| validate_binding_path(profile, path))
+    }
+
+    /// Validates the bindings and converts them to [`openxr::Path`]s, grouped per interaction profile.
+    pub fn to_paths(
+        &self,
+        instance: &OxrInstance,
+    ) -> Result<Vec<(openxr::Path, Vec<openxr::Path>)>> {
+        self.validate()?;
+        let mut profiles: Vec<(&str, Vec<openxr::Path>)> = Vec::new();
+        for (profile, path) in &self.0 {
+            let path = instance.string_to_path(path)?;
+            match profiles.iter_mut().find(|(p, _)| p == profile) {
+                Some((_, paths)) => paths.push(path),
+                None => profiles.push((*profile, vec![path])),
+            }
+        }
+        profiles
+            .into_iter()
+            .map(|(profile, paths)| Ok((instance.string_to_path(profile)?, paths)))
+            .collect()
+    }
+}
+
+/// Creates [`OxrBindings`] from interaction profiles in [`profiles`] and their binding paths.
+///
+/// ```ignore
+/// let bindings = oxr_bindings!(
+///     oculus_touch => ["/user/hand/left/input/trigger/value", "/user/hand/right/input/trigger/value"],
+///     valve_index => ["/user/hand/left/input/trigger/value"],
+/// );
+/// let trigger = set.create_action::<f32>("trigger", "Trigger", &bindings)?;
+/// ```
+///
+/// A misspelled profile fails to compile, the paths are checked with [`validate_binding_path`] when they are suggested.
+#[macro_export]
+macro_rules! oxr_bindings {
+    ($($profile:ident => [$($path:literal),* $(,)?]),* $(,)?) => {
+        $crate::action_binding::OxrBindings::new()
+            $(.profile($crate::action_binding::profiles::$profile, [$($path),*]))*
+    };
+}
+
+/// Paths of the interaction profiles known to [`validate_binding_path`].
+#[allow(non_upper_case_globals)]
+pub mod profiles {
+    pub const khr_simple: &str = "/interaction_profiles/khr/simple_controller";
+    pub const oculus_touch: &str = "/interaction_profiles/oculus/touch_controller";
+    pub const valve_index: &str = "/interaction_profiles/valve/index_controller";
+    pub const htc_vive: &str = "/interaction_profiles/htc/vive_controller";
+    pub const microsoft_motion: &str = "/interaction_profiles/microsoft/motion_controller";
+    pub const eye_gaze: &str = "/interaction_profiles/ext/eye_gaze_interaction";
+}
+
+const HANDS: &[&str] = &["/user/hand/left", "/user/hand/right"];
+const POSES: &[&str] = &[
+    "input/grip/pose",
+    "input/aim/pose",
+    "input/grip_surface/pose",
+    "output/haptic",
+];
+
+/// Identifiers added to 2D inputs by `XR_EXT_dpad_binding`, e.g. `input/thumbstick/dpad_left`.
+const DPAD_IDENTIFIERS: &[&str] = &[
+    "dpad_up",
+    "dpad_down",
+    "dpad_left",
+    "dpad_right",
+    "dpad_center",
+];
+
+/// Vendor suffixes of components added by extensions, e.g. `palm_ext`.
+const EXTENSION_SUFFIXES: &[&str] = &["_ext", "_fb", "_htc", "_meta", "_msft", "_ml", "_bd"];
+
+/// The standard paths of a known interaction profile, each entry applies to all of its top level user paths.
+const KNOWN_PATHS: &[(&str, &[&str], &[&str])] = &[
+    (
+        profiles::khr_simple,
+        HANDS,
+        &["input/select/click", "input/menu/click"],
+    ),
+    (
+        profiles::oculus_touch,
+        HANDS,
+        &[
+            "input/squeeze/value",
+            "input/trigger/value",
+            "input/trigger/touch",
+            "input/thumbstick/x",
+            "input/thumbstick/y",
+            "input/thumbstick/click",
+            "input/thumbstick/touch",
+            "input/thumbrest/touch",
+        ],
+    ),
+    (
+        profiles::oculus_touch,
+        &["/user/hand/left"],
+        &[
+            "input/x/click",
+            "input/x/touch",
+            "input/y/click",
+            "input/y/touch",
+            "input/menu/click",
+        ],
+    ),
+    (
+        profiles::oculus_touch,
+        &["/user/hand/right"],
+        &[
+            "input/a/click",
+            "input/a/touch",
+            "input/b/click",
+            "input/b/touch",
+            "input/system/click",
+        ],
+    ),
+    (
+        profiles::valve_index,
+        HANDS,
+        &[
+            "input/system/click",
+            "input/system/touch",
+            "input/a/click",
+            "input/a/touch",
+            "input/b/click",
+            "input/b/touch",
+            "input/squeeze/value",
+            "input/squeeze/force",
+            "input/trigger/click",
+            "input/trigger/value",
+            "input/trigger/touch",
+            "input/thumbstick/x",
+            "input/thumbstick/y",
+            "input/thumbstick/click",
+            "input/thumbstick/touch",
+            "input/trackpad/x",
+            "input/trackpad/y",
+            "input/trackpad/force",
+            "input/trackpad/touch",
+        ],
+    ),
+    (
+        profiles::htc_vive,
+        HANDS,
+        &[
+            "input/system/click",
+            "input/squeeze/click",
+            "input/menu/click",
+            "input/trigger/click",
+            "input/trigger/value",
+            "input/trackpad/x",
+            "input/trackpad/y",
+            "input/trackpad/click",
+            "input/trackpad/touch",
+        ],
+    ),
+    (
+        profiles::microsoft_motion,
+        HANDS,
+        &[
+            "input/menu/click",
+            "input/squeeze/click",
+            "input/trigger/value",
+            "input/thumbstick/x",
+            "input/thumbstick/y",
+            "input/thumbstick/click",
+            "input/trackpad/x",
+            "input/trackpad/y",
+            "input/trackpad/click",
+            "input/trackpad/touch",
+        ],
+    ),
+    (
+        profiles::eye_gaze,
+        &["/user/eyes_ext"],
+        &["input/gaze_ext/pose"],
+    ),
+];
+
+/// Checks `path` against the standard paths of the interaction `profile`.
+///
+/// Paths of profiles that aren't in [`profiles`] are always accepted, as are components added by extensions,
+/// e.g. `input/palm_ext/pose`. Binding to a component without identifier, e.g. `input/thumbstick`, is allowed
+/// if the profile has an identifier for it, as are the dpad identifiers of those components, e.g. `input/thumbstick/dpad_left`.
+/// Fails with [`OxrError::UnknownBindingPath`] for anything else, which usually is a typo.
+pub fn validate_binding_path(profile: &str, path: &str) -> Result<()> {
+    let mut known_profile = false;
+    for (_, user_paths, components) in KNOWN_PATHS.iter().filter(|(p, ..)| *p == profile) {
+        known_profile = true;
+        let Some(component) = user_paths.iter().find_map(|user_path| {
+            path.strip_prefix(user_path)
+                .and_then(|rest| rest.strip_prefix('/'))
+        }) else {
+            continue;
+        };
+        let has_identifier = |component: &str| {
+            components.iter().any(|known| {
+                known
+                    .strip_prefix(component)
+                    .is_some_and(|identifier| identifier.starts_with('/'))
+            })
+        };
+        let is_known = components.contains(&component) || has_identifier(component);
+        let is_dpad = component
+            .rsplit_once('/')
+            .is_some_and(|(input, identifier)| {
+                DPAD_IDENTIFIERS.contains(&identifier) && has_identifier(input)
+            });
+        let is_extension = component.split('/').nth(1).is_some_and(|name| {
+            EXTENSION_SUFFIXES
+                .iter()
+                .any(|suffix| name.ends_with(suffix))
+        });
+        let is_pose = user_paths == &HANDS && POSES.contains(&component);
+        if is_known || is_dpad || is_pose || is_extension {
+            return Ok(());
+        }
+    }
+    if known_profile {
+        Err(OxrError::UnknownBindingPath(path.to_string()))
+    } else {
+        Ok(())
+    }
+}
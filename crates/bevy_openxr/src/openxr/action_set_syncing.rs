@@ -1,5 +1,13 @@
-use crate::{openxr_session_running, session::OxrSession};
+use crate::{
+    actions::{OxrActionSets, OxrActiveActionSets},
+    error::cvt,
+    exts::OxrEnabledExtensions,
+    openxr_session_running,
+    session::OxrSession,
+};
 use bevy::prelude::*;
+use bevy_mod_xr::session::XrSessionFocused;
+use openxr::sys;
 
 #[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone, Copy)]
 pub struct OxrActionSetSyncSet;
@@ -16,22 +24,96 @@ impl Plugin for OxrActionSyncingPlugin {
     }
 }
 
-fn sync_sets(session: Res<OxrSession>, mut events: EventReader<OxrSyncActionSet>) {
-    let sets = events
-        .read()
-        .map(|v| &v.0)
-        .map(openxr::ActiveActionSet::new)
-        .collect::<Vec<_>>();
+/// Syncs the requested action sets while the session is focused.
+///
+/// The sets are synced once more when the session loses focus, the runtime then deactivates all actions,
+/// so no action stays "pressed" while the app doesn't receive input.
+///
+/// The priorities of the [`OxrActiveActionSets`] are applied if `XR_EXT_active_action_set_priority` is enabled.
+#[allow(clippy::too_many_arguments)]
+fn sync_sets(
+    session: Res<OxrSession>,
+    focused: Res<XrSessionFocused>,
+    exts: Option<Res<OxrEnabledExtensions>>,
+    action_sets: Option<Res<OxrActionSets>>,
+    active_sets: Option<Res<OxrActiveActionSets>>,
+    mut was_focused: Local<bool>,
+    mut events: EventReader<OxrSyncActionSet>,
+) {
+    let mut sets: Vec<&openxr::ActionSet> = vec![];
+    for set in events.read().map(|v| &v.0) {
+        // a set is synced at most once, even if it was requested multiple times
+        if !sets.iter().any(|synced| synced.as_raw() == set.as_raw()) {
+            sets.push(set);
+        }
+    }
 
-    if sets.is_empty() {
+    let lost_focus = std::mem::replace(&mut *was_focused, focused.0) && !focused.0;
+    if sets.is_empty() || !(focused.0 || lost_focus) {
         return;
     }
 
-    if let Err(err) = session.sync_actions(&sets) {
+    let priorities = match (exts, action_sets, active_sets) {
+        (Some(exts), Some(action_sets), Some(active_sets)) if exts.active_action_set_priority() => {
+            active_sets
+                .iter()
+                .filter_map(|active| {
+                    let set = action_sets.get(&active.name)?;
+                    Some(sys::ActiveActionSetPriorityEXT {
+                        action_set: set.as_raw(),
+                        priority_override: active.priority,
+                    })
+                })
+                .filter(|priority| sets.iter().any(|set| set.as_raw() == priority.action_set))
+                .collect()
+        }
+        _ => vec![],
+    };
+
+    if let Err(err) = session.sync_actions_with_priorities(&sets, &priorities) {
         warn!("error while syncing actionsets: {}", err.to_string());
     }
 }
 
+impl OxrSession {
+    /// Syncs `sets` like [`sync_actions`](openxr::Session::sync_actions),
+    /// overriding the priorities the sets were created with by `priorities`.
+    ///
+    /// Requires [`XR_EXT_active_action_set_priority`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#XR_EXT_active_action_set_priority)
+    /// unless `priorities` is empty.
+    pub fn sync_actions_with_priorities(
+        &self,
+        sets: &[&openxr::ActionSet],
+        priorities: &[sys::ActiveActionSetPriorityEXT],
+    ) -> openxr::Result<()> {
+        let active_sets = sets
+            .iter()
+            .map(|set| sys::ActiveActionSet {
+                action_set: set.as_raw(),
+                subaction_path: openxr::Path::NULL,
+            })
+            .collect::<Vec<_>>();
+        let priorities_info = sys::ActiveActionSetPrioritiesEXT {
+            ty: sys::ActiveActionSetPrioritiesEXT::TYPE,
+            next: std::ptr::null(),
+            action_set_priority_count: priorities.len() as u32,
+            action_set_priorities: priorities.as_ptr(),
+        };
+        let info = sys::ActionsSyncInfo {
+            ty: sys::ActionsSyncInfo::TYPE,
+            next: if priorities.is_empty() {
+                std::ptr::null()
+            } else {
+                &priorities_info as *const _ as *const _
+            },
+            count_active_action_sets: active_sets.len() as u32,
+            active_action_sets: active_sets.as_ptr(),
+        };
+        cvt(unsafe { (self.instance().fp().sync_actions)(self.as_raw(), &info) })?;
+        Ok(())
+    }
+}
+
 #[derive(Event, Clone)]
 /// Send this event for every ActionSet you want to attach to the [`OxrSession`] once the Session Status changed to Ready. all requests will
 pub struct OxrSyncActionSet(pub openxr::ActionSet);
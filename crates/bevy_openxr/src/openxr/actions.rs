@@ -0,0 +1,857 @@
+use std::borrow::Cow;
+
+use bevy::{
+    ecs::component::{ComponentHooks, StorageType},
+    prelude::*,
+};
+use bevy_mod_xr::{hands::HandSide, session::XrSessionCreated, types::XrPose};
+
+use crate::{
+    action_binding::{
+        OxrDpadSettings, OxrSendActionBindings, OxrSuggestActionBinding, OxrSuggestDpadBinding,
+    },
+    action_set_attaching::OxrAttachActionSet,
+    action_set_syncing::{OxrActionSetSyncSet, OxrSyncActionSet},
+    exts::OxrEnabledExtensions,
+    helper_traits::ToVec2,
+    openxr_session_running,
+    resources::OxrInstance,
+    session::OxrSession,
+};
+
+/// Manages the lifetime of the [`OxrActionSet`] and [`OxrActionSets`] resources.
+///
+/// Insert an [`OxrActionSet`] during [`Startup`] and spawn entities with [`OxrAction`] components for every action
+/// you want to read, this plugin will suggest the bindings, attach the set and update the [`OxrActionState`]
+/// components every frame.
+/// Apps with multiple modes, e.g. menus and gameplay, can insert [`OxrActionSets`] instead and select the synced sets
+/// with the [`OxrActiveActionSets`].
+pub struct OxrActionsPlugin;
+
+impl Plugin for OxrActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<OxrActiveActionSets>();
+        app.add_systems(
+            OxrSendActionBindings,
+            suggest_bindings.run_if(has_action_sets),
+        )
+        .add_systems(XrSessionCreated, attach_set.run_if(has_action_sets))
+        .add_systems(
+            PreUpdate,
+            (
+                sync_set.before(OxrActionSetSyncSet),
+                (
+                    update_action_states::<bool>,
+                    update_action_states::<f32>,
+                    update_action_states::<Vec2>,
+                    update_action_states::<XrPose>,
+                    process_stick_states.after(update_action_states::<Vec2>),
+                    update_controller_touch_states,
+                    update_dpad_states,
+                )
+                    .after(OxrActionSetSyncSet),
+            )
+                .run_if(openxr_session_running)
+                .run_if(has_action_sets),
+        );
+    }
+}
+
+fn has_action_sets(set: Option<Res<OxrActionSet>>, sets: Option<Res<OxrActionSets>>) -> bool {
+    set.is_some() || sets.is_some()
+}
+
+/// Returns the [`OxrActionSet`] followed by the [`OxrActionSets`].
+fn all_sets<'a>(
+    set: Option<&'a OxrActionSet>,
+    sets: Option<&'a OxrActionSets>,
+) -> impl Iterator<Item = &'a OxrActionSet> {
+    set.into_iter()
+        .chain(sets.into_iter().flat_map(|sets| sets.iter()))
+}
+
+/// Wrapper around an [`openxr::ActionSet`] that also keeps track of the bindings suggested for its actions.
+///
+/// The bindings are suggested when the session is created, so all actions have to be created before that.
+#[derive(Resource, Deref, Clone)]
+pub struct OxrActionSet {
+    #[deref]
+    set: openxr::ActionSet,
+    name: String,
+    bindings: Vec<OxrSuggestActionBinding>,
+    dpad_bindings: Vec<OxrSuggestDpadBinding>,
+}
+
+impl OxrActionSet {
+    /// Creates a new [`OxrActionSet`].
+    ///
+    /// If multiple active sets bind the same input, only the actions of the set with the highest `priority` receive it.
+    ///
+    /// Calls [`create_action_set`](openxr::Instance::create_action_set) internally.
+    pub fn new(
+        instance: &OxrInstance,
+        name: &str,
+        localized_name: &str,
+        priority: u32,
+    ) -> openxr::Result<Self> {
+        Ok(Self {
+            set: instance.create_action_set(name, localized_name, priority)?,
+            name: name.to_owned(),
+            bindings: Vec::new(),
+            dpad_bindings: Vec::new(),
+        })
+    }
+
+    /// Creates an action in this set.
+    ///
+    /// `bindings` is a list of interaction profiles (e.g. `/interaction_profiles/oculus/touch_controller`)
+    /// paired with the input paths (e.g. `/user/hand/right/input/trigger/value`) the action should be bound to.
+    /// The paths are validated when they are suggested to the runtime, invalid paths are logged and skipped.
+    /// Use [`oxr_bindings!`](crate::oxr_bindings) to group the paths per interaction profile.
+    ///
+    /// Calls [`create_action`](openxr::ActionSet::create_action) internally.
+    pub fn create_action<T: OxrActionTy>(
+        &mut self,
+        name: &str,
+        localized_name: &str,
+        bindings: &[(&'static str, &'static str)],
+    ) -> openxr::Result<OxrAction<T>> {
+        let action = self
+            .set
+            .create_action::<T::Raw>(name, localized_name, &[])?;
+        for (profile, path) in bindings {
+            self.bindings.push(OxrSuggestActionBinding {
+                action: action.as_raw(),
+                interaction_profile: Cow::Borrowed(profile),
+                bindings: vec![Cow::Borrowed(path)],
+            });
+        }
+        Ok(OxrAction(action))
+    }
+
+    /// Creates a boolean action that is active while a 2D input is in a dpad `region`, e.g. to snap turn with a thumbstick.
+    ///
+    /// `bindings` pairs interaction profiles with the 2D inputs, e.g. `/user/hand/left/input/thumbstick`.
+    /// If [`dpad_binding`](OxrEnabledExtensions::dpad_binding) is enabled the action is bound to the dpad path of the region,
+    /// e.g. `/user/hand/left/input/thumbstick/dpad_left`, and the runtime applies the `settings`.
+    /// Only the settings of the first dpad action of a set on an input are used, the runtime applies them to all regions.
+    ///
+    /// Otherwise the action is bound to the 2D input and thresholded by [`OxrDpadRegion::contains`] when the actions are synced.
+    /// This fallback only uses the [`center_region`](OxrDpadSettings::center_region) and [`wedge_angle`](OxrDpadSettings::wedge_angle):
+    /// it ignores the force thresholds, so trackpads activate regions when touched instead of when pressed,
+    /// regions aren't sticky and no haptics are played.
+    ///
+    /// ```ignore
+    /// let bindings = oxr_bindings!(oculus_touch => ["/user/hand/right/input/thumbstick"]);
+    /// let turn_left = set.create_dpad_action("turn_left", "Turn Left", OxrDpadRegion::Left, default(), &bindings, &exts)?;
+    /// let turn_right = set.create_dpad_action("turn_right", "Turn Right", OxrDpadRegion::Right, default(), &bindings, &exts)?;
+    /// ```
+    pub fn create_dpad_action(
+        &mut self,
+        name: &str,
+        localized_name: &str,
+        region: OxrDpadRegion,
+        settings: OxrDpadSettings,
+        bindings: &[(&'static str, &'static str)],
+        exts: &OxrEnabledExtensions,
+    ) -> openxr::Result<OxrDpadAction> {
+        if !exts.dpad_binding() {
+            let stick = self.create_action::<Vec2>(name, localized_name, bindings)?;
+            return Ok(OxrDpadAction {
+                region,
+                settings,
+                source: OxrDpadSource::Software(stick),
+            });
+        }
+        let action = self.set.create_action::<bool>(name, localized_name, &[])?;
+        for (profile, path) in bindings {
+            self.bindings.push(OxrSuggestActionBinding {
+                action: action.as_raw(),
+                interaction_profile: Cow::Borrowed(profile),
+                bindings: vec![Cow::Owned(format!("{path}/{}", region.identifier()))],
+            });
+            if !self
+                .dpad_bindings
+                .iter()
+                .any(|dpad| dpad.interaction_profile == *profile && dpad.binding == *path)
+            {
+                self.dpad_bindings.push(OxrSuggestDpadBinding {
+                    action_set: self.set.as_raw(),
+                    interaction_profile: Cow::Borrowed(profile),
+                    binding: Cow::Borrowed(path),
+                    settings,
+                });
+            }
+        }
+        Ok(OxrDpadAction {
+            region,
+            settings,
+            source: OxrDpadSource::Runtime(OxrAction(action)),
+        })
+    }
+
+    /// Returns the name the set was created with.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the bindings that will be suggested for the actions of this set.
+    pub fn bindings(&self) -> &[OxrSuggestActionBinding] {
+        &self.bindings
+    }
+
+    /// Returns the dpad settings that will be suggested for the actions of this set.
+    pub fn dpad_bindings(&self) -> &[OxrSuggestDpadBinding] {
+        &self.dpad_bindings
+    }
+
+    /// Returns the inner [`openxr::ActionSet`].
+    pub fn inner(&self) -> &openxr::ActionSet {
+        &self.set
+    }
+}
+
+/// Action sets that can be switched at runtime with the [`OxrActiveActionSets`], e.g. one each for menus, walking and driving.
+///
+/// All sets are attached to the session when it is created, so they have to be inserted and their actions created before that.
+/// Switching between them only changes which sets are synced, the session doesn't have to be recreated.
+#[derive(Resource, Default, Clone)]
+pub struct OxrActionSets(Vec<OxrActionSet>);
+
+impl OxrActionSets {
+    /// Adds `set`, replacing the set with the same [`name`](OxrActionSet::name).
+    pub fn insert(&mut self, set: OxrActionSet) {
+        self.0.retain(|existing| existing.name != set.name);
+        self.0.push(set);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&OxrActionSet> {
+        self.0.iter().find(|set| set.name == name)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut OxrActionSet> {
+        self.0.iter_mut().find(|set| set.name == name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &OxrActionSet> {
+        self.0.iter()
+    }
+}
+
+/// A set of the [`OxrActionSets`] that is synced every frame.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OxrActiveActionSet {
+    /// The [`name`](OxrActionSet::name) of the set.
+    pub name: Cow<'static, str>,
+    /// Overrides the priority the set was created with, if multiple active sets bind the same input
+    /// only the actions of the set with the highest priority receive it.
+    ///
+    /// Requires [`XR_EXT_active_action_set_priority`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#XR_EXT_active_action_set_priority),
+    /// the priority passed to [`OxrActionSet::new`] is used otherwise.
+    pub priority: u32,
+}
+
+/// Selects which of the [`OxrActionSets`] are synced, sets that aren't active report all their actions as inactive.
+///
+/// The [`OxrActionSet`] resource is always synced.
+#[derive(Resource, Clone, Debug, Default, Deref, DerefMut)]
+pub struct OxrActiveActionSets(pub Vec<OxrActiveActionSet>);
+
+impl OxrActiveActionSets {
+    /// Activates the set with `name`, or changes its priority if it's already active.
+    pub fn activate(&mut self, name: impl Into<Cow<'static, str>>, priority: u32) {
+        let name = name.into();
+        match self.0.iter_mut().find(|set| set.name == name) {
+            Some(set) => set.priority = priority,
+            None => self.0.push(OxrActiveActionSet { name, priority }),
+        }
+    }
+
+    pub fn deactivate(&mut self, name: &str) {
+        self.0.retain(|set| set.name != name);
+    }
+
+    pub fn is_active(&self, name: &str) -> bool {
+        self.0.iter().any(|set| set.name == name)
+    }
+}
+
+/// A typed action, created with [`OxrActionSet::create_action`].
+///
+/// When added to an entity an [`OxrActionState`] is inserted and kept up to date.
+#[derive(Deref, Clone)]
+pub struct OxrAction<T: OxrActionTy>(pub openxr::Action<T::Raw>);
+
+impl<T: OxrActionTy> Component for OxrAction<T> {
+    const STORAGE_TYPE: StorageType = StorageType::Table;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        hooks.on_add(|mut world, entity, _| {
+            world
+                .commands()
+                .entity(entity)
+                .insert(OxrActionState::<T>::default());
+        });
+    }
+}
+
+/// The state of an [`OxrAction`] since the last time the actions were synced.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct OxrActionState<T: OxrActionTy> {
+    pub current_state: T,
+    pub changed_since_last_sync: bool,
+    pub last_change_time: openxr::Time,
+    pub is_active: bool,
+}
+
+impl<T: OxrActionTy> Default for OxrActionState<T> {
+    fn default() -> Self {
+        Self {
+            current_state: T::default(),
+            changed_since_last_sync: false,
+            last_change_time: openxr::Time::from_nanos(0),
+            is_active: false,
+        }
+    }
+}
+
+/// Shapes the state of an `OxrAction<Vec2>` on the same entity, e.g. of a thumbstick or trackpad.
+///
+/// The shaped value is written to the [`OxrActionState`] right after it is updated,
+/// the value reported by the runtime is kept in the [`OxrStickRawState`] inserted together with this.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OxrStickProcessing {
+    /// Values with a length below this are reported as zero, the remaining range is rescaled to start at zero.
+    ///
+    /// The dead zone is radial, so it doesn't favor the cardinal directions like a dead zone per axis would.
+    pub dead_zone: f32,
+    /// Maps the square some runtimes report the values in to the unit circle, so diagonals aren't faster than the axes.
+    pub square_to_circle: bool,
+    /// The length of the value after applying the dead zone is raised to this power,
+    /// values above 1 give more precision for small movements.
+    pub exponent: f32,
+}
+
+impl Default for OxrStickProcessing {
+    fn default() -> Self {
+        Self {
+            dead_zone: 0.15,
+            square_to_circle: false,
+            exponent: 1.0,
+        }
+    }
+}
+
+impl OxrStickProcessing {
+    /// Returns the shaped `value`.
+    pub fn apply(&self, mut value: Vec2) -> Vec2 {
+        if self.square_to_circle {
+            value = Vec2::new(
+                value.x * (1.0 - value.y * value.y / 2.0).max(0.0).sqrt(),
+                value.y * (1.0 - value.x * value.x / 2.0).max(0.0).sqrt(),
+            );
+        }
+        let length = value.length().min(1.0);
+        if length <= self.dead_zone {
+            return Vec2::ZERO;
+        }
+        let scaled = (length - self.dead_zone) / (1.0 - self.dead_zone);
+        value.normalize() * scaled.powf(self.exponent)
+    }
+}
+
+impl Component for OxrStickProcessing {
+    const STORAGE_TYPE: StorageType = StorageType::Table;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        hooks.on_add(|mut world, entity, _| {
+            world
+                .commands()
+                .entity(entity)
+                .insert(OxrStickRawState::default());
+        });
+    }
+}
+
+/// The value of an `OxrAction<Vec2>` as reported by the runtime, before the [`OxrStickProcessing`].
+#[derive(Component, Clone, Copy, Debug, Default, Deref)]
+pub struct OxrStickRawState(pub Vec2);
+
+/// A region of a 2D input, see [`OxrActionSet::create_dpad_action`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OxrDpadRegion {
+    Up,
+    Down,
+    Left,
+    Right,
+    /// The [`center_region`](OxrDpadSettings::center_region), the runtime only supports it for trackpads.
+    Center,
+}
+
+impl OxrDpadRegion {
+    /// The identifier of the region in binding paths, e.g. `dpad_left`.
+    pub fn identifier(self) -> &'static str {
+        match self {
+            OxrDpadRegion::Up => "dpad_up",
+            OxrDpadRegion::Down => "dpad_down",
+            OxrDpadRegion::Left => "dpad_left",
+            OxrDpadRegion::Right => "dpad_right",
+            OxrDpadRegion::Center => "dpad_center",
+        }
+    }
+
+    /// Returns `true` if `value` of the 2D input is in this region, used if the runtime doesn't threshold the input.
+    ///
+    /// The [`center_region`](OxrDpadSettings::center_region) is the dead zone of an [`OxrStickProcessing`],
+    /// so the center is also active while the input is at rest.
+    pub fn contains(self, value: Vec2, settings: &OxrDpadSettings) -> bool {
+        let value = OxrStickProcessing {
+            dead_zone: settings.center_region,
+            ..default()
+        }
+        .apply(value);
+        let direction = match self {
+            OxrDpadRegion::Up => Vec2::Y,
+            OxrDpadRegion::Down => Vec2::NEG_Y,
+            OxrDpadRegion::Left => Vec2::NEG_X,
+            OxrDpadRegion::Right => Vec2::X,
+            OxrDpadRegion::Center => return value == Vec2::ZERO,
+        };
+        value != Vec2::ZERO
+            && value.normalize().dot(direction) >= (settings.wedge_angle / 2.0).cos()
+    }
+}
+
+#[derive(Clone)]
+enum OxrDpadSource {
+    /// Bound to the dpad path of the region and thresholded by the runtime.
+    Runtime(OxrAction<bool>),
+    /// Bound to the 2D input and thresholded by [`OxrDpadRegion::contains`].
+    Software(OxrAction<Vec2>),
+}
+
+/// A boolean action for a dpad region of a 2D input, created with [`OxrActionSet::create_dpad_action`].
+///
+/// When added to an entity an [`OxrActionState<bool>`] is inserted and kept up to date.
+#[derive(Clone)]
+pub struct OxrDpadAction {
+    region: OxrDpadRegion,
+    settings: OxrDpadSettings,
+    source: OxrDpadSource,
+}
+
+impl OxrDpadAction {
+    pub fn region(&self) -> OxrDpadRegion {
+        self.region
+    }
+
+    pub fn settings(&self) -> &OxrDpadSettings {
+        &self.settings
+    }
+
+    /// Returns `true` if the runtime thresholds the input, `false` if the fallback of [`OxrDpadRegion::contains`] is used.
+    pub fn uses_dpad_binding(&self) -> bool {
+        matches!(self.source, OxrDpadSource::Runtime(_))
+    }
+}
+
+impl Component for OxrDpadAction {
+    const STORAGE_TYPE: StorageType = StorageType::Table;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        hooks.on_add(|mut world, entity, _| {
+            world
+                .commands()
+                .entity(entity)
+                .insert(OxrActionState::<bool>::default());
+        });
+    }
+}
+
+const OCULUS_TOUCH_PROFILE: &str = "/interaction_profiles/oculus/touch_controller";
+const VALVE_INDEX_PROFILE: &str = "/interaction_profiles/valve/index_controller";
+
+/// The capacitive touch actions of the controller in one hand, keeps the [`OxrControllerTouchState`] inserted together with this up to date.
+///
+/// The actions are bound for the Oculus Touch and Valve Index controllers,
+/// touch sensors a controller doesn't have are never active, e.g. the thumb rest of Index controllers.
+#[derive(Clone)]
+pub struct OxrControllerTouchActions {
+    pub thumb_rest: OxrAction<bool>,
+    pub trigger: OxrAction<bool>,
+    pub thumbstick: OxrAction<bool>,
+    /// The lower face button, `X` on the left Oculus Touch controller and `A` otherwise.
+    pub primary_button: OxrAction<bool>,
+    /// The upper face button, `Y` on the left Oculus Touch controller and `B` otherwise.
+    pub secondary_button: OxrAction<bool>,
+}
+
+impl OxrControllerTouchActions {
+    /// Creates the touch actions of the controller in `hand` in `set`.
+    pub fn new(set: &mut OxrActionSet, hand: HandSide) -> openxr::Result<Self> {
+        let (side, primary, secondary) = match hand {
+            HandSide::Left => ("left", "x", "y"),
+            HandSide::Right => ("right", "a", "b"),
+        };
+        let mut create = |name: &str,
+                          oculus_touch: Option<&str>,
+                          valve_index: Option<&str>|
+         -> openxr::Result<OxrAction<bool>> {
+            let action = set.set.create_action::<bool>(
+                &format!("{side}_{name}_touch"),
+                &format!("{side} {} touch", name.replace('_', " ")),
+                &[],
+            )?;
+            for (profile, input) in [
+                (OCULUS_TOUCH_PROFILE, oculus_touch),
+                (VALVE_INDEX_PROFILE, valve_index),
+            ] {
+                let Some(input) = input else {
+                    continue;
+                };
+                set.bindings.push(OxrSuggestActionBinding {
+                    action: action.as_raw(),
+                    interaction_profile: Cow::Borrowed(profile),
+                    bindings: vec![Cow::Owned(format!("/user/hand/{side}/input/{input}/touch"))],
+                });
+            }
+            Ok(OxrAction(action))
+        };
+        Ok(Self {
+            thumb_rest: create("thumb_rest", Some("thumbrest"), None)?,
+            trigger: create("trigger", Some("trigger"), Some("trigger"))?,
+            thumbstick: create("thumbstick", Some("thumbstick"), Some("thumbstick"))?,
+            primary_button: create("primary_button", Some(primary), Some("a"))?,
+            secondary_button: create("secondary_button", Some(secondary), Some("b"))?,
+        })
+    }
+}
+
+impl Component for OxrControllerTouchActions {
+    const STORAGE_TYPE: StorageType = StorageType::Table;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        hooks.on_add(|mut world, entity, _| {
+            world
+                .commands()
+                .entity(entity)
+                .insert(OxrControllerTouchState::default());
+        });
+    }
+}
+
+/// Which touch sensors of a controller are touched, updated from the [`OxrControllerTouchActions`] on the same entity.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OxrControllerTouchState {
+    pub thumb_rest: bool,
+    pub trigger: bool,
+    pub thumbstick: bool,
+    pub primary_button: bool,
+    pub secondary_button: bool,
+}
+
+impl OxrControllerTouchState {
+    /// Returns `true` if the thumb touches anything on the face of the controller.
+    pub fn thumb(&self) -> bool {
+        self.thumb_rest || self.thumbstick || self.primary_button || self.secondary_button
+    }
+
+    /// Approximates how far the fingers are curled, e.g. to pose a hand mesh without hand tracking.
+    ///
+    /// `trigger` and `grip` are the values of the trigger and grip (squeeze) actions of the same controller.
+    /// An untouched trigger extends the index finger for pointing, an untouched face extends the thumb for a thumbs up.
+    pub fn finger_curls(&self, trigger: f32, grip: f32) -> OxrFingerCurls {
+        let grip = grip.clamp(0.0, 1.0);
+        OxrFingerCurls {
+            thumb: if self.thumb() { 0.75 } else { 0.0 },
+            index: if self.trigger {
+                0.4 + 0.6 * trigger.clamp(0.0, 1.0)
+            } else {
+                0.0
+            },
+            middle: grip,
+            ring: grip,
+            little: grip,
+        }
+    }
+}
+
+/// How far each finger is curled, from 0 for an extended finger to 1 for a fully curled one.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct OxrFingerCurls {
+    pub thumb: f32,
+    pub index: f32,
+    pub middle: f32,
+    pub ring: f32,
+    pub little: f32,
+}
+
+/// Types that can be used for an [`OxrAction`].
+pub trait OxrActionTy: Copy + Default + Send + Sync + 'static {
+    /// The openxr type used to create the action.
+    type Raw: openxr::ActionTy;
+
+    /// Gets the current state of the action.
+    fn state(
+        action: &openxr::Action<Self::Raw>,
+        session: &OxrSession,
+        subaction_path: openxr::Path,
+    ) -> openxr::Result<OxrActionState<Self>>;
+}
+
+impl OxrActionTy for bool {
+    type Raw = bool;
+
+    fn state(
+        action: &openxr::Action<Self::Raw>,
+        session: &OxrSession,
+        subaction_path: openxr::Path,
+    ) -> openxr::Result<OxrActionState<Self>> {
+        let state = action.state(session, subaction_path)?;
+        Ok(OxrActionState {
+            current_state: state.current_state,
+            changed_since_last_sync: state.changed_since_last_sync,
+            last_change_time: state.last_change_time,
+            is_active: state.is_active,
+        })
+    }
+}
+
+impl OxrActionTy for f32 {
+    type Raw = f32;
+
+    fn state(
+        action: &openxr::Action<Self::Raw>,
+        session: &OxrSession,
+        subaction_path: openxr::Path,
+    ) -> openxr::Result<OxrActionState<Self>> {
+        let state = action.state(session, subaction_path)?;
+        Ok(OxrActionState {
+            current_state: state.current_state,
+            changed_since_last_sync: state.changed_since_last_sync,
+            last_change_time: state.last_change_time,
+            is_active: state.is_active,
+        })
+    }
+}
+
+impl OxrActionTy for Vec2 {
+    type Raw = openxr::Vector2f;
+
+    fn state(
+        action: &openxr::Action<Self::Raw>,
+        session: &OxrSession,
+        subaction_path: openxr::Path,
+    ) -> openxr::Result<OxrActionState<Self>> {
+        let state = action.state(session, subaction_path)?;
+        Ok(OxrActionState {
+            current_state: state.current_state.to_vec2(),
+            changed_since_last_sync: state.changed_since_last_sync,
+            last_change_time: state.last_change_time,
+            is_active: state.is_active,
+        })
+    }
+}
+
+/// Pose actions only report if they are active, use [`create_action_space`](OxrSession::create_action_space)
+/// to locate them.
+impl OxrActionTy for XrPose {
+    type Raw = openxr::Posef;
+
+    fn state(
+        action: &openxr::Action<Self::Raw>,
+        session: &OxrSession,
+        subaction_path: openxr::Path,
+    ) -> openxr::Result<OxrActionState<Self>> {
+        Ok(OxrActionState {
+            is_active: action.is_active(session, subaction_path)?,
+            ..Default::default()
+        })
+    }
+}
+
+/// Marker type for haptic output actions, e.g. bound to `/user/hand/right/output/haptic`.
+///
+/// Haptic actions have no state, use [`apply_haptic_feedback`](OxrSession::apply_haptic_feedback) to trigger them.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Haptic;
+
+impl OxrActionTy for Haptic {
+    type Raw = openxr::Haptic;
+
+    fn state(
+        _action: &openxr::Action<Self::Raw>,
+        _session: &OxrSession,
+        _subaction_path: openxr::Path,
+    ) -> openxr::Result<OxrActionState<Self>> {
+        Ok(OxrActionState::default())
+    }
+}
+
+impl OxrSession {
+    /// Starts a haptic vibration on the devices bound to `action`.
+    ///
+    /// `amplitude` is clamped to `0.0..=1.0`. Use [`openxr::Duration::MIN_HAPTIC`] as the `duration` for a single minimal pulse,
+    /// and [`openxr::FREQUENCY_UNSPECIFIED`] as the `frequency` to let the runtime pick one.
+    ///
+    /// Calls [`apply_feedback`](openxr::Action::apply_feedback) internally.
+    pub fn apply_haptic_feedback(
+        &self,
+        action: &OxrAction<Haptic>,
+        amplitude: f32,
+        duration: openxr::Duration,
+        frequency: f32,
+    ) -> openxr::Result<()> {
+        action.0.apply_feedback(
+            self,
+            openxr::Path::NULL,
+            &openxr::HapticVibration::new()
+                .amplitude(amplitude.clamp(0.0, 1.0))
+                .duration(duration)
+                .frequency(frequency),
+        )
+    }
+
+    /// Stops any haptic vibration on the devices bound to `action`.
+    ///
+    /// Calls [`stop_feedback`](openxr::Action::stop_feedback) internally.
+    pub fn stop_haptic_feedback(&self, action: &OxrAction<Haptic>) -> openxr::Result<()> {
+        action.0.stop_feedback(self, openxr::Path::NULL)
+    }
+
+    /// Returns the current state of an [`OxrAction`].
+    pub fn get_action_state<T: OxrActionTy>(
+        &self,
+        action: &OxrAction<T>,
+    ) -> openxr::Result<OxrActionState<T>> {
+        T::state(&action.0, self, openxr::Path::NULL)
+    }
+}
+
+fn suggest_bindings(
+    set: Option<Res<OxrActionSet>>,
+    sets: Option<Res<OxrActionSets>>,
+    mut bindings: EventWriter<OxrSuggestActionBinding>,
+    mut dpad_bindings: EventWriter<OxrSuggestDpadBinding>,
+) {
+    for set in all_sets(set.as_deref(), sets.as_deref()) {
+        bindings.send_batch(set.bindings.iter().cloned());
+        dpad_bindings.send_batch(set.dpad_bindings.iter().cloned());
+    }
+}
+
+fn attach_set(
+    set: Option<Res<OxrActionSet>>,
+    sets: Option<Res<OxrActionSets>>,
+    mut attach: EventWriter<OxrAttachActionSet>,
+) {
+    // sets can only be attached once, so all of them are attached even if they aren't active yet
+    for set in all_sets(set.as_deref(), sets.as_deref()) {
+        attach.send(OxrAttachActionSet(set.set.clone()));
+    }
+}
+
+fn sync_set(
+    set: Option<Res<OxrActionSet>>,
+    sets: Option<Res<OxrActionSets>>,
+    active: Res<OxrActiveActionSets>,
+    mut sync: EventWriter<OxrSyncActionSet>,
+) {
+    if let Some(set) = set {
+        sync.send(OxrSyncActionSet(set.set.clone()));
+    }
+    let Some(sets) = sets else {
+        return;
+    };
+    for active in active.iter() {
+        match sets.get(&active.name) {
+            Some(set) => {
+                sync.send(OxrSyncActionSet(set.set.clone()));
+            }
+            None => bevy::log::warn_once!("active action set \"{}\" doesn't exist", active.name),
+        }
+    }
+}
+
+fn update_action_states<T: OxrActionTy>(
+    session: Res<OxrSession>,
+    mut query: Query<(&OxrAction<T>, &mut OxrActionState<T>)>,
+) {
+    for (action, mut state) in &mut query {
+        match session.get_action_state(action) {
+            Ok(new_state) => *state = new_state,
+            Err(err) => warn!("error while getting action state: {}", err),
+        }
+    }
+}
+
+fn update_controller_touch_states(
+    session: Res<OxrSession>,
+    mut query: Query<(&OxrControllerTouchActions, &mut OxrControllerTouchState)>,
+) {
+    let touched = |action: &OxrAction<bool>| match session.get_action_state(action) {
+        Ok(state) => state.is_active && state.current_state,
+        Err(err) => {
+            warn!("error while getting touch state: {}", err);
+            false
+        }
+    };
+    for (actions, mut state) in &mut query {
+        *state = OxrControllerTouchState {
+            thumb_rest: touched(&actions.thumb_rest),
+            trigger: touched(&actions.trigger),
+            thumbstick: touched(&actions.thumbstick),
+            primary_button: touched(&actions.primary_button),
+            secondary_button: touched(&actions.secondary_button),
+        };
+    }
+}
+
+fn update_dpad_states(
+    session: Res<OxrSession>,
+    mut query: Query<(&OxrDpadAction, &mut OxrActionState<bool>)>,
+) {
+    for (action, mut state) in &mut query {
+        let new_state = match &action.source {
+            OxrDpadSource::Runtime(dpad) => session.get_action_state(dpad),
+            OxrDpadSource::Software(stick) => session.get_action_state(stick).map(|stick| {
+                let pressed = stick.is_active
+                    && action
+                        .region
+                        .contains(stick.current_state, &action.settings);
+                let changed = pressed != state.current_state;
+                OxrActionState {
+                    current_state: pressed,
+                    changed_since_last_sync: changed,
+                    last_change_time: if changed {
+                        stick.last_change_time
+                    } else {
+                        state.last_change_time
+                    },
+                    is_active: stick.is_active,
+                }
+            }),
+        };
+        match new_state {
+            Ok(new_state) => *state = new_state,
+            Err(err) => warn!("error while getting dpad state: {}", err),
+        }
+    }
+}
+
+fn process_stick_states(
+    mut query: Query<
+        (
+            &OxrStickProcessing,
+            &mut OxrActionState<Vec2>,
+            &mut OxrStickRawState,
+        ),
+        // the state isn't updated if getting it failed, it must not be shaped twice
+        Changed<OxrActionState<Vec2>>,
+    >,
+) {
+    for (processing, mut state, mut raw) in &mut query {
+        raw.0 = state.current_state;
+        state.current_state = processing.apply(raw.0);
+    }
+}
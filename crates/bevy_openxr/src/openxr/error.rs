@@ -2,6 +2,10 @@ use std::borrow::Cow;
 use std::fmt;
 
 use super::graphics::GraphicsBackend;
+use super::types::{OxrFrameLoopState, OxrGraphicsAdapter};
+
+use bevy::math::UVec2;
+use openxr::EnvironmentBlendMode;
 
 use thiserror::Error;
 
@@ -19,20 +23,66 @@ pub enum OxrError {
     WgpuRequestDeviceError(#[from] wgpu::RequestDeviceError),
     #[error("Unsupported texture format: {0:?}")]
     UnsupportedTextureFormat(wgpu::TextureFormat),
+    #[error("Texture format {format:?} doesn't support the usage {usage:?}")]
+    UnsupportedTextureUsage {
+        usage: wgpu::TextureUsages,
+        format: wgpu::TextureFormat,
+    },
+    #[error("Swapchain usage {usage:?} requires the usage flags {required:?}, but the swapchain is created with {usage_flags:?}")]
+    MissingSwapchainUsageFlags {
+        usage: wgpu::TextureUsages,
+        required: openxr::SwapchainUsageFlags,
+        usage_flags: openxr::SwapchainUsageFlags,
+    },
+    #[error("Invalid swapchain create info: {0}")]
+    InvalidSwapchainCreateInfo(Cow<'static, str>),
+    #[error("Unknown binding path: {0}")]
+    UnknownBindingPath(String),
+    #[error("Invalid passthrough mesh: {0}")]
+    InvalidPassthroughMesh(Cow<'static, str>),
     #[error("Graphics backend '{0:?}' is not available")]
     UnavailableBackend(GraphicsBackend),
     #[error("No compatible backend available")]
     NoAvailableBackend,
     #[error("No compatible view configuration available")]
     NoAvailableViewConfiguration,
+    #[error("Unsupported view configuration: {0:?}")]
+    UnsupportedViewConfiguration(openxr::ViewConfigurationType),
     #[error("No compatible blend mode available")]
     NoAvailableBlendMode,
+    #[error("None of the requested blend modes {requested:?} are available, available blend modes: {available:?}")]
+    NoMatchingBlendMode {
+        requested: Vec<EnvironmentBlendMode>,
+        available: Vec<EnvironmentBlendMode>,
+    },
     #[error("No compatible format available")]
     NoAvailableFormat,
+    #[error("None of the requested swapchain formats {requested:?} are available, available formats: {available:?}")]
+    NoMatchingSwapchainFormat {
+        requested: Vec<wgpu::TextureFormat>,
+        available: Vec<wgpu::TextureFormat>,
+    },
+    #[error("None of the requested resolutions {requested:?} are supported, recommended resolutions: {available:?}")]
+    NoMatchingResolution {
+        requested: Vec<UVec2>,
+        available: Vec<UVec2>,
+    },
+    #[error("Swapchain '{swapchain}' failed: {source}")]
+    SwapchainSetError {
+        swapchain: Cow<'static, str>,
+        source: Box<OxrError>,
+    },
+    #[error("Unsupported display refresh rate: {0}Hz")]
+    UnsupportedRefreshRate(f32),
     #[error("OpenXR runtime does not support these extensions: {0}")]
     UnavailableExtensions(UnavailableExts),
     #[error("Could not meet graphics requirements for platform. See console for details")]
     FailedGraphicsRequirements,
+    #[error("The OpenXR runtime requires the adapter {required:?}, but WGPU picked '{adapter}'")]
+    GraphicsAdapterMismatch {
+        required: OxrGraphicsAdapter,
+        adapter: String,
+    },
     #[error(
         "Tried to use item {item} with backend {backend}. Expected backend {expected_backend}"
     )]
@@ -41,6 +91,20 @@ pub enum OxrError {
         backend: &'static str,
         expected_backend: &'static str,
     },
+    #[error("Called {call} in the wrong order, the frame loop is {state:?}")]
+    FrameLoopOutOfOrder {
+        call: &'static str,
+        state: OxrFrameLoopState,
+    },
+    #[error("Timed out waiting for a swapchain image")]
+    SwapchainWaitTimedOut,
+    #[error("Headless sessions have no graphics, so they can't create swapchains")]
+    HeadlessSessionNoGraphics,
+    #[error("Pose prediction offset of {offset:?} exceeds the maximum of {max:?}")]
+    PredictionOffsetOutOfRange {
+        offset: std::time::Duration,
+        max: std::time::Duration,
+    },
     #[error("Failed to create CString: {0}")]
     NulError(#[from] std::ffi::NulError),
     #[error("Graphics init error: {0}")]
@@ -63,6 +127,8 @@ mod init_error {
         VulkanLoadingError(ash::LoadingError),
         #[cfg(all(feature = "d3d12", windows))]
         FailedToFindD3D12Adapter,
+        #[cfg(all(feature = "metal", target_os = "macos"))]
+        FailedToFindMetalDevice,
     }
 
     impl fmt::Display for InitError {
@@ -79,6 +145,11 @@ mod init_error {
                     f,
                     "Failed to find D3D12 adapter matching LUID provided by the OpenXR runtime"
                 ),
+                #[cfg(all(feature = "metal", target_os = "macos"))]
+                InitError::FailedToFindMetalDevice => write!(
+                    f,
+                    "Failed to find Metal device matching the device provided by the OpenXR runtime"
+                ),
             }
         }
     }
@@ -115,3 +186,12 @@ impl fmt::Display for UnavailableExts {
         Ok(())
     }
 }
+
+/// Converts the result of a raw OpenXR call into an [`openxr::Result`], keeping success codes like `TIMEOUT_EXPIRED`.
+pub(crate) fn cvt(x: openxr::sys::Result) -> openxr::Result<openxr::sys::Result> {
+    if x.into_raw() >= 0 {
+        Ok(x)
+    } else {
+        Err(x)
+    }
+}
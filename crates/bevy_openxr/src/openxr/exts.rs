@@ -1,8 +1,102 @@
 use bevy::{ecs::system::Resource, prelude::{Deref, DerefMut}};
 use openxr::ExtensionSet;
 
+/// The extensions enabled on the [`OxrInstance`](crate::resources::OxrInstance), inserted into the main and render world right after the instance is created.
+///
+/// Plugins should check this instead of querying the runtime, e.g. in [`Plugin::build`](bevy::app::Plugin::build).
 #[derive(Clone, Debug, Eq, PartialEq, Deref, DerefMut, Resource)]
 pub struct OxrEnabledExtensions(pub OxrExtensions);
+impl OxrEnabledExtensions {
+    /// `XR_EXT_hand_tracking`
+    pub fn hand_tracking(&self) -> bool {
+        self.ext_hand_tracking
+    }
+    /// `XR_EXT_hand_tracking_data_source` together with `XR_EXT_hand_tracking`
+    pub fn hand_tracking_data_source(&self) -> bool {
+        self.ext_hand_tracking && self.ext_hand_tracking_data_source
+    }
+    /// `XR_FB_passthrough`
+    pub fn passthrough(&self) -> bool {
+        self.fb_passthrough
+    }
+    /// All extensions needed by the [`OxrFoveationPlugin`](crate::features::foveation::OxrFoveationPlugin).
+    pub fn foveation(&self) -> bool {
+        self.fb_foveation && self.fb_foveation_configuration && self.fb_swapchain_update_state
+    }
+    /// `XR_EXT_eye_gaze_interaction`
+    pub fn eye_gaze(&self) -> bool {
+        self.ext_eye_gaze_interaction
+    }
+    /// `XR_MSFT_controller_model`
+    pub fn controller_model(&self) -> bool {
+        self.msft_controller_model
+    }
+    /// All extensions needed by the [`OxrSecondaryViewPlugin`](crate::features::secondary_view::OxrSecondaryViewPlugin).
+    pub fn secondary_view(&self) -> bool {
+        self.msft_secondary_view_configuration && self.msft_first_person_observer
+    }
+    /// `XR_KHR_visibility_mask`
+    pub fn visibility_mask(&self) -> bool {
+        self.khr_visibility_mask
+    }
+    /// `XR_MSFT_spatial_anchor`
+    pub fn spatial_anchor(&self) -> bool {
+        self.msft_spatial_anchor
+    }
+    /// `XR_EXTX_overlay`
+    pub fn overlay(&self) -> bool {
+        self.contains("XR_EXTX_overlay")
+    }
+    /// `XR_KHR_composition_layer_depth`
+    pub fn composition_layer_depth(&self) -> bool {
+        self.khr_composition_layer_depth
+    }
+    /// `XR_FB_space_warp`
+    pub fn space_warp(&self) -> bool {
+        self.fb_space_warp
+    }
+    /// `XR_FB_color_space`
+    pub fn color_space(&self) -> bool {
+        self.fb_color_space
+    }
+    /// `XR_EXT_performance_settings`
+    pub fn performance_settings(&self) -> bool {
+        self.ext_performance_settings
+    }
+    /// `XR_KHR_locate_spaces`
+    pub fn locate_spaces(&self) -> bool {
+        self.contains("XR_KHR_locate_spaces")
+    }
+    /// `XR_META_environment_depth`
+    pub fn environment_depth(&self) -> bool {
+        self.contains("XR_META_environment_depth")
+    }
+    /// `XR_EXT_active_action_set_priority`
+    pub fn active_action_set_priority(&self) -> bool {
+        self.ext_active_action_set_priority
+    }
+    /// `XR_EXT_dpad_binding` together with `XR_KHR_binding_modification`
+    pub fn dpad_binding(&self) -> bool {
+        self.ext_dpad_binding && self.khr_binding_modification
+    }
+    /// `XR_VARJO_quad_views`
+    pub fn quad_views(&self) -> bool {
+        self.varjo_quad_views
+    }
+    /// `XR_FB_composition_layer_settings`
+    pub fn composition_layer_settings(&self) -> bool {
+        self.fb_composition_layer_settings
+    }
+    /// `XR_KHR_composition_layer_color_scale_bias`
+    pub fn composition_layer_color_scale_bias(&self) -> bool {
+        self.khr_composition_layer_color_scale_bias
+    }
+    /// All extensions needed by the [`OxrPassthroughGeometryPlugin`](crate::features::passthrough_geometry::OxrPassthroughGeometryPlugin).
+    #[cfg(feature = "passthrough")]
+    pub fn passthrough_geometry(&self) -> bool {
+        self.fb_passthrough && self.fb_triangle_mesh
+    }
+}
 
 #[derive(Clone, Debug, Eq, PartialEq, Deref, DerefMut)]
 pub struct OxrExtensions(ExtensionSet);
@@ -13,6 +107,10 @@ impl OxrExtensions {
     pub fn raw(&self) -> &ExtensionSet {
         &self.0
     }
+    /// Returns the names of the extensions in this set, e.g. `fb_passthrough`, to log them.
+    pub fn names(&self) -> Vec<std::borrow::Cow<'static, str>> {
+        OxrExtensions::default().unavailable_exts(self)
+    }
     pub fn enable_fb_passthrough(&mut self) -> &mut Self {
         self.0.fb_passthrough = true;
         self
@@ -29,6 +127,245 @@ impl OxrExtensions {
         self.0.ext_hand_tracking = false;
         self
     }
+    /// Allows overriding the priority of the [`OxrActiveActionSets`](crate::actions::OxrActiveActionSets).
+    pub fn enable_active_action_set_priority(&mut self) -> &mut Self {
+        self.0.ext_active_action_set_priority = true;
+        self
+    }
+    pub fn disable_active_action_set_priority(&mut self) -> &mut Self {
+        self.0.ext_active_action_set_priority = false;
+        self
+    }
+    /// Lets the runtime threshold the dpad actions created with [`create_dpad_action`](crate::actions::OxrActionSet::create_dpad_action).
+    pub fn enable_dpad_binding(&mut self) -> &mut Self {
+        self.0.ext_dpad_binding = true;
+        self.0.khr_binding_modification = true;
+        self
+    }
+    pub fn disable_dpad_binding(&mut self) -> &mut Self {
+        self.0.ext_dpad_binding = false;
+        self.0.khr_binding_modification = false;
+        self
+    }
+    /// Enables the [`PRIMARY_QUAD_VARJO`](openxr::ViewConfigurationType::PRIMARY_QUAD_VARJO) view configuration,
+    /// this is done automatically if it's the requested [`view_configuration`](crate::init::OxrInitPlugin::view_configuration).
+    pub fn enable_varjo_quad_views(&mut self) -> &mut Self {
+        self.0.varjo_quad_views = true;
+        self
+    }
+    pub fn disable_varjo_quad_views(&mut self) -> &mut Self {
+        self.0.varjo_quad_views = false;
+        self
+    }
+    pub fn enable_hand_tracking_data_source(&mut self) -> &mut Self {
+        self.0.ext_hand_tracking_data_source = true;
+        self
+    }
+    pub fn disable_hand_tracking_data_source(&mut self) -> &mut Self {
+        self.0.ext_hand_tracking_data_source = false;
+        self
+    }
+    /// Enables all extensions needed by the [`OxrFoveationPlugin`](crate::features::foveation::OxrFoveationPlugin).
+    pub fn enable_fb_foveation(&mut self) -> &mut Self {
+        self.0.fb_foveation = true;
+        self.0.fb_foveation_configuration = true;
+        self.0.fb_swapchain_update_state = true;
+        self
+    }
+    pub fn disable_fb_foveation(&mut self) -> &mut Self {
+        self.0.fb_foveation = false;
+        self.0.fb_foveation_configuration = false;
+        self.0.fb_swapchain_update_state = false;
+        self
+    }
+    pub fn enable_fb_display_refresh_rate(&mut self) -> &mut Self {
+        self.0.fb_display_refresh_rate = true;
+        self
+    }
+    pub fn disable_fb_display_refresh_rate(&mut self) -> &mut Self {
+        self.0.fb_display_refresh_rate = false;
+        self
+    }
+    pub fn enable_eye_gaze_interaction(&mut self) -> &mut Self {
+        self.0.ext_eye_gaze_interaction = true;
+        self
+    }
+    pub fn disable_eye_gaze_interaction(&mut self) -> &mut Self {
+        self.0.ext_eye_gaze_interaction = false;
+        self
+    }
+    pub fn enable_msft_controller_model(&mut self) -> &mut Self {
+        self.0.msft_controller_model = true;
+        self
+    }
+    pub fn disable_msft_controller_model(&mut self) -> &mut Self {
+        self.0.msft_controller_model = false;
+        self
+    }
+    pub fn enable_khr_composition_layer_cylinder(&mut self) -> &mut Self {
+        self.0.khr_composition_layer_cylinder = true;
+        self
+    }
+    pub fn disable_khr_composition_layer_cylinder(&mut self) -> &mut Self {
+        self.0.khr_composition_layer_cylinder = false;
+        self
+    }
+    pub fn enable_khr_composition_layer_equirect2(&mut self) -> &mut Self {
+        self.0.khr_composition_layer_equirect2 = true;
+        self
+    }
+    pub fn disable_khr_composition_layer_equirect2(&mut self) -> &mut Self {
+        self.0.khr_composition_layer_equirect2 = false;
+        self
+    }
+    pub fn enable_khr_composition_layer_cube(&mut self) -> &mut Self {
+        self.0.khr_composition_layer_cube = true;
+        self
+    }
+    pub fn disable_khr_composition_layer_cube(&mut self) -> &mut Self {
+        self.0.khr_composition_layer_cube = false;
+        self
+    }
+    pub fn enable_khr_visibility_mask(&mut self) -> &mut Self {
+        self.0.khr_visibility_mask = true;
+        self
+    }
+    pub fn disable_khr_visibility_mask(&mut self) -> &mut Self {
+        self.0.khr_visibility_mask = false;
+        self
+    }
+    pub fn enable_msft_spatial_anchor(&mut self) -> &mut Self {
+        self.0.msft_spatial_anchor = true;
+        self
+    }
+    pub fn disable_msft_spatial_anchor(&mut self) -> &mut Self {
+        self.0.msft_spatial_anchor = false;
+        self
+    }
+    pub fn enable_khr_convert_timespec_time(&mut self) -> &mut Self {
+        self.0.khr_convert_timespec_time = true;
+        self
+    }
+    pub fn disable_khr_convert_timespec_time(&mut self) -> &mut Self {
+        self.0.khr_convert_timespec_time = false;
+        self
+    }
+    #[cfg(windows)]
+    pub fn enable_khr_win32_convert_performance_counter_time(&mut self) -> &mut Self {
+        self.0.khr_win32_convert_performance_counter_time = true;
+        self
+    }
+    #[cfg(windows)]
+    pub fn disable_khr_win32_convert_performance_counter_time(&mut self) -> &mut Self {
+        self.0.khr_win32_convert_performance_counter_time = false;
+        self
+    }
+    pub fn enable_khr_composition_layer_depth(&mut self) -> &mut Self {
+        self.0.khr_composition_layer_depth = true;
+        self
+    }
+    pub fn disable_khr_composition_layer_depth(&mut self) -> &mut Self {
+        self.0.khr_composition_layer_depth = false;
+        self
+    }
+    pub fn enable_fb_space_warp(&mut self) -> &mut Self {
+        self.0.fb_space_warp = true;
+        self
+    }
+    pub fn disable_fb_space_warp(&mut self) -> &mut Self {
+        self.0.fb_space_warp = false;
+        self
+    }
+    pub fn enable_fb_color_space(&mut self) -> &mut Self {
+        self.0.fb_color_space = true;
+        self
+    }
+    pub fn disable_fb_color_space(&mut self) -> &mut Self {
+        self.0.fb_color_space = false;
+        self
+    }
+    pub fn enable_ext_performance_settings(&mut self) -> &mut Self {
+        self.0.ext_performance_settings = true;
+        self
+    }
+    pub fn disable_ext_performance_settings(&mut self) -> &mut Self {
+        self.0.ext_performance_settings = false;
+        self
+    }
+    /// `XR_KHR_locate_spaces` isn't known to the openxr crate yet, so it's enabled through [`other`](ExtensionSet::other).
+    pub fn enable_khr_locate_spaces(&mut self) -> &mut Self {
+        if !self.contains("XR_KHR_locate_spaces") {
+            self.0.other.push("XR_KHR_locate_spaces\0".into());
+        }
+        self
+    }
+    pub fn disable_khr_locate_spaces(&mut self) -> &mut Self {
+        self.0
+            .other
+            .retain(|ext| ext.trim_end_matches('\0') != "XR_KHR_locate_spaces");
+        self
+    }
+    /// `XR_META_environment_depth` isn't known to the openxr crate yet, so it's enabled through [`other`](ExtensionSet::other).
+    pub fn enable_meta_environment_depth(&mut self) -> &mut Self {
+        if !self.contains("XR_META_environment_depth") {
+            self.0.other.push("XR_META_environment_depth\0".into());
+        }
+        self
+    }
+    pub fn disable_meta_environment_depth(&mut self) -> &mut Self {
+        self.0
+            .other
+            .retain(|ext| ext.trim_end_matches('\0') != "XR_META_environment_depth");
+        self
+    }
+    /// `XR_KHR_metal_enable` isn't known to the openxr crate yet, so it's enabled through [`other`](ExtensionSet::other).
+    pub fn enable_khr_metal_enable(&mut self) -> &mut Self {
+        if !self.contains("XR_KHR_metal_enable") {
+            self.0.other.push("XR_KHR_metal_enable\0".into());
+        }
+        self
+    }
+    pub fn disable_khr_metal_enable(&mut self) -> &mut Self {
+        self.0
+            .other
+            .retain(|ext| ext.trim_end_matches('\0') != "XR_KHR_metal_enable");
+        self
+    }
+    pub fn enable_fb_composition_layer_settings(&mut self) -> &mut Self {
+        self.0.fb_composition_layer_settings = true;
+        self
+    }
+    pub fn disable_fb_composition_layer_settings(&mut self) -> &mut Self {
+        self.0.fb_composition_layer_settings = false;
+        self
+    }
+    pub fn enable_khr_composition_layer_color_scale_bias(&mut self) -> &mut Self {
+        self.0.khr_composition_layer_color_scale_bias = true;
+        self
+    }
+    pub fn disable_khr_composition_layer_color_scale_bias(&mut self) -> &mut Self {
+        self.0.khr_composition_layer_color_scale_bias = false;
+        self
+    }
+    pub fn enable_fb_triangle_mesh(&mut self) -> &mut Self {
+        self.0.fb_triangle_mesh = true;
+        self
+    }
+    pub fn disable_fb_triangle_mesh(&mut self) -> &mut Self {
+        self.0.fb_triangle_mesh = false;
+        self
+    }
+    /// Enables all extensions needed by the [`OxrSecondaryViewPlugin`](crate::features::secondary_view::OxrSecondaryViewPlugin).
+    pub fn enable_msft_first_person_observer(&mut self) -> &mut Self {
+        self.0.msft_secondary_view_configuration = true;
+        self.0.msft_first_person_observer = true;
+        self
+    }
+    pub fn disable_msft_first_person_observer(&mut self) -> &mut Self {
+        self.0.msft_secondary_view_configuration = false;
+        self.0.msft_first_person_observer = false;
+        self
+    }
     /// returns true if all of the extensions enabled are also available in `available_exts`
     pub fn is_available(&self, available_exts: &OxrExtensions) -> bool {
         self.clone() & available_exts.clone() == *self
@@ -159,6 +496,40 @@ macro_rules! bitand {
     };
 }
 
+macro_rules! contains {
+    (
+        $exts:ty;
+        $(
+            $(
+                #[$meta:meta]
+            )*
+            $ident:ident
+        ),*
+        $(,)?
+    ) => {
+        impl $exts {
+            /// Returns if the extension with the full `name`, e.g. `XR_EXT_hand_tracking`, is enabled in `self`
+            pub fn contains(&self, name: &str) -> bool {
+                let Some(short_name) = name.strip_prefix("XR_") else {
+                    return false;
+                };
+                $(
+                    $(
+                        #[$meta]
+                    )*
+                    if short_name.eq_ignore_ascii_case(stringify!($ident)) {
+                        return self.0.$ident;
+                    }
+                )*
+                self.0
+                    .other
+                    .iter()
+                    .any(|ext| ext.trim_end_matches('\0') == name)
+            }
+        }
+    };
+}
+
 macro_rules! impl_ext {
     (
         $(
@@ -322,4 +693,4 @@ macro_rules! impl_ext {
     };
 }
 
-impl_ext!(bitor, bitand, unavailable_exts);
+impl_ext!(bitor, bitand, unavailable_exts, contains);
@@ -0,0 +1,150 @@
+use std::ptr;
+
+use bevy::prelude::*;
+use bevy_mod_xr::session::XrSessionCreated;
+use openxr::sys;
+
+use crate::{
+    error::cvt,
+    exts::OxrEnabledExtensions,
+    resources::{OxrInstance, OxrSystemId},
+    session::OxrSession,
+    types::Result,
+};
+
+pub use openxr::sys::ColorSpaceFB;
+
+/// Sets the color space the runtime interprets the rendered images in, see [`OxrColorSpace`].
+///
+/// The color space only picks the primaries and white point the colors are mapped to.
+/// The transfer function is still handled by the swapchain format, with an sRGB format the GPU encodes the rendered colors
+/// and no gamma correction should be applied on top.
+/// Assets authored in sRGB should use [`REC709`](ColorSpaceFB::REC709), which has the same primaries as sRGB.
+/// Requires [`XR_FB_color_space`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#XR_FB_color_space).
+#[derive(Default)]
+pub struct OxrColorSpacePlugin {
+    /// The color space applied once the session is created.
+    pub color_space: OxrColorSpace,
+}
+
+impl Plugin for OxrColorSpacePlugin {
+    fn build(&self, app: &mut App) {
+        if !app
+            .world()
+            .get_resource::<OxrEnabledExtensions>()
+            .is_some_and(|exts| exts.color_space())
+        {
+            info!("Color space extension not enabled, disabling color space");
+            return;
+        }
+
+        app.insert_resource(self.color_space)
+            .add_systems(XrSessionCreated, update_color_space)
+            .add_systems(
+                PreUpdate,
+                update_color_space
+                    .run_if(resource_exists::<OxrSession>)
+                    .run_if(resource_changed::<OxrColorSpace>),
+            );
+    }
+}
+
+/// The color space of the rendered images. Changing this resource updates the color space at runtime.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OxrColorSpace {
+    /// The native color space of the display, e.g. [`RIFT_CV1`](ColorSpaceFB::RIFT_CV1) on Quest 1
+    /// and [`REC2020`](ColorSpaceFB::REC2020) on Quest 2.
+    #[default]
+    Native,
+    Custom(ColorSpaceFB),
+}
+
+fn update_color_space(
+    color_space: Res<OxrColorSpace>,
+    instance: Res<OxrInstance>,
+    system_id: Res<OxrSystemId>,
+    session: Res<OxrSession>,
+) {
+    let color_space = match *color_space {
+        OxrColorSpace::Native => match instance.native_color_space(**system_id) {
+            Ok(color_space) => color_space,
+            Err(err) => {
+                warn!("Failed to get native color space: {err}");
+                return;
+            }
+        },
+        OxrColorSpace::Custom(color_space) => color_space,
+    };
+    if let Err(err) = session.set_color_space(color_space) {
+        warn!("Failed to set color space {color_space:?}: {err}");
+    }
+}
+
+impl OxrInstance {
+    /// Returns the native color space of the display of `system`.
+    ///
+    /// Requires [`XR_FB_color_space`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#XR_FB_color_space).
+    pub fn native_color_space(&self, system: openxr::SystemId) -> openxr::Result<ColorSpaceFB> {
+        unsafe {
+            let mut color_space = sys::SystemColorSpacePropertiesFB {
+                ty: sys::SystemColorSpacePropertiesFB::TYPE,
+                next: ptr::null_mut(),
+                color_space: ColorSpaceFB::UNMANAGED,
+            };
+            let mut p = sys::SystemProperties::out(&mut color_space as *mut _ as _);
+            cvt((self.fp().get_system_properties)(
+                self.as_raw(),
+                system,
+                p.as_mut_ptr(),
+            ))?;
+            Ok(color_space.color_space)
+        }
+    }
+}
+
+impl OxrSession {
+    /// Returns the color spaces supported by the display.
+    ///
+    /// Requires [`XR_FB_color_space`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#XR_FB_color_space).
+    pub fn enumerate_color_spaces(&self) -> Result<Vec<ColorSpaceFB>> {
+        let fns = self
+            .instance()
+            .exts()
+            .fb_color_space
+            .as_ref()
+            .ok_or(sys::Result::ERROR_EXTENSION_NOT_PRESENT)?;
+        let mut count = 0;
+        unsafe {
+            cvt((fns.enumerate_color_spaces)(
+                self.as_raw(),
+                0,
+                &mut count,
+                ptr::null_mut(),
+            ))?;
+            let mut color_spaces = vec![ColorSpaceFB::UNMANAGED; count as usize];
+            cvt((fns.enumerate_color_spaces)(
+                self.as_raw(),
+                count,
+                &mut count,
+                color_spaces.as_mut_ptr(),
+            ))?;
+            color_spaces.truncate(count as usize);
+            Ok(color_spaces)
+        }
+    }
+
+    /// Sets the color space the runtime interprets the rendered images in,
+    /// which has to be one of the color spaces returned by [`enumerate_color_spaces`](OxrSession::enumerate_color_spaces).
+    ///
+    /// Requires [`XR_FB_color_space`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#XR_FB_color_space).
+    pub fn set_color_space(&self, color_space: ColorSpaceFB) -> Result<()> {
+        let fns = self
+            .instance()
+            .exts()
+            .fb_color_space
+            .as_ref()
+            .ok_or(sys::Result::ERROR_EXTENSION_NOT_PRESENT)?;
+        cvt(unsafe { (fns.set_color_space)(self.as_raw(), color_space) })?;
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+use bevy::prelude::*;
+use bevy_mod_xr::hands::HandSide;
+
+use crate::types::Result;
+
+/// Keeps track of the battery levels of the controllers in the [`OxrControllerBattery`]
+/// and sends an [`OxrControllerBatteryLow`] when a level drops below the threshold, e.g. to warn the user.
+///
+/// No OpenXR extension reports controller battery levels yet, so the levels are unknown unless they are set
+/// with [`set_battery_level`](OxrControllerBattery::set_battery_level), e.g. from a platform SDK.
+pub struct OxrControllerBatteryPlugin {
+    /// The battery level in 0..=1 below which an [`OxrControllerBatteryLow`] is sent.
+    pub low_threshold: f32,
+}
+
+impl Default for OxrControllerBatteryPlugin {
+    fn default() -> Self {
+        Self { low_threshold: 0.2 }
+    }
+}
+
+impl Plugin for OxrControllerBatteryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<OxrControllerBatteryLow>()
+            .insert_resource(OxrControllerBattery {
+                levels: [None; 2],
+                low_threshold: self.low_threshold,
+            })
+            .add_systems(
+                PreUpdate,
+                send_battery_low_events.run_if(resource_changed::<OxrControllerBattery>),
+            );
+    }
+}
+
+/// The battery levels of the controllers.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct OxrControllerBattery {
+    levels: [Option<f32>; 2],
+    /// The battery level in 0..=1 below which an [`OxrControllerBatteryLow`] is sent.
+    pub low_threshold: f32,
+}
+
+impl OxrControllerBattery {
+    /// Returns the battery level of the controller in `hand` in 0..=1, or `None` if the level is unknown.
+    pub fn battery_level(&self, hand: HandSide) -> Result<Option<f32>> {
+        Ok(self.levels[hand_index(hand)])
+    }
+
+    /// Sets the battery level of the controller in `hand`, the level is clamped to 0..=1.
+    pub fn set_battery_level(&mut self, hand: HandSide, level: Option<f32>) {
+        self.levels[hand_index(hand)] = level.map(|level| level.clamp(0.0, 1.0));
+    }
+}
+
+/// Sent once when the battery level of a controller drops below the [`low_threshold`](OxrControllerBattery::low_threshold).
+#[derive(Event, Clone, Copy, Debug)]
+pub struct OxrControllerBatteryLow {
+    pub hand: HandSide,
+    pub level: f32,
+}
+
+fn send_battery_low_events(
+    battery: Res<OxrControllerBattery>,
+    mut was_low: Local<[bool; 2]>,
+    mut writer: EventWriter<OxrControllerBatteryLow>,
+) {
+    for hand in [HandSide::Left, HandSide::Right] {
+        let index = hand_index(hand);
+        let level = battery.levels[index];
+        let is_low = level.is_some_and(|level| level < battery.low_threshold);
+        if let Some(level) = level.filter(|_| is_low && !was_low[index]) {
+            writer.send(OxrControllerBatteryLow { hand, level });
+        }
+        was_low[index] = is_low;
+    }
+}
+
+fn hand_index(hand: HandSide) -> usize {
+    match hand {
+        HandSide::Left => 0,
+        HandSide::Right => 1,
+    }
+}
@@ -0,0 +1,442 @@
+use std::{
+    ffi::CStr,
+    path::Path,
+    ptr,
+    sync::{Arc, RwLock},
+};
+
+use bevy::{
+    asset::io::{AssetReader, AssetReaderError, AssetSource, PathStream, Reader, VecReader},
+    prelude::*,
+    scene::SceneInstance,
+    utils::HashMap,
+};
+use bevy_mod_xr::hands::HandSide;
+use openxr::sys;
+
+use crate::{
+    action_set_syncing::OxrActionSetSyncSet, error::cvt, exts::OxrEnabledExtensions,
+    helper_traits::ToTransform, openxr_session_running, resources::OxrInstance,
+    session::OxrSession,
+};
+
+/// The [`AssetSource`] the models in the [`OxrControllerModelCache`] are loaded from,
+/// see [`OxrControllerModelData::scene_path`].
+pub const CONTROLLER_MODEL_ASSET_SOURCE: &str = "openxr_controller_model";
+
+/// Loads the glTF models of the controllers held by the user and keeps the poses of their nodes up to date.
+///
+/// Add an [`OxrControllerModel`] to an entity to get an [`OxrControllerModelData`] for that hand,
+/// the model is spawned as a [`Scene`] below the entity and its nodes are moved with the controller's buttons and sticks.
+/// Requires [`XR_MSFT_controller_model`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#XR_MSFT_controller_model),
+/// if it is unavailable a generic box mesh is added to the entities instead.
+///
+/// Registers the [`CONTROLLER_MODEL_ASSET_SOURCE`], so it has to be added before the [`AssetPlugin`],
+/// e.g. with `add_xr_plugins(DefaultPlugins).add_before::<AssetPlugin, _>(OxrControllerModelPlugin)`.
+pub struct OxrControllerModelPlugin;
+
+impl Plugin for OxrControllerModelPlugin {
+    fn build(&self, app: &mut App) {
+        let cache = OxrControllerModelCache::default();
+        let models = cache.0.clone();
+        app.insert_resource(cache).register_asset_source(
+            CONTROLLER_MODEL_ASSET_SOURCE,
+            AssetSource::build()
+                .with_reader(move || Box::new(OxrControllerModelReader(models.clone()))),
+        );
+    }
+
+    fn finish(&self, app: &mut App) {
+        if !app
+            .world()
+            .get_resource::<OxrEnabledExtensions>()
+            .is_some_and(|exts| exts.controller_model())
+        {
+            info!("Controller model extension not enabled, using fallback controller models");
+            app.add_systems(Update, add_fallback_models);
+            return;
+        }
+
+        app.add_systems(
+            PreUpdate,
+            (update_controller_models, pose_controller_model_nodes)
+                .chain()
+                .after(OxrActionSetSyncSet)
+                .run_if(openxr_session_running),
+        );
+    }
+}
+
+/// Requests the model of the controller held in `hand`.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct OxrControllerModel {
+    pub hand: HandSide,
+}
+
+/// The model of the controller requested by an [`OxrControllerModel`].
+///
+/// Removed while the runtime has no model for the controller, e.g. because no controller is connected.
+#[derive(Component, Clone, Debug)]
+pub struct OxrControllerModelData {
+    pub key: sys::ControllerModelKeyMSFT,
+    /// The model as binary glTF.
+    pub model: Arc<[u8]>,
+    /// The animatable nodes of the model, updated every frame.
+    pub nodes: Vec<OxrControllerModelNode>,
+}
+
+#[derive(Clone, Debug)]
+pub struct OxrControllerModelNode {
+    /// Name of the node in the glTF model.
+    pub name: String,
+    /// Name of the parent of the node, the `transform` is relative to it.
+    ///
+    /// Empty if the node name is unique in the model.
+    pub parent_name: String,
+    pub transform: Transform,
+}
+
+impl OxrControllerModelNode {
+    /// Whether an entity of the spawned [`Scene`] named `name` below an entity named `parent_name` is this node.
+    fn matches(&self, name: &Name, parent_name: Option<&Name>) -> bool {
+        self.name == name.as_str()
+            && (self.parent_name.is_empty()
+                || parent_name.is_some_and(|parent_name| self.parent_name == parent_name.as_str()))
+    }
+}
+
+impl OxrControllerModelData {
+    /// The path of the [`Scene`] of the model, loaded from the [`OxrControllerModelCache`].
+    pub fn scene_path(&self) -> String {
+        format!(
+            "{CONTROLLER_MODEL_ASSET_SOURCE}://{}.glb#Scene0",
+            self.key.into_raw()
+        )
+    }
+}
+
+/// Models loaded by the [`OxrControllerModelPlugin`], indexed by their key.
+///
+/// Shared with the [`CONTROLLER_MODEL_ASSET_SOURCE`].
+#[derive(Resource, Clone, Default, Deref)]
+pub struct OxrControllerModelCache(pub Arc<RwLock<HashMap<u64, Arc<[u8]>>>>);
+
+/// Reads the models of the [`OxrControllerModelCache`] as `<key>.glb`.
+struct OxrControllerModelReader(Arc<RwLock<HashMap<u64, Arc<[u8]>>>>);
+
+impl AssetReader for OxrControllerModelReader {
+    async fn read<'a>(&'a self, path: &'a Path) -> Result<Box<Reader<'a>>, AssetReaderError> {
+        let model = path
+            .file_stem()
+            .and_then(|key| key.to_str()?.parse::<u64>().ok())
+            .and_then(|key| self.0.read().unwrap().get(&key).cloned())
+            .ok_or_else(|| AssetReaderError::NotFound(path.to_owned()))?;
+        Ok(Box::new(VecReader::new(model.to_vec())))
+    }
+
+    async fn read_meta<'a>(&'a self, path: &'a Path) -> Result<Box<Reader<'a>>, AssetReaderError> {
+        Err(AssetReaderError::NotFound(path.to_owned()))
+    }
+
+    async fn read_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> Result<Box<PathStream>, AssetReaderError> {
+        Err(AssetReaderError::NotFound(path.to_owned()))
+    }
+
+    async fn is_directory<'a>(&'a self, _path: &'a Path) -> Result<bool, AssetReaderError> {
+        Ok(false)
+    }
+}
+
+fn add_fallback_models(
+    query: Query<Entity, Added<OxrControllerModel>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut cmds: Commands,
+) {
+    for entity in &query {
+        cmds.entity(entity).insert((
+            meshes.add(Cuboid::new(0.05, 0.05, 0.15)),
+            materials.add(StandardMaterial::default()),
+        ));
+    }
+}
+
+fn update_controller_models(
+    instance: Res<OxrInstance>,
+    session: Res<OxrSession>,
+    cache: Res<OxrControllerModelCache>,
+    asset_server: Res<AssetServer>,
+    mut scene_spawner: ResMut<SceneSpawner>,
+    mut query: Query<(
+        Entity,
+        &OxrControllerModel,
+        Option<&mut OxrControllerModelData>,
+        Option<&SceneInstance>,
+    )>,
+    mut cmds: Commands,
+) {
+    for (entity, controller, data, scene_instance) in &mut query {
+        let path = match controller.hand {
+            HandSide::Left => "/user/hand/left",
+            HandSide::Right => "/user/hand/right",
+        };
+        let key = match instance
+            .string_to_path(path)
+            .and_then(|path| session.load_controller_model_key(path))
+        {
+            Ok(key) => key,
+            Err(err) => {
+                warn!("error while getting controller model key: {}", err);
+                continue;
+            }
+        };
+        if key.into_raw() == 0 {
+            if data.is_some() {
+                cmds.entity(entity)
+                    .remove::<(OxrControllerModelData, Handle<Scene>, SceneInstance)>();
+            }
+            if let Some(scene_instance) = scene_instance {
+                scene_spawner.despawn_instance(**scene_instance);
+            }
+            continue;
+        }
+
+        let transforms = match session.controller_model_node_transforms(key) {
+            Ok(transforms) => transforms,
+            Err(err) => {
+                warn!("error while getting controller model state: {}", err);
+                continue;
+            }
+        };
+        match data {
+            Some(mut data) if data.key.into_raw() == key.into_raw() => {
+                for (node, transform) in data.nodes.iter_mut().zip(transforms) {
+                    node.transform = transform;
+                }
+            }
+            _ => {
+                let cached = cache.read().unwrap().get(&key.into_raw()).cloned();
+                let model = match cached {
+                    Some(model) => model,
+                    None => match session.load_controller_model(key) {
+                        Ok(model) => {
+                            let model: Arc<[u8]> = model.into();
+                            cache.write().unwrap().insert(key.into_raw(), model.clone());
+                            model
+                        }
+                        Err(err) => {
+                            warn!("error while loading controller model: {}", err);
+                            continue;
+                        }
+                    },
+                };
+                let nodes = match session.controller_model_nodes(key) {
+                    Ok(nodes) => nodes,
+                    Err(err) => {
+                        warn!("error while getting controller model properties: {}", err);
+                        continue;
+                    }
+                };
+                let data = OxrControllerModelData {
+                    key,
+                    model,
+                    nodes: nodes
+                        .into_iter()
+                        .zip(transforms)
+                        .map(|((name, parent_name), transform)| OxrControllerModelNode {
+                            name,
+                            parent_name,
+                            transform,
+                        })
+                        .collect(),
+                };
+                // the scene spawner replaces the instance of the previous model
+                let scene: Handle<Scene> = asset_server.load(data.scene_path());
+                cmds.entity(entity).insert((data, scene));
+            }
+        }
+    }
+}
+
+/// Applies the poses of the [`OxrControllerModelNode`]s to the named entities of the spawned [`Scene`]s.
+fn pose_controller_model_nodes(
+    models: Query<(Entity, &OxrControllerModelData), With<SceneInstance>>,
+    children: Query<&Children>,
+    names: Query<&Name>,
+    mut nodes: Query<(&Name, Option<&Parent>, &mut Transform)>,
+) {
+    for (entity, data) in &models {
+        for descendant in children.iter_descendants(entity) {
+            let Ok((name, parent, mut transform)) = nodes.get_mut(descendant) else {
+                continue;
+            };
+            let parent_name = parent.and_then(|parent| names.get(parent.get()).ok());
+            if let Some(node) = data
+                .nodes
+                .iter()
+                .find(|node| node.matches(name, parent_name))
+            {
+                *transform = node.transform;
+            }
+        }
+    }
+}
+
+impl OxrSession {
+    /// Returns the key of the controller model currently bound to `top_level_user_path`, e.g. `/user/hand/left`.
+    ///
+    /// The key is null if the runtime has no model for the controller.
+    pub fn load_controller_model_key(
+        &self,
+        top_level_user_path: openxr::Path,
+    ) -> openxr::Result<sys::ControllerModelKeyMSFT> {
+        let fns = self.controller_model_fns()?;
+        let mut key_state = sys::ControllerModelKeyStateMSFT {
+            ty: sys::ControllerModelKeyStateMSFT::TYPE,
+            next: ptr::null_mut(),
+            model_key: sys::ControllerModelKeyMSFT::from_raw(0),
+        };
+        unsafe {
+            cvt((fns.get_controller_model_key)(
+                self.as_raw(),
+                top_level_user_path,
+                &mut key_state,
+            ))?;
+        }
+        Ok(key_state.model_key)
+    }
+
+    /// Loads the binary glTF model for `key`.
+    pub fn load_controller_model(
+        &self,
+        key: sys::ControllerModelKeyMSFT,
+    ) -> openxr::Result<Vec<u8>> {
+        let fns = self.controller_model_fns()?;
+        let mut count = 0;
+        unsafe {
+            cvt((fns.load_controller_model)(
+                self.as_raw(),
+                key,
+                0,
+                &mut count,
+                ptr::null_mut(),
+            ))?;
+            let mut buffer = vec![0; count as usize];
+            cvt((fns.load_controller_model)(
+                self.as_raw(),
+                key,
+                count,
+                &mut count,
+                buffer.as_mut_ptr(),
+            ))?;
+            buffer.truncate(count as usize);
+            Ok(buffer)
+        }
+    }
+
+    /// Returns the names and parent names of the animatable nodes of the model for `key`.
+    pub fn controller_model_nodes(
+        &self,
+        key: sys::ControllerModelKeyMSFT,
+    ) -> openxr::Result<Vec<(String, String)>> {
+        let fns = self.controller_model_fns()?;
+        let mut properties = sys::ControllerModelPropertiesMSFT {
+            ty: sys::ControllerModelPropertiesMSFT::TYPE,
+            next: ptr::null_mut(),
+            node_capacity_input: 0,
+            node_count_output: 0,
+            node_properties: ptr::null_mut(),
+        };
+        unsafe {
+            cvt((fns.get_controller_model_properties)(
+                self.as_raw(),
+                key,
+                &mut properties,
+            ))?;
+            let mut nodes = vec![
+                sys::ControllerModelNodePropertiesMSFT {
+                    ty: sys::ControllerModelNodePropertiesMSFT::TYPE,
+                    next: ptr::null_mut(),
+                    parent_node_name: [0; sys::MAX_CONTROLLER_MODEL_NODE_NAME_SIZE_MSFT],
+                    node_name: [0; sys::MAX_CONTROLLER_MODEL_NODE_NAME_SIZE_MSFT],
+                };
+                properties.node_count_output as usize
+            ];
+            properties.node_capacity_input = nodes.len() as u32;
+            properties.node_properties = nodes.as_mut_ptr();
+            cvt((fns.get_controller_model_properties)(
+                self.as_raw(),
+                key,
+                &mut properties,
+            ))?;
+            nodes.truncate(properties.node_count_output as usize);
+            Ok(nodes
+                .iter()
+                .map(|node| {
+                    (
+                        CStr::from_ptr(node.node_name.as_ptr())
+                            .to_string_lossy()
+                            .into_owned(),
+                        CStr::from_ptr(node.parent_node_name.as_ptr())
+                            .to_string_lossy()
+                            .into_owned(),
+                    )
+                })
+                .collect())
+        }
+    }
+
+    /// Returns the current transforms of the animatable nodes of the model for `key`,
+    /// in the same order as [`controller_model_nodes`](OxrSession::controller_model_nodes).
+    pub fn controller_model_node_transforms(
+        &self,
+        key: sys::ControllerModelKeyMSFT,
+    ) -> openxr::Result<Vec<Transform>> {
+        let fns = self.controller_model_fns()?;
+        let mut state = sys::ControllerModelStateMSFT {
+            ty: sys::ControllerModelStateMSFT::TYPE,
+            next: ptr::null_mut(),
+            node_capacity_input: 0,
+            node_count_output: 0,
+            node_states: ptr::null_mut(),
+        };
+        unsafe {
+            cvt((fns.get_controller_model_state)(
+                self.as_raw(),
+                key,
+                &mut state,
+            ))?;
+            let mut nodes = vec![
+                sys::ControllerModelNodeStateMSFT {
+                    ty: sys::ControllerModelNodeStateMSFT::TYPE,
+                    next: ptr::null_mut(),
+                    node_pose: openxr::Posef::IDENTITY,
+                };
+                state.node_count_output as usize
+            ];
+            state.node_capacity_input = nodes.len() as u32;
+            state.node_states = nodes.as_mut_ptr();
+            cvt((fns.get_controller_model_state)(
+                self.as_raw(),
+                key,
+                &mut state,
+            ))?;
+            nodes.truncate(state.node_count_output as usize);
+            Ok(nodes
+                .iter()
+                .map(|node| node.node_pose.to_transform())
+                .collect())
+        }
+    }
+
+    fn controller_model_fns(&self) -> openxr::Result<&openxr::raw::ControllerModelMSFT> {
+        self.instance()
+            .exts()
+            .msft_controller_model
+            .as_ref()
+            .ok_or(sys::Result::ERROR_EXTENSION_NOT_PRESENT)
+    }
+}
@@ -0,0 +1,620 @@
+use std::{mem, ptr};
+
+use bevy::{
+    asset::load_internal_asset,
+    prelude::*,
+    render::{
+        extract_resource::{ExtractResource, ExtractResourcePlugin},
+        render_resource::{ShaderType, TextureView, UniformBuffer},
+        renderer::{RenderDevice, RenderQueue},
+        ExtractSchedule, MainWorld, Render, RenderApp, RenderSet,
+    },
+};
+use bevy_mod_xr::{
+    session::{XrPreDestroySession, XrRenderSet, XrRootTransform, XrSessionCreated},
+    spaces::XrPrimaryReferenceSpace,
+};
+use openxr::sys;
+
+use crate::{
+    error::cvt,
+    exts::OxrEnabledExtensions,
+    graphics::{graphics_match, GraphicsExt},
+    helper_traits::ToTransform,
+    init::should_run_frame_loop,
+    resources::{OxrFrameState, OxrInstance, OxrSwapchainImages, OxrSystemId},
+    session::OxrSession,
+    spaces::OxrSpaceExt as _,
+    types::{Result, SwapchainImageLayout},
+};
+
+/// Handle of the shader library with helpers for occlusion by the environment depth,
+/// imported with `#import bevy_mod_openxr::environment_depth`.
+pub const ENVIRONMENT_DEPTH_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(0x6e1d_4f0b_90a3_4c8e_b5d2_7a13_c0f9_e842);
+
+/// Starts the environment depth provider of the runtime, which estimates the depth of the real environment,
+/// e.g. to hide virtual objects behind real ones.
+///
+/// The depth is acquired every frame in the render world and stored in the [`OxrEnvironmentDepth`],
+/// the [`OxrEnvironmentDepthTexture`] has the acquired image and the projections needed to sample it in a shader.
+/// The shader library at [`ENVIRONMENT_DEPTH_SHADER_HANDLE`] has `environment_depth_occlusion` to test fragments against it.
+/// Requires [`XR_META_environment_depth`](https://registry.khronos.org/OpenXR/specs/1.1/html/xrspec.html#XR_META_environment_depth).
+/// Does nothing if the system doesn't support it.
+#[derive(Default)]
+pub struct OxrEnvironmentDepthPlugin {
+    /// Removes the hands of the user from the environment depth, if the system supports it.
+    /// Useful if the hands are tracked and rendered by the app anyway.
+    pub hand_removal: bool,
+}
+
+impl Plugin for OxrEnvironmentDepthPlugin {
+    fn build(&self, app: &mut App) {
+        if !app
+            .world()
+            .get_resource::<OxrEnabledExtensions>()
+            .is_some_and(|exts| exts.environment_depth())
+        {
+            info!("Environment depth extension not enabled, disabling environment depth");
+            return;
+        }
+
+        load_internal_asset!(
+            app,
+            ENVIRONMENT_DEPTH_SHADER_HANDLE,
+            "environment_depth.wgsl",
+            Shader::from_wgsl
+        );
+
+        app.insert_resource(OxrEnvironmentDepthSettings {
+            hand_removal: self.hand_removal,
+        })
+        .add_plugins((
+            ExtractResourcePlugin::<OxrEnvironmentDepthInfo>::default(),
+            ExtractResourcePlugin::<OxrEnvironmentDepthImages>::default(),
+        ))
+        .add_systems(XrSessionCreated, init_environment_depth)
+        .add_systems(XrPreDestroySession, cleanup_environment_depth);
+
+        app.sub_app_mut(RenderApp)
+            .add_systems(ExtractSchedule, transfer_environment_depth_provider)
+            .add_systems(
+                Render,
+                (
+                    acquire_environment_depth
+                        .in_set(XrRenderSet::PreRender)
+                        .run_if(should_run_frame_loop),
+                    prepare_environment_depth_texture
+                        .in_set(RenderSet::PrepareResources)
+                        .run_if(resource_exists::<OxrEnvironmentDepth>),
+                )
+                    .run_if(resource_exists::<OxrEnvironmentDepthProvider>),
+            )
+            .add_systems(XrPreDestroySession, cleanup_environment_depth_provider);
+    }
+}
+
+#[derive(Resource, Clone, Copy)]
+struct OxrEnvironmentDepthSettings {
+    hand_removal: bool,
+}
+
+/// The format and resolution of the environment depth images.
+#[derive(Resource, ExtractResource, Clone, Copy, Debug)]
+pub struct OxrEnvironmentDepthInfo {
+    pub resolution: UVec2,
+    pub format: wgpu::TextureFormat,
+    /// Whether the hands of the user are removed from the environment depth.
+    pub hand_removal: bool,
+}
+
+/// The images of the environment depth swapchain, with one array layer per view.
+#[derive(Resource, ExtractResource, Clone, Copy, Deref)]
+pub struct OxrEnvironmentDepthImages(pub OxrSwapchainImages);
+
+/// The environment depth acquired for the current frame. Only exists in the render world.
+///
+/// The depth is estimated asynchronously, so it usually lags behind the rendered frame.
+/// The [`views`](Self::views) are the poses the depth was captured from, so projecting with them
+/// accounts for the lag, and [`updated`](Self::updated) is false if no new depth was available this frame.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct OxrEnvironmentDepth {
+    /// The index of the acquired image in the [`OxrEnvironmentDepthImages`].
+    pub image_index: u32,
+    pub views: [OxrEnvironmentDepthView; 2],
+    /// The near plane of the depth projection in meters.
+    pub near: f32,
+    /// The far plane of the depth projection in meters, may be infinite.
+    pub far: f32,
+    /// The display time the depth was acquired for.
+    pub display_time: openxr::Time,
+    /// Whether the depth was acquired this frame, otherwise it's the depth of an earlier frame.
+    pub updated: bool,
+}
+
+/// A view of the environment depth, matching the layer of the same index of the depth image.
+#[derive(Clone, Copy, Debug)]
+pub struct OxrEnvironmentDepthView {
+    /// The pose the depth was captured from, relative to the [`XrPrimaryReferenceSpace`].
+    pub pose: Transform,
+    pub fov: openxr::Fovf,
+    /// Projects world space positions into the clip space of this view of the depth image,
+    /// this includes the [`XrTrackingRoot`](bevy_mod_xr::session::XrTrackingRoot).
+    pub view_projection: Mat4,
+}
+
+/// The uniform of an [`OxrEnvironmentDepthTexture`], `EnvironmentDepth` in the shader library.
+#[derive(ShaderType, Clone, Copy, Debug, Default)]
+pub struct OxrEnvironmentDepthUniform {
+    pub view_projections: [Mat4; 2],
+    pub near: f32,
+    /// 0 for an infinite far plane.
+    pub far: f32,
+}
+
+/// The GPU resources needed to sample the [`OxrEnvironmentDepth`] in a shader. Only exists in the render world.
+///
+/// Bind the `texture` as a `texture_depth_2d_array` and the `uniform` as an `EnvironmentDepth` uniform.
+#[derive(Resource)]
+pub struct OxrEnvironmentDepthTexture {
+    pub texture: TextureView,
+    pub uniform: UniformBuffer<OxrEnvironmentDepthUniform>,
+}
+
+/// The provider and swapchain of the environment depth. Only exists in the render world.
+#[derive(Resource)]
+struct OxrEnvironmentDepthProvider {
+    provider: u64,
+    swapchain: u64,
+    fns: EnvironmentDepthFns,
+}
+
+/// Used to transport the environment depth provider from the main world to the render world.
+#[derive(Resource)]
+struct OxrEnvironmentDepthRenderResources(OxrEnvironmentDepthProvider);
+
+fn init_environment_depth(
+    instance: Res<OxrInstance>,
+    system_id: Res<OxrSystemId>,
+    session: Res<OxrSession>,
+    settings: Res<OxrEnvironmentDepthSettings>,
+    device: Res<RenderDevice>,
+    mut commands: Commands,
+) {
+    let properties = match environment_depth_properties(&instance, **system_id) {
+        Ok(properties) => properties,
+        Err(err) => {
+            error!("Failed to get environment depth properties: {err}");
+            return;
+        }
+    };
+    if properties.supports_environment_depth == sys::FALSE {
+        info!("System doesn't support environment depth, disabling environment depth");
+        return;
+    }
+    let hand_removal = settings.hand_removal && properties.supports_hand_removal != sys::FALSE;
+    match create_environment_depth_provider(&session, device.wgpu_device(), hand_removal) {
+        Ok((info, provider, images)) => {
+            commands.insert_resource(info);
+            commands.insert_resource(images);
+            commands.insert_resource(OxrEnvironmentDepthRenderResources(provider));
+        }
+        Err(err) => error!("Failed to create environment depth provider: {err}"),
+    }
+}
+
+fn create_environment_depth_provider(
+    session: &OxrSession,
+    device: &wgpu::Device,
+    hand_removal: bool,
+) -> Result<(
+    OxrEnvironmentDepthInfo,
+    OxrEnvironmentDepthProvider,
+    OxrEnvironmentDepthImages,
+)> {
+    let fns = EnvironmentDepthFns::load(session.instance())?;
+    let format = wgpu::TextureFormat::Depth16Unorm;
+    unsafe {
+        let mut provider = 0;
+        cvt((fns.create_provider)(
+            session.as_raw(),
+            &EnvironmentDepthProviderCreateInfoMETA {
+                ty: sys::StructureType::from_raw(TYPE_ENVIRONMENT_DEPTH_PROVIDER_CREATE_INFO_META),
+                next: ptr::null(),
+                create_flags: 0,
+            },
+            &mut provider,
+        ))?;
+        let mut swapchain = 0;
+        if let Err(err) = cvt((fns.create_swapchain)(
+            provider,
+            &EnvironmentDepthSwapchainCreateInfoMETA {
+                ty: sys::StructureType::from_raw(TYPE_ENVIRONMENT_DEPTH_SWAPCHAIN_CREATE_INFO_META),
+                next: ptr::null(),
+                create_flags: 0,
+            },
+            &mut swapchain,
+        )) {
+            (fns.destroy_provider)(provider);
+            return Err(err.into());
+        }
+        let provider = OxrEnvironmentDepthProvider {
+            provider,
+            swapchain,
+            fns,
+        };
+        // the provider is destroyed when dropped if anything below fails
+        let mut state = EnvironmentDepthSwapchainStateMETA {
+            ty: sys::StructureType::from_raw(TYPE_ENVIRONMENT_DEPTH_SWAPCHAIN_STATE_META),
+            next: ptr::null_mut(),
+            width: 0,
+            height: 0,
+        };
+        cvt((fns.get_swapchain_state)(swapchain, &mut state))?;
+        let resolution = UVec2::new(state.width, state.height);
+        let images = graphics_match!(
+            &session.1;
+            _ => {
+                let mut textures = vec![];
+                let mut enumerate = |capacity, count: &mut u32, images| {
+                    (fns.enumerate_swapchain_images)(swapchain, capacity, count, images)
+                };
+                for image in Api::enumerate_raw_swapchain_images(&mut enumerate)? {
+                    textures.push(Api::to_wgpu_img(
+                        image,
+                        device,
+                        format,
+                        &[],
+                        wgpu::TextureUsages::TEXTURE_BINDING,
+                        SwapchainImageLayout::array(resolution, 2),
+                        1,
+                    )?);
+                }
+                OxrSwapchainImages(textures.leak())
+            }
+        );
+        if hand_removal {
+            cvt((fns.set_hand_removal)(
+                provider.provider,
+                &EnvironmentDepthHandRemovalSetInfoMETA {
+                    ty: sys::StructureType::from_raw(
+                        TYPE_ENVIRONMENT_DEPTH_HAND_REMOVAL_SET_INFO_META,
+                    ),
+                    next: ptr::null(),
+                    enabled: sys::TRUE,
+                },
+            ))?;
+        }
+        cvt((fns.start_provider)(provider.provider))?;
+
+        Ok((
+            OxrEnvironmentDepthInfo {
+                resolution,
+                format,
+                hand_removal,
+            },
+            provider,
+            OxrEnvironmentDepthImages(images),
+        ))
+    }
+}
+
+fn environment_depth_properties(
+    instance: &OxrInstance,
+    system: openxr::SystemId,
+) -> openxr::Result<SystemEnvironmentDepthPropertiesMETA> {
+    unsafe {
+        let mut properties = SystemEnvironmentDepthPropertiesMETA {
+            ty: sys::StructureType::from_raw(TYPE_SYSTEM_ENVIRONMENT_DEPTH_PROPERTIES_META),
+            next: ptr::null_mut(),
+            supports_environment_depth: sys::FALSE,
+            supports_hand_removal: sys::FALSE,
+        };
+        let mut p = sys::SystemProperties::out(&mut properties as *mut _ as _);
+        cvt((instance.fp().get_system_properties)(
+            instance.as_raw(),
+            system,
+            p.as_mut_ptr(),
+        ))?;
+        Ok(properties)
+    }
+}
+
+fn cleanup_environment_depth(mut commands: Commands) {
+    commands.remove_resource::<OxrEnvironmentDepthInfo>();
+    commands.remove_resource::<OxrEnvironmentDepthImages>();
+    commands.remove_resource::<OxrEnvironmentDepthRenderResources>();
+}
+
+fn cleanup_environment_depth_provider(mut commands: Commands) {
+    commands.remove_resource::<OxrEnvironmentDepthProvider>();
+    commands.remove_resource::<OxrEnvironmentDepth>();
+    commands.remove_resource::<OxrEnvironmentDepthTexture>();
+}
+
+fn transfer_environment_depth_provider(mut commands: Commands, mut world: ResMut<MainWorld>) {
+    if let Some(OxrEnvironmentDepthRenderResources(provider)) = world.remove_resource() {
+        commands.insert_resource(provider);
+    }
+}
+
+fn acquire_environment_depth(
+    provider: Res<OxrEnvironmentDepthProvider>,
+    frame_state: Res<OxrFrameState>,
+    ref_space: Res<XrPrimaryReferenceSpace>,
+    root: Res<XrRootTransform>,
+    current: Option<ResMut<OxrEnvironmentDepth>>,
+    mut commands: Commands,
+) {
+    let _span = info_span!("xr_acquire_environment_depth");
+    let info = EnvironmentDepthImageAcquireInfoMETA {
+        ty: sys::StructureType::from_raw(TYPE_ENVIRONMENT_DEPTH_IMAGE_ACQUIRE_INFO_META),
+        next: ptr::null(),
+        space: ref_space.as_raw_openxr_space(),
+        display_time: frame_state.predicted_display_time,
+    };
+    let mut image: EnvironmentDepthImageMETA = unsafe { mem::zeroed() };
+    image.ty = sys::StructureType::from_raw(TYPE_ENVIRONMENT_DEPTH_IMAGE_META);
+    for view in &mut image.views {
+        view.ty = sys::StructureType::from_raw(TYPE_ENVIRONMENT_DEPTH_IMAGE_VIEW_META);
+    }
+    let result = unsafe { (provider.fns.acquire_image)(provider.provider, &info, &mut image) };
+    if result.into_raw() == ENVIRONMENT_DEPTH_NOT_AVAILABLE_META {
+        if let Some(mut current) = current {
+            current.updated = false;
+        }
+        return;
+    }
+    if let Err(err) = cvt(result) {
+        warn!("Failed to acquire environment depth: {err}");
+        return;
+    }
+
+    let views = image.views.map(|view| {
+        let pose = view.pose.to_transform();
+        let world_from_view = root.0.mul_transform(pose).compute_matrix();
+        OxrEnvironmentDepthView {
+            pose,
+            fov: view.fov,
+            view_projection: depth_projection(view.fov, image.near_z, image.far_z)
+                * world_from_view.inverse(),
+        }
+    });
+    commands.insert_resource(OxrEnvironmentDepth {
+        image_index: image.swapchain_index,
+        views,
+        near: image.near_z,
+        far: image.far_z,
+        display_time: frame_state.predicted_display_time,
+        updated: true,
+    });
+}
+
+/// The OpenGL style projection the environment depth is stored with, the depth is mapped from -1..1 to 0..1.
+fn depth_projection(fov: openxr::Fovf, near: f32, far: f32) -> Mat4 {
+    let left = fov.angle_left.tan();
+    let right = fov.angle_right.tan();
+    let up = fov.angle_up.tan();
+    let down = fov.angle_down.tan();
+    let width = right - left;
+    let height = up - down;
+    let (z_scale, z_offset) = if far.is_finite() {
+        (
+            -(far + near) / (far - near),
+            -2.0 * far * near / (far - near),
+        )
+    } else {
+        (-1.0, -2.0 * near)
+    };
+    Mat4::from_cols(
+        Vec4::new(2.0 / width, 0.0, 0.0, 0.0),
+        Vec4::new(0.0, 2.0 / height, 0.0, 0.0),
+        Vec4::new((right + left) / width, (up + down) / height, z_scale, -1.0),
+        Vec4::new(0.0, 0.0, z_offset, 0.0),
+    )
+}
+
+fn prepare_environment_depth_texture(
+    depth: Res<OxrEnvironmentDepth>,
+    images: Res<OxrEnvironmentDepthImages>,
+    device: Res<RenderDevice>,
+    queue: Res<RenderQueue>,
+    texture: Option<ResMut<OxrEnvironmentDepthTexture>>,
+    mut commands: Commands,
+) {
+    if !depth.is_changed() && texture.is_some() {
+        return;
+    }
+    let Some(image) = images.get(depth.image_index as usize) else {
+        return;
+    };
+    let view = image.create_view(&wgpu::TextureViewDescriptor {
+        dimension: Some(wgpu::TextureViewDimension::D2Array),
+        ..default()
+    });
+    let uniform = OxrEnvironmentDepthUniform {
+        view_projections: depth.views.map(|view| view.view_projection),
+        near: depth.near,
+        far: if depth.far.is_finite() {
+            depth.far
+        } else {
+            0.0
+        },
+    };
+    match texture {
+        Some(mut texture) => {
+            texture.texture = view.into();
+            texture.uniform.set(uniform);
+            texture.uniform.write_buffer(&device, &queue);
+        }
+        None => {
+            let mut buffer = UniformBuffer::from(uniform);
+            buffer.set_label(Some("xr_environment_depth_uniform"));
+            buffer.write_buffer(&device, &queue);
+            commands.insert_resource(OxrEnvironmentDepthTexture {
+                texture: view.into(),
+                uniform: buffer,
+            });
+        }
+    }
+}
+
+impl Drop for OxrEnvironmentDepthProvider {
+    fn drop(&mut self) {
+        unsafe {
+            (self.fns.stop_provider)(self.provider);
+            (self.fns.destroy_swapchain)(self.swapchain);
+            (self.fns.destroy_provider)(self.provider);
+        }
+    }
+}
+
+// `XR_META_environment_depth` is newer than the bindings of the openxr crate, so its types are declared here.
+const TYPE_ENVIRONMENT_DEPTH_PROVIDER_CREATE_INFO_META: i32 = 1000291000;
+const TYPE_ENVIRONMENT_DEPTH_SWAPCHAIN_CREATE_INFO_META: i32 = 1000291001;
+const TYPE_ENVIRONMENT_DEPTH_SWAPCHAIN_STATE_META: i32 = 1000291002;
+const TYPE_ENVIRONMENT_DEPTH_IMAGE_ACQUIRE_INFO_META: i32 = 1000291003;
+const TYPE_ENVIRONMENT_DEPTH_IMAGE_VIEW_META: i32 = 1000291004;
+const TYPE_ENVIRONMENT_DEPTH_IMAGE_META: i32 = 1000291005;
+const TYPE_ENVIRONMENT_DEPTH_HAND_REMOVAL_SET_INFO_META: i32 = 1000291006;
+const TYPE_SYSTEM_ENVIRONMENT_DEPTH_PROPERTIES_META: i32 = 1000291007;
+/// Success code of `xrAcquireEnvironmentDepthImageMETA` if no new depth is available yet.
+const ENVIRONMENT_DEPTH_NOT_AVAILABLE_META: i32 = 1000291000;
+
+#[repr(C)]
+struct EnvironmentDepthProviderCreateInfoMETA {
+    ty: sys::StructureType,
+    next: *const std::ffi::c_void,
+    create_flags: u64,
+}
+
+#[repr(C)]
+struct EnvironmentDepthSwapchainCreateInfoMETA {
+    ty: sys::StructureType,
+    next: *const std::ffi::c_void,
+    create_flags: u64,
+}
+
+#[repr(C)]
+struct EnvironmentDepthSwapchainStateMETA {
+    ty: sys::StructureType,
+    next: *mut std::ffi::c_void,
+    width: u32,
+    height: u32,
+}
+
+#[repr(C)]
+struct EnvironmentDepthImageAcquireInfoMETA {
+    ty: sys::StructureType,
+    next: *const std::ffi::c_void,
+    space: sys::Space,
+    display_time: openxr::Time,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct EnvironmentDepthImageViewMETA {
+    ty: sys::StructureType,
+    next: *const std::ffi::c_void,
+    fov: openxr::Fovf,
+    pose: openxr::Posef,
+}
+
+#[repr(C)]
+struct EnvironmentDepthImageMETA {
+    ty: sys::StructureType,
+    next: *const std::ffi::c_void,
+    swapchain_index: u32,
+    near_z: f32,
+    far_z: f32,
+    views: [EnvironmentDepthImageViewMETA; 2],
+}
+
+#[repr(C)]
+struct EnvironmentDepthHandRemovalSetInfoMETA {
+    ty: sys::StructureType,
+    next: *const std::ffi::c_void,
+    enabled: sys::Bool32,
+}
+
+#[repr(C)]
+struct SystemEnvironmentDepthPropertiesMETA {
+    ty: sys::StructureType,
+    next: *mut std::ffi::c_void,
+    supports_environment_depth: sys::Bool32,
+    supports_hand_removal: sys::Bool32,
+}
+
+#[derive(Clone, Copy)]
+struct EnvironmentDepthFns {
+    create_provider: unsafe extern "system" fn(
+        sys::Session,
+        *const EnvironmentDepthProviderCreateInfoMETA,
+        *mut u64,
+    ) -> sys::Result,
+    destroy_provider: unsafe extern "system" fn(u64) -> sys::Result,
+    start_provider: unsafe extern "system" fn(u64) -> sys::Result,
+    stop_provider: unsafe extern "system" fn(u64) -> sys::Result,
+    create_swapchain: unsafe extern "system" fn(
+        u64,
+        *const EnvironmentDepthSwapchainCreateInfoMETA,
+        *mut u64,
+    ) -> sys::Result,
+    destroy_swapchain: unsafe extern "system" fn(u64) -> sys::Result,
+    enumerate_swapchain_images: unsafe extern "system" fn(
+        u64,
+        u32,
+        *mut u32,
+        *mut sys::SwapchainImageBaseHeader,
+    ) -> sys::Result,
+    get_swapchain_state:
+        unsafe extern "system" fn(u64, *mut EnvironmentDepthSwapchainStateMETA) -> sys::Result,
+    acquire_image: unsafe extern "system" fn(
+        u64,
+        *const EnvironmentDepthImageAcquireInfoMETA,
+        *mut EnvironmentDepthImageMETA,
+    ) -> sys::Result,
+    set_hand_removal: unsafe extern "system" fn(
+        u64,
+        *const EnvironmentDepthHandRemovalSetInfoMETA,
+    ) -> sys::Result,
+}
+
+impl EnvironmentDepthFns {
+    fn load(instance: &openxr::Instance) -> openxr::Result<Self> {
+        unsafe {
+            Ok(Self {
+                create_provider: load_fn(instance, b"xrCreateEnvironmentDepthProviderMETA\0")?,
+                destroy_provider: load_fn(instance, b"xrDestroyEnvironmentDepthProviderMETA\0")?,
+                start_provider: load_fn(instance, b"xrStartEnvironmentDepthProviderMETA\0")?,
+                stop_provider: load_fn(instance, b"xrStopEnvironmentDepthProviderMETA\0")?,
+                create_swapchain: load_fn(instance, b"xrCreateEnvironmentDepthSwapchainMETA\0")?,
+                destroy_swapchain: load_fn(instance, b"xrDestroyEnvironmentDepthSwapchainMETA\0")?,
+                enumerate_swapchain_images: load_fn(
+                    instance,
+                    b"xrEnumerateEnvironmentDepthSwapchainImagesMETA\0",
+                )?,
+                get_swapchain_state: load_fn(
+                    instance,
+                    b"xrGetEnvironmentDepthSwapchainStateMETA\0",
+                )?,
+                acquire_image: load_fn(instance, b"xrAcquireEnvironmentDepthImageMETA\0")?,
+                set_hand_removal: load_fn(instance, b"xrSetEnvironmentDepthHandRemovalMETA\0")?,
+            })
+        }
+    }
+}
+
+/// # Safety
+///
+/// `T` has to be the function pointer type of the function called `name`, which has to be nul terminated.
+unsafe fn load_fn<T: Copy>(instance: &openxr::Instance, name: &[u8]) -> openxr::Result<T> {
+    let mut function = None;
+    cvt((instance.fp().get_instance_proc_addr)(
+        instance.as_raw(),
+        name.as_ptr() as _,
+        &mut function,
+    ))?;
+    let function = function.ok_or(sys::Result::ERROR_FUNCTION_UNSUPPORTED)?;
+    Ok(mem::transmute_copy(&function))
+}
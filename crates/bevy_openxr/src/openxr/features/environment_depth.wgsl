@@ -0,0 +1,45 @@
+#define_import_path bevy_mod_openxr::environment_depth
+
+// Matches `OxrEnvironmentDepthUniform`.
+struct EnvironmentDepth {
+    view_projections: array<mat4x4<f32>, 2>,
+    near: f32,
+    // 0 for an infinite far plane
+    far: f32,
+}
+
+// Converts a value of the environment depth texture to the distance from the depth camera along its view direction in meters.
+fn environment_depth_to_linear(depth: f32, near: f32, far: f32) -> f32 {
+    let ndc = depth * 2.0 - 1.0;
+    if far == 0.0 {
+        return 2.0 * near / (1.0 - ndc);
+    }
+    return 2.0 * near * far / (far + near - ndc * (far - near));
+}
+
+// Returns 1 if the fragment at `world_position` is hidden behind the real environment as seen from the view `view_index`, 0 otherwise.
+//
+// `bias` is the distance in meters the fragment has to be behind the environment to be occluded,
+// a few centimeters hide the noise of the depth estimation.
+// Fragments outside of the area covered by the environment depth are never occluded.
+fn environment_depth_occlusion(
+    depth_texture: texture_depth_2d_array,
+    environment_depth: EnvironmentDepth,
+    view_index: u32,
+    world_position: vec3<f32>,
+    bias: f32,
+) -> f32 {
+    let clip = environment_depth.view_projections[view_index] * vec4(world_position, 1.0);
+    if clip.w <= 0.0 {
+        return 0.0;
+    }
+    let ndc = clip.xy / clip.w;
+    let uv = vec2(ndc.x * 0.5 + 0.5, 0.5 - ndc.y * 0.5);
+    if any(uv < vec2(0.0)) || any(uv >= vec2(1.0)) {
+        return 0.0;
+    }
+    let texel = vec2<i32>(uv * vec2<f32>(textureDimensions(depth_texture)));
+    let depth = textureLoad(depth_texture, texel, view_index, 0);
+    let environment = environment_depth_to_linear(depth, environment_depth.near, environment_depth.far);
+    return select(0.0, 1.0, clip.w > environment + bias);
+}
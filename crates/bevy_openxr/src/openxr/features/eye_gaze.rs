@@ -0,0 +1,224 @@
+use std::borrow::Cow;
+
+use bevy::prelude::*;
+use bevy_mod_xr::{
+    session::{XrPreDestroySession, XrRootTransform, XrSessionCreated},
+    spaces::{XrPrimaryReferenceSpace, XrSpace},
+    types::XrPose,
+};
+use openxr::{sys, SpaceLocationFlags};
+
+use crate::{
+    action_binding::{OxrSendActionBindings, OxrSuggestActionBinding},
+    action_set_attaching::OxrAttachActionSet,
+    action_set_syncing::{OxrActionSetSyncSet, OxrSyncActionSet},
+    error::cvt,
+    exts::OxrEnabledExtensions,
+    helper_traits::ToTransform,
+    openxr_session_available, openxr_session_running,
+    resources::{OxrFrameState, OxrInstance, OxrSystemId, Pipelined},
+    session::OxrSession,
+};
+
+/// Tracks where the user is looking and stores it in the [`OxrEyeGaze`] and [`OxrEyeGazeRay`] resources.
+///
+/// Requires [`XR_EXT_eye_gaze_interaction`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#XR_EXT_eye_gaze_interaction).
+/// Does nothing if the extension is unavailable or the system doesn't support eye tracking.
+pub struct OxrEyeGazePlugin;
+
+impl Plugin for OxrEyeGazePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OxrSendActionBindings,
+            suggest_eye_gaze_binding.run_if(resource_exists::<OxrEyeGazeAction>),
+        )
+        .add_systems(
+            XrSessionCreated,
+            create_eye_gaze_space.run_if(resource_exists::<OxrEyeGazeAction>),
+        )
+        .add_systems(
+            PreUpdate,
+            (
+                sync_eye_gaze_set.before(OxrActionSetSyncSet),
+                locate_eye_gaze
+                    .after(OxrActionSetSyncSet)
+                    .run_if(resource_exists::<OxrEyeGazeSpace>),
+            )
+                .run_if(openxr_session_running)
+                .run_if(resource_exists::<OxrEyeGazeAction>),
+        );
+    }
+
+    // the extensions are inserted by the `OxrInitPlugin`, which may be added after this plugin
+    fn finish(&self, app: &mut App) {
+        if !app
+            .world()
+            .get_resource::<OxrEnabledExtensions>()
+            .is_some_and(|exts| exts.eye_gaze())
+        {
+            info!("Eye gaze interaction extension not enabled, disabling eye gaze");
+            return;
+        }
+
+        app.init_resource::<OxrEyeGaze>()
+            .add_systems(
+                Startup,
+                create_eye_gaze_action.run_if(openxr_session_available),
+            )
+            .add_systems(XrPreDestroySession, cleanup);
+    }
+}
+
+/// The latest eye gaze of the user in world space, looking along the forward direction.
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct OxrEyeGaze {
+    pub transform: GlobalTransform,
+    /// If the gaze is tracked.
+    ///
+    /// This is false while the runtime doesn't provide the gaze, e.g. because the user didn't give their consent to eye tracking.
+    pub valid: bool,
+}
+
+/// The ray the user is looking along in world space. Only exists while the [`OxrEyeGaze`] is valid.
+#[derive(Resource, Clone, Copy, Debug, Deref)]
+pub struct OxrEyeGazeRay(pub Ray3d);
+
+#[derive(Resource)]
+struct OxrEyeGazeAction {
+    set: openxr::ActionSet,
+    action: openxr::Action<openxr::Posef>,
+}
+
+#[derive(Resource)]
+struct OxrEyeGazeSpace(XrSpace);
+
+fn create_eye_gaze_action(
+    instance: Res<OxrInstance>,
+    system_id: Res<OxrSystemId>,
+    mut cmds: Commands,
+) {
+    match supports_eye_gaze_interaction(&instance, *system_id) {
+        Ok(true) => {}
+        Ok(false) => {
+            info!("System doesn't support eye gaze interaction, disabling eye gaze");
+            return;
+        }
+        Err(err) => {
+            error!("Failed to check eye gaze interaction support: {err}");
+            return;
+        }
+    }
+    let action = instance
+        .create_action_set("eye_gaze", "Eye Gaze", 0)
+        .and_then(|set| {
+            let action = set.create_action("gaze_pose", "Gaze Pose", &[])?;
+            Ok(OxrEyeGazeAction { set, action })
+        });
+    match action {
+        Ok(action) => cmds.insert_resource(action),
+        Err(err) => error!("Failed to create eye gaze action: {err}"),
+    }
+}
+
+fn suggest_eye_gaze_binding(
+    action: Res<OxrEyeGazeAction>,
+    mut bindings: EventWriter<OxrSuggestActionBinding>,
+) {
+    bindings.send(OxrSuggestActionBinding {
+        action: action.action.as_raw(),
+        interaction_profile: Cow::Borrowed("/interaction_profiles/ext/eye_gaze_interaction"),
+        bindings: vec![Cow::Borrowed("/user/eyes_ext/input/gaze_ext/pose")],
+    });
+}
+
+fn create_eye_gaze_space(
+    session: Res<OxrSession>,
+    action: Res<OxrEyeGazeAction>,
+    mut attach: EventWriter<OxrAttachActionSet>,
+    mut cmds: Commands,
+) {
+    attach.send(OxrAttachActionSet(action.set.clone()));
+    match session.create_action_space(&action.action, openxr::Path::NULL, XrPose::IDENTITY) {
+        Ok(space) => cmds.insert_resource(OxrEyeGazeSpace(space)),
+        Err(err) => error!("Failed to create eye gaze space: {err}"),
+    }
+}
+
+fn sync_eye_gaze_set(action: Res<OxrEyeGazeAction>, mut sync: EventWriter<OxrSyncActionSet>) {
+    sync.send(OxrSyncActionSet(action.set.clone()));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn locate_eye_gaze(
+    session: Res<OxrSession>,
+    action: Res<OxrEyeGazeAction>,
+    space: Res<OxrEyeGazeSpace>,
+    ref_space: Res<XrPrimaryReferenceSpace>,
+    frame_state: Res<OxrFrameState>,
+    root: Res<XrRootTransform>,
+    pipelined: Option<Res<Pipelined>>,
+    mut gaze: ResMut<OxrEyeGaze>,
+    mut cmds: Commands,
+) {
+    let time = frame_state.locate_time(pipelined.is_some());
+    let location = action
+        .action
+        .is_active(&session, openxr::Path::NULL)
+        .and_then(|active| {
+            active
+                .then(|| session.locate_space(&space.0, &ref_space, time))
+                .transpose()
+        });
+    let location = match location {
+        Ok(location) => location,
+        Err(err) => {
+            warn!("error while locating eye gaze: {}", err);
+            None
+        }
+    };
+
+    let valid = location.as_ref().is_some_and(|location| {
+        location
+            .location_flags
+            .contains(SpaceLocationFlags::POSITION_VALID | SpaceLocationFlags::ORIENTATION_VALID)
+    });
+    gaze.valid = valid;
+    match location {
+        Some(location) if valid => {
+            gaze.transform = root.0.mul_transform(location.pose.to_transform());
+            cmds.insert_resource(OxrEyeGazeRay(Ray3d {
+                origin: gaze.transform.translation(),
+                direction: gaze.transform.forward(),
+            }));
+        }
+        _ => cmds.remove_resource::<OxrEyeGazeRay>(),
+    }
+}
+
+fn cleanup(mut gaze: ResMut<OxrEyeGaze>, mut cmds: Commands) {
+    // the space is destroyed together with the session
+    gaze.valid = false;
+    cmds.remove_resource::<OxrEyeGazeSpace>();
+    cmds.remove_resource::<OxrEyeGazeRay>();
+}
+
+#[inline]
+pub fn supports_eye_gaze_interaction(
+    instance: &OxrInstance,
+    system: OxrSystemId,
+) -> openxr::Result<bool> {
+    unsafe {
+        let mut eye_gaze = sys::SystemEyeGazeInteractionPropertiesEXT {
+            ty: sys::SystemEyeGazeInteractionPropertiesEXT::TYPE,
+            next: std::ptr::null_mut(),
+            supports_eye_gaze_interaction: sys::FALSE,
+        };
+        let mut p = sys::SystemProperties::out(&mut eye_gaze as *mut _ as _);
+        cvt((instance.fp().get_system_properties)(
+            instance.as_raw(),
+            system.0,
+            p.as_mut_ptr(),
+        ))?;
+        Ok(eye_gaze.supports_eye_gaze_interaction.into())
+    }
+}
@@ -0,0 +1,219 @@
+use std::{mem, ptr};
+
+use bevy::prelude::*;
+use bevy::render::extract_resource::{ExtractResource, ExtractResourcePlugin};
+use bevy::render::{Render, RenderApp};
+use bevy_mod_xr::session::XrRenderSet;
+use openxr::sys;
+
+use crate::{
+    error::cvt,
+    exts::OxrEnabledExtensions,
+    graphics::graphics_match,
+    init::should_run_frame_loop,
+    next_chain::{OxrNextChainStructBase, OxrNextChainStructProvider},
+    resources::{OxrPerEyeSwapchains, OxrSwapchain},
+    session::{OxrSession, OxrSwapchainCreateNextChain, OxrSwapchainCreateNextProvider},
+};
+
+/// Enables fixed foveated rendering for the main swapchain.
+///
+/// Requires `XR_FB_foveation`, `XR_FB_foveation_configuration` and `XR_FB_swapchain_update_state`,
+/// see [`OxrExtensions::enable_fb_foveation`](crate::exts::OxrExtensions::enable_fb_foveation).
+/// Does nothing if any of them are unavailable.
+pub struct OxrFoveationPlugin {
+    /// The foveation applied once the session is created.
+    pub foveation: OxrFoveation,
+}
+
+impl Default for OxrFoveationPlugin {
+    fn default() -> Self {
+        Self {
+            foveation: OxrFoveation {
+                level: FoveationLevel::Medium,
+                vertical_offset: 0.0,
+            },
+        }
+    }
+}
+
+impl Plugin for OxrFoveationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(ExtractResourcePlugin::<OxrFoveation>::default());
+
+        app.sub_app_mut(RenderApp).add_systems(
+            Render,
+            update_foveation
+                .in_set(XrRenderSet::PreRender)
+                .run_if(should_run_frame_loop)
+                .run_if(resource_exists::<OxrSwapchain>)
+                .run_if(resource_exists::<OxrFoveation>)
+                .run_if(
+                    resource_exists_and_changed::<OxrFoveation>
+                        .or_else(resource_added::<OxrSwapchain>),
+                ),
+        );
+    }
+
+    // the extensions and the next chain are inserted by the `OxrInitPlugin`, which may be added after this plugin
+    fn finish(&self, app: &mut App) {
+        if !app
+            .world()
+            .get_resource::<OxrEnabledExtensions>()
+            .is_some_and(|exts| exts.foveation())
+        {
+            info!("Foveation extensions not enabled, disabling foveation");
+            return;
+        }
+
+        let Some(mut chain) = app
+            .world_mut()
+            .get_non_send_resource_mut::<OxrSwapchainCreateNextChain>()
+        else {
+            warn!("No swapchain create next chain, disabling foveation");
+            return;
+        };
+        chain.push(OxrSwapchainCreateInfoFoveation::new(
+            sys::SwapchainCreateFoveationFlagsFB::FRAGMENT_DENSITY_MAP,
+        ));
+
+        app.insert_resource(self.foveation);
+    }
+}
+
+/// The foveation level of the main swapchain. Changing this resource updates the swapchain at runtime.
+#[derive(Resource, ExtractResource, Clone, Copy, Debug, PartialEq)]
+pub struct OxrFoveation {
+    pub level: FoveationLevel,
+    /// Vertical offset of the foveation center in degrees, positive values move it up.
+    pub vertical_offset: f32,
+}
+
+impl OxrFoveation {
+    pub fn set_foveation_level(&mut self, level: FoveationLevel) {
+        self.level = level;
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FoveationLevel {
+    None,
+    Low,
+    Medium,
+    High,
+}
+
+impl From<FoveationLevel> for sys::FoveationLevelFB {
+    fn from(level: FoveationLevel) -> Self {
+        match level {
+            FoveationLevel::None => sys::FoveationLevelFB::NONE,
+            FoveationLevel::Low => sys::FoveationLevelFB::LOW,
+            FoveationLevel::Medium => sys::FoveationLevelFB::MEDIUM,
+            FoveationLevel::High => sys::FoveationLevelFB::HIGH,
+        }
+    }
+}
+
+fn update_foveation(
+    foveation: Res<OxrFoveation>,
+    session: Res<OxrSession>,
+    swapchain: Res<OxrSwapchain>,
+    per_eye_swapchains: Option<Res<OxrPerEyeSwapchains>>,
+) {
+    let per_eye_swapchains = per_eye_swapchains
+        .iter()
+        .flat_map(|swapchains| swapchains.iter());
+    for swapchain in std::iter::once(&*swapchain).chain(per_eye_swapchains) {
+        if let Err(err) = session.update_swapchain_foveation(swapchain, *foveation) {
+            warn!("Failed to update swapchain foveation: {err}");
+        }
+    }
+}
+
+impl OxrSession {
+    /// Applies a new foveation profile to `swapchain`.
+    ///
+    /// The swapchain has to be created with an [`OxrSwapchainCreateInfoFoveation`] in its next chain.
+    pub fn update_swapchain_foveation(
+        &self,
+        swapchain: &OxrSwapchain,
+        foveation: OxrFoveation,
+    ) -> openxr::Result<()> {
+        let exts = self.instance().exts();
+        let (Some(foveation_fns), Some(update_state_fns)) = (
+            exts.fb_foveation.as_ref(),
+            exts.fb_swapchain_update_state.as_ref(),
+        ) else {
+            return Err(sys::Result::ERROR_EXTENSION_NOT_PRESENT);
+        };
+        let level_info = sys::FoveationLevelProfileCreateInfoFB {
+            ty: sys::FoveationLevelProfileCreateInfoFB::TYPE,
+            next: ptr::null_mut(),
+            level: foveation.level.into(),
+            vertical_offset: foveation.vertical_offset,
+            dynamic: sys::FoveationDynamicFB::DISABLED,
+        };
+        let profile_info = sys::FoveationProfileCreateInfoFB {
+            ty: sys::FoveationProfileCreateInfoFB::TYPE,
+            next: &level_info as *const _ as _,
+        };
+        let mut profile = sys::FoveationProfileFB::NULL;
+        unsafe {
+            cvt((foveation_fns.create_foveation_profile)(
+                self.as_raw(),
+                &profile_info,
+                &mut profile,
+            ))?;
+            let state = sys::SwapchainStateFoveationFB {
+                ty: sys::SwapchainStateFoveationFB::TYPE,
+                next: ptr::null_mut(),
+                flags: sys::SwapchainStateFoveationFlagsFB::EMPTY,
+                profile,
+            };
+            let raw_swapchain = graphics_match!(
+                &swapchain.0;
+                swap => swap.as_raw()
+            );
+            let result = cvt((update_state_fns.update_swapchain)(
+                raw_swapchain,
+                &state as *const _ as _,
+            ));
+            // the profile can be destroyed as soon as it was applied
+            (foveation_fns.destroy_foveation_profile)(profile);
+            result?;
+        }
+        Ok(())
+    }
+}
+
+/// Chained onto the create info of a swapchain to allow applying foveation profiles to it.
+pub struct OxrSwapchainCreateInfoFoveation {
+    inner: sys::SwapchainCreateInfoFoveationFB,
+}
+
+impl OxrSwapchainCreateInfoFoveation {
+    pub const fn new(flags: sys::SwapchainCreateFoveationFlagsFB) -> Self {
+        Self {
+            inner: sys::SwapchainCreateInfoFoveationFB {
+                ty: sys::SwapchainCreateInfoFoveationFB::TYPE,
+                next: ptr::null_mut(),
+                flags,
+            },
+        }
+    }
+}
+
+impl OxrNextChainStructProvider for OxrSwapchainCreateInfoFoveation {
+    fn header(&self) -> &OxrNextChainStructBase {
+        unsafe { mem::transmute(&self.inner) }
+    }
+
+    fn set_next(&mut self, next: &OxrNextChainStructBase) {
+        self.inner.next = next as *const _ as *mut _;
+    }
+    fn clear_next(&mut self) {
+        self.inner.next = ptr::null_mut();
+    }
+}
+
+impl OxrSwapchainCreateNextProvider for OxrSwapchainCreateInfoFoveation {}
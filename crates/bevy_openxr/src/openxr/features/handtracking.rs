@@ -3,19 +3,22 @@ use bevy_mod_xr::hands::{
     spawn_hand_bones, HandBone, HandBoneRadius, HandSide, SpawnHandTracker,
     SpawnHandTrackerCommandExecutor,
 };
-use bevy_mod_xr::hands::{LeftHand, RightHand, XrHandBoneEntities};
+use bevy_mod_xr::hands::{LeftHand, RightHand, XrHandBoneEntities, HAND_JOINT_COUNT};
 use bevy_mod_xr::session::{XrPreDestroySession, XrSessionCreated, XrTrackingRoot};
 use bevy_mod_xr::spaces::{
-    XrPrimaryReferenceSpace, XrReferenceSpace, XrSpaceLocationFlags, XrSpaceVelocityFlags,
+    XrPrimaryReferenceSpace, XrReferenceSpace, XrSpace, XrSpaceLocationFlags, XrSpaceVelocityFlags,
     XrVelocity,
 };
-use openxr::{SpaceLocationFlags, SpaceVelocityFlags};
+use openxr::{sys, SpaceLocationFlags, SpaceVelocityFlags};
+use std::{mem::MaybeUninit, ptr};
 
-use crate::helper_traits::ToVec3;
+use crate::error::cvt;
+use crate::exts::OxrEnabledExtensions;
+use crate::helper_traits::{ToTransform, ToVec3};
 use crate::resources::OxrFrameState;
 use crate::resources::Pipelined;
 use crate::session::OxrSession;
-use crate::spaces::{OxrSpaceLocationFlags, OxrSpaceVelocityFlags};
+use crate::spaces::{OxrSpaceExt, OxrSpaceLocationFlags, OxrSpaceVelocityFlags};
 use crate::{openxr_session_available, openxr_session_running};
 
 pub struct HandTrackingPlugin {
@@ -31,29 +34,98 @@ impl Default for HandTrackingPlugin {
 
 impl Plugin for HandTrackingPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(PreUpdate, locate_hands.run_if(openxr_session_running));
+        app.init_resource::<OxrRequestedHandDataSources>()
+            .add_systems(
+                PreUpdate,
+                (
+                    locate_hands.run_if(hand_tracking_enabled),
+                    update_hand_data_sources
+                        .after(locate_hands)
+                        .run_if(hand_tracking_data_source_enabled),
+                )
+                    .run_if(openxr_session_running),
+            );
         if self.default_hands {
             app.add_systems(XrPreDestroySession, clean_up_default_hands)
-                .add_systems(XrSessionCreated, spawn_default_hands);
+                .add_systems(
+                    XrSessionCreated,
+                    spawn_default_hands.run_if(hand_tracking_enabled),
+                );
         }
         app.add_systems(Startup, set_spawn_executor.run_if(openxr_session_available));
     }
 }
 
+/// A [`Condition`](bevy::ecs::schedule::Condition) system that says if `XR_EXT_hand_tracking` was enabled on the instance.
+pub fn hand_tracking_enabled(exts: Option<Res<OxrEnabledExtensions>>) -> bool {
+    exts.is_some_and(|exts| exts.hand_tracking())
+}
+
+/// A [`Condition`](bevy::ecs::schedule::Condition) system that says if `XR_EXT_hand_tracking_data_source` was enabled on the instance.
+pub fn hand_tracking_data_source_enabled(exts: Option<Res<OxrEnabledExtensions>>) -> bool {
+    exts.is_some_and(|exts| exts.hand_tracking_data_source())
+}
+
+/// The sources hand trackers are allowed to be driven by, used when a hand tracker is spawned.
+///
+/// Only used if `XR_EXT_hand_tracking_data_source` is enabled, requests both the camera based
+/// [`UNOBSTRUCTED`](sys::HandTrackingDataSourceEXT::UNOBSTRUCTED) and the [`CONTROLLER`](sys::HandTrackingDataSourceEXT::CONTROLLER) source by default.
+#[derive(Resource, Clone, Debug, Deref, DerefMut)]
+pub struct OxrRequestedHandDataSources(pub Vec<sys::HandTrackingDataSourceEXT>);
+
+impl Default for OxrRequestedHandDataSources {
+    fn default() -> Self {
+        Self(vec![
+            sys::HandTrackingDataSourceEXT::UNOBSTRUCTED,
+            sys::HandTrackingDataSourceEXT::CONTROLLER,
+        ])
+    }
+}
+
+/// The source that produced the current joints of an [`OxrHandTracker`], inserted on hand trackers
+/// if `XR_EXT_hand_tracking_data_source` is enabled.
+///
+/// Can be used to e.g. hide the controller models while the hands are tracked by the cameras.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OxrHandDataSource {
+    /// [`None`] while the hand isn't tracked.
+    pub active: Option<sys::HandTrackingDataSourceEXT>,
+}
+
+impl OxrHandDataSource {
+    /// Whether the joints are tracked from the actual hand, e.g. by the headset cameras.
+    pub fn is_unobstructed(&self) -> bool {
+        self.active == Some(sys::HandTrackingDataSourceEXT::UNOBSTRUCTED)
+    }
+    /// Whether the joints are inferred from a controller held by the hand.
+    pub fn is_controller(&self) -> bool {
+        self.active == Some(sys::HandTrackingDataSourceEXT::CONTROLLER)
+    }
+}
+
 fn set_spawn_executor(mut cmds: Commands) {
     cmds.insert_resource(SpawnHandTrackerCommandExecutor(handle_tracker_spawn))
 }
 
 fn handle_tracker_spawn(world: &mut World, tracker: Entity, side: HandSide) {
-    let Some(session) = world.get_resource_mut::<OxrSession>() else {
+    let Some(session) = world.get_resource::<OxrSession>() else {
         error!("unable to get session while creating hand tracker");
         return;
     };
     debug!("spawning hand");
-    let oxr_tracker = match session.create_hand_tracker(match side {
+    let hand = match side {
         HandSide::Left => openxr::HandEXT::LEFT,
         HandSide::Right => openxr::HandEXT::RIGHT,
-    }) {
+    };
+    let data_sources = world
+        .get_resource::<OxrEnabledExtensions>()
+        .is_some_and(|exts| exts.hand_tracking_data_source())
+        .then(|| world.resource::<OxrRequestedHandDataSources>());
+    let oxr_tracker = match data_sources {
+        Some(data_sources) => session.create_hand_tracker_with_data_sources(hand, data_sources),
+        None => session.create_hand_tracker(hand),
+    };
+    let oxr_tracker = match oxr_tracker {
         Ok(t) => t,
         Err(openxr::sys::Result::ERROR_EXTENSION_NOT_PRESENT) => {
             warn!("Handtracking Extension not loaded, Unable to create Handtracker!");
@@ -65,9 +137,12 @@ fn handle_tracker_spawn(world: &mut World, tracker: Entity, side: HandSide) {
         }
     };
 
-    world
-        .entity_mut(tracker)
-        .insert(OxrHandTracker(oxr_tracker));
+    let has_data_sources = data_sources.is_some();
+    let mut tracker = world.entity_mut(tracker);
+    tracker.insert(OxrHandTracker(oxr_tracker));
+    if has_data_sources {
+        tracker.insert(OxrHandDataSource::default());
+    }
 }
 
 fn spawn_default_hands(mut cmds: Commands, root: Query<Entity, With<XrTrackingRoot>>) {
@@ -123,6 +198,129 @@ fn clean_up_default_hands(
 #[derive(Deref, DerefMut, Component)]
 pub struct OxrHandTracker(pub openxr::HandTracker);
 
+impl OxrHandTracker {
+    /// Locates all joints of this hand relative to `base`.
+    ///
+    /// Returns [`None`] if the hand is currently not tracked.
+    pub fn locate_hand_joints(
+        &self,
+        session: &OxrSession,
+        base: &XrSpace,
+        time: openxr::Time,
+    ) -> openxr::Result<Option<[OxrHandJoint; HAND_JOINT_COUNT]>> {
+        Ok(session
+            .locate_hand_joints(&self.0, base, time)?
+            .map(|joints| joints.map(OxrHandJoint::from)))
+    }
+}
+
+impl OxrHandTracker {
+    /// Returns the source that currently drives this hand.
+    ///
+    /// Returns [`None`] if the hand is currently not tracked.
+    /// Requires `XR_EXT_hand_tracking_data_source` and a tracker created with [`OxrSession::create_hand_tracker_with_data_sources`].
+    pub fn data_source(
+        &self,
+        session: &OxrSession,
+        base: &XrSpace,
+        time: openxr::Time,
+    ) -> openxr::Result<Option<sys::HandTrackingDataSourceEXT>> {
+        let exts = session.instance().exts();
+        if exts.ext_hand_tracking_data_source.is_none() {
+            return Err(sys::Result::ERROR_EXTENSION_NOT_PRESENT);
+        }
+        let fns = exts
+            .ext_hand_tracking
+            .as_ref()
+            .ok_or(sys::Result::ERROR_EXTENSION_NOT_PRESENT)?;
+        let locate_info = sys::HandJointsLocateInfoEXT {
+            ty: sys::HandJointsLocateInfoEXT::TYPE,
+            next: ptr::null(),
+            base_space: base.as_raw_openxr_space(),
+            time,
+        };
+        let mut state = sys::HandTrackingDataSourceStateEXT {
+            ty: sys::HandTrackingDataSourceStateEXT::TYPE,
+            next: ptr::null_mut(),
+            is_active: false.into(),
+            data_source: sys::HandTrackingDataSourceEXT::UNOBSTRUCTED,
+        };
+        let mut locations = MaybeUninit::<[openxr::HandJointLocation; HAND_JOINT_COUNT]>::uninit();
+        let mut location_info = sys::HandJointLocationsEXT {
+            ty: sys::HandJointLocationsEXT::TYPE,
+            next: &mut state as *mut _ as _,
+            is_active: false.into(),
+            joint_count: HAND_JOINT_COUNT as u32,
+            joint_locations: locations.as_mut_ptr() as _,
+        };
+        unsafe {
+            cvt((fns.locate_hand_joints)(
+                self.as_raw(),
+                &locate_info,
+                &mut location_info,
+            ))?;
+        }
+        Ok((location_info.is_active.into() && state.is_active.into()).then_some(state.data_source))
+    }
+}
+
+impl OxrSession {
+    /// Creates a hand tracker that may be driven by any of `data_sources`.
+    ///
+    /// Requires `XR_EXT_hand_tracking` and `XR_EXT_hand_tracking_data_source`,
+    /// use [`OxrHandTracker::data_source`] to get the source currently driving the hand.
+    pub fn create_hand_tracker_with_data_sources(
+        &self,
+        hand: openxr::HandEXT,
+        data_sources: &[sys::HandTrackingDataSourceEXT],
+    ) -> openxr::Result<openxr::HandTracker> {
+        let exts = self.instance().exts();
+        if exts.ext_hand_tracking_data_source.is_none() {
+            return Err(sys::Result::ERROR_EXTENSION_NOT_PRESENT);
+        }
+        let fns = exts
+            .ext_hand_tracking
+            .as_ref()
+            .ok_or(sys::Result::ERROR_EXTENSION_NOT_PRESENT)?;
+        let data_source_info = sys::HandTrackingDataSourceInfoEXT {
+            ty: sys::HandTrackingDataSourceInfoEXT::TYPE,
+            next: ptr::null(),
+            requested_data_source_count: data_sources.len() as u32,
+            requested_data_sources: data_sources.as_ptr() as *mut _,
+        };
+        let info = sys::HandTrackerCreateInfoEXT {
+            ty: sys::HandTrackerCreateInfoEXT::TYPE,
+            next: &data_source_info as *const _ as _,
+            hand,
+            hand_joint_set: sys::HandJointSetEXT::DEFAULT,
+        };
+        let mut handle = sys::HandTrackerEXT::NULL;
+        unsafe {
+            cvt((fns.create_hand_tracker)(self.as_raw(), &info, &mut handle))?;
+            Ok(openxr::HandTracker::from_raw(&self.0, handle))
+        }
+    }
+}
+
+/// The location of a single hand joint, relative to the space it was located in.
+#[derive(Clone, Copy)]
+pub struct OxrHandJoint {
+    pub transform: Transform,
+    pub radius: f32,
+    /// Use these to tell tracked joints apart from estimated ones.
+    pub flags: OxrSpaceLocationFlags,
+}
+
+impl From<openxr::HandJointLocation> for OxrHandJoint {
+    fn from(joint: openxr::HandJointLocation) -> Self {
+        Self {
+            transform: joint.pose.to_transform(),
+            radius: joint.radius,
+            flags: OxrSpaceLocationFlags(joint.location_flags),
+        }
+    }
+}
+
 fn locate_hands(
     default_ref_space: Res<XrPrimaryReferenceSpace>,
     frame_state: Res<OxrFrameState>,
@@ -150,14 +348,7 @@ fn locate_hands(
             .iter()
             .filter_map(|e| bone_query.get(*e).ok())
             .any(|v| v.3.is_some());
-        let time = if pipelined.is_some() {
-            openxr::Time::from_nanos(
-                frame_state.predicted_display_time.as_nanos()
-                    + frame_state.predicted_display_period.as_nanos(),
-            )
-        } else {
-            frame_state.predicted_display_time
-        };
+        let time = frame_state.locate_time(pipelined.is_some());
         let ref_space = ref_space.map(|v| &v.0).unwrap_or(&default_ref_space.0);
         let mut clear_flags = || {
             for e in hand_entities.0.iter() {
@@ -283,3 +474,28 @@ fn locate_hands(
         }
     }
 }
+
+fn update_hand_data_sources(
+    default_ref_space: Res<XrPrimaryReferenceSpace>,
+    frame_state: Res<OxrFrameState>,
+    session: Res<OxrSession>,
+    mut query: Query<(
+        &OxrHandTracker,
+        Option<&XrReferenceSpace>,
+        &mut OxrHandDataSource,
+    )>,
+    pipelined: Option<Res<Pipelined>>,
+) {
+    let time = frame_state.locate_time(pipelined.is_some());
+    for (tracker, ref_space, mut data_source) in &mut query {
+        let ref_space = ref_space.map(|v| &v.0).unwrap_or(&default_ref_space.0);
+        let active = match tracker.data_source(&session, ref_space, time) {
+            Ok(active) => active,
+            Err(err) => {
+                warn!("Error while getting hand data source: {}", err);
+                None
+            }
+        };
+        data_source.set_if_neq(OxrHandDataSource { active });
+    }
+}
@@ -0,0 +1,279 @@
+use bevy::{
+    core_pipeline::core_3d::Camera3dDepthTextureUsage,
+    prelude::*,
+    render::{
+        extract_resource::{ExtractResource, ExtractResourcePlugin},
+        renderer::{RenderDevice, RenderQueue},
+        view::ViewDepthTexture,
+        ExtractSchedule, MainWorld, Render, RenderApp,
+    },
+};
+use bevy_mod_xr::{
+    camera::XrCamera,
+    session::{XrPreDestroySession, XrRenderSet, XrSessionCreated},
+};
+
+use crate::{
+    exts::OxrEnabledExtensions,
+    init::{should_render, should_run_frame_loop, OxrSwapchainRecreated},
+    render::{end_frame, insert_texture_views, OxrRenderFrameCounters},
+    resources::{OxrDepthSwapchain, OxrGraphicsInfo, OxrSwapchainImages, OxrWaitImagePolicy},
+    session::OxrSession,
+    types::Result,
+};
+
+/// Creates the [`OxrDepthSwapchain`] and copies the depth of the [`XrCamera`]s into it every frame,
+/// so the runtime can use it for reprojection.
+///
+/// Replace the [`ProjectionLayer`](crate::layer_builder::ProjectionLayer) in the [`OxrRenderLayers`](crate::resources::OxrRenderLayers)
+/// with a [`ProjectionLayerDepth`](crate::layer_builder::ProjectionLayerDepth) to submit it.
+/// Requires [`XR_KHR_composition_layer_depth`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#XR_KHR_composition_layer_depth)
+/// and a runtime supporting [`Depth32Float`](wgpu::TextureFormat::Depth32Float) swapchains, does nothing otherwise.
+/// The depth can't be copied from multisampled depth textures, so [`Msaa`] has to be [`Off`](Msaa::Off),
+/// and views with a different resolution than the largest view, e.g. the peripheral views of quad view sessions, keep their old depth.
+pub struct OxrLayerDepthPlugin;
+
+impl Plugin for OxrLayerDepthPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(ExtractResourcePlugin::<OxrDepthSwapchainImages>::default())
+            .add_systems(XrSessionCreated, init_depth_swapchain)
+            .add_systems(XrPreDestroySession, cleanup_depth_swapchain)
+            .add_systems(
+                PostUpdate,
+                (
+                    allow_depth_copies,
+                    recreate_depth_swapchain.run_if(on_event::<OxrSwapchainRecreated>()),
+                )
+                    .run_if(resource_exists::<OxrDepthSwapchainImages>),
+            );
+
+        app.sub_app_mut(RenderApp)
+            .add_systems(ExtractSchedule, transfer_depth_swapchain)
+            .add_systems(
+                Render,
+                (
+                    acquire_depth_image
+                        .run_if(should_render)
+                        .after(insert_texture_views)
+                        .in_set(XrRenderSet::PreRender),
+                    clean_depth_image_index
+                        .run_if(not(should_render))
+                        .in_set(XrRenderSet::PreRender),
+                    (copy_depth, release_depth_image)
+                        .chain()
+                        .run_if(resource_exists::<OxrDepthImageIndex>)
+                        .before(end_frame)
+                        .in_set(XrRenderSet::PostRender),
+                )
+                    .run_if(should_run_frame_loop)
+                    .run_if(resource_exists::<OxrDepthSwapchain>),
+            )
+            .add_systems(XrPreDestroySession, cleanup_depth_swapchain_render);
+    }
+}
+
+/// The images of the [`OxrDepthSwapchain`], with one array layer per view.
+#[derive(Resource, ExtractResource, Clone, Copy)]
+pub struct OxrDepthSwapchainImages(pub OxrSwapchainImages);
+
+/// The index of the [`OxrDepthSwapchainImages`] acquired for the current frame,
+/// the [`ProjectionLayerDepth`](crate::layer_builder::ProjectionLayerDepth) only submits the depth while this exists.
+/// Only exists in the render world.
+#[derive(Resource, Clone, Copy, Debug, Deref)]
+pub struct OxrDepthImageIndex(pub u32);
+
+/// Used to transport the depth swapchain from the main world to the render world,
+/// [`None`] removes it from the render world.
+#[derive(Resource)]
+struct OxrDepthRenderResources(Option<OxrDepthSwapchain>);
+
+/// The format of the depth textures of bevy's cameras, copies require the swapchain to have the same format.
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+fn init_depth_swapchain(
+    exts: Res<OxrEnabledExtensions>,
+    session: Res<OxrSession>,
+    graphics_info: Res<OxrGraphicsInfo>,
+    device: Res<RenderDevice>,
+    mut commands: Commands,
+) {
+    if !exts.composition_layer_depth() {
+        info!("Composition layer depth extension not enabled, not submitting depth");
+        return;
+    }
+    match create_depth_swapchain(&session, device.wgpu_device(), &graphics_info) {
+        Ok(Some((swapchain, images))) => {
+            commands.insert_resource(images);
+            commands.insert_resource(OxrDepthRenderResources(Some(swapchain)));
+        }
+        Ok(None) => {
+            warn!("The runtime doesn't support {DEPTH_FORMAT:?} swapchains, not submitting depth")
+        }
+        Err(err) => error!("Failed to create depth swapchain: {err}"),
+    }
+}
+
+/// Recreates the depth swapchain at the new resolution after an [`OxrRecreateSwapchain`](crate::init::OxrRecreateSwapchain),
+/// so the depth always covers the [`image_rects`](crate::layer_builder::ProjectionLayerDepth::image_rects) of the colors.
+fn recreate_depth_swapchain(
+    mut events: EventReader<OxrSwapchainRecreated>,
+    session: Res<OxrSession>,
+    graphics_info: Res<OxrGraphicsInfo>,
+    device: Res<RenderDevice>,
+    mut commands: Commands,
+) {
+    events.clear();
+    match create_depth_swapchain(&session, device.wgpu_device(), &graphics_info) {
+        Ok(Some((swapchain, images))) => {
+            commands.insert_resource(images);
+            commands.insert_resource(OxrDepthRenderResources(Some(swapchain)));
+        }
+        result => {
+            if let Err(err) = result {
+                error!("Failed to recreate depth swapchain, not submitting depth anymore: {err}");
+            }
+            // the old depth swapchain is too small for the new resolution
+            commands.remove_resource::<OxrDepthSwapchainImages>();
+            commands.insert_resource(OxrDepthRenderResources(None));
+        }
+    }
+}
+
+fn create_depth_swapchain(
+    session: &OxrSession,
+    device: &wgpu::Device,
+    graphics_info: &OxrGraphicsInfo,
+) -> Result<Option<(OxrDepthSwapchain, OxrDepthSwapchainImages)>> {
+    if !session
+        .enumerate_depth_swapchain_formats()?
+        .contains(&DEPTH_FORMAT)
+    {
+        return Ok(None);
+    }
+    // large enough for every view, the focus views of quad view sessions can be larger than the others
+    let resolution = (0..graphics_info.view_count)
+        .map(|view_index| graphics_info.view_resolution(view_index))
+        .fold(UVec2::ZERO, UVec2::max);
+    let swapchain =
+        session.create_depth_swapchain(DEPTH_FORMAT, resolution, graphics_info.view_count)?;
+    let images =
+        swapchain.enumerate_images(device, DEPTH_FORMAT, resolution, graphics_info.view_count)?;
+    Ok(Some((swapchain, OxrDepthSwapchainImages(images))))
+}
+
+/// Lets the depth textures of the [`XrCamera`]s be copied into the [`OxrDepthSwapchainImages`].
+fn allow_depth_copies(mut cameras: Query<&mut Camera3d, With<XrCamera>>) {
+    for mut camera in &mut cameras {
+        let usages = wgpu::TextureUsages::from(camera.depth_texture_usages);
+        if !usages.contains(wgpu::TextureUsages::COPY_SRC) {
+            camera.depth_texture_usages =
+                Camera3dDepthTextureUsage::from(usages | wgpu::TextureUsages::COPY_SRC);
+        }
+    }
+}
+
+fn cleanup_depth_swapchain(mut commands: Commands) {
+    commands.remove_resource::<OxrDepthSwapchainImages>();
+    commands.remove_resource::<OxrDepthRenderResources>();
+}
+
+fn cleanup_depth_swapchain_render(mut commands: Commands) {
+    commands.remove_resource::<OxrDepthSwapchain>();
+    commands.remove_resource::<OxrDepthImageIndex>();
+}
+
+fn transfer_depth_swapchain(mut commands: Commands, mut world: ResMut<MainWorld>) {
+    match world.remove_resource() {
+        Some(OxrDepthRenderResources(Some(swapchain))) => commands.insert_resource(swapchain),
+        Some(OxrDepthRenderResources(None)) => {
+            commands.remove_resource::<OxrDepthSwapchain>();
+            commands.remove_resource::<OxrDepthImageIndex>();
+        }
+        None => {}
+    }
+}
+
+fn acquire_depth_image(
+    mut swapchain: ResMut<OxrDepthSwapchain>,
+    policy: Res<OxrWaitImagePolicy>,
+    counters: Res<OxrRenderFrameCounters>,
+    mut commands: Commands,
+) {
+    let _span = info_span!("xr_acquire_depth_image");
+    let index = swapchain
+        .acquire_next_image()
+        .expect("Failed to acquire image");
+    // an image that timed out is waited for again next frame, until then no depth is submitted
+    if swapchain
+        .wait_acquired_image(**policy)
+        .expect("Failed to wait image")
+    {
+        commands.insert_resource(OxrDepthImageIndex(index));
+    } else {
+        counters.add_image_wait_timeouts(1);
+        commands.remove_resource::<OxrDepthImageIndex>();
+    }
+}
+
+fn clean_depth_image_index(mut commands: Commands) {
+    commands.remove_resource::<OxrDepthImageIndex>();
+}
+
+/// Copies the depth of every [`XrCamera`] into the array layer of its view.
+fn copy_depth(
+    images: Res<OxrDepthSwapchainImages>,
+    index: Res<OxrDepthImageIndex>,
+    cameras: Query<(&XrCamera, &ViewDepthTexture)>,
+    device: Res<RenderDevice>,
+    queue: Res<RenderQueue>,
+) {
+    let _span = info_span!("xr_copy_depth");
+    let image = images.0.image(index.0);
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("xr_copy_depth"),
+    });
+    for (camera, depth) in &cameras {
+        let size = wgpu::Extent3d {
+            width: image.width(),
+            height: image.height(),
+            depth_or_array_layers: 1,
+        };
+        // depth can only be copied between whole textures of the same format and sample count
+        if depth.texture.sample_count() != 1
+            || depth.texture.format() != image.format()
+            || depth.texture.size() != size
+        {
+            warn_once!(
+                "Can't copy the {} sample {:?} depth of XrCamera {} into the {:?} depth swapchain of {}x{}, its depth isn't submitted",
+                depth.texture.sample_count(),
+                depth.texture.format(),
+                camera.0,
+                image.format(),
+                size.width,
+                size.height
+            );
+            continue;
+        }
+        encoder.copy_texture_to_texture(
+            depth.texture.as_image_copy(),
+            wgpu::ImageCopyTexture {
+                texture: image,
+                mip_level: 0,
+                origin: wgpu::Origin3d {
+                    x: 0,
+                    y: 0,
+                    z: camera.0,
+                },
+                aspect: wgpu::TextureAspect::All,
+            },
+            size,
+        );
+    }
+    // submitted after the render graph, so the depth has been rendered
+    queue.submit([encoder.finish()]);
+}
+
+fn release_depth_image(mut swapchain: ResMut<OxrDepthSwapchain>) {
+    let _span = info_span!("xr_release_depth_image");
+    swapchain.release_image().unwrap();
+}
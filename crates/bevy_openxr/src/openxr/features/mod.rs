@@ -1,4 +1,19 @@
+pub mod color_space;
+pub mod controller_battery;
+pub mod controller_model;
+pub mod environment_depth;
+pub mod eye_gaze;
+pub mod foveation;
 pub mod handtracking;
+pub mod layer_depth;
 #[cfg(feature = "passthrough")]
 pub mod passthrough;
+#[cfg(feature = "passthrough")]
+pub mod passthrough_geometry;
 pub mod overlay;
+pub mod performance_settings;
+pub mod refresh_rate;
+pub mod secondary_view;
+pub mod space_warp;
+pub mod spatial_anchor;
+pub mod visibility_mask;
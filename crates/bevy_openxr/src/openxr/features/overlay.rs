@@ -11,12 +11,20 @@ use crate::{
     session::{OxrSessionCreateNextChain, OxrSessionCreateNextProvider},
 };
 
+/// Creates the session as an overlay on top of another OpenXR app, the main session.
+///
+/// The [`OxrOverlaySettings`] are used when the session is created and [`OxrOverlayState`] tracks whether the main session is visible.
+/// The layers of an overlay are always blended over the layers of the main session,
+/// the [`ProjectionLayer`](crate::layer_builder::ProjectionLayer) blends with the alpha of the rendered views by default,
+/// so everything cleared to a transparent [`ClearColor`] shows the main session.
+/// Requires [`XR_EXTX_overlay`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#XR_EXTX_overlay).
 pub struct OxrOverlayPlugin;
 
 impl Plugin for OxrOverlayPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
         app.add_event::<OxrOverlaySessionEvent>();
         app.init_resource::<OxrOverlaySettings>();
+        app.init_resource::<OxrOverlayState>();
         app.add_systems(
             First,
             add_overlay_info_to_chain.run_if(openxr_session_available),
@@ -25,9 +33,15 @@ impl Plugin for OxrOverlayPlugin {
     }
 }
 
-fn handle_overlay_event(event: In<OxrEvent>, mut writer: EventWriter<OxrOverlaySessionEvent>) {
+fn handle_overlay_event(
+    event: In<OxrEvent>,
+    mut state: ResMut<OxrOverlayState>,
+    mut writer: EventWriter<OxrOverlaySessionEvent>,
+) {
     // this unwrap will never panic since we are in a valid scope
     if let Event::MainSessionVisibilityChangedEXTX(event) = unsafe { event.get() }.unwrap() {
+        state.main_session_visible = event.visible();
+        state.main_session_flags = event.flags();
         writer.send(OxrOverlaySessionEvent::MainSessionVisibilityChanged {
             visible: event.visible(),
             flags: event.flags(),
@@ -35,8 +49,27 @@ fn handle_overlay_event(event: In<OxrEvent>, mut writer: EventWriter<OxrOverlayS
     }
 }
 
+/// The visibility of the main session the overlay is drawn on top of, updated from [`OxrOverlaySessionEvent::MainSessionVisibilityChanged`].
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct OxrOverlayState {
+    /// Whether the main session is visible, the overlay is only shown together with it.
+    pub main_session_visible: bool,
+    pub main_session_flags: openxr::OverlayMainSessionFlagsEXTX,
+}
+
+impl Default for OxrOverlayState {
+    fn default() -> Self {
+        OxrOverlayState {
+            main_session_visible: false,
+            main_session_flags: openxr::OverlayMainSessionFlagsEXTX::EMPTY,
+        }
+    }
+}
+
+/// Settings used to create the overlay session.
 #[derive(Resource)]
 pub struct OxrOverlaySettings {
+    /// The placement of the overlay relative to other overlays, higher values are drawn on top.
     pub session_layer_placement: u32,
     pub flags: openxr::OverlaySessionCreateFlagsEXTX,
 }
@@ -55,7 +88,7 @@ fn add_overlay_info_to_chain(
     exts: Res<OxrEnabledExtensions>,
     settings: Res<OxrOverlaySettings>,
 ) {
-    if exts.other.contains(&"XR_EXTX_overlay\0".to_string()) {
+    if exts.overlay() {
         chain.push(OxrSessionCreateInfoOverlay::new(
             settings.flags,
             settings.session_layer_placement,
@@ -5,6 +5,7 @@ use bevy::render::RenderSet;
 use openxr::sys::SystemPassthroughProperties2FB;
 use openxr::PassthroughCapabilityFlagsFB;
 
+use crate::error::cvt;
 use crate::layer_builder::PassthroughLayer;
 use crate::resources::*;
 use crate::session::OxrSession;
@@ -25,6 +26,7 @@ impl Plugin for OxrPassthroughPlugin {
         if resources.is_some_and(|(instance, system)| {
             supports_passthrough(instance, *system).is_ok_and(|s| s)
         }) {
+            app.insert_resource(OxrPassthroughEnabled);
             app.sub_app_mut(RenderApp).add_systems(
                 Render,
                 insert_passthrough
@@ -37,6 +39,11 @@ impl Plugin for OxrPassthroughPlugin {
     }
 }
 
+/// Inserted by the [`OxrPassthroughPlugin`] if the runtime supports passthrough,
+/// the passthrough layer is then submitted below the projection layer of every session.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct OxrPassthroughEnabled;
+
 pub fn insert_passthrough(world: &mut World) {
     let session = world.resource::<OxrSession>();
 
@@ -49,7 +56,7 @@ pub fn insert_passthrough(world: &mut World) {
 
     world
         .resource_mut::<OxrRenderLayers>()
-        .insert(0, Box::new(PassthroughLayer));
+        .insert(0, Box::new(PassthroughLayer::default()));
     world.insert_resource(passthrough);
     world.insert_resource(passthrough_layer);
 }
@@ -109,11 +116,3 @@ pub fn supports_passthrough(instance: &OxrInstance, system: OxrSystemId) -> Resu
         )
     }
 }
-
-fn cvt(x: openxr::sys::Result) -> openxr::Result<openxr::sys::Result> {
-    if x.into_raw() >= 0 {
-        Ok(x)
-    } else {
-        Err(x)
-    }
-}
@@ -0,0 +1,347 @@
+use std::{borrow::Cow, ptr};
+
+use bevy::{
+    ecs::entity::EntityHashMap,
+    prelude::*,
+    render::{
+        mesh::{PrimitiveTopology, VertexAttributeValues},
+        Extract, ExtractSchedule, Render, RenderApp, RenderSet,
+    },
+    utils::HashSet,
+};
+use bevy_mod_xr::{
+    session::XrRootTransform,
+    spaces::{XrPrimaryReferenceSpace, XrSpace},
+};
+use openxr::sys;
+
+use crate::{
+    error::{cvt, OxrError},
+    exts::OxrEnabledExtensions,
+    features::passthrough::insert_passthrough,
+    helper_traits::{ToPosef, ToVector3f},
+    layer_builder::{CompositionLayer, CompositionLayerPassthrough, LayerProvider},
+    resources::{OxrFrameState, OxrPassthrough, OxrPassthroughLayer, OxrRenderLayers, Pipelined},
+    session::OxrSession,
+    spaces::OxrSpaceExt,
+    types::Result,
+};
+
+/// Only shows passthrough where the meshes of [`OxrPassthroughMesh`] entities are, e.g. to cut portals into the real world.
+///
+/// The full passthrough layer of the [`OxrPassthroughPlugin`](super::passthrough::OxrPassthroughPlugin) is paused
+/// and replaced by a [`ProjectedPassthroughLayer`], so this plugin has to be added after it.
+/// The geometry follows the [`GlobalTransform`] of its entity every frame and is recreated when the mesh asset is modified.
+/// Requires [`XR_FB_passthrough`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#XR_FB_passthrough)
+/// and [`XR_FB_triangle_mesh`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#XR_FB_triangle_mesh).
+pub struct OxrPassthroughGeometryPlugin;
+
+impl Plugin for OxrPassthroughGeometryPlugin {
+    fn build(&self, app: &mut App) {
+        if !app
+            .world()
+            .get_resource::<OxrEnabledExtensions>()
+            .is_some_and(|exts| exts.passthrough_geometry())
+        {
+            info!("Triangle mesh extension not enabled, disabling passthrough geometry");
+            return;
+        }
+
+        app.sub_app_mut(RenderApp)
+            .init_resource::<OxrPassthroughGeometries>()
+            .add_systems(ExtractSchedule, extract_passthrough_geometries)
+            .add_systems(
+                Render,
+                insert_projected_passthrough_layer
+                    .in_set(RenderSet::PrepareAssets)
+                    .after(insert_passthrough)
+                    .run_if(resource_added::<OxrPassthrough>),
+            );
+    }
+}
+
+/// The mesh that passthrough is projected onto, placed at the [`GlobalTransform`] of the entity.
+///
+/// The mesh has to be a [`TriangleList`](PrimitiveTopology::TriangleList) with [`Float32x3`](VertexAttributeValues::Float32x3) positions,
+/// and has to be kept in the main world, i.e. use [`RenderAssetUsages::MAIN_WORLD`](bevy::render::render_asset::RenderAssetUsages::MAIN_WORLD).
+#[derive(Clone, Component, Debug, Default, Deref, DerefMut)]
+pub struct OxrPassthroughMesh(pub Handle<Mesh>);
+
+/// The passthrough layer the geometry of [`OxrPassthroughMesh`]es is added to, inserted into the render world.
+#[derive(Resource, Deref, DerefMut)]
+pub struct OxrProjectedPassthroughLayer(pub OxrPassthroughLayer);
+
+/// Submits the [`OxrProjectedPassthroughLayer`].
+pub struct ProjectedPassthroughLayer {
+    pub flags: openxr::CompositionLayerFlags,
+}
+
+impl ProjectedPassthroughLayer {
+    pub fn with_flags(mut self, flags: openxr::CompositionLayerFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+}
+
+impl Default for ProjectedPassthroughLayer {
+    fn default() -> Self {
+        Self {
+            flags: openxr::CompositionLayerFlags::BLEND_TEXTURE_SOURCE_ALPHA,
+        }
+    }
+}
+
+impl LayerProvider for ProjectedPassthroughLayer {
+    fn get<'a>(&'a self, world: &'a World) -> Option<Box<dyn CompositionLayer + '_>> {
+        Some(Box::new(
+            CompositionLayerPassthrough::new()
+                .layer_handle(world.get_resource::<OxrProjectedPassthroughLayer>()?)
+                .layer_flags(self.flags),
+        ))
+    }
+}
+
+/// A triangle mesh placed in a projected passthrough layer, destroyed when dropped.
+///
+/// The geometry keeps the session alive, so it should be dropped before the session is destroyed.
+pub struct OxrPassthroughGeometry {
+    instance: sys::GeometryInstanceFB,
+    mesh: sys::TriangleMeshFB,
+    session: OxrSession,
+}
+
+impl OxrPassthroughGeometry {
+    pub fn as_raw(&self) -> sys::GeometryInstanceFB {
+        self.instance
+    }
+
+    /// Moves the geometry to `transform` relative to `base` at `time`, the scale of the transform is applied as well.
+    pub fn set_transform(
+        &self,
+        base: &XrSpace,
+        time: openxr::Time,
+        transform: Transform,
+    ) -> openxr::Result<()> {
+        let fns = self.session.passthrough_fns()?;
+        let info = sys::GeometryInstanceTransformFB {
+            ty: sys::GeometryInstanceTransformFB::TYPE,
+            next: ptr::null(),
+            base_space: base.as_raw_openxr_space(),
+            time,
+            pose: transform.to_posef(),
+            scale: transform.scale.to_vector3f(),
+        };
+        unsafe {
+            cvt((fns.geometry_instance_set_transform)(self.instance, &info))?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for OxrPassthroughGeometry {
+    fn drop(&mut self) {
+        if let Ok(fns) = self.session.passthrough_fns() {
+            if let Err(err) = cvt(unsafe { (fns.destroy_geometry_instance)(self.instance) }) {
+                warn!(
+                    "error while destroying passthrough geometry instance: {}",
+                    err
+                );
+            }
+        }
+        if let Ok(fns) = self.session.triangle_mesh_fns() {
+            if let Err(err) = cvt(unsafe { (fns.destroy_triangle_mesh)(self.mesh) }) {
+                warn!("error while destroying passthrough triangle mesh: {}", err);
+            }
+        }
+    }
+}
+
+impl OxrSession {
+    /// Adds `mesh` to the projected passthrough `layer` at `transform` relative to `base`.
+    ///
+    /// The `layer` has to be created with [`PROJECTED`](openxr::PassthroughLayerPurposeFB::PROJECTED) as purpose.
+    /// Fails with [`OxrError::InvalidPassthroughMesh`] if `mesh` isn't a triangle list with [`Float32x3`](VertexAttributeValues::Float32x3) positions.
+    pub fn create_passthrough_geometry(
+        &self,
+        layer: &OxrPassthroughLayer,
+        mesh: &Mesh,
+        base: &XrSpace,
+        transform: Transform,
+    ) -> Result<OxrPassthroughGeometry> {
+        let passthrough_fns = self.passthrough_fns()?;
+        let triangle_mesh_fns = self.triangle_mesh_fns()?;
+        let (vertices, indices) = passthrough_mesh_buffers(mesh)?;
+
+        let mesh_info = sys::TriangleMeshCreateInfoFB {
+            ty: sys::TriangleMeshCreateInfoFB::TYPE,
+            next: ptr::null(),
+            flags: sys::TriangleMeshFlagsFB::EMPTY,
+            winding_order: sys::WindingOrderFB::CCW,
+            vertex_count: vertices.len() as u32,
+            vertex_buffer: vertices.as_ptr(),
+            triangle_count: (indices.len() / 3) as u32,
+            index_buffer: indices.as_ptr(),
+        };
+        let mut triangle_mesh = sys::TriangleMeshFB::NULL;
+        unsafe {
+            cvt((triangle_mesh_fns.create_triangle_mesh)(
+                self.as_raw(),
+                &mesh_info,
+                &mut triangle_mesh,
+            ))?;
+        }
+
+        let instance_info = sys::GeometryInstanceCreateInfoFB {
+            ty: sys::GeometryInstanceCreateInfoFB::TYPE,
+            next: ptr::null(),
+            layer: *layer.inner(),
+            mesh: triangle_mesh,
+            base_space: base.as_raw_openxr_space(),
+            pose: transform.to_posef(),
+            scale: transform.scale.to_vector3f(),
+        };
+        let mut instance = sys::GeometryInstanceFB::NULL;
+        if let Err(err) = cvt(unsafe {
+            (passthrough_fns.create_geometry_instance)(self.as_raw(), &instance_info, &mut instance)
+        }) {
+            unsafe { (triangle_mesh_fns.destroy_triangle_mesh)(triangle_mesh) };
+            return Err(err.into());
+        }
+
+        Ok(OxrPassthroughGeometry {
+            instance,
+            mesh: triangle_mesh,
+            session: self.clone(),
+        })
+    }
+
+    fn passthrough_fns(&self) -> openxr::Result<&openxr::raw::PassthroughFB> {
+        self.instance()
+            .exts()
+            .fb_passthrough
+            .as_ref()
+            .ok_or(sys::Result::ERROR_EXTENSION_NOT_PRESENT)
+    }
+
+    fn triangle_mesh_fns(&self) -> openxr::Result<&openxr::raw::TriangleMeshFB> {
+        self.instance()
+            .exts()
+            .fb_triangle_mesh
+            .as_ref()
+            .ok_or(sys::Result::ERROR_EXTENSION_NOT_PRESENT)
+    }
+}
+
+fn passthrough_mesh_buffers(mesh: &Mesh) -> Result<(Vec<sys::Vector3f>, Vec<u32>)> {
+    let invalid = |reason: &'static str| OxrError::InvalidPassthroughMesh(Cow::Borrowed(reason));
+    if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+        return Err(invalid("the topology has to be a triangle list"));
+    }
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        return Err(invalid("the positions have to be Float32x3"));
+    };
+    let vertices: Vec<sys::Vector3f> = positions
+        .iter()
+        .map(|&position| Vec3::from(position).to_vector3f())
+        .collect();
+    let indices: Vec<u32> = match mesh.indices() {
+        Some(indices) => indices.iter().map(|index| index as u32).collect(),
+        None => (0..vertices.len() as u32).collect(),
+    };
+    if indices.is_empty() || indices.len() % 3 != 0 {
+        return Err(invalid("the mesh has to consist of whole triangles"));
+    }
+    if indices
+        .iter()
+        .any(|&index| index as usize >= vertices.len())
+    {
+        return Err(invalid("an index is out of bounds"));
+    }
+    Ok((vertices, indices))
+}
+
+pub fn insert_projected_passthrough_layer(world: &mut World) {
+    let session = world.resource::<OxrSession>();
+    let passthrough = world.resource::<OxrPassthrough>();
+    let layer = match session
+        .create_passthrough_layer(passthrough, openxr::PassthroughLayerPurposeFB::PROJECTED)
+    {
+        Ok(layer) => layer,
+        Err(err) => {
+            error!("error while creating projected passthrough layer: {}", err);
+            return;
+        }
+    };
+    if let Err(err) = world.resource::<OxrPassthroughLayer>().pause() {
+        warn!(
+            "error while pausing reconstruction passthrough layer: {}",
+            err
+        );
+    }
+
+    // the reconstruction layer was just inserted at the front by `insert_passthrough`
+    world.resource_mut::<OxrRenderLayers>()[0] = Box::new(ProjectedPassthroughLayer::default());
+    world.insert_resource(OxrProjectedPassthroughLayer(layer));
+}
+
+/// The geometry of every [`OxrPassthroughMesh`] entity and the mesh it was created from.
+#[derive(Resource, Default)]
+struct OxrPassthroughGeometries(EntityHashMap<(AssetId<Mesh>, OxrPassthroughGeometry)>);
+
+#[allow(clippy::too_many_arguments)]
+fn extract_passthrough_geometries(
+    mut geometries: ResMut<OxrPassthroughGeometries>,
+    session: Option<Res<OxrSession>>,
+    layer: Option<Res<OxrProjectedPassthroughLayer>>,
+    ref_space: Extract<Option<Res<XrPrimaryReferenceSpace>>>,
+    frame_state: Extract<Option<Res<OxrFrameState>>>,
+    pipelined: Extract<Option<Res<Pipelined>>>,
+    root: Extract<Res<XrRootTransform>>,
+    meshes: Extract<Res<Assets<Mesh>>>,
+    mut mesh_events: Extract<EventReader<AssetEvent<Mesh>>>,
+    query: Extract<Query<(Entity, &OxrPassthroughMesh, &GlobalTransform)>>,
+) {
+    let (Some(session), Some(layer), Some(ref_space), Some(frame_state)) =
+        (session, layer, ref_space.as_deref(), frame_state.as_deref())
+    else {
+        geometries.0.clear();
+        return;
+    };
+    let changed: HashSet<AssetId<Mesh>> = mesh_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } | AssetEvent::Removed { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    let time = frame_state.locate_time(pipelined.is_some());
+    let world_to_ref = root.0.affine().inverse();
+
+    geometries.0.retain(|entity, _| query.contains(*entity));
+    for (entity, mesh, global) in &query {
+        let transform = Transform::from_matrix((world_to_ref * global.affine()).into());
+        let id = mesh.id();
+        match geometries.0.get(&entity) {
+            Some((geometry_id, geometry)) if *geometry_id == id && !changed.contains(&id) => {
+                if let Err(err) = geometry.set_transform(ref_space, time, transform) {
+                    warn!("error while moving passthrough geometry: {}", err);
+                }
+                continue;
+            }
+            _ => {
+                geometries.0.remove(&entity);
+            }
+        }
+        let Some(mesh) = meshes.get(id) else {
+            continue;
+        };
+        match session.create_passthrough_geometry(&layer, mesh, ref_space, transform) {
+            Ok(geometry) => {
+                geometries.0.insert(entity, (id, geometry));
+            }
+            Err(err) => warn!("error while creating passthrough geometry: {}", err),
+        }
+    }
+}
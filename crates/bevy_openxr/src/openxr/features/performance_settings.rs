@@ -0,0 +1,231 @@
+use bevy::prelude::*;
+use bevy_mod_xr::session::XrSessionCreated;
+use openxr::{sys, Event};
+
+use crate::{
+    error::cvt,
+    exts::OxrEnabledExtensions,
+    poll_events::{OxrEvent, OxrEventHandlerExt},
+    session::OxrSession,
+    types::Result,
+};
+
+/// Applies the performance levels of the [`OxrPerformanceSettings`] and sends an [`OxrPerformanceNotification`]
+/// whenever the runtime reports a change in performance, e.g. because the device is thermal throttling.
+///
+/// Requires [`XR_EXT_performance_settings`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#XR_EXT_performance_settings).
+#[derive(Default)]
+pub struct OxrPerformanceSettingsPlugin {
+    /// The performance settings applied once the session is created.
+    pub settings: OxrPerformanceSettings,
+}
+
+impl Plugin for OxrPerformanceSettingsPlugin {
+    fn build(&self, app: &mut App) {
+        if !app
+            .world()
+            .get_resource::<OxrEnabledExtensions>()
+            .is_some_and(|exts| exts.performance_settings())
+        {
+            info!("Performance settings extension not enabled, disabling performance settings");
+            return;
+        }
+
+        app.add_event::<OxrPerformanceNotification>()
+            .insert_resource(self.settings)
+            .add_oxr_event_handler(handle_performance_event)
+            .add_systems(XrSessionCreated, update_performance_settings)
+            .add_systems(
+                PreUpdate,
+                update_performance_settings
+                    .run_if(resource_exists::<OxrSession>)
+                    .run_if(resource_changed::<OxrPerformanceSettings>),
+            );
+    }
+}
+
+/// The performance levels hinted to the runtime for the CPU and GPU, `None` leaves the level of the domain to the runtime.
+/// Changing this resource updates the levels at runtime, e.g. to save power in menus.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OxrPerformanceSettings {
+    pub cpu: Option<OxrPerfSettingsLevel>,
+    pub gpu: Option<OxrPerfSettingsLevel>,
+}
+
+impl OxrPerformanceSettings {
+    /// Sets the performance level hinted for `domain`.
+    pub fn set_performance_level(
+        &mut self,
+        domain: OxrPerfSettingsDomain,
+        level: OxrPerfSettingsLevel,
+    ) {
+        match domain {
+            OxrPerfSettingsDomain::Cpu => self.cpu = Some(level),
+            OxrPerfSettingsDomain::Gpu => self.gpu = Some(level),
+        }
+    }
+}
+
+/// A processing domain the performance level can be set for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OxrPerfSettingsDomain {
+    Cpu,
+    Gpu,
+}
+
+/// The performance level hinted to the runtime, from lowest to highest power draw.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum OxrPerfSettingsLevel {
+    /// Saves as much power as possible, e.g. for menus or loading screens.
+    PowerSavings,
+    /// Keeps the device cool, e.g. for scenes that don't need much processing.
+    SustainedLow,
+    /// The level the app can run at indefinitely without thermal throttling.
+    SustainedHigh,
+    /// Runs at the highest clocks, which can't be sustained and leads to thermal throttling.
+    Boost,
+}
+
+/// The part of a domain an [`OxrPerformanceNotification`] is about.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OxrPerfSettingsSubDomain {
+    /// The runtime's compositor.
+    Compositing,
+    /// The rendering of the app.
+    Rendering,
+    /// The temperature of the device.
+    Thermal,
+}
+
+/// How close the runtime is to missing its performance target.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum OxrPerfSettingsNotificationLevel {
+    /// The sub domain is within its limits.
+    Normal,
+    /// The sub domain is close to its limits, e.g. the app should reduce the render resolution.
+    Warning,
+    /// The sub domain exceeded its limits and the runtime is degrading, e.g. by reprojecting.
+    Impaired,
+}
+
+/// Sent when the runtime reports a change of the performance of a sub domain.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OxrPerformanceNotification {
+    pub domain: OxrPerfSettingsDomain,
+    pub sub_domain: OxrPerfSettingsSubDomain,
+    pub from: OxrPerfSettingsNotificationLevel,
+    pub to: OxrPerfSettingsNotificationLevel,
+}
+
+fn handle_performance_event(
+    event: In<OxrEvent>,
+    mut writer: EventWriter<OxrPerformanceNotification>,
+) {
+    // this unwrap will never panic since we are in a valid scope
+    let Event::PerfSettingsEXT(event) = unsafe { event.get() }.unwrap() else {
+        return;
+    };
+    let (Some(domain), Some(sub_domain), Some(from), Some(to)) = (
+        OxrPerfSettingsDomain::from_raw(event.domain()),
+        OxrPerfSettingsSubDomain::from_raw(event.sub_domain()),
+        OxrPerfSettingsNotificationLevel::from_raw(event.from_level()),
+        OxrPerfSettingsNotificationLevel::from_raw(event.to_level()),
+    ) else {
+        warn!("Received a performance notification with unknown values");
+        return;
+    };
+    writer.send(OxrPerformanceNotification {
+        domain,
+        sub_domain,
+        from,
+        to,
+    });
+}
+
+fn update_performance_settings(settings: Res<OxrPerformanceSettings>, session: Res<OxrSession>) {
+    for (domain, level) in [
+        (OxrPerfSettingsDomain::Cpu, settings.cpu),
+        (OxrPerfSettingsDomain::Gpu, settings.gpu),
+    ] {
+        let Some(level) = level else {
+            continue;
+        };
+        if let Err(err) = session.set_performance_level(domain, level) {
+            warn!("Failed to set {domain:?} performance level to {level:?}: {err}");
+        }
+    }
+}
+
+impl OxrSession {
+    /// Hints the runtime to run `domain` at the performance `level`.
+    ///
+    /// Requires [`XR_EXT_performance_settings`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#XR_EXT_performance_settings).
+    pub fn set_performance_level(
+        &self,
+        domain: OxrPerfSettingsDomain,
+        level: OxrPerfSettingsLevel,
+    ) -> Result<()> {
+        let fns = self
+            .instance()
+            .exts()
+            .ext_performance_settings
+            .as_ref()
+            .ok_or(sys::Result::ERROR_EXTENSION_NOT_PRESENT)?;
+        cvt(unsafe {
+            (fns.perf_settings_set_performance_level)(self.as_raw(), domain.into(), level.into())
+        })?;
+        Ok(())
+    }
+}
+
+impl OxrPerfSettingsDomain {
+    fn from_raw(domain: sys::PerfSettingsDomainEXT) -> Option<Self> {
+        match domain {
+            sys::PerfSettingsDomainEXT::CPU => Some(Self::Cpu),
+            sys::PerfSettingsDomainEXT::GPU => Some(Self::Gpu),
+            _ => None,
+        }
+    }
+}
+
+impl From<OxrPerfSettingsDomain> for sys::PerfSettingsDomainEXT {
+    fn from(domain: OxrPerfSettingsDomain) -> Self {
+        match domain {
+            OxrPerfSettingsDomain::Cpu => Self::CPU,
+            OxrPerfSettingsDomain::Gpu => Self::GPU,
+        }
+    }
+}
+
+impl From<OxrPerfSettingsLevel> for sys::PerfSettingsLevelEXT {
+    fn from(level: OxrPerfSettingsLevel) -> Self {
+        match level {
+            OxrPerfSettingsLevel::PowerSavings => Self::POWER_SAVINGS,
+            OxrPerfSettingsLevel::SustainedLow => Self::SUSTAINED_LOW,
+            OxrPerfSettingsLevel::SustainedHigh => Self::SUSTAINED_HIGH,
+            OxrPerfSettingsLevel::Boost => Self::BOOST,
+        }
+    }
+}
+
+impl OxrPerfSettingsSubDomain {
+    fn from_raw(sub_domain: sys::PerfSettingsSubDomainEXT) -> Option<Self> {
+        match sub_domain {
+            sys::PerfSettingsSubDomainEXT::COMPOSITING => Some(Self::Compositing),
+            sys::PerfSettingsSubDomainEXT::RENDERING => Some(Self::Rendering),
+            sys::PerfSettingsSubDomainEXT::THERMAL => Some(Self::Thermal),
+            _ => None,
+        }
+    }
+}
+
+impl OxrPerfSettingsNotificationLevel {
+    fn from_raw(level: sys::PerfSettingsNotificationLevelEXT) -> Option<Self> {
+        match level {
+            sys::PerfSettingsNotificationLevelEXT::NORMAL => Some(Self::Normal),
+            sys::PerfSettingsNotificationLevelEXT::WARNING => Some(Self::Warning),
+            sys::PerfSettingsNotificationLevelEXT::IMPAIRED => Some(Self::Impaired),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_mod_xr::session::XrFixedTimestep;
+use openxr::{sys, Event};
+
+use crate::{
+    error::OxrError,
+    poll_events::{OxrEvent, OxrEventHandlerExt},
+    session::OxrSession,
+    types::Result,
+};
+
+/// Sends an [`OxrDisplayRefreshRateChanged`] event whenever the runtime changes the refresh rate of the display.
+///
+/// The refresh rate itself can be queried and changed using the methods on [`OxrSession`],
+/// e.g. [`request_refresh_rate`](OxrSession::request_refresh_rate).
+/// The [`XrFixedTimestep`] is updated right away when the refresh rate changes.
+/// Requires [`XR_FB_display_refresh_rate`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#XR_FB_display_refresh_rate).
+pub struct OxrRefreshRatePlugin;
+
+impl Plugin for OxrRefreshRatePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<OxrDisplayRefreshRateChanged>();
+        app.add_oxr_event_handler(handle_refresh_rate_event);
+        app.add_systems(
+            PreUpdate,
+            update_fixed_timestep
+                .run_if(resource_exists::<XrFixedTimestep>)
+                .run_if(on_event::<OxrDisplayRefreshRateChanged>()),
+        );
+    }
+}
+
+/// Sent when the refresh rate of the display changed, either because it was requested or because the runtime changed it.
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub struct OxrDisplayRefreshRateChanged {
+    pub from: f32,
+    pub to: f32,
+}
+
+fn handle_refresh_rate_event(
+    event: In<OxrEvent>,
+    mut writer: EventWriter<OxrDisplayRefreshRateChanged>,
+) {
+    // this unwrap will never panic since we are in a valid scope
+    if let Event::DisplayRefreshRateChangedFB(event) = unsafe { event.get() }.unwrap() {
+        writer.send(OxrDisplayRefreshRateChanged {
+            from: event.from_display_refresh_rate(),
+            to: event.to_display_refresh_rate(),
+        });
+    }
+}
+
+fn update_fixed_timestep(
+    mut events: EventReader<OxrDisplayRefreshRateChanged>,
+    mut fixed_timestep: ResMut<XrFixedTimestep>,
+) {
+    let Some(event) = events.read().last() else {
+        return;
+    };
+    if event.to > 0.0 {
+        fixed_timestep.display_period = Some(Duration::from_secs_f32(event.to.recip()));
+    }
+}
+
+impl OxrSession {
+    /// Returns the refresh rates supported by the display in Hz.
+    ///
+    /// The methods for the refresh rate fail with [`ERROR_EXTENSION_NOT_PRESENT`](sys::Result::ERROR_EXTENSION_NOT_PRESENT)
+    /// if `XR_FB_display_refresh_rate` isn't enabled.
+    ///
+    /// Calls [`enumerate_display_refresh_rates`](openxr::Session::enumerate_display_refresh_rates) internally.
+    pub fn enumerate_refresh_rates(&self) -> Result<Vec<f32>> {
+        self.check_refresh_rate_ext()?;
+        Ok(self.enumerate_display_refresh_rates()?)
+    }
+
+    /// Returns the current refresh rate of the display in Hz.
+    ///
+    /// Calls [`get_display_refresh_rate`](openxr::Session::get_display_refresh_rate) internally.
+    pub fn get_refresh_rate(&self) -> Result<f32> {
+        self.check_refresh_rate_ext()?;
+        Ok(self.get_display_refresh_rate()?)
+    }
+
+    /// Requests the display to run at `hz`, which has to be one of the rates returned by [`enumerate_refresh_rates`](OxrSession::enumerate_refresh_rates).
+    /// Passing `0.0` lets the runtime pick the refresh rate.
+    ///
+    /// Returns [`OxrError::UnsupportedRefreshRate`] if the runtime doesn't support the rate.
+    ///
+    /// Calls [`request_display_refresh_rate`](openxr::Session::request_display_refresh_rate) internally.
+    pub fn request_refresh_rate(&self, hz: f32) -> Result<()> {
+        self.check_refresh_rate_ext()?;
+        match self.request_display_refresh_rate(hz) {
+            Err(sys::Result::ERROR_DISPLAY_REFRESH_RATE_UNSUPPORTED_FB) => {
+                Err(OxrError::UnsupportedRefreshRate(hz))
+            }
+            result => Ok(result?),
+        }
+    }
+
+    /// The openxr crate panics when calling functions of extensions that aren't loaded.
+    fn check_refresh_rate_ext(&self) -> Result<()> {
+        if self.instance().exts().fb_display_refresh_rate.is_none() {
+            return Err(sys::Result::ERROR_EXTENSION_NOT_PRESENT.into());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,651 @@
+use std::ptr;
+
+use bevy::{
+    ecs::query::QuerySingleError,
+    prelude::*,
+    render::{
+        camera::{ManualTextureView, ManualTextureViewHandle, ManualTextureViews, RenderTarget},
+        extract_resource::{ExtractResource, ExtractResourcePlugin},
+        renderer::RenderDevice,
+        ExtractSchedule, MainWorld, Render, RenderApp,
+    },
+    transform::TransformSystem,
+};
+use bevy_mod_xr::{
+    camera::{XrCamera, XrCameraBundle, XrProjection},
+    session::{XrPreDestroySession, XrRenderSet, XrSessionCreated, XrTrackingRoot},
+    spaces::XrPrimaryReferenceSpace,
+};
+use openxr::{sys, CompositionLayerFlags};
+
+use crate::{
+    error::cvt,
+    exts::OxrEnabledExtensions,
+    graphics::graphics_match,
+    helper_traits::ToTransform,
+    init::should_run_frame_loop,
+    layer_builder::{
+        CompositionLayer, CompositionLayerProjection, CompositionLayerProjectionView,
+        LayerProvider, SwapchainSubImage,
+    },
+    render::{
+        calculate_projection, end_frame, insert_texture_views, OxrRenderFrameCounters,
+        XR_TEXTURE_INDEX,
+    },
+    resources::{
+        OxrFrameState, OxrGraphicsInfo, OxrInstance, OxrSwapchain, OxrSwapchainImages, OxrSystemId,
+        OxrWaitImagePolicy, Pipelined,
+    },
+    session::OxrSession,
+    types::{
+        Result, SwapchainCreateFlags, SwapchainCreateInfo, SwapchainLayout, SwapchainUsageFlags,
+    },
+};
+
+/// Renders and submits an additional camera for the first person observer view used by mixed reality capture.
+///
+/// Requires `XR_MSFT_secondary_view_configuration` and `XR_MSFT_first_person_observer`.
+/// Does nothing if they are unavailable or the runtime doesn't support the view configuration.
+pub struct OxrSecondaryViewPlugin;
+
+impl Plugin for OxrSecondaryViewPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            ExtractResourcePlugin::<OxrSecondaryViewConfiguration>::default(),
+            ExtractResourcePlugin::<OxrSecondaryViewState>::default(),
+            ExtractResourcePlugin::<OxrSecondaryGraphicsInfo>::default(),
+            ExtractResourcePlugin::<OxrSecondarySwapchainImages>::default(),
+            ExtractResourcePlugin::<OxrSecondaryViews>::default(),
+        ))
+        .add_systems(XrPreDestroySession, cleanup_secondary_view)
+        .add_systems(
+            PostUpdate,
+            (locate_secondary_views, update_secondary_camera)
+                .chain()
+                .before(TransformSystem::TransformPropagate)
+                .run_if(should_run_frame_loop)
+                .run_if(resource_exists::<OxrSecondaryGraphicsInfo>),
+        );
+
+        app.sub_app_mut(RenderApp)
+            .add_systems(ExtractSchedule, transfer_secondary_swapchain)
+            .add_systems(
+                Render,
+                (
+                    acquire_secondary_image
+                        .after(insert_texture_views)
+                        .in_set(XrRenderSet::PreRender),
+                    release_secondary_image
+                        .run_if(resource_exists::<OxrSecondarySwapchainImageIndex>)
+                        .before(end_frame)
+                        .in_set(XrRenderSet::PostRender),
+                )
+                    .run_if(should_run_frame_loop)
+                    .run_if(secondary_view_active)
+                    .run_if(resource_exists::<OxrSecondarySwapchain>),
+            )
+            .add_systems(XrPreDestroySession, cleanup_secondary_swapchain)
+            .insert_resource(OxrSecondaryRenderLayers(vec![Box::new(
+                SecondaryProjectionLayer::default(),
+            )]));
+    }
+
+    // the extensions are inserted by the `OxrInitPlugin`, which may be added after this plugin
+    fn finish(&self, app: &mut App) {
+        if !app
+            .world()
+            .get_resource::<OxrEnabledExtensions>()
+            .is_some_and(|exts| exts.secondary_view())
+        {
+            info!("Secondary view configuration extensions not enabled, disabling secondary views");
+            return;
+        }
+
+        app.add_systems(XrSessionCreated, init_secondary_view);
+    }
+}
+
+/// The secondary view configuration enabled for the current session.
+#[derive(Resource, ExtractResource, Clone, Copy, Debug, Deref)]
+pub struct OxrSecondaryViewConfiguration(pub openxr::ViewConfigurationType);
+
+/// If the runtime wants the secondary view to be rendered, updated every frame by [`wait_frame`](crate::render::wait_frame).
+#[derive(Resource, ExtractResource, Clone, Copy, Debug, Default)]
+pub struct OxrSecondaryViewState {
+    pub active: bool,
+}
+
+/// Graphics info of the secondary view, the resolution is the recommended resolution of the secondary view configuration.
+#[derive(Resource, ExtractResource, Clone, Copy, Deref)]
+pub struct OxrSecondaryGraphicsInfo(pub OxrGraphicsInfo);
+
+/// The swapchain the secondary view is rendered to. Only exists in the render world.
+#[derive(Resource, Deref, DerefMut)]
+pub struct OxrSecondarySwapchain(pub OxrSwapchain);
+
+#[derive(Resource, ExtractResource, Clone, Copy, Deref)]
+pub struct OxrSecondarySwapchainImages(pub OxrSwapchainImages);
+
+/// The index of the [`OxrSecondarySwapchainImages`] acquired for the current frame,
+/// the [`SecondaryProjectionLayer`] is only submitted while this exists. Only exists in the render world.
+#[derive(Resource, Clone, Copy, Debug, Deref)]
+pub struct OxrSecondarySwapchainImageIndex(pub u32);
+
+/// The latest located views of the secondary view configuration.
+#[derive(Resource, ExtractResource, Clone, Deref, DerefMut, Default)]
+pub struct OxrSecondaryViews(pub Vec<openxr::View>);
+
+/// Composition layers submitted for the secondary view, the secondary equivalent of [`OxrRenderLayers`](crate::resources::OxrRenderLayers).
+#[derive(Resource, Deref, DerefMut, Default)]
+pub struct OxrSecondaryRenderLayers(pub Vec<Box<dyn LayerProvider + Send + Sync>>);
+
+/// Marker for the camera rendering the secondary view.
+#[derive(Component)]
+pub struct OxrSecondaryViewCamera;
+
+/// Used to transport the secondary swapchain from the main world to the render world.
+#[derive(Resource)]
+struct OxrSecondaryRenderResources(OxrSecondarySwapchain);
+
+/// A [`Condition`](bevy::ecs::schedule::Condition) system that says if the secondary view should be rendered this frame.
+pub fn secondary_view_active(state: Option<Res<OxrSecondaryViewState>>) -> bool {
+    state.is_some_and(|state| state.active)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn init_secondary_view(
+    instance: Res<OxrInstance>,
+    system_id: Res<OxrSystemId>,
+    session: Res<OxrSession>,
+    graphics_info: Res<OxrGraphicsInfo>,
+    device: Res<RenderDevice>,
+    mut manual_texture_views: ResMut<ManualTextureViews>,
+    root: Query<Entity, With<XrTrackingRoot>>,
+    mut commands: Commands,
+) {
+    let view_configuration =
+        openxr::ViewConfigurationType::SECONDARY_MONO_FIRST_PERSON_OBSERVER_MSFT;
+    match instance.enumerate_view_configurations(**system_id) {
+        Ok(view_configurations) if view_configurations.contains(&view_configuration) => {}
+        Ok(_) => {
+            info!(
+                "Runtime doesn't support the first person observer view, disabling secondary views"
+            );
+            return;
+        }
+        Err(err) => {
+            error!("Failed to enumerate view configurations: {err}");
+            return;
+        }
+    }
+
+    let (secondary_info, swapchain, images) = match create_secondary_swapchain(
+        &instance,
+        **system_id,
+        &session,
+        device.wgpu_device(),
+        *graphics_info,
+        view_configuration,
+    ) {
+        Ok(secondary) => secondary,
+        Err(err) => {
+            error!("Failed to create secondary view swapchain: {err}");
+            return;
+        }
+    };
+
+    // the secondary camera comes after the cameras of the primary views
+    let index = graphics_info.view_count;
+    let view_handle = add_secondary_texture_view(
+        &mut manual_texture_views,
+        images.first().unwrap(),
+        &secondary_info,
+        index,
+    );
+    let cam = commands
+        .spawn((
+            XrCameraBundle {
+                camera: Camera {
+                    target: RenderTarget::TextureView(view_handle),
+                    is_active: false,
+                    ..Default::default()
+                },
+                view: XrCamera(index),
+                ..Default::default()
+            },
+            OxrSecondaryViewCamera,
+        ))
+        .id();
+    match root.get_single() {
+        Ok(root) => {
+            commands.entity(root).add_child(cam);
+        }
+        Err(QuerySingleError::NoEntities(_)) => {
+            warn!("No XrTrackingRoot!");
+        }
+        Err(QuerySingleError::MultipleEntities(_)) => {
+            warn!("Multiple XrTrackingRoots! this is not allowed");
+        }
+    }
+
+    commands.insert_resource(OxrSecondaryViewConfiguration(view_configuration));
+    commands.insert_resource(OxrSecondaryViewState::default());
+    commands.insert_resource(OxrSecondaryGraphicsInfo(secondary_info));
+    commands.insert_resource(OxrSecondarySwapchainImages(images));
+    commands.insert_resource(OxrSecondaryViews::default());
+    commands.insert_resource(OxrSecondaryRenderResources(OxrSecondarySwapchain(
+        swapchain,
+    )));
+}
+
+fn create_secondary_swapchain(
+    instance: &OxrInstance,
+    system_id: openxr::SystemId,
+    session: &OxrSession,
+    device: &wgpu::Device,
+    graphics_info: OxrGraphicsInfo,
+    view_configuration: openxr::ViewConfigurationType,
+) -> Result<(OxrGraphicsInfo, OxrSwapchain, OxrSwapchainImages)> {
+    let view = *instance
+        .enumerate_view_configuration_views(system_id, view_configuration)?
+        .first()
+        .ok_or(crate::error::OxrError::NoAvailableViewConfiguration)?;
+    let resolution = UVec2::new(
+        view.recommended_image_rect_width,
+        view.recommended_image_rect_height,
+    );
+    let blend_mode = *instance
+        .enumerate_environment_blend_modes(system_id, view_configuration)?
+        .first()
+        .ok_or(crate::error::OxrError::NoAvailableBlendMode)?;
+
+    let mut usage_flags = SwapchainUsageFlags::COLOR_ATTACHMENT | SwapchainUsageFlags::SAMPLED;
+    if graphics_info.view_format != graphics_info.format {
+        usage_flags |= SwapchainUsageFlags::MUTABLE_FORMAT;
+    }
+    let swapchain = session.create_swapchain(SwapchainCreateInfo {
+        create_flags: SwapchainCreateFlags::EMPTY,
+        usage_flags,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        format: graphics_info.format,
+        sample_count: 1,
+        width: resolution.x,
+        height: resolution.y,
+        face_count: 1,
+        array_size: 1,
+        mip_count: 1,
+    })?;
+    let images = swapchain.enumerate_images_with_view_format(
+        device,
+        graphics_info.format,
+        graphics_info.view_format,
+        resolution,
+        1,
+        1,
+    )?;
+
+    Ok((
+        OxrGraphicsInfo {
+            blend_mode,
+            resolution,
+            format: graphics_info.format,
+            view_format: graphics_info.view_format,
+            view_configuration,
+            view_count: 1,
+            sample_count: 1,
+            swapchain_layout: SwapchainLayout::SingleArray,
+            max_layer_count: graphics_info.max_layer_count,
+            focus_resolution: None,
+        },
+        swapchain,
+        images,
+    ))
+}
+
+fn cleanup_secondary_view(mut commands: Commands) {
+    // the camera is despawned together with the primary cameras
+    commands.remove_resource::<OxrSecondaryViewConfiguration>();
+    commands.remove_resource::<OxrSecondaryViewState>();
+    commands.remove_resource::<OxrSecondaryGraphicsInfo>();
+    commands.remove_resource::<OxrSecondarySwapchainImages>();
+    commands.remove_resource::<OxrSecondaryViews>();
+    commands.remove_resource::<OxrSecondaryRenderResources>();
+}
+
+fn cleanup_secondary_swapchain(mut commands: Commands) {
+    commands.remove_resource::<OxrSecondarySwapchain>();
+    commands.remove_resource::<OxrSecondarySwapchainImageIndex>();
+}
+
+fn transfer_secondary_swapchain(mut commands: Commands, mut world: ResMut<MainWorld>) {
+    if let Some(OxrSecondaryRenderResources(swapchain)) = world.remove_resource() {
+        commands.insert_resource(swapchain);
+    }
+}
+
+fn locate_secondary_views(
+    session: Res<OxrSession>,
+    ref_space: Res<XrPrimaryReferenceSpace>,
+    frame_state: Res<OxrFrameState>,
+    view_configuration: Res<OxrSecondaryViewConfiguration>,
+    state: Res<OxrSecondaryViewState>,
+    mut views: ResMut<OxrSecondaryViews>,
+    pipelined: Option<Res<Pipelined>>,
+) {
+    if !state.active {
+        return;
+    }
+    let time = frame_state.locate_time(pipelined.is_some());
+    match session.locate_views(**view_configuration, time, &ref_space) {
+        Ok((_, located_views)) => views.0 = located_views,
+        Err(err) => error!("Failed to locate secondary views: {err}"),
+    }
+}
+
+fn update_secondary_camera(
+    frame_state: Res<OxrFrameState>,
+    state: Res<OxrSecondaryViewState>,
+    views: Res<OxrSecondaryViews>,
+    mut query: Query<
+        (&mut Camera, &mut Transform, &mut XrProjection),
+        With<OxrSecondaryViewCamera>,
+    >,
+) {
+    for (mut camera, mut transform, mut projection) in &mut query {
+        camera.is_active = frame_state.should_render && state.active;
+        let Some(view) = views.first() else {
+            continue;
+        };
+        projection.projection_matrix = calculate_projection(projection.near, view.fov);
+        *transform = view.pose.to_transform();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn acquire_secondary_image(
+    mut swapchain: ResMut<OxrSecondarySwapchain>,
+    images: Res<OxrSecondarySwapchainImages>,
+    graphics_info: Res<OxrGraphicsInfo>,
+    secondary_info: Res<OxrSecondaryGraphicsInfo>,
+    policy: Res<OxrWaitImagePolicy>,
+    counters: Res<OxrRenderFrameCounters>,
+    mut manual_texture_views: ResMut<ManualTextureViews>,
+    mut commands: Commands,
+) {
+    let _span = info_span!("xr_acquire_secondary_image");
+    let index = swapchain
+        .acquire_next_image()
+        .expect("Failed to acquire image");
+    if swapchain
+        .wait_acquired_image(**policy)
+        .expect("Failed to wait image")
+    {
+        add_secondary_texture_view(
+            &mut manual_texture_views,
+            images.image(index),
+            &secondary_info,
+            graphics_info.view_count,
+        );
+        commands.insert_resource(OxrSecondarySwapchainImageIndex(index));
+    } else {
+        // the secondary camera has no target this frame, the image is waited for again next frame
+        counters.add_image_wait_timeouts(1);
+        manual_texture_views.remove(&ManualTextureViewHandle(
+            XR_TEXTURE_INDEX + graphics_info.view_count,
+        ));
+        commands.remove_resource::<OxrSecondarySwapchainImageIndex>();
+    }
+}
+
+/// Like [`add_texture_view`](crate::render::add_texture_view), but always uses the first array layer
+/// since the secondary swapchain only has a single one.
+fn add_secondary_texture_view(
+    manual_texture_views: &mut ManualTextureViews,
+    texture: &wgpu::Texture,
+    info: &OxrGraphicsInfo,
+    index: u32,
+) -> ManualTextureViewHandle {
+    let view = texture.create_view(&wgpu::TextureViewDescriptor {
+        dimension: Some(wgpu::TextureViewDimension::D2),
+        array_layer_count: Some(1),
+        base_array_layer: 0,
+        format: Some(info.view_format),
+        ..default()
+    });
+    let view = ManualTextureView {
+        texture_view: view.into(),
+        size: info.resolution,
+        format: info.view_format,
+    };
+    let handle = ManualTextureViewHandle(XR_TEXTURE_INDEX + index);
+    manual_texture_views.insert(handle, view);
+    handle
+}
+
+fn release_secondary_image(mut swapchain: ResMut<OxrSecondarySwapchain>) {
+    let _span = info_span!("xr_release_secondary_image");
+    swapchain.release_image().unwrap();
+}
+
+/// Submits the secondary view rendered by the [`OxrSecondaryViewPlugin`].
+///
+/// `flags` default to [`BLEND_TEXTURE_SOURCE_ALPHA`](CompositionLayerFlags::BLEND_TEXTURE_SOURCE_ALPHA).
+pub struct SecondaryProjectionLayer {
+    pub flags: CompositionLayerFlags,
+}
+
+impl SecondaryProjectionLayer {
+    pub fn with_flags(mut self, flags: CompositionLayerFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+}
+
+impl Default for SecondaryProjectionLayer {
+    fn default() -> Self {
+        Self {
+            flags: CompositionLayerFlags::BLEND_TEXTURE_SOURCE_ALPHA,
+        }
+    }
+}
+
+impl LayerProvider for SecondaryProjectionLayer {
+    fn get<'a>(&'a self, world: &'a World) -> Option<Box<dyn CompositionLayer + '_>> {
+        // nothing was rendered if waiting for the image timed out
+        world.get_resource::<OxrSecondarySwapchainImageIndex>()?;
+        let stage = world.get_resource::<XrPrimaryReferenceSpace>()?;
+        let view = world.get_resource::<OxrSecondaryViews>()?.first()?;
+        let swapchain = world.get_resource::<OxrSecondarySwapchain>()?;
+        let graphics_info = world.get_resource::<OxrSecondaryGraphicsInfo>()?;
+        let rect = openxr::Rect2Di {
+            offset: openxr::Offset2Di { x: 0, y: 0 },
+            extent: openxr::Extent2Di {
+                width: graphics_info.resolution.x as _,
+                height: graphics_info.resolution.y as _,
+            },
+        };
+
+        Some(Box::new(
+            CompositionLayerProjection::new()
+                .layer_flags(self.flags)
+                .space(stage)
+                .views(&[CompositionLayerProjectionView::new()
+                    .pose(view.pose)
+                    .fov(view.fov)
+                    .sub_image(
+                        SwapchainSubImage::new()
+                            .swapchain(swapchain)
+                            .image_array_index(0)
+                            .image_rect(rect),
+                    )]),
+        ))
+    }
+}
+
+impl OxrSession {
+    /// Begins the session with `secondary` enabled as a secondary view configuration.
+    ///
+    /// Calls [`xrBeginSession`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#xrBeginSession) internally.
+    pub fn begin_with_secondary_view(
+        &self,
+        primary: openxr::ViewConfigurationType,
+        secondary: openxr::ViewConfigurationType,
+    ) -> openxr::Result<()> {
+        let secondary_info = sys::SecondaryViewConfigurationSessionBeginInfoMSFT {
+            ty: sys::SecondaryViewConfigurationSessionBeginInfoMSFT::TYPE,
+            next: ptr::null(),
+            view_configuration_count: 1,
+            enabled_view_configuration_types: &secondary,
+        };
+        let info = sys::SessionBeginInfo {
+            ty: sys::SessionBeginInfo::TYPE,
+            next: &secondary_info as *const _ as _,
+            primary_view_configuration_type: primary,
+        };
+        unsafe {
+            cvt((self.instance().fp().begin_session)(self.as_raw(), &info))?;
+        }
+        Ok(())
+    }
+
+    /// Waits for the next frame and returns if the `secondary` view configuration is active.
+    ///
+    /// This has to be called instead of [`FrameWaiter::wait`](openxr::FrameWaiter::wait) when the session was begun
+    /// using [`begin_with_secondary_view`](OxrSession::begin_with_secondary_view).
+    pub fn wait_frame_with_secondary_view(
+        &self,
+        secondary: openxr::ViewConfigurationType,
+    ) -> openxr::Result<(openxr::FrameState, bool)> {
+        let mut view_state = sys::SecondaryViewConfigurationStateMSFT {
+            ty: sys::SecondaryViewConfigurationStateMSFT::TYPE,
+            next: ptr::null_mut(),
+            view_configuration_type: secondary,
+            active: sys::FALSE,
+        };
+        let mut secondary_state = sys::SecondaryViewConfigurationFrameStateMSFT {
+            ty: sys::SecondaryViewConfigurationFrameStateMSFT::TYPE,
+            next: ptr::null_mut(),
+            view_configuration_count: 1,
+            view_configuration_states: &mut view_state,
+        };
+        let mut state = sys::FrameState {
+            ty: sys::FrameState::TYPE,
+            next: &mut secondary_state as *mut _ as _,
+            predicted_display_time: openxr::Time::from_nanos(0),
+            predicted_display_period: openxr::Duration::from_nanos(0),
+            should_render: sys::FALSE,
+        };
+        let info = sys::FrameWaitInfo {
+            ty: sys::FrameWaitInfo::TYPE,
+            next: ptr::null(),
+        };
+        unsafe {
+            cvt((self.instance().fp().wait_frame)(
+                self.as_raw(),
+                &info,
+                &mut state,
+            ))?;
+        }
+        Ok((
+            openxr::FrameState {
+                predicted_display_time: state.predicted_display_time,
+                predicted_display_period: state.predicted_display_period,
+                should_render: state.should_render.into(),
+            },
+            view_state.active.into(),
+        ))
+    }
+
+    /// Ends the frame, submitting `secondary_layers` for the `secondary` view configuration.
+    ///
+    /// Layers using a different graphics api than the session are excluded from the frame submission.
+    ///
+    /// Calls [`xrEndFrame`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#xrEndFrame) internally.
+    pub fn end_frame_with_secondary_view(
+        &self,
+        display_time: openxr::Time,
+        environment_blend_mode: openxr::EnvironmentBlendMode,
+        layers: &[&dyn CompositionLayer],
+        secondary: openxr::ViewConfigurationType,
+        secondary_blend_mode: openxr::EnvironmentBlendMode,
+        secondary_layers: &[&dyn CompositionLayer],
+    ) -> openxr::Result<()> {
+        let layers = self.layer_headers(layers);
+        let secondary_layers = self.layer_headers(secondary_layers);
+        let layer_info = sys::SecondaryViewConfigurationLayerInfoMSFT {
+            ty: sys::SecondaryViewConfigurationLayerInfoMSFT::TYPE,
+            next: ptr::null(),
+            view_configuration_type: secondary,
+            environment_blend_mode: secondary_blend_mode,
+            layer_count: secondary_layers.len() as u32,
+            layers: secondary_layers.as_ptr(),
+        };
+        let secondary_info = sys::SecondaryViewConfigurationFrameEndInfoMSFT {
+            ty: sys::SecondaryViewConfigurationFrameEndInfoMSFT::TYPE,
+            next: ptr::null(),
+            view_configuration_count: 1,
+            view_configuration_layers_info: &layer_info,
+        };
+        let info = sys::FrameEndInfo {
+            ty: sys::FrameEndInfo::TYPE,
+            next: &secondary_info as *const _ as _,
+            display_time,
+            environment_blend_mode,
+            layer_count: layers.len() as u32,
+            layers: layers.as_ptr(),
+        };
+        unsafe {
+            cvt((self.instance().fp().end_frame)(self.as_raw(), &info))?;
+        }
+        Ok(())
+    }
+
+    fn layer_headers(
+        &self,
+        layers: &[&dyn CompositionLayer],
+    ) -> Vec<*const sys::CompositionLayerBaseHeader> {
+        graphics_match!(
+            &self.1;
+            _ => layers
+                .iter()
+                .filter(|layer| {
+                    layer
+                        .swapchain()
+                        .map_or(true, |swapchain| swapchain.0.using_graphics::<Api>())
+                })
+                .map(|layer| layer.header() as *const _)
+                .collect()
+        )
+    }
+}
+
+/// Ends the frame including the layers of the secondary view, used by [`end_frame`](crate::render::end_frame)
+/// while the secondary view is active.
+pub(crate) fn end_secondary_frame(
+    world: &World,
+    display_time: openxr::Time,
+    environment_blend_mode: openxr::EnvironmentBlendMode,
+    layers: &[&dyn CompositionLayer],
+) -> openxr::Result<()> {
+    let view_configuration = world.resource::<OxrSecondaryViewConfiguration>();
+    let graphics_info = world.resource::<OxrSecondaryGraphicsInfo>();
+    let mut secondary_layers = vec![];
+    if world.resource::<OxrFrameState>().should_render {
+        for layer in world.resource::<OxrSecondaryRenderLayers>().iter() {
+            if let Some(layer) = layer.get(world) {
+                secondary_layers.push(layer);
+            }
+        }
+    }
+    let secondary_layers: Vec<_> = secondary_layers.iter().map(Box::as_ref).collect();
+    world
+        .resource::<OxrSession>()
+        .end_frame_with_secondary_view(
+            display_time,
+            environment_blend_mode,
+            layers,
+            **view_configuration,
+            graphics_info.blend_mode,
+            &secondary_layers,
+        )
+}
@@ -0,0 +1,433 @@
+use bevy::{
+    prelude::*,
+    render::{
+        extract_resource::{ExtractResource, ExtractResourcePlugin},
+        renderer::RenderDevice,
+        ExtractSchedule, MainWorld, Render, RenderApp,
+    },
+};
+use bevy_mod_xr::{
+    session::{XrPreDestroySession, XrRenderSet, XrRootTransform, XrSessionCreated},
+    spaces::XrPrimaryReferenceSpace,
+};
+use openxr::{sys, CompositionLayerFlags, CompositionLayerSpaceWarpInfoFlagsFB};
+
+use crate::{
+    error::cvt,
+    exts::OxrEnabledExtensions,
+    helper_traits::ToPosef,
+    init::{should_render, should_run_frame_loop},
+    layer_builder::{
+        CompositionLayer, CompositionLayerProjection, CompositionLayerProjectionView,
+        CompositionLayerSpaceWarpInfo, LayerProvider, SwapchainSubImage,
+    },
+    render::{end_frame, insert_texture_views, OxrRenderFrameCounters},
+    resources::{
+        OxrGraphicsInfo, OxrInstance, OxrPerEyeSwapchains, OxrSwapchain, OxrSwapchainImages,
+        OxrSystemId, OxrViews, OxrWaitImagePolicy,
+    },
+    session::OxrSession,
+    types::{Result, SwapchainCreateFlags, SwapchainCreateInfo, SwapchainUsageFlags},
+};
+
+/// Creates the motion vector and depth swapchains for application space warp,
+/// which lets the runtime synthesize every other frame from the motion vectors and depth rendered by the app.
+///
+/// Render the motion vectors and depth of every view into the [`OxrSpaceWarpImages`] at the [`OxrSpaceWarpImageIndices`],
+/// and replace the [`ProjectionLayer`](crate::layer_builder::ProjectionLayer) in the [`OxrRenderLayers`](crate::resources::OxrRenderLayers)
+/// with a [`ProjectionLayerSpaceWarp`] to submit them.
+/// Requires [`XR_FB_space_warp`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#XR_FB_space_warp).
+/// Does nothing if the system doesn't support it.
+pub struct OxrSpaceWarpPlugin;
+
+impl Plugin for OxrSpaceWarpPlugin {
+    fn build(&self, app: &mut App) {
+        if !app
+            .world()
+            .get_resource::<OxrEnabledExtensions>()
+            .is_some_and(|exts| exts.space_warp())
+        {
+            info!("Space warp extension not enabled, disabling space warp");
+            return;
+        }
+
+        app.add_plugins((
+            ExtractResourcePlugin::<OxrSpaceWarpInfo>::default(),
+            ExtractResourcePlugin::<OxrSpaceWarpImages>::default(),
+        ))
+        .add_systems(XrSessionCreated, init_space_warp)
+        .add_systems(XrPreDestroySession, cleanup_space_warp);
+
+        app.sub_app_mut(RenderApp)
+            .add_systems(ExtractSchedule, transfer_space_warp_swapchains)
+            .add_systems(
+                Render,
+                (
+                    (
+                        acquire_space_warp_images.run_if(should_render),
+                        update_app_space_delta,
+                    )
+                        .after(insert_texture_views)
+                        .in_set(XrRenderSet::PreRender),
+                    clean_space_warp_image_indices
+                        .run_if(not(should_render))
+                        .in_set(XrRenderSet::PreRender),
+                    release_space_warp_images
+                        .run_if(resource_exists::<OxrSpaceWarpImageIndices>)
+                        .before(end_frame)
+                        .in_set(XrRenderSet::PostRender),
+                )
+                    .run_if(should_run_frame_loop)
+                    .run_if(resource_exists::<OxrSpaceWarpSwapchains>),
+            )
+            .add_systems(XrPreDestroySession, cleanup_space_warp_swapchains)
+            .init_resource::<OxrSpaceWarpAppSpaceDelta>();
+    }
+}
+
+/// The formats and resolution of the space warp swapchains.
+#[derive(Resource, ExtractResource, Clone, Copy, Debug)]
+pub struct OxrSpaceWarpInfo {
+    /// The resolution recommended by the runtime, usually lower than the resolution of the views.
+    pub resolution: UVec2,
+    pub motion_vector_format: wgpu::TextureFormat,
+    pub depth_format: wgpu::TextureFormat,
+}
+
+/// The images of the space warp swapchains, with one array layer per view.
+///
+/// Motion vectors are the movement of every pixel since the last frame in normalized device coordinates,
+/// the depth uses bevy's reversed infinite depth.
+#[derive(Resource, ExtractResource, Clone, Copy)]
+pub struct OxrSpaceWarpImages {
+    pub motion_vectors: OxrSwapchainImages,
+    pub depth: OxrSwapchainImages,
+}
+
+/// The indices of the [`OxrSpaceWarpImages`] acquired for the current frame, the [`ProjectionLayerSpaceWarp`]
+/// only submits the motion vectors and depth while this exists. Only exists in the render world.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct OxrSpaceWarpImageIndices {
+    pub motion_vectors: u32,
+    pub depth: u32,
+}
+
+/// The swapchains the motion vectors and depth are rendered to. Only exists in the render world.
+#[derive(Resource)]
+pub struct OxrSpaceWarpSwapchains {
+    pub motion_vectors: OxrSwapchain,
+    pub depth: OxrSwapchain,
+}
+
+/// The motion of the [`XrTrackingRoot`](bevy_mod_xr::session::XrTrackingRoot) since the last frame,
+/// so the runtime can tell it apart from the motion of the rendered objects. Only exists in the render world.
+#[derive(Resource, Clone, Copy, Debug, Default, Deref)]
+pub struct OxrSpaceWarpAppSpaceDelta(pub Transform);
+
+/// Used to transport the space warp swapchains from the main world to the render world.
+#[derive(Resource)]
+struct OxrSpaceWarpRenderResources(OxrSpaceWarpSwapchains);
+
+fn init_space_warp(
+    instance: Res<OxrInstance>,
+    system_id: Res<OxrSystemId>,
+    session: Res<OxrSession>,
+    graphics_info: Res<OxrGraphicsInfo>,
+    device: Res<RenderDevice>,
+    mut commands: Commands,
+) {
+    let resolution = match space_warp_resolution(&instance, **system_id) {
+        Ok(Some(resolution)) => resolution,
+        Ok(None) => {
+            info!("System doesn't support space warp, disabling space warp");
+            return;
+        }
+        Err(err) => {
+            error!("Failed to get space warp properties: {err}");
+            return;
+        }
+    };
+    match create_space_warp_swapchains(
+        &session,
+        device.wgpu_device(),
+        resolution,
+        graphics_info.view_count,
+    ) {
+        Ok((info, swapchains, images)) => {
+            commands.insert_resource(info);
+            commands.insert_resource(images);
+            commands.insert_resource(OxrSpaceWarpRenderResources(swapchains));
+        }
+        Err(err) => error!("Failed to create space warp swapchains: {err}"),
+    }
+}
+
+fn create_space_warp_swapchains(
+    session: &OxrSession,
+    device: &wgpu::Device,
+    resolution: UVec2,
+    view_count: u32,
+) -> Result<(OxrSpaceWarpInfo, OxrSpaceWarpSwapchains, OxrSpaceWarpImages)> {
+    let motion_vector_format = wgpu::TextureFormat::Rgba16Float;
+    let depth_formats = session.enumerate_depth_swapchain_formats()?;
+    let depth_format = [
+        wgpu::TextureFormat::Depth24PlusStencil8,
+        wgpu::TextureFormat::Depth32Float,
+    ]
+    .into_iter()
+    .find(|format| depth_formats.contains(format))
+    .or_else(|| depth_formats.first().copied())
+    .ok_or(crate::error::OxrError::NoAvailableFormat)?;
+
+    let motion_vectors = session.create_swapchain(SwapchainCreateInfo {
+        create_flags: SwapchainCreateFlags::EMPTY,
+        usage_flags: SwapchainUsageFlags::COLOR_ATTACHMENT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        format: motion_vector_format,
+        sample_count: 1,
+        width: resolution.x,
+        height: resolution.y,
+        face_count: 1,
+        array_size: view_count,
+        mip_count: 1,
+    })?;
+    let depth = session
+        .create_depth_swapchain(depth_format, resolution, view_count)?
+        .0;
+    let images = OxrSpaceWarpImages {
+        motion_vectors: motion_vectors.enumerate_images(
+            device,
+            motion_vector_format,
+            resolution,
+            view_count,
+        )?,
+        depth: depth.enumerate_images(device, depth_format, resolution, view_count)?,
+    };
+
+    Ok((
+        OxrSpaceWarpInfo {
+            resolution,
+            motion_vector_format,
+            depth_format,
+        },
+        OxrSpaceWarpSwapchains {
+            motion_vectors,
+            depth,
+        },
+        images,
+    ))
+}
+
+/// Returns the recommended resolution of the space warp swapchains, or [`None`] if the system doesn't support space warp.
+pub fn space_warp_resolution(
+    instance: &OxrInstance,
+    system: openxr::SystemId,
+) -> openxr::Result<Option<UVec2>> {
+    unsafe {
+        let mut space_warp = sys::SystemSpaceWarpPropertiesFB {
+            ty: sys::SystemSpaceWarpPropertiesFB::TYPE,
+            next: std::ptr::null_mut(),
+            recommended_motion_vector_image_rect_width: 0,
+            recommended_motion_vector_image_rect_height: 0,
+        };
+        let mut p = sys::SystemProperties::out(&mut space_warp as *mut _ as _);
+        cvt((instance.fp().get_system_properties)(
+            instance.as_raw(),
+            system,
+            p.as_mut_ptr(),
+        ))?;
+        let resolution = UVec2::new(
+            space_warp.recommended_motion_vector_image_rect_width,
+            space_warp.recommended_motion_vector_image_rect_height,
+        );
+        Ok((resolution.x != 0 && resolution.y != 0).then_some(resolution))
+    }
+}
+
+fn cleanup_space_warp(mut commands: Commands) {
+    commands.remove_resource::<OxrSpaceWarpInfo>();
+    commands.remove_resource::<OxrSpaceWarpImages>();
+    commands.remove_resource::<OxrSpaceWarpRenderResources>();
+}
+
+fn cleanup_space_warp_swapchains(mut commands: Commands) {
+    commands.remove_resource::<OxrSpaceWarpSwapchains>();
+    commands.remove_resource::<OxrSpaceWarpImageIndices>();
+}
+
+fn transfer_space_warp_swapchains(mut commands: Commands, mut world: ResMut<MainWorld>) {
+    if let Some(OxrSpaceWarpRenderResources(swapchains)) = world.remove_resource() {
+        commands.insert_resource(swapchains);
+    }
+}
+
+fn acquire_space_warp_images(
+    mut swapchains: ResMut<OxrSpaceWarpSwapchains>,
+    policy: Res<OxrWaitImagePolicy>,
+    counters: Res<OxrRenderFrameCounters>,
+    mut commands: Commands,
+) {
+    let _span = info_span!("xr_acquire_space_warp_images");
+    let motion_vectors = swapchains
+        .motion_vectors
+        .acquire_next_image()
+        .expect("Failed to acquire image");
+    let depth = swapchains
+        .depth
+        .acquire_next_image()
+        .expect("Failed to acquire image");
+    let motion_vectors_ready = swapchains
+        .motion_vectors
+        .wait_acquired_image(**policy)
+        .expect("Failed to wait image");
+    let depth_ready = swapchains
+        .depth
+        .wait_acquired_image(**policy)
+        .expect("Failed to wait image");
+    if motion_vectors_ready && depth_ready {
+        commands.insert_resource(OxrSpaceWarpImageIndices {
+            motion_vectors,
+            depth,
+        });
+        return;
+    }
+    // the frame is submitted without space warp, images that timed out are waited for again next frame
+    counters.add_image_wait_timeouts(u64::from(!motion_vectors_ready) + u64::from(!depth_ready));
+    if motion_vectors_ready {
+        swapchains.motion_vectors.release_image().unwrap();
+    }
+    if depth_ready {
+        swapchains.depth.release_image().unwrap();
+    }
+    commands.remove_resource::<OxrSpaceWarpImageIndices>();
+}
+
+fn clean_space_warp_image_indices(mut commands: Commands) {
+    commands.remove_resource::<OxrSpaceWarpImageIndices>();
+}
+
+fn release_space_warp_images(mut swapchains: ResMut<OxrSpaceWarpSwapchains>) {
+    let _span = info_span!("xr_release_space_warp_images");
+    swapchains.motion_vectors.release_image().unwrap();
+    swapchains.depth.release_image().unwrap();
+}
+
+fn update_app_space_delta(
+    root: Res<XrRootTransform>,
+    mut delta: ResMut<OxrSpaceWarpAppSpaceDelta>,
+    mut previous: Local<Option<GlobalTransform>>,
+) {
+    delta.0 = previous
+        .replace(root.0)
+        .map_or(Transform::IDENTITY, |previous| {
+            root.0.reparented_to(&previous)
+        });
+}
+
+/// A [`ProjectionLayer`](crate::layer_builder::ProjectionLayer) that also submits the motion vectors and depth
+/// rendered for the [`OxrSpaceWarpPlugin`].
+///
+/// The depth is expected to use bevy's reversed infinite depth, so it is submitted with a `min_depth` of 0 and a `max_depth` of 1,
+/// a `near_z` of infinity and a `far_z` of `near`, which should match the near plane of the [`XrProjection`](bevy_mod_xr::camera::XrProjection).
+/// Falls back to a plain projection layer while the space warp swapchains don't exist or no images were acquired for the frame.
+pub struct ProjectionLayerSpaceWarp {
+    pub near: f32,
+    pub flags: CompositionLayerFlags,
+    pub space_warp_flags: CompositionLayerSpaceWarpInfoFlagsFB,
+}
+
+impl ProjectionLayerSpaceWarp {
+    pub fn with_flags(mut self, flags: CompositionLayerFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+}
+
+impl Default for ProjectionLayerSpaceWarp {
+    fn default() -> Self {
+        Self {
+            near: 0.1,
+            flags: CompositionLayerFlags::BLEND_TEXTURE_SOURCE_ALPHA,
+            space_warp_flags: CompositionLayerSpaceWarpInfoFlagsFB::EMPTY,
+        }
+    }
+}
+
+impl LayerProvider for ProjectionLayerSpaceWarp {
+    fn get<'a>(&'a self, world: &'a World) -> Option<Box<dyn CompositionLayer + '_>> {
+        let stage = world.get_resource::<XrPrimaryReferenceSpace>()?;
+        let openxr_views = world.get_resource::<OxrViews>()?;
+        let swapchain = world.get_resource::<OxrSwapchain>()?;
+        let per_eye_swapchains = world.get_resource::<OxrPerEyeSwapchains>();
+        let graphics_info = world.get_resource::<OxrGraphicsInfo>()?;
+        let space_warp = world
+            .get_resource::<OxrSpaceWarpSwapchains>()
+            .zip(world.get_resource::<OxrSpaceWarpInfo>())
+            .filter(|_| world.contains_resource::<OxrSpaceWarpImageIndices>());
+        let app_space_delta = world
+            .get_resource::<OxrSpaceWarpAppSpaceDelta>()
+            .map_or(Transform::IDENTITY, |delta| delta.0);
+        let rect = |resolution: UVec2| openxr::Rect2Di {
+            offset: openxr::Offset2Di { x: 0, y: 0 },
+            extent: openxr::Extent2Di {
+                width: resolution.x as _,
+                height: resolution.y as _,
+            },
+        };
+
+        if openxr_views.len() < graphics_info.view_count as usize {
+            return None;
+        }
+
+        let views = openxr_views
+            .iter()
+            .take(graphics_info.view_count as usize)
+            .enumerate()
+            .map(|(i, view)| {
+                let projection_view = CompositionLayerProjectionView::new()
+                    .pose(view.pose)
+                    .fov(view.fov)
+                    .sub_image(
+                        SwapchainSubImage::new()
+                            .swapchain(per_eye_swapchains.map_or(swapchain, |swapchains| {
+                                swapchains.view_swapchain(swapchain, i as u32)
+                            }))
+                            .image_array_index(graphics_info.swapchain_layout.array_layer(i as u32))
+                            .image_rect(rect(graphics_info.view_resolution(i as u32))),
+                    );
+                let Some((swapchains, info)) = space_warp else {
+                    return projection_view;
+                };
+                projection_view.space_warp_info(
+                    CompositionLayerSpaceWarpInfo::new()
+                        .layer_flags(self.space_warp_flags)
+                        .motion_vector_sub_image(
+                            SwapchainSubImage::new()
+                                .swapchain(&swapchains.motion_vectors)
+                                .image_array_index(i as u32)
+                                .image_rect(rect(info.resolution)),
+                        )
+                        .app_space_delta_pose(app_space_delta.to_posef())
+                        .depth_sub_image(
+                            SwapchainSubImage::new()
+                                .swapchain(&swapchains.depth)
+                                .image_array_index(i as u32)
+                                .image_rect(rect(info.resolution)),
+                        )
+                        .min_depth(0.0)
+                        .max_depth(1.0)
+                        // reversed depth, 0.0 is at infinity
+                        .near_z(f32::INFINITY)
+                        .far_z(self.near),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        Some(Box::new(
+            CompositionLayerProjection::new()
+                .layer_flags(self.flags)
+                .space(stage)
+                .views(&views),
+        ))
+    }
+}
@@ -0,0 +1,134 @@
+use std::ptr;
+
+use bevy::prelude::*;
+use bevy_mod_xr::{session::XrPreDestroySession, spaces::XrSpace, types::XrPose};
+use openxr::sys;
+
+use crate::{
+    error::cvt, exts::OxrEnabledExtensions, helper_traits::ToPosef, session::OxrSession,
+    spaces::OxrSpaceExt,
+};
+
+/// Allows placing [`OxrSpatialAnchor`]s at fixed points in the real world.
+///
+/// Add the [`XrSpace`] returned by [`OxrSession::create_spatial_anchor_space`] to an entity to keep it at the anchor,
+/// since the anchor is tracked by the runtime the entity stays there even if the reference space is recentered.
+/// All anchors are removed from their entities when the session is destroyed.
+/// Requires [`XR_MSFT_spatial_anchor`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#XR_MSFT_spatial_anchor).
+pub struct OxrSpatialAnchorPlugin;
+
+impl Plugin for OxrSpatialAnchorPlugin {
+    fn build(&self, app: &mut App) {
+        if !app
+            .world()
+            .get_resource::<OxrEnabledExtensions>()
+            .is_some_and(|exts| exts.spatial_anchor())
+        {
+            info!("Spatial anchor extension not enabled, disabling spatial anchors");
+            return;
+        }
+
+        app.add_systems(XrPreDestroySession, cleanup);
+    }
+}
+
+/// A point in the real world tracked by the runtime, destroyed when dropped.
+///
+/// The anchor keeps the session alive, so it should be dropped before the session is destroyed,
+/// the [`OxrSpatialAnchorPlugin`] does this for anchors stored as components.
+#[derive(Component)]
+pub struct OxrSpatialAnchor {
+    anchor: sys::SpatialAnchorMSFT,
+    session: OxrSession,
+}
+
+impl OxrSpatialAnchor {
+    pub fn as_raw(&self) -> sys::SpatialAnchorMSFT {
+        self.anchor
+    }
+}
+
+impl Drop for OxrSpatialAnchor {
+    fn drop(&mut self) {
+        let Ok(fns) = self.session.spatial_anchor_fns() else {
+            return;
+        };
+        if let Err(err) = cvt(unsafe { (fns.destroy_spatial_anchor)(self.anchor) }) {
+            warn!("error while destroying spatial anchor: {}", err);
+        }
+    }
+}
+
+fn cleanup(query: Query<Entity, With<OxrSpatialAnchor>>, mut cmds: Commands) {
+    // the anchor spaces are destroyed together with the session
+    for entity in &query {
+        cmds.entity(entity).remove::<OxrSpatialAnchor>();
+    }
+}
+
+impl OxrSession {
+    /// Creates an anchor at `pose` relative to `space` at `time`.
+    ///
+    /// Fails with [`ERROR_CREATE_SPATIAL_ANCHOR_FAILED_MSFT`](sys::Result::ERROR_CREATE_SPATIAL_ANCHOR_FAILED_MSFT)
+    /// if the runtime can't create the anchor right now, e.g. because tracking is lost.
+    pub fn create_spatial_anchor(
+        &self,
+        space: &XrSpace,
+        pose: XrPose,
+        time: openxr::Time,
+    ) -> openxr::Result<OxrSpatialAnchor> {
+        let fns = self.spatial_anchor_fns()?;
+        let info = sys::SpatialAnchorCreateInfoMSFT {
+            ty: sys::SpatialAnchorCreateInfoMSFT::TYPE,
+            next: ptr::null(),
+            space: space.as_raw_openxr_space(),
+            pose: pose.to_posef(),
+            time,
+        };
+        let mut anchor = sys::SpatialAnchorMSFT::NULL;
+        unsafe {
+            cvt((fns.create_spatial_anchor)(
+                self.as_raw(),
+                &info,
+                &mut anchor,
+            ))?;
+        }
+        Ok(OxrSpatialAnchor {
+            anchor,
+            session: self.clone(),
+        })
+    }
+
+    /// Creates a space located at `anchor`, it can be located like any other space.
+    ///
+    /// The space has to be destroyed separately, it can't be located anymore once the anchor is dropped.
+    pub fn create_spatial_anchor_space(
+        &self,
+        anchor: &OxrSpatialAnchor,
+    ) -> openxr::Result<XrSpace> {
+        let fns = self.spatial_anchor_fns()?;
+        let info = sys::SpatialAnchorSpaceCreateInfoMSFT {
+            ty: sys::SpatialAnchorSpaceCreateInfoMSFT::TYPE,
+            next: ptr::null(),
+            anchor: anchor.anchor,
+            pose_in_anchor_space: openxr::Posef::IDENTITY,
+        };
+        let mut out = sys::Space::NULL;
+        unsafe {
+            cvt((fns.create_spatial_anchor_space)(
+                self.as_raw(),
+                &info,
+                &mut out,
+            ))?;
+            Ok(XrSpace::from_raw(out.into_raw()))
+        }
+    }
+
+    fn spatial_anchor_fns(&self) -> openxr::Result<&openxr::raw::SpatialAnchorMSFT> {
+        self.instance()
+            .exts()
+            .msft_spatial_anchor
+            .as_ref()
+            .ok_or(sys::Result::ERROR_EXTENSION_NOT_PRESENT)
+    }
+}
@@ -0,0 +1,284 @@
+use std::ptr;
+
+use bevy::{
+    pbr::{NotShadowCaster, NotShadowReceiver},
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        render_asset::RenderAssetUsages,
+        view::RenderLayers,
+    },
+};
+use bevy_mod_xr::{
+    camera::{XrCamera, XrProjection},
+    session::XrPreDestroySession,
+};
+use openxr::{sys, Event};
+
+use crate::{
+    error::cvt,
+    exts::OxrEnabledExtensions,
+    openxr_session_running,
+    poll_events::{OxrEvent, OxrEventHandlerExt},
+    resources::OxrGraphicsInfo,
+    session::OxrSession,
+};
+
+/// The first render layer used for the occluders of the [`OxrVisibilityMaskPlugin`], view `i` uses layer `OXR_VISIBILITY_MASK_RENDER_LAYER + i`.
+pub const OXR_VISIBILITY_MASK_RENDER_LAYER: usize = 24;
+
+/// Loads the visibility masks of the views into [`OxrVisibilityMasks`] and keeps them up to date.
+///
+/// If `occlude_hidden_area` is set, the areas of the views that can't be seen by the user are covered by an occluder mesh right in front of each camera,
+/// so the depth test skips them during the main pass. The cameras are moved to [`OXR_VISIBILITY_MASK_RENDER_LAYER`] + their view index in addition to layer 0 for this.
+/// Requires [`XR_KHR_visibility_mask`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#XR_KHR_visibility_mask).
+pub struct OxrVisibilityMaskPlugin {
+    pub occlude_hidden_area: bool,
+}
+
+impl Default for OxrVisibilityMaskPlugin {
+    fn default() -> Self {
+        Self {
+            occlude_hidden_area: true,
+        }
+    }
+}
+
+impl Plugin for OxrVisibilityMaskPlugin {
+    fn build(&self, app: &mut App) {
+        if !app
+            .world()
+            .get_resource::<OxrEnabledExtensions>()
+            .is_some_and(|exts| exts.visibility_mask())
+        {
+            info!("Visibility mask extension not enabled, disabling visibility masks");
+            return;
+        }
+
+        app.add_event::<OxrVisibilityMaskChanged>()
+            .add_oxr_event_handler(handle_visibility_mask_event)
+            .init_resource::<OxrVisibilityMasks>()
+            .add_systems(
+                Update,
+                update_visibility_masks.run_if(openxr_session_running),
+            )
+            .add_systems(XrPreDestroySession, cleanup);
+        if self.occlude_hidden_area {
+            app.add_systems(
+                Update,
+                spawn_occluders
+                    .after(update_visibility_masks)
+                    .run_if(openxr_session_running),
+            );
+        }
+    }
+}
+
+/// Sent when the runtime changed the visibility mask of a view, the [`OxrVisibilityMasks`] are updated in the same frame.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct OxrVisibilityMaskChanged {
+    pub view_configuration: openxr::ViewConfigurationType,
+    pub view_index: u32,
+}
+
+/// The visibility mask meshes of the views, indexed by the view index.
+///
+/// The meshes lie on the `z = -1` plane of the view space.
+#[derive(Resource, Default, Debug)]
+pub struct OxrVisibilityMasks {
+    /// Covers the area of the view visible to the user.
+    pub visible: Vec<Handle<Mesh>>,
+    /// Covers the area of the view hidden from the user.
+    pub hidden: Vec<Handle<Mesh>>,
+}
+
+/// Marks a mesh covering the hidden area of the view of an [`XrCamera`].
+#[derive(Component, Clone, Copy, Debug)]
+pub struct OxrVisibilityMaskOccluder(pub u32);
+
+fn handle_visibility_mask_event(
+    event: In<OxrEvent>,
+    mut writer: EventWriter<OxrVisibilityMaskChanged>,
+) {
+    // this unwrap will never panic since we are in a valid scope
+    if let Event::VisibilityMaskChangedKHR(event) = unsafe { event.get() }.unwrap() {
+        writer.send(OxrVisibilityMaskChanged {
+            view_configuration: event.view_configuration_type(),
+            view_index: event.view_index(),
+        });
+    }
+}
+
+fn update_visibility_masks(
+    session: Res<OxrSession>,
+    graphics_info: Res<OxrGraphicsInfo>,
+    cameras: Query<&XrCamera, Added<XrCamera>>,
+    mut events: EventReader<OxrVisibilityMaskChanged>,
+    mut masks: ResMut<OxrVisibilityMasks>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let changed = events
+        .read()
+        .filter(|event| event.view_configuration == graphics_info.view_configuration)
+        .map(|event| event.view_index);
+    for view_index in cameras.iter().map(|camera| camera.0).chain(changed) {
+        let index = view_index as usize;
+        if masks.visible.len() <= index {
+            masks.visible.resize_with(index + 1, default);
+            masks.hidden.resize_with(index + 1, default);
+        }
+        for (ty, handle) in [
+            (
+                sys::VisibilityMaskTypeKHR::VISIBLE_TRIANGLE_MESH,
+                &mut masks.visible[index],
+            ),
+            (
+                sys::VisibilityMaskTypeKHR::HIDDEN_TRIANGLE_MESH,
+                &mut masks.hidden[index],
+            ),
+        ] {
+            let mesh =
+                match session.get_visibility_mask(graphics_info.view_configuration, view_index, ty)
+                {
+                    Ok((vertices, indices)) => visibility_mask_mesh(&vertices, indices),
+                    Err(err) => {
+                        error!("Failed to get visibility mask of view {view_index}: {err}");
+                        continue;
+                    }
+                };
+            if handle.id() == AssetId::default() {
+                *handle = meshes.add(mesh);
+            } else {
+                meshes.insert(handle.id(), mesh);
+            }
+        }
+    }
+}
+
+fn spawn_occluders(
+    cameras: Query<(Entity, &XrCamera, &XrProjection), Added<XrCamera>>,
+    masks: Res<OxrVisibilityMasks>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut commands: Commands,
+) {
+    if cameras.is_empty() {
+        return;
+    }
+    let material = materials.add(StandardMaterial {
+        base_color: Color::BLACK,
+        unlit: true,
+        cull_mode: None,
+        ..default()
+    });
+    for (entity, camera, projection) in &cameras {
+        let Some(mesh) = masks.hidden.get(camera.0 as usize) else {
+            continue;
+        };
+        let layer = RenderLayers::layer(OXR_VISIBILITY_MASK_RENDER_LAYER + camera.0 as usize);
+        commands
+            .entity(entity)
+            .insert(RenderLayers::layer(0).union(&layer))
+            .with_children(|parent| {
+                parent.spawn((
+                    PbrBundle {
+                        mesh: mesh.clone(),
+                        material: material.clone(),
+                        // slightly behind the near plane so the occluder isn't clipped
+                        transform: Transform::from_scale(Vec3::splat(projection.near * 1.01)),
+                        ..default()
+                    },
+                    layer,
+                    NotShadowCaster,
+                    NotShadowReceiver,
+                    OxrVisibilityMaskOccluder(camera.0),
+                ));
+            });
+    }
+}
+
+fn cleanup(mut masks: ResMut<OxrVisibilityMasks>) {
+    // the occluders are despawned together with the cameras
+    masks.visible.clear();
+    masks.hidden.clear();
+}
+
+/// Creates a mesh from the `vertices` and `indices` of a visibility mask, the vertices are placed on the `z = -1` plane.
+pub fn visibility_mask_mesh(vertices: &[Vec2], indices: Vec<u32>) -> Mesh {
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+    .with_inserted_attribute(
+        Mesh::ATTRIBUTE_POSITION,
+        vertices
+            .iter()
+            .map(|vertex| [vertex.x, vertex.y, -1.0])
+            .collect::<Vec<_>>(),
+    )
+    .with_inserted_attribute(
+        Mesh::ATTRIBUTE_NORMAL,
+        vec![[0.0, 0.0, 1.0]; vertices.len()],
+    )
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+impl OxrSession {
+    /// Returns the vertices and indices of the visibility mask of type `ty` for the view `view_index` of `view_configuration`.
+    ///
+    /// The vertices are on the `z = -1` plane of the view space, the triangles are wound counter-clockwise.
+    pub fn get_visibility_mask(
+        &self,
+        view_configuration: openxr::ViewConfigurationType,
+        view_index: u32,
+        ty: sys::VisibilityMaskTypeKHR,
+    ) -> openxr::Result<(Vec<Vec2>, Vec<u32>)> {
+        let fns = self
+            .instance()
+            .exts()
+            .khr_visibility_mask
+            .as_ref()
+            .ok_or(sys::Result::ERROR_EXTENSION_NOT_PRESENT)?;
+        let mut mask = sys::VisibilityMaskKHR {
+            ty: sys::VisibilityMaskKHR::TYPE,
+            next: ptr::null_mut(),
+            vertex_capacity_input: 0,
+            vertex_count_output: 0,
+            vertices: ptr::null_mut(),
+            index_capacity_input: 0,
+            index_count_output: 0,
+            indices: ptr::null_mut(),
+        };
+        unsafe {
+            cvt((fns.get_visibility_mask)(
+                self.as_raw(),
+                view_configuration,
+                view_index,
+                ty,
+                &mut mask,
+            ))?;
+            let mut vertices =
+                vec![openxr::Vector2f { x: 0.0, y: 0.0 }; mask.vertex_count_output as usize];
+            let mut indices = vec![0; mask.index_count_output as usize];
+            mask.vertex_capacity_input = vertices.len() as u32;
+            mask.vertices = vertices.as_mut_ptr();
+            mask.index_capacity_input = indices.len() as u32;
+            mask.indices = indices.as_mut_ptr();
+            cvt((fns.get_visibility_mask)(
+                self.as_raw(),
+                view_configuration,
+                view_index,
+                ty,
+                &mut mask,
+            ))?;
+            vertices.truncate(mask.vertex_count_output as usize);
+            indices.truncate(mask.index_count_output as usize);
+            Ok((
+                vertices
+                    .into_iter()
+                    .map(|vertex| Vec2::new(vertex.x, vertex.y))
+                    .collect(),
+                indices,
+            ))
+        }
+    }
+}
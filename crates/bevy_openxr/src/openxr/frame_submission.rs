@@ -0,0 +1,288 @@
+use std::{
+    ptr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Mutex,
+    },
+    thread,
+};
+
+use bevy::{
+    prelude::*,
+    render::{Render, RenderApp},
+};
+use bevy_mod_xr::session::{XrPreDestroySession, XrRenderSet};
+use openxr::sys;
+
+use crate::{
+    error::cvt, graphics::graphics_match, init::should_run_frame_loop,
+    layer_builder::CompositionLayer, render::begin_frame, session::OxrSession,
+};
+
+/// Submits frames from a dedicated thread, so `xrEndFrame` doesn't block the render thread.
+///
+/// The composition layers of a frame are copied at the end of the frame and owned by the submission thread until they are submitted.
+/// Only one frame is submitted at a time and [`begin_frame`] waits for the previous submission to finish,
+/// so `xrWaitFrame`, `xrBeginFrame` and `xrEndFrame` are still called in the order required by the spec.
+///
+/// Frames that contain layers which can't be copied, e.g. because they have extension structs in their `next` chain,
+/// and frames submitted while a secondary view is active are submitted synchronously instead.
+pub struct OxrAsyncFrameSubmissionPlugin;
+
+impl Plugin for OxrAsyncFrameSubmissionPlugin {
+    fn build(&self, app: &mut App) {
+        app.sub_app_mut(RenderApp)
+            .add_systems(
+                Render,
+                start_frame_submitter
+                    .before(begin_frame)
+                    .in_set(XrRenderSet::PreRender)
+                    .run_if(should_run_frame_loop)
+                    .run_if(resource_exists::<OxrSession>)
+                    .run_if(not(resource_exists::<OxrFrameSubmitter>)),
+            )
+            .add_systems(XrPreDestroySession, stop_frame_submitter);
+    }
+}
+
+fn start_frame_submitter(session: Res<OxrSession>, mut commands: Commands) {
+    match OxrFrameSubmitter::new(session.clone()) {
+        Ok(submitter) => commands.insert_resource(submitter),
+        Err(err) => error!("Failed to start frame submission thread: {err}"),
+    }
+}
+
+fn stop_frame_submitter(mut commands: Commands) {
+    commands.remove_resource::<OxrFrameSubmitter>();
+}
+
+/// Handle to the thread submitting the frames, inserted into the render world by the [`OxrAsyncFrameSubmissionPlugin`].
+#[derive(Resource)]
+pub struct OxrFrameSubmitter {
+    sender: Option<mpsc::SyncSender<OxrFrameSubmission>>,
+    results: Mutex<mpsc::Receiver<openxr::Result<()>>>,
+    pending: AtomicBool,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl OxrFrameSubmitter {
+    fn new(session: OxrSession) -> std::io::Result<Self> {
+        let (sender, receiver) = mpsc::sync_channel::<OxrFrameSubmission>(1);
+        let (result_sender, results) = mpsc::channel();
+        let thread = thread::Builder::new()
+            .name("OpenXR frame submission".into())
+            .spawn(move || {
+                #[cfg(target_os = "android")]
+                let _env = {
+                    let ctx = ndk_context::android_context();
+                    let vm = unsafe { jni::JavaVM::from_raw(ctx.vm().cast()) }.unwrap();
+                    vm.attach_current_thread_as_daemon()
+                };
+                for mut submission in receiver {
+                    let _span = info_span!("xr_submit_frame").entered();
+                    if result_sender.send(submission.submit(&session)).is_err() {
+                        break;
+                    }
+                }
+            })?;
+        Ok(Self {
+            sender: Some(sender),
+            results: Mutex::new(results),
+            pending: AtomicBool::new(false),
+            thread: Some(thread),
+        })
+    }
+
+    /// Queues the frame for submission on the submission thread.
+    ///
+    /// Returns `false` if the layers couldn't be copied, the frame has to be submitted synchronously then.
+    /// Layers using a different graphics api than the session are excluded from the frame submission.
+    pub fn submit(
+        &self,
+        session: &OxrSession,
+        display_time: openxr::Time,
+        environment_blend_mode: openxr::EnvironmentBlendMode,
+        layers: &[&dyn CompositionLayer],
+    ) -> bool {
+        // there is at most one frame in flight, its storage is freed once the result is received
+        self.wait();
+        let Some(sender) = &self.sender else {
+            return false;
+        };
+        let layers = graphics_match!(
+            &session.1;
+            _ => layers
+                .iter()
+                .filter(|layer| {
+                    layer
+                        .swapchain()
+                        .map_or(true, |swapchain| swapchain.0.using_graphics::<Api>())
+                })
+                .map(|layer| unsafe { OxrOwnedLayer::copy(layer.header()) })
+                .collect::<Option<Vec<_>>>()
+        );
+        let Some(layers) = layers else {
+            return false;
+        };
+        let submission = OxrFrameSubmission {
+            display_time,
+            environment_blend_mode,
+            layers,
+        };
+        if sender.send(submission).is_err() {
+            return false;
+        }
+        self.pending.store(true, Ordering::Release);
+        true
+    }
+
+    /// Blocks until the frame queued by the last [`submit`](OxrFrameSubmitter::submit) has been submitted.
+    pub fn wait(&self) {
+        if !self.pending.swap(false, Ordering::AcqRel) {
+            return;
+        }
+        match self.results.lock().unwrap().recv() {
+            Ok(Ok(())) => {}
+            Ok(Err(err)) => error!("Failed to end frame stream: {err}"),
+            Err(_) => error!("Frame submission thread stopped unexpectedly"),
+        }
+    }
+}
+
+impl Drop for OxrFrameSubmitter {
+    fn drop(&mut self) {
+        self.wait();
+        // closing the channel stops the thread
+        self.sender = None;
+        if let Some(thread) = self.thread.take() {
+            if thread.join().is_err() {
+                error!("Frame submission thread panicked");
+            }
+        }
+    }
+}
+
+struct OxrFrameSubmission {
+    display_time: openxr::Time,
+    environment_blend_mode: openxr::EnvironmentBlendMode,
+    layers: Vec<OxrOwnedLayer>,
+}
+
+// SAFETY: the pointers in the layers only point into storage owned by the submission itself,
+// they are set right before submitting the frame on the submission thread.
+unsafe impl Send for OxrFrameSubmission {}
+
+impl OxrFrameSubmission {
+    fn submit(&mut self, session: &OxrSession) -> openxr::Result<()> {
+        let headers: Vec<_> = self.layers.iter_mut().map(OxrOwnedLayer::header).collect();
+        let info = sys::FrameEndInfo {
+            ty: sys::FrameEndInfo::TYPE,
+            next: ptr::null(),
+            display_time: self.display_time,
+            environment_blend_mode: self.environment_blend_mode,
+            layer_count: headers.len() as u32,
+            layers: headers.as_ptr(),
+        };
+        unsafe {
+            cvt((session.instance().fp().end_frame)(session.as_raw(), &info))?;
+        }
+        Ok(())
+    }
+}
+
+/// An owned copy of a composition layer, including the storage its `header` points into.
+enum OxrOwnedLayer {
+    Projection {
+        layer: sys::CompositionLayerProjection,
+        views: Vec<sys::CompositionLayerProjectionView>,
+        depth_infos: Vec<Option<sys::CompositionLayerDepthInfoKHR>>,
+    },
+    Quad(sys::CompositionLayerQuad),
+    Cylinder(sys::CompositionLayerCylinderKHR),
+    Equirect2(sys::CompositionLayerEquirect2KHR),
+    Cube(sys::CompositionLayerCubeKHR),
+    Passthrough(sys::CompositionLayerPassthroughFB),
+}
+
+impl OxrOwnedLayer {
+    /// Copies the layer pointed to by `header`, returns [`None`] if the layer type or any struct in its `next` chain is unknown.
+    ///
+    /// # Safety
+    ///
+    /// `header` has to be a valid composition layer.
+    unsafe fn copy(header: &sys::CompositionLayerBaseHeader) -> Option<Self> {
+        if !header.next.is_null() {
+            return None;
+        }
+        let ptr = header as *const sys::CompositionLayerBaseHeader;
+        match header.ty {
+            sys::CompositionLayerProjection::TYPE => {
+                let layer = *ptr.cast::<sys::CompositionLayerProjection>();
+                let views =
+                    std::slice::from_raw_parts(layer.views, layer.view_count as usize).to_vec();
+                let mut depth_infos = Vec::with_capacity(views.len());
+                for view in &views {
+                    if view.next.is_null() {
+                        depth_infos.push(None);
+                        continue;
+                    }
+                    if *view.next.cast::<sys::StructureType>()
+                        != sys::CompositionLayerDepthInfoKHR::TYPE
+                    {
+                        return None;
+                    }
+                    let depth_info = *view.next.cast::<sys::CompositionLayerDepthInfoKHR>();
+                    if !depth_info.next.is_null() {
+                        return None;
+                    }
+                    depth_infos.push(Some(depth_info));
+                }
+                Some(Self::Projection {
+                    layer,
+                    views,
+                    depth_infos,
+                })
+            }
+            sys::CompositionLayerQuad::TYPE => {
+                Some(Self::Quad(*ptr.cast::<sys::CompositionLayerQuad>()))
+            }
+            sys::CompositionLayerCylinderKHR::TYPE => Some(Self::Cylinder(
+                *ptr.cast::<sys::CompositionLayerCylinderKHR>(),
+            )),
+            sys::CompositionLayerEquirect2KHR::TYPE => Some(Self::Equirect2(
+                *ptr.cast::<sys::CompositionLayerEquirect2KHR>(),
+            )),
+            sys::CompositionLayerCubeKHR::TYPE => {
+                Some(Self::Cube(*ptr.cast::<sys::CompositionLayerCubeKHR>()))
+            }
+            sys::CompositionLayerPassthroughFB::TYPE => Some(Self::Passthrough(
+                *ptr.cast::<sys::CompositionLayerPassthroughFB>(),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Updates the pointers into the owned storage and returns the header of the layer.
+    fn header(&mut self) -> *const sys::CompositionLayerBaseHeader {
+        match self {
+            Self::Projection {
+                layer,
+                views,
+                depth_infos,
+            } => {
+                for (view, depth_info) in views.iter_mut().zip(depth_infos.iter()) {
+                    if let Some(depth_info) = depth_info {
+                        view.next = depth_info as *const _ as _;
+                    }
+                }
+                layer.views = views.as_ptr();
+                layer as *const sys::CompositionLayerProjection as _
+            }
+            Self::Quad(layer) => layer as *const sys::CompositionLayerQuad as _,
+            Self::Cylinder(layer) => layer as *const sys::CompositionLayerCylinderKHR as _,
+            Self::Equirect2(layer) => layer as *const sys::CompositionLayerEquirect2KHR as _,
+            Self::Cube(layer) => layer as *const sys::CompositionLayerCubeKHR as _,
+            Self::Passthrough(layer) => layer as *const sys::CompositionLayerPassthroughFB as _,
+        }
+    }
+}
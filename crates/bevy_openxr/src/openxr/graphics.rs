@@ -1,14 +1,17 @@
 #[cfg(all(feature = "d3d12", windows))]
 mod d3d12;
+#[cfg(feature = "headless")]
+mod headless;
+#[cfg(all(feature = "metal", target_os = "macos"))]
+pub mod metal;
 #[cfg(feature = "vulkan")]
 pub mod vulkan;
 
 use std::any::TypeId;
 
-use bevy::math::UVec2;
-use openxr::{FrameStream, FrameWaiter, Session};
+use openxr::{sys, FrameStream, FrameWaiter, Session};
 
-use crate::{session::OxrSessionCreateNextChain, types::{AppInfo, OxrExtensions, Result, WgpuGraphics}};
+use crate::{session::OxrSessionCreateNextChain, types::{AppInfo, OxrExtensions, Result, SwapchainImageLayout, WgpuGraphics}};
 
 /// This is an extension trait to the [`Graphics`](openxr::Graphics) trait and is how the graphics API should be interacted with.
 pub unsafe trait GraphicsExt: openxr::Graphics {
@@ -22,6 +25,11 @@ pub unsafe trait GraphicsExt: openxr::Graphics {
     fn into_wgpu_format(format: Self::Format) -> Option<wgpu::TextureFormat>;
     /// Convert an API specific swapchain image to a [`Texture`](wgpu::Texture).
     ///
+    /// `view_formats` are the additional formats views of the texture can be created with,
+    /// these are only valid if the swapchain was created with [`MUTABLE_FORMAT`](openxr::SwapchainUsageFlags::MUTABLE_FORMAT).
+    /// `usage` has to be covered by the usage flags the swapchain was created with.
+    /// `layout` and `sample_count` have to match the layout and sample count the swapchain was created with.
+    ///
     /// # Safety
     ///
     /// The `image` argument must be a valid handle.
@@ -29,8 +37,21 @@ pub unsafe trait GraphicsExt: openxr::Graphics {
         image: Self::SwapchainImage,
         device: &wgpu::Device,
         format: wgpu::TextureFormat,
-        resolution: UVec2,
+        view_formats: &[wgpu::TextureFormat],
+        usage: wgpu::TextureUsages,
+        layout: SwapchainImageLayout,
+        sample_count: u32,
     ) -> Result<wgpu::Texture>;
+    /// Enumerates the API specific images of a swapchain that isn't an [`openxr::Swapchain`], e.g. one created by an extension.
+    ///
+    /// `enumerate` is called with the capacity, the count output and the images, like `xrEnumerateSwapchainImages`.
+    ///
+    /// # Safety
+    ///
+    /// `enumerate` has to write at most `capacity` images of the API specific swapchain image struct.
+    unsafe fn enumerate_raw_swapchain_images(
+        enumerate: &mut dyn FnMut(u32, &mut u32, *mut sys::SwapchainImageBaseHeader) -> sys::Result,
+    ) -> openxr::Result<Vec<Self::SwapchainImage>>;
     /// Initialize graphics for this backend and return a [`WgpuGraphics`] for bevy and an API specific [Self::SessionCreateInfo] for openxr
     fn init_graphics(
         app_info: &AppInfo,
@@ -76,6 +97,10 @@ impl GraphicsBackend {
         Self::Vulkan(()),
         #[cfg(all(feature = "d3d12", windows))]
         Self::D3D12(()),
+        #[cfg(all(feature = "metal", target_os = "macos"))]
+        Self::Metal(()),
+        #[cfg(feature = "headless")]
+        Self::Headless(()),
     ];
 
     pub fn available_backends(exts: &OxrExtensions) -> Vec<Self> {
@@ -105,6 +130,13 @@ pub enum GraphicsWrap<T: GraphicsType> {
     Vulkan(T::Inner<openxr::Vulkan>),
     #[cfg(all(feature = "d3d12", windows))]
     D3D12(T::Inner<openxr::D3D12>),
+    #[cfg(all(feature = "metal", target_os = "macos"))]
+    Metal(T::Inner<metal::Metal>),
+    /// No graphics at all, see [`XR_MND_headless`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#XR_MND_headless).
+    ///
+    /// Only used if it's listed in the [`backends`](crate::init::OxrInitPlugin::backends).
+    #[cfg(feature = "headless")]
+    Headless(T::Inner<openxr::Headless>),
 }
 
 impl<T: GraphicsType> GraphicsWrap<T> {
@@ -128,6 +160,18 @@ impl<T: GraphicsType> GraphicsWrap<T> {
         self.graphics_type() == TypeId::of::<G>()
     }
 
+    /// Checks if this struct belongs to a headless session, which can't create swapchains.
+    pub fn is_headless(&self) -> bool {
+        #[cfg(feature = "headless")]
+        {
+            self.using_graphics::<openxr::Headless>()
+        }
+        #[cfg(not(feature = "headless"))]
+        {
+            false
+        }
+    }
+
     /// Checks if the two values are both using the same graphics backend
     pub fn using_graphics_of_val<V: GraphicsType>(&self, other: &GraphicsWrap<V>) -> bool {
         self.graphics_type() == other.graphics_type()
@@ -179,6 +223,18 @@ macro_rules! graphics_match {
                 type Api = openxr::D3D12;
                 graphics_match!(@arm_impl D3D12; $expr $(=> $($return)*)?)
             },
+            #[cfg(all(feature = "metal", target_os = "macos"))]
+            $crate::graphics::GraphicsWrap::Metal($var) => {
+                #[allow(unused)]
+                type Api = $crate::graphics::metal::Metal;
+                graphics_match!(@arm_impl Metal; $expr $(=> $($return)*)?)
+            },
+            #[cfg(feature = "headless")]
+            $crate::graphics::GraphicsWrap::Headless($var) => {
+                #[allow(unused)]
+                type Api = openxr::Headless;
+                graphics_match!(@arm_impl Headless; $expr $(=> $($return)*)?)
+            },
         }
     };
 
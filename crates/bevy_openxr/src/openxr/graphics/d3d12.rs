@@ -5,9 +5,11 @@ use winapi::shared::dxgiformat::DXGI_FORMAT;
 use winapi::um::d3d12 as winapi_d3d12;
 
 use super::{GraphicsExt, GraphicsType, GraphicsWrap};
-use crate::error::OxrError;
+use crate::error::{cvt, OxrError};
 use crate::session::OxrSessionCreateNextChain;
-use crate::types::{AppInfo, OxrExtensions, Result, WgpuGraphics};
+use crate::types::{
+    AppInfo, OxrExtensions, OxrGraphicsAdapter, Result, SwapchainImageLayout, WgpuGraphics,
+};
 
 unsafe impl GraphicsExt for openxr::D3D12 {
     fn wrap<T: GraphicsType>(item: T::Inner<Self>) -> GraphicsWrap<T> {
@@ -32,40 +34,53 @@ unsafe impl GraphicsExt for openxr::D3D12 {
         image: Self::SwapchainImage,
         device: &wgpu::Device,
         format: wgpu::TextureFormat,
-        resolution: bevy::prelude::UVec2,
+        view_formats: &[wgpu::TextureFormat],
+        usage: wgpu::TextureUsages,
+        layout: SwapchainImageLayout,
+        sample_count: u32,
     ) -> Result<wgpu::Texture> {
         let wgpu_hal_texture = <wgpu_hal::dx12::Api as wgpu_hal::Api>::Device::texture_from_raw(
             d3d12::ComPtr::from_raw(image as *mut _),
             format,
-            wgpu::TextureDimension::D2,
-            wgpu::Extent3d {
-                width: resolution.x,
-                height: resolution.y,
-                depth_or_array_layers: 2,
-            },
-            1,
+            layout.texture_dimension(),
+            layout.extent(),
             1,
+            sample_count,
         );
         let texture = device.create_texture_from_hal::<wgpu_hal::dx12::Api>(
             wgpu_hal_texture,
             &wgpu::TextureDescriptor {
                 label: Some("VR Swapchain"),
-                size: wgpu::Extent3d {
-                    width: resolution.x,
-                    height: resolution.y,
-                    depth_or_array_layers: 2,
-                },
+                size: layout.extent(),
                 mip_level_count: 1,
-                sample_count: 1,
-                dimension: wgpu::TextureDimension::D2,
+                sample_count,
+                dimension: layout.texture_dimension(),
                 format: format,
-                usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_DST,
-                view_formats: &[],
+                usage,
+                view_formats,
             },
         );
         Ok(texture)
     }
 
+    unsafe fn enumerate_raw_swapchain_images(
+        enumerate: &mut dyn FnMut(u32, &mut u32, *mut sys::SwapchainImageBaseHeader) -> sys::Result,
+    ) -> openxr::Result<Vec<Self::SwapchainImage>> {
+        let mut count = 0;
+        cvt(enumerate(0, &mut count, std::ptr::null_mut()))?;
+        let mut images = vec![
+            sys::SwapchainImageD3D12KHR {
+                ty: sys::SwapchainImageD3D12KHR::TYPE,
+                next: std::ptr::null_mut(),
+                texture: std::ptr::null_mut(),
+            };
+            count as usize
+        ];
+        cvt(enumerate(count, &mut count, images.as_mut_ptr() as _))?;
+        images.truncate(count as usize);
+        Ok(images.into_iter().map(|image| image.texture).collect())
+    }
+
     fn init_graphics(
         app_info: &AppInfo,
         instance: &openxr::Instance,
@@ -101,6 +116,25 @@ unsafe impl GraphicsExt for openxr::D3D12 {
                 crate::error::InitError::FailedToFindD3D12Adapter,
             ))?;
 
+        let required_adapter = {
+            let mut desc = unsafe { std::mem::zeroed() };
+            unsafe {
+                wgpu_exposed_adapter
+                    .adapter
+                    .raw_adapter()
+                    .GetDesc1(&mut desc)
+            };
+            let mut luid = [0; 8];
+            luid[..4].copy_from_slice(&reqs.adapter_luid.LowPart.to_ne_bytes());
+            luid[4..].copy_from_slice(&reqs.adapter_luid.HighPart.to_ne_bytes());
+            OxrGraphicsAdapter {
+                vendor: desc.VendorId,
+                device: desc.DeviceId,
+                luid: Some(luid),
+                uuid: None,
+            }
+        };
+
         let wgpu_instance =
             unsafe { wgpu::Instance::from_hal::<wgpu_hal::api::Dx12>(wgpu_raw_instance) };
 
@@ -143,13 +177,22 @@ unsafe impl GraphicsExt for openxr::D3D12 {
             )?
         };
 
+        let wgpu_adapter_info = wgpu_adapter.get_info();
+        if !required_adapter.matches(&wgpu_adapter_info) {
+            return Err(OxrError::GraphicsAdapterMismatch {
+                required: required_adapter,
+                adapter: wgpu_adapter_info.name,
+            });
+        }
+
         Ok((
             WgpuGraphics(
                 wgpu_device,
                 wgpu_queue,
-                wgpu_adapter.get_info(),
+                wgpu_adapter_info,
                 wgpu_adapter,
                 wgpu_instance,
+                required_adapter,
             ),
             Self::SessionCreateInfo {
                 device: raw_device.cast(),
@@ -194,14 +237,6 @@ unsafe impl GraphicsExt for openxr::D3D12 {
     }
 }
 
-fn cvt(x: sys::Result) -> openxr::Result<sys::Result> {
-    if x.into_raw() >= 0 {
-        Ok(x)
-    } else {
-        Err(x)
-    }
-}
-
 // Extracted from https://github.com/gfx-rs/wgpu/blob/1161a22f4fbb4fc204eb06f2ac4243f83e0e980d/wgpu-hal/src/dx12/adapter.rs#L73-L94
 // license: MIT OR Apache-2.0
 fn get_device_feature_level(
@@ -390,4 +425,3 @@ fn wgpu_to_d3d12(format: wgpu::TextureFormat) -> Option<DXGI_FORMAT> {
         } => return None,
     })
 }
-
@@ -0,0 +1,129 @@
+use bevy::log::error;
+use bevy::tasks::block_on;
+use openxr::sys;
+
+use super::{GraphicsExt, GraphicsType, GraphicsWrap};
+use crate::error::{cvt, OxrError};
+use crate::session::OxrSessionCreateNextChain;
+use crate::types::{
+    AppInfo, OxrExtensions, OxrGraphicsAdapter, Result, SwapchainImageLayout, WgpuGraphics,
+};
+
+/// Sessions without a graphics binding, using `XR_MND_headless`.
+///
+/// They can sync actions and locate spaces, but have no swapchains, so nothing is rendered into the headset.
+/// Bevy still gets a device of its own, e.g. to render into a window.
+unsafe impl GraphicsExt for openxr::Headless {
+    fn wrap<T: GraphicsType>(item: T::Inner<Self>) -> GraphicsWrap<T> {
+        GraphicsWrap::Headless(item)
+    }
+
+    fn required_exts() -> OxrExtensions {
+        let mut extensions = openxr::ExtensionSet::default();
+        extensions.mnd_headless = true;
+        extensions.into()
+    }
+
+    fn from_wgpu_format(_format: wgpu::TextureFormat) -> Option<Self::Format> {
+        None
+    }
+
+    fn into_wgpu_format(_format: Self::Format) -> Option<wgpu::TextureFormat> {
+        None
+    }
+
+    unsafe fn to_wgpu_img(
+        _image: Self::SwapchainImage,
+        _device: &wgpu::Device,
+        _format: wgpu::TextureFormat,
+        _view_formats: &[wgpu::TextureFormat],
+        _usage: wgpu::TextureUsages,
+        _layout: SwapchainImageLayout,
+        _sample_count: u32,
+    ) -> Result<wgpu::Texture> {
+        Err(OxrError::HeadlessSessionNoGraphics)
+    }
+
+    unsafe fn enumerate_raw_swapchain_images(
+        _enumerate: &mut dyn FnMut(
+            u32,
+            &mut u32,
+            *mut sys::SwapchainImageBaseHeader,
+        ) -> sys::Result,
+    ) -> openxr::Result<Vec<Self::SwapchainImage>> {
+        Err(sys::Result::ERROR_FUNCTION_UNSUPPORTED)
+    }
+
+    fn init_graphics(
+        _app_info: &AppInfo,
+        _instance: &openxr::Instance,
+        _system_id: openxr::SystemId,
+    ) -> Result<(WgpuGraphics, Self::SessionCreateInfo)> {
+        // the runtime doesn't use the device, so any adapter works
+        let wgpu_instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+        let Some(wgpu_adapter) =
+            block_on(wgpu_instance.request_adapter(&wgpu::RequestAdapterOptions::default()))
+        else {
+            error!("No wgpu adapter available for the headless session");
+            return Err(OxrError::FailedGraphicsRequirements);
+        };
+        let (wgpu_device, wgpu_queue) = block_on(wgpu_adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: None,
+                required_features: wgpu_adapter.features(),
+                required_limits: wgpu_adapter.limits(),
+            },
+            None,
+        ))?;
+
+        let wgpu_adapter_info = wgpu_adapter.get_info();
+        let adapter = OxrGraphicsAdapter {
+            vendor: wgpu_adapter_info.vendor,
+            device: wgpu_adapter_info.device,
+            luid: None,
+            uuid: None,
+        };
+
+        Ok((
+            WgpuGraphics(
+                wgpu_device,
+                wgpu_queue,
+                wgpu_adapter_info,
+                wgpu_adapter,
+                wgpu_instance,
+                adapter,
+            ),
+            openxr::headless::SessionCreateInfo {},
+        ))
+    }
+
+    unsafe fn create_session(
+        instance: &openxr::Instance,
+        system_id: openxr::SystemId,
+        _info: &Self::SessionCreateInfo,
+        session_create_info_chain: &mut OxrSessionCreateNextChain,
+    ) -> openxr::Result<(
+        openxr::Session<Self>,
+        openxr::FrameWaiter,
+        openxr::FrameStream<Self>,
+    )> {
+        // no graphics binding in the next chain makes this a headless session
+        let info = sys::SessionCreateInfo {
+            ty: sys::SessionCreateInfo::TYPE,
+            next: session_create_info_chain.chain_pointer(),
+            create_flags: Default::default(),
+            system_id,
+        };
+        let mut out = sys::Session::NULL;
+        cvt((instance.fp().create_session)(
+            instance.as_raw(),
+            &info,
+            &mut out,
+        ))?;
+        Ok(openxr::Session::from_raw(
+            instance.clone(),
+            out,
+            Box::new(()),
+        ))
+    }
+}
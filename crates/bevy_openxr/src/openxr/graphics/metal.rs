@@ -0,0 +1,426 @@
+use std::ffi::{c_char, c_void};
+use std::ptr;
+
+use metal::foreign_types::{ForeignType, ForeignTypeRef};
+use metal::MTLPixelFormat;
+use openxr::sys;
+use wgpu_hal::{Adapter, Instance};
+
+use super::{GraphicsExt, GraphicsType, GraphicsWrap};
+use crate::error::{cvt, InitError, OxrError};
+use crate::session::OxrSessionCreateNextChain;
+use crate::types::{
+    AppInfo, OxrExtensions, OxrGraphicsAdapter, Result, SwapchainImageLayout, WgpuGraphics,
+};
+
+/// The Metal graphics API, using `XR_KHR_metal_enable`.
+///
+/// The openxr crate doesn't implement [`Graphics`](openxr::Graphics) for Metal, so it's implemented here on top of
+/// hand-declared bindings of the extension.
+pub struct Metal;
+
+/// The requirements of the runtime, returned by `xrGetMetalGraphicsRequirementsKHR`.
+#[derive(Clone, Copy, Debug)]
+pub struct Requirements {
+    /// The `id<MTLDevice>` the session has to be created with.
+    pub metal_device: *mut c_void,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SessionCreateInfo {
+    /// An `id<MTLCommandQueue>` of the device returned in the [`Requirements`].
+    pub command_queue: *mut c_void,
+}
+
+impl openxr::Graphics for Metal {
+    type Requirements = Requirements;
+    type SessionCreateInfo = SessionCreateInfo;
+    /// A raw `MTLPixelFormat`.
+    type Format = u64;
+    /// An `id<MTLTexture>`.
+    type SwapchainImage = *mut c_void;
+
+    fn raise_format(x: i64) -> Self::Format {
+        x as _
+    }
+
+    fn lower_format(x: Self::Format) -> i64 {
+        x as _
+    }
+
+    fn requirements(
+        instance: &openxr::Instance,
+        system: openxr::SystemId,
+    ) -> openxr::Result<Self::Requirements> {
+        let get_requirements = get_metal_graphics_requirements_fn(instance)
+            .ok_or(sys::Result::ERROR_FUNCTION_UNSUPPORTED)?;
+        let mut out = GraphicsRequirementsMetalKHR {
+            ty: sys::StructureType::from_raw(TYPE_GRAPHICS_REQUIREMENTS_METAL_KHR),
+            next: ptr::null_mut(),
+            metal_device: ptr::null_mut(),
+        };
+        cvt(unsafe { get_requirements(instance.as_raw(), system, &mut out) })?;
+        Ok(Requirements {
+            metal_device: out.metal_device,
+        })
+    }
+
+    unsafe fn create_session(
+        instance: &openxr::Instance,
+        system: openxr::SystemId,
+        info: &Self::SessionCreateInfo,
+    ) -> openxr::Result<sys::Session> {
+        let binding = GraphicsBindingMetalKHR {
+            ty: sys::StructureType::from_raw(TYPE_GRAPHICS_BINDING_METAL_KHR),
+            next: ptr::null(),
+            command_queue: info.command_queue,
+        };
+        let info = sys::SessionCreateInfo {
+            ty: sys::SessionCreateInfo::TYPE,
+            next: &binding as *const _ as *const _,
+            create_flags: Default::default(),
+            system_id: system,
+        };
+        let mut out = sys::Session::NULL;
+        cvt((instance.fp().create_session)(
+            instance.as_raw(),
+            &info,
+            &mut out,
+        ))?;
+        Ok(out)
+    }
+
+    fn enumerate_swapchain_images(
+        swapchain: &openxr::Swapchain<Self>,
+    ) -> openxr::Result<Vec<Self::SwapchainImage>> {
+        unsafe {
+            Self::enumerate_raw_swapchain_images(&mut |capacity, count, images| {
+                (swapchain.instance().fp().enumerate_swapchain_images)(
+                    swapchain.as_raw(),
+                    capacity,
+                    count,
+                    images,
+                )
+            })
+        }
+    }
+}
+
+unsafe impl GraphicsExt for Metal {
+    fn wrap<T: GraphicsType>(item: T::Inner<Self>) -> GraphicsWrap<T> {
+        GraphicsWrap::Metal(item)
+    }
+
+    fn required_exts() -> OxrExtensions {
+        let mut extensions = OxrExtensions::default();
+        extensions.enable_khr_metal_enable();
+        extensions
+    }
+
+    fn from_wgpu_format(format: wgpu::TextureFormat) -> Option<Self::Format> {
+        FORMATS
+            .iter()
+            .find(|(_, wgpu_format)| *wgpu_format == format)
+            .map(|(metal_format, _)| *metal_format as u64)
+    }
+
+    fn into_wgpu_format(format: Self::Format) -> Option<wgpu::TextureFormat> {
+        FORMATS
+            .iter()
+            .find(|(metal_format, _)| *metal_format as u64 == format)
+            .map(|(_, wgpu_format)| *wgpu_format)
+    }
+
+    unsafe fn to_wgpu_img(
+        image: Self::SwapchainImage,
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        view_formats: &[wgpu::TextureFormat],
+        usage: wgpu::TextureUsages,
+        layout: SwapchainImageLayout,
+        sample_count: u32,
+    ) -> Result<wgpu::Texture> {
+        let raw_type = match (layout.is_cube(), layout.array_size > 1) {
+            (true, true) => metal::MTLTextureType::CubeArray,
+            (true, false) => metal::MTLTextureType::Cube,
+            (false, true) => metal::MTLTextureType::D2Array,
+            (false, false) if sample_count > 1 => metal::MTLTextureType::D2Multisample,
+            (false, false) => metal::MTLTextureType::D2,
+        };
+        // the texture is owned by the runtime, wgpu gets a retained reference of its own
+        let raw_texture = metal::TextureRef::from_ptr(image.cast()).to_owned();
+        let wgpu_hal_texture = <wgpu_hal::metal::Api as wgpu_hal::Api>::Device::texture_from_raw(
+            raw_texture,
+            format,
+            raw_type,
+            layout.array_layer_count(),
+            1,
+            wgpu_hal::CopyExtent {
+                width: layout.resolution.x,
+                height: layout.resolution.y,
+                depth: 1,
+            },
+        );
+        let texture = device.create_texture_from_hal::<wgpu_hal::metal::Api>(
+            wgpu_hal_texture,
+            &wgpu::TextureDescriptor {
+                label: Some("VR Swapchain"),
+                size: layout.extent(),
+                mip_level_count: 1,
+                sample_count,
+                dimension: layout.texture_dimension(),
+                format,
+                usage,
+                view_formats,
+            },
+        );
+        Ok(texture)
+    }
+
+    unsafe fn enumerate_raw_swapchain_images(
+        enumerate: &mut dyn FnMut(u32, &mut u32, *mut sys::SwapchainImageBaseHeader) -> sys::Result,
+    ) -> openxr::Result<Vec<Self::SwapchainImage>> {
+        let mut count = 0;
+        cvt(enumerate(0, &mut count, ptr::null_mut()))?;
+        let mut images = vec![
+            SwapchainImageMetalKHR {
+                ty: sys::StructureType::from_raw(TYPE_SWAPCHAIN_IMAGE_METAL_KHR),
+                next: ptr::null_mut(),
+                texture: ptr::null_mut(),
+            };
+            count as usize
+        ];
+        cvt(enumerate(count, &mut count, images.as_mut_ptr() as _))?;
+        images.truncate(count as usize);
+        Ok(images.into_iter().map(|image| image.texture).collect())
+    }
+
+    fn init_graphics(
+        app_info: &AppInfo,
+        instance: &openxr::Instance,
+        system_id: openxr::SystemId,
+    ) -> Result<(WgpuGraphics, Self::SessionCreateInfo)> {
+        let reqs = instance.graphics_requirements::<Metal>(system_id)?;
+        let required_device = unsafe { metal::DeviceRef::from_ptr(reqs.metal_device.cast()) };
+
+        let instance_descriptor = &wgpu_hal::InstanceDescriptor {
+            name: &app_info.name,
+            dx12_shader_compiler: Default::default(),
+            flags: wgpu::InstanceFlags::from_build_config().with_env(),
+            gles_minor_version: Default::default(),
+        };
+        let wgpu_raw_instance: wgpu_hal::metal::Instance =
+            unsafe { wgpu_hal::metal::Instance::init(instance_descriptor)? };
+        let wgpu_adapters: Vec<wgpu_hal::ExposedAdapter<wgpu_hal::metal::Api>> =
+            unsafe { wgpu_raw_instance.enumerate_adapters() };
+
+        let wgpu_exposed_adapter = wgpu_adapters
+            .into_iter()
+            .find(|a| a.info.name == required_device.name())
+            .ok_or(OxrError::InitError(InitError::FailedToFindMetalDevice))?;
+
+        let wgpu_instance =
+            unsafe { wgpu::Instance::from_hal::<wgpu_hal::api::Metal>(wgpu_raw_instance) };
+
+        // Metal doesn't support multiview in wgpu, so only the features the adapter has are requested
+        let wgpu_features = wgpu_exposed_adapter.features
+            & (wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES
+                | wgpu::Features::MULTIVIEW
+                | wgpu::Features::MULTI_DRAW_INDIRECT_COUNT
+                | wgpu::Features::MULTI_DRAW_INDIRECT);
+
+        let wgpu_limits = wgpu_exposed_adapter.capabilities.limits.clone();
+
+        let wgpu_open_device = unsafe {
+            wgpu_exposed_adapter
+                .adapter
+                .open(wgpu_features, &wgpu_limits)?
+        };
+
+        // device names aren't unique, the registry id is
+        if wgpu_open_device.device.raw_device().lock().registry_id()
+            != required_device.registry_id()
+        {
+            return Err(OxrError::InitError(InitError::FailedToFindMetalDevice));
+        }
+
+        let wgpu_adapter = unsafe { wgpu_instance.create_adapter_from_hal(wgpu_exposed_adapter) };
+        let raw_queue = wgpu_open_device.queue.as_raw().lock().as_ptr();
+        let (wgpu_device, wgpu_queue) = unsafe {
+            wgpu_adapter.create_device_from_hal(
+                wgpu_open_device,
+                &wgpu::DeviceDescriptor {
+                    label: Some("bevy_oxr device"),
+                    required_features: wgpu_features,
+                    required_limits: wgpu_limits,
+                },
+                None,
+            )?
+        };
+
+        let wgpu_adapter_info = wgpu_adapter.get_info();
+        let required_adapter = OxrGraphicsAdapter {
+            vendor: wgpu_adapter_info.vendor,
+            device: wgpu_adapter_info.device,
+            luid: None,
+            uuid: None,
+        };
+
+        Ok((
+            WgpuGraphics(
+                wgpu_device,
+                wgpu_queue,
+                wgpu_adapter_info,
+                wgpu_adapter,
+                wgpu_instance,
+                required_adapter,
+            ),
+            SessionCreateInfo {
+                command_queue: raw_queue.cast(),
+            },
+        ))
+    }
+
+    unsafe fn create_session(
+        instance: &openxr::Instance,
+        system_id: openxr::SystemId,
+        info: &Self::SessionCreateInfo,
+        session_create_info_chain: &mut OxrSessionCreateNextChain,
+    ) -> openxr::Result<(
+        openxr::Session<Self>,
+        openxr::FrameWaiter,
+        openxr::FrameStream<Self>,
+    )> {
+        let binding = GraphicsBindingMetalKHR {
+            ty: sys::StructureType::from_raw(TYPE_GRAPHICS_BINDING_METAL_KHR),
+            next: session_create_info_chain.chain_pointer(),
+            command_queue: info.command_queue,
+        };
+        let info = sys::SessionCreateInfo {
+            ty: sys::SessionCreateInfo::TYPE,
+            next: &binding as *const _ as *const _,
+            create_flags: Default::default(),
+            system_id,
+        };
+        let mut out = sys::Session::NULL;
+        cvt((instance.fp().create_session)(
+            instance.as_raw(),
+            &info,
+            &mut out,
+        ))?;
+        Ok(openxr::Session::from_raw(
+            instance.clone(),
+            out,
+            Box::new(()),
+        ))
+    }
+}
+
+// `XR_KHR_metal_enable` is newer than the bindings of the openxr crate, so its types are declared here.
+const TYPE_GRAPHICS_BINDING_METAL_KHR: i32 = 1000029000;
+const TYPE_SWAPCHAIN_IMAGE_METAL_KHR: i32 = 1000029001;
+const TYPE_GRAPHICS_REQUIREMENTS_METAL_KHR: i32 = 1000029002;
+
+type GetMetalGraphicsRequirementsKHR = unsafe extern "system" fn(
+    instance: sys::Instance,
+    system_id: sys::SystemId,
+    graphics_requirements: *mut GraphicsRequirementsMetalKHR,
+) -> sys::Result;
+
+#[repr(C)]
+struct GraphicsBindingMetalKHR {
+    ty: sys::StructureType,
+    next: *const c_void,
+    command_queue: *mut c_void,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct SwapchainImageMetalKHR {
+    ty: sys::StructureType,
+    next: *mut c_void,
+    texture: *mut c_void,
+}
+
+#[repr(C)]
+struct GraphicsRequirementsMetalKHR {
+    ty: sys::StructureType,
+    next: *mut c_void,
+    metal_device: *mut c_void,
+}
+
+/// Returns `xrGetMetalGraphicsRequirementsKHR`, or [`None`] if the runtime doesn't provide it.
+fn get_metal_graphics_requirements_fn(
+    instance: &openxr::Instance,
+) -> Option<GetMetalGraphicsRequirementsKHR> {
+    let mut function = None;
+    let result = unsafe {
+        (instance.fp().get_instance_proc_addr)(
+            instance.as_raw(),
+            b"xrGetMetalGraphicsRequirementsKHR\0".as_ptr() as *const c_char,
+            &mut function,
+        )
+    };
+    if result.into_raw() < 0 {
+        return None;
+    }
+    function.map(|function| unsafe {
+        std::mem::transmute::<sys::pfn::VoidFunction, GetMetalGraphicsRequirementsKHR>(function)
+    })
+}
+
+const FORMATS: &[(MTLPixelFormat, wgpu::TextureFormat)] = {
+    use metal::MTLPixelFormat as Mf;
+    use wgpu::TextureFormat as Tf;
+
+    &[
+        (Mf::R8Unorm, Tf::R8Unorm),
+        (Mf::R8Snorm, Tf::R8Snorm),
+        (Mf::R8Uint, Tf::R8Uint),
+        (Mf::R8Sint, Tf::R8Sint),
+        (Mf::R16Uint, Tf::R16Uint),
+        (Mf::R16Sint, Tf::R16Sint),
+        (Mf::R16Unorm, Tf::R16Unorm),
+        (Mf::R16Snorm, Tf::R16Snorm),
+        (Mf::R16Float, Tf::R16Float),
+        (Mf::RG8Unorm, Tf::Rg8Unorm),
+        (Mf::RG8Snorm, Tf::Rg8Snorm),
+        (Mf::RG8Uint, Tf::Rg8Uint),
+        (Mf::RG8Sint, Tf::Rg8Sint),
+        (Mf::RG16Unorm, Tf::Rg16Unorm),
+        (Mf::RG16Snorm, Tf::Rg16Snorm),
+        (Mf::R32Uint, Tf::R32Uint),
+        (Mf::R32Sint, Tf::R32Sint),
+        (Mf::R32Float, Tf::R32Float),
+        (Mf::RG16Uint, Tf::Rg16Uint),
+        (Mf::RG16Sint, Tf::Rg16Sint),
+        (Mf::RG16Float, Tf::Rg16Float),
+        (Mf::RGBA8Unorm, Tf::Rgba8Unorm),
+        (Mf::RGBA8Unorm_sRGB, Tf::Rgba8UnormSrgb),
+        (Mf::BGRA8Unorm_sRGB, Tf::Bgra8UnormSrgb),
+        (Mf::RGBA8Snorm, Tf::Rgba8Snorm),
+        (Mf::BGRA8Unorm, Tf::Bgra8Unorm),
+        (Mf::RGBA8Uint, Tf::Rgba8Uint),
+        (Mf::RGBA8Sint, Tf::Rgba8Sint),
+        (Mf::RGB9E5Float, Tf::Rgb9e5Ufloat),
+        (Mf::RGB10A2Uint, Tf::Rgb10a2Uint),
+        (Mf::RGB10A2Unorm, Tf::Rgb10a2Unorm),
+        (Mf::RG11B10Float, Tf::Rg11b10Float),
+        (Mf::RG32Uint, Tf::Rg32Uint),
+        (Mf::RG32Sint, Tf::Rg32Sint),
+        (Mf::RG32Float, Tf::Rg32Float),
+        (Mf::RGBA16Uint, Tf::Rgba16Uint),
+        (Mf::RGBA16Sint, Tf::Rgba16Sint),
+        (Mf::RGBA16Unorm, Tf::Rgba16Unorm),
+        (Mf::RGBA16Snorm, Tf::Rgba16Snorm),
+        (Mf::RGBA16Float, Tf::Rgba16Float),
+        (Mf::RGBA32Uint, Tf::Rgba32Uint),
+        (Mf::RGBA32Sint, Tf::Rgba32Sint),
+        (Mf::RGBA32Float, Tf::Rgba32Float),
+        (Mf::Stencil8, Tf::Stencil8),
+        (Mf::Depth16Unorm, Tf::Depth16Unorm),
+        (Mf::Depth32Float, Tf::Depth32Float),
+        (Mf::Depth24Unorm_Stencil8, Tf::Depth24PlusStencil8),
+        (Mf::Depth32Float_Stencil8, Tf::Depth32FloatStencil8),
+    ]
+};
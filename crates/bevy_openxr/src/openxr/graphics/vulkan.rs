@@ -2,15 +2,16 @@ use std::ffi::{c_void, CString};
 
 use ash::vk::Handle;
 use bevy::log::error;
-use bevy::math::UVec2;
 use openxr::{sys, Version};
 use wgpu_hal::api::Vulkan;
 use wgpu_hal::Api;
 
 use super::{GraphicsExt, GraphicsType, GraphicsWrap};
-use crate::error::OxrError;
+use crate::error::{cvt, OxrError};
 use crate::session::OxrSessionCreateNextChain;
-use crate::types::{AppInfo, OxrExtensions, Result, WgpuGraphics};
+use crate::types::{
+    AppInfo, OxrExtensions, OxrGraphicsAdapter, Result, SwapchainImageLayout, WgpuGraphics,
+};
 
 #[cfg(not(target_os = "android"))]
 const VK_TARGET_VERSION: Version = Version::new(1, 2, 0);
@@ -47,7 +48,10 @@ unsafe impl GraphicsExt for openxr::Vulkan {
         color_image: Self::SwapchainImage,
         device: &wgpu::Device,
         format: wgpu::TextureFormat,
-        resolution: UVec2,
+        view_formats: &[wgpu::TextureFormat],
+        usage: wgpu::TextureUsages,
+        layout: SwapchainImageLayout,
+        sample_count: u32,
     ) -> Result<wgpu::Texture> {
         let color_image = ash::vk::Image::from_raw(color_image);
         let wgpu_hal_texture = unsafe {
@@ -55,18 +59,14 @@ unsafe impl GraphicsExt for openxr::Vulkan {
                 color_image,
                 &wgpu_hal::TextureDescriptor {
                     label: Some("VR Swapchain"),
-                    size: wgpu::Extent3d {
-                        width: resolution.x,
-                        height: resolution.y,
-                        depth_or_array_layers: 2,
-                    },
+                    size: layout.extent(),
                     mip_level_count: 1,
-                    sample_count: 1,
-                    dimension: wgpu::TextureDimension::D2,
+                    sample_count,
+                    dimension: layout.texture_dimension(),
                     format: format,
-                    usage: wgpu_hal::TextureUses::COLOR_TARGET | wgpu_hal::TextureUses::COPY_DST,
+                    usage: hal_texture_uses(usage, format),
                     memory_flags: wgpu_hal::MemoryFlags::empty(),
-                    view_formats: vec![],
+                    view_formats: view_formats.to_vec(),
                 },
                 None,
             )
@@ -76,23 +76,37 @@ unsafe impl GraphicsExt for openxr::Vulkan {
                 wgpu_hal_texture,
                 &wgpu::TextureDescriptor {
                     label: Some("VR Swapchain"),
-                    size: wgpu::Extent3d {
-                        width: resolution.x,
-                        height: resolution.y,
-                        depth_or_array_layers: 2,
-                    },
+                    size: layout.extent(),
                     mip_level_count: 1,
-                    sample_count: 1,
-                    dimension: wgpu::TextureDimension::D2,
+                    sample_count,
+                    dimension: layout.texture_dimension(),
                     format: format,
-                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_DST,
-                    view_formats: &[],
+                    usage,
+                    view_formats,
                 },
             )
         };
         Ok(texture)
     }
 
+    unsafe fn enumerate_raw_swapchain_images(
+        enumerate: &mut dyn FnMut(u32, &mut u32, *mut sys::SwapchainImageBaseHeader) -> sys::Result,
+    ) -> openxr::Result<Vec<Self::SwapchainImage>> {
+        let mut count = 0;
+        cvt(enumerate(0, &mut count, std::ptr::null_mut()))?;
+        let mut images = vec![
+            sys::SwapchainImageVulkanKHR {
+                ty: sys::SwapchainImageVulkanKHR::TYPE,
+                next: std::ptr::null_mut(),
+                image: 0,
+            };
+            count as usize
+        ];
+        cvt(enumerate(count, &mut count, images.as_mut_ptr() as _))?;
+        images.truncate(count as usize);
+        Ok(images.into_iter().map(|image| image.image).collect())
+    }
+
     fn init_graphics(
         app_info: &AppInfo,
         instance: &openxr::Instance,
@@ -126,11 +140,12 @@ unsafe impl GraphicsExt for openxr::Vulkan {
             let extensions_cchar: Vec<_> = extensions.iter().map(|s| s.as_ptr()).collect();
 
             let app_name = CString::new(app_info.name.clone().into_owned())?;
+            let engine_name = CString::new(app_info.engine_name())?;
             let vk_app_info = ash::vk::ApplicationInfo::builder()
                 .application_name(&app_name)
                 .application_version(1)
-                .engine_name(&app_name)
-                .engine_version(1)
+                .engine_name(&engine_name)
+                .engine_version(app_info.engine_version().to_u32())
                 .api_version(VK_TARGET_VERSION_ASH);
 
             let vk_instance = instance
@@ -171,6 +186,22 @@ unsafe impl GraphicsExt for openxr::Vulkan {
             return Err(OxrError::FailedGraphicsRequirements);
         }
 
+        let required_adapter = {
+            let mut id_properties = ash::vk::PhysicalDeviceIDProperties::default();
+            let mut properties =
+                ash::vk::PhysicalDeviceProperties2::builder().push_next(&mut id_properties);
+            unsafe {
+                vk_instance.get_physical_device_properties2(vk_physical_device, &mut properties)
+            };
+            OxrGraphicsAdapter {
+                vendor: vk_device_properties.vendor_id,
+                device: vk_device_properties.device_id,
+                luid: (id_properties.device_luid_valid == ash::vk::TRUE)
+                    .then_some(id_properties.device_luid),
+                uuid: Some(id_properties.device_uuid),
+            }
+        };
+
         let wgpu_vk_instance = unsafe {
             <Vulkan as Api>::Instance::from_raw(
                 vk_entry.clone(),
@@ -278,13 +309,22 @@ unsafe impl GraphicsExt for openxr::Vulkan {
             )
         }?;
 
+        let wgpu_adapter_info = wgpu_adapter.get_info();
+        if !required_adapter.matches(&wgpu_adapter_info) {
+            return Err(OxrError::GraphicsAdapterMismatch {
+                required: required_adapter,
+                adapter: wgpu_adapter_info.name,
+            });
+        }
+
         Ok((
             WgpuGraphics(
                 wgpu_device,
                 wgpu_queue,
-                wgpu_adapter.get_info(),
+                wgpu_adapter_info,
                 wgpu_adapter,
                 wgpu_instance,
+                required_adapter,
             ),
             openxr::vulkan::SessionCreateInfo {
                 instance: vk_instance_ptr,
@@ -336,12 +376,31 @@ unsafe impl GraphicsExt for openxr::Vulkan {
     }
 }
 
-fn cvt(x: sys::Result) -> openxr::Result<sys::Result> {
-    if x.into_raw() >= 0 {
-        Ok(x)
-    } else {
-        Err(x)
+fn hal_texture_uses(
+    usage: wgpu::TextureUsages,
+    format: wgpu::TextureFormat,
+) -> wgpu_hal::TextureUses {
+    let mut uses = wgpu_hal::TextureUses::empty();
+    if usage.contains(wgpu::TextureUsages::RENDER_ATTACHMENT) {
+        uses |= if format.is_depth_stencil_format() {
+            wgpu_hal::TextureUses::DEPTH_STENCIL_READ | wgpu_hal::TextureUses::DEPTH_STENCIL_WRITE
+        } else {
+            wgpu_hal::TextureUses::COLOR_TARGET
+        };
+    }
+    if usage.contains(wgpu::TextureUsages::TEXTURE_BINDING) {
+        uses |= wgpu_hal::TextureUses::RESOURCE;
     }
+    if usage.contains(wgpu::TextureUsages::STORAGE_BINDING) {
+        uses |= wgpu_hal::TextureUses::STORAGE_READ | wgpu_hal::TextureUses::STORAGE_READ_WRITE;
+    }
+    if usage.contains(wgpu::TextureUsages::COPY_SRC) {
+        uses |= wgpu_hal::TextureUses::COPY_SRC;
+    }
+    if usage.contains(wgpu::TextureUsages::COPY_DST) {
+        uses |= wgpu_hal::TextureUses::COPY_DST;
+    }
+    uses
 }
 
 fn vulkan_to_wgpu(format: ash::vk::Format) -> Option<wgpu::TextureFormat> {
@@ -724,4 +783,3 @@ fn wgpu_to_vulkan(format: wgpu::TextureFormat) -> Option<ash::vk::Format> {
         },
     })
 }
-
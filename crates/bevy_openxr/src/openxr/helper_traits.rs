@@ -28,6 +28,9 @@ pub trait ToVector2f {
 pub trait ToVec2 {
     fn to_vec2(&self) -> Vec2;
 }
+pub trait ToColor4f {
+    fn to_color4f(&self) -> openxr::Color4f;
+}
 impl ToPosef for Transform {
     fn to_posef(&self) -> openxr::Posef {
         openxr::Posef {
@@ -115,3 +118,13 @@ impl ToVec2 for openxr::Vector2f {
         }
     }
 }
+impl ToColor4f for Vec4 {
+    fn to_color4f(&self) -> openxr::Color4f {
+        openxr::Color4f {
+            r: self.x,
+            g: self.y,
+            b: self.z,
+            a: self.w,
+        }
+    }
+}
@@ -1,6 +1,9 @@
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
 
+use bevy::app::AppExit;
 use bevy::prelude::*;
 use bevy::render::extract_resource::ExtractResourcePlugin;
 use bevy::render::renderer::RenderAdapter;
@@ -20,10 +23,13 @@ use bevy_mod_xr::session::*;
 use openxr::Event;
 
 use crate::error::OxrError;
+use crate::features::passthrough::OxrPassthroughEnabled;
+use crate::features::secondary_view::OxrSecondaryViewConfiguration;
 use crate::graphics::*;
 use crate::resources::*;
 use crate::session::OxrSession;
 use crate::session::OxrSessionCreateNextChain;
+use crate::session::OxrSwapchainCreateNextChain;
 use crate::types::*;
 
 use super::exts::OxrEnabledExtensions;
@@ -37,9 +43,11 @@ pub fn session_started(started: Option<Res<OxrSessionStarted>>) -> bool {
 pub fn should_run_frame_loop(
     started: Option<Res<OxrSessionStarted>>,
     state: Option<Res<XrState>>,
+    suspended: Option<Res<OxrAppSuspended>>,
 ) -> bool {
     started.is_some_and(|started| started.0)
         && state.is_some_and(|state| *state != XrState::Stopping)
+        && !suspended.is_some_and(|suspended| suspended.0)
 }
 
 pub fn should_render(frame_state: Option<Res<OxrFrameState>>) -> bool {
@@ -52,14 +60,56 @@ pub struct OxrInitPlugin {
     /// Extensions wanted for this session.
     // TODO!() This should be changed to take a simpler list of features wanted that this crate supports. i.e. hand tracking
     pub exts: OxrExtensions,
-    /// List of blend modes the openxr session can use. If [None], pick the first available blend mode.
+    /// API layers wanted for this instance, e.g. `XR_APILAYER_LUNARG_core_validation` during development.
+    ///
+    /// Layers that aren't available are skipped with a warning, the enabled layers are stored in the [`OxrEnabledApiLayers`].
+    /// See [`OxrEntry::enumerate_api_layers`] for the available layers.
+    pub api_layers: Vec<String>,
+    /// The view configuration the openxr session should use. If [None], use [`PRIMARY_STEREO`](openxr::ViewConfigurationType::PRIMARY_STEREO)
+    /// if available, otherwise pick the first available view configuration.
+    ///
+    /// [`PRIMARY_QUAD_VARJO`](openxr::ViewConfigurationType::PRIMARY_QUAD_VARJO) enables `XR_VARJO_quad_views` and renders
+    /// four views, each into its own swapchain, see [`focus_resolution`](OxrGraphicsInfo::focus_resolution).
+    pub view_configuration: Option<openxr::ViewConfigurationType>,
+    /// List of blend modes the openxr session can use, in order of preference. If [None], pick the first available blend mode.
+    ///
+    /// Session creation fails with [`NoMatchingBlendMode`](OxrError::NoMatchingBlendMode) if none of the listed blend modes are available.
     pub blend_modes: Option<Vec<EnvironmentBlendMode>>,
     /// List of backends the openxr session can use. If [None], pick the first available backend.
+    ///
+    /// Headless sessions without graphics are only created if [`GraphicsBackend::Headless`] is listed,
+    /// which requires the `headless` feature and `XR_MND_headless`.
     pub backends: Option<Vec<GraphicsBackend>>,
-    /// List of formats the openxr session can use. If [None], pick the first available format
+    /// List of formats the openxr session can use, in order of preference. If [None], use the [`format_preference`](Self::format_preference).
+    ///
+    /// Session creation fails with [`NoMatchingSwapchainFormat`](OxrError::NoMatchingSwapchainFormat) if none of the formats are available.
     pub formats: Option<Vec<wgpu::TextureFormat>>,
+    /// How the swapchain format is picked if no [`formats`](Self::formats) are given, see [`SwapchainFormatPreference`].
+    ///
+    /// How the format was picked is stored in the [`OxrSwapchainFormatInfo`].
+    pub format_preference: SwapchainFormatPreference,
+    /// Format of the texture views bevy renders into. If [None], use the format of the swapchain.
+    ///
+    /// This may only differ from the swapchain format in being its sRGB variant or the other way around,
+    /// e.g. [`Rgba8Unorm`](wgpu::TextureFormat::Rgba8Unorm) for a swapchain using [`Rgba8UnormSrgb`](wgpu::TextureFormat::Rgba8UnormSrgb).
+    /// The swapchain is then created with [`MUTABLE_FORMAT`](openxr::SwapchainUsageFlags::MUTABLE_FORMAT).
+    /// The runtime always interprets the images according to the swapchain format, so when rendering into
+    /// a linear view of an sRGB swapchain the rendered colors have to be gamma encoded already.
+    /// The chosen pair is stored in the [`OxrGraphicsInfo`].
+    pub view_format: Option<wgpu::TextureFormat>,
     /// List of resolutions that the openxr swapchain can use. If [None] pick the first available resolution.
+    ///
+    /// Session creation fails with [`NoMatchingResolution`](OxrError::NoMatchingResolution) if none of the resolutions are supported.
     pub resolutions: Option<Vec<UVec2>>,
+    /// Sample count of the swapchain images, clamped to the maximum supported by the runtime.
+    ///
+    /// Most runtimes only support 1 and resolve multisampled images internally instead, if creating the swapchain fails it is created with 1.
+    /// Bevy resolves the [`Msaa`](bevy::prelude::Msaa) of the cameras into single-sampled targets,
+    /// so this is only useful when rendering into the swapchain images directly.
+    /// The used sample count is stored in the [`OxrGraphicsInfo`].
+    pub sample_count: u32,
+    /// How the images of the views are split across swapchains, see [`SwapchainLayout`].
+    pub swapchain_layout: SwapchainLayout,
     /// Passed into the render plugin when added to the app.
     pub synchronous_pipeline_compilation: bool,
 }
@@ -73,10 +123,16 @@ impl Default for OxrInitPlugin {
                 exts.enable_hand_tracking();
                 exts
             },
+            api_layers: default(),
+            view_configuration: default(),
             blend_modes: default(),
             backends: default(),
             formats: Some(vec![wgpu::TextureFormat::Rgba8UnormSrgb]),
+            format_preference: default(),
+            view_format: default(),
             resolutions: default(),
+            sample_count: 1,
+            swapchain_layout: default(),
             synchronous_pipeline_compilation: default(),
         }
     }
@@ -85,15 +141,23 @@ impl Default for OxrInitPlugin {
 impl Plugin for OxrInitPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<OxrInteractionProfileChanged>();
+        app.add_event::<OxrSessionStateChanged>();
+        app.add_event::<OxrInstanceLossPending>();
+        app.add_event::<OxrSessionRecovered>();
+        app.add_event::<OxrRecreateSwapchain>();
+        app.add_event::<OxrSwapchainRecreated>();
         match self.init_xr() {
             Ok((
                 instance,
                 system_id,
-                WgpuGraphics(device, queue, adapter_info, adapter, wgpu_instance),
+                WgpuGraphics(device, queue, adapter_info, adapter, wgpu_instance, graphics_adapter),
                 session_create_info,
                 enabled_exts,
+                enabled_api_layers,
             )) => {
-                app.insert_resource(enabled_exts)
+                app.insert_resource(enabled_exts.clone())
+                    .insert_resource(enabled_api_layers)
+                    .insert_resource(graphics_adapter)
                     .add_plugins((
                         RenderPlugin {
                             render_creation: RenderCreation::manual(
@@ -106,12 +170,17 @@ impl Plugin for OxrInitPlugin {
                             synchronous_pipeline_compilation: self.synchronous_pipeline_compilation,
                         },
                         ExtractResourcePlugin::<OxrSessionStarted>::default(),
+                        ExtractResourcePlugin::<OxrAppSuspended>::default(),
                     ))
                     .add_oxr_event_handler(handle_events)
                     .add_systems(
                         XrFirst,
                         (
+                            retry_lost_session
+                                .run_if(state_equals(XrState::Available))
+                                .run_if(resource_exists::<OxrSessionLossRecovery>),
                             create_xr_session
+                                .after(retry_lost_session)
                                 .run_if(state_equals(XrState::Available))
                                 .run_if(on_event::<XrCreateSessionEvent>()),
                             (
@@ -132,9 +201,22 @@ impl Plugin for OxrInitPlugin {
                             request_exit_xr_session
                                 .run_if(session_created)
                                 .run_if(on_event::<XrRequestExitEvent>()),
+                            recreate_swapchain
+                                .run_if(session_created)
+                                .run_if(on_event::<OxrRecreateSwapchain>()),
                         )
                             .in_set(XrHandleEvents::SessionStateUpdateEvents),
                     )
+                    .add_systems(
+                        Last,
+                        (
+                            delay_app_exit.run_if(
+                                session_created.or_else(resource_exists::<OxrPendingAppExit>),
+                            ),
+                            exit_app_after_session.run_if(resource_exists::<OxrPendingAppExit>),
+                        )
+                            .chain(),
+                    )
                     .insert_resource(instance.clone())
                     .insert_resource(system_id)
                     .insert_resource(XrState::Available)
@@ -143,8 +225,16 @@ impl Plugin for OxrInitPlugin {
                         unfocused_mode: UpdateMode::Continuous,
                     })
                     .insert_resource(OxrSessionStarted(false))
+                    .init_resource::<OxrAppSuspended>()
                     .insert_non_send_resource(session_create_info)
-                    .init_non_send_resource::<OxrSessionCreateNextChain>();
+                    .init_non_send_resource::<OxrSessionCreateNextChain>()
+                    .init_non_send_resource::<OxrSwapchainCreateNextChain>();
+
+                #[cfg(target_os = "android")]
+                app.add_systems(
+                    XrFirst,
+                    update_app_suspended.before(XrHandleEvents::FrameLoop),
+                );
 
                 app.world_mut()
                     .spawn((SpatialBundle::default(), XrTrackingRoot));
@@ -156,11 +246,17 @@ impl Plugin for OxrInitPlugin {
                 let render_app = app.sub_app_mut(RenderApp);
 
                 render_app
-                    .add_systems(ExtractSchedule, transfer_xr_resources)
+                    .add_systems(
+                        ExtractSchedule,
+                        (transfer_xr_resources, transfer_recreated_swapchains),
+                    )
                     .insert_resource(instance)
                     .insert_resource(system_id)
+                    .insert_resource(enabled_exts)
+                    .insert_resource(graphics_adapter)
                     .insert_resource(XrState::Available)
-                    .insert_resource(OxrSessionStarted(false));
+                    .insert_resource(OxrSessionStarted(false))
+                    .init_resource::<OxrAppSuspended>();
             }
             Err(e) => {
                 error!("Failed to initialize openxr: {e}");
@@ -194,6 +290,7 @@ impl OxrInitPlugin {
         WgpuGraphics,
         SessionConfigInfo,
         OxrEnabledExtensions,
+        OxrEnabledApiLayers,
     )> {
         #[cfg(windows)]
         let entry = OxrEntry(openxr::Entry::linked());
@@ -212,6 +309,19 @@ impl OxrInitPlugin {
             );
         }
 
+        let available_layers = entry.enumerate_api_layers()?;
+        let mut api_layers = vec![];
+        for layer in &self.api_layers {
+            if available_layers
+                .iter()
+                .any(|available| available.layer_name == *layer)
+            {
+                api_layers.push(layer.clone());
+            } else {
+                warn!("API layer \"{layer}\" not available in the current OpenXR runtime. Disabling API layer.");
+            }
+        }
+
         let available_backends = GraphicsBackend::available_backends(&available_exts);
 
         // Backend selection
@@ -225,17 +335,23 @@ impl OxrInitPlugin {
             }
             backend
         } else {
-            available_backends.first().copied()
+            available_backends
+                .iter()
+                .copied()
+                .find(|backend| !backend.is_headless())
         }
         .ok_or(OxrError::NoAvailableBackend)?;
 
-        let exts = self.exts.clone() & available_exts;
+        let mut exts = self.exts.clone();
+        if self.view_configuration == Some(openxr::ViewConfigurationType::PRIMARY_QUAD_VARJO) {
+            exts.enable_varjo_quad_views();
+        }
+        let exts = exts & available_exts;
 
         let instance = entry.create_instance(
             self.app_info.clone(),
             exts.clone(),
-            // &["XR_APILAYER_LUNARG_api_dump"],
-            &[],
+            &api_layers.iter().map(String::as_str).collect::<Vec<_>>(),
             backend,
         )?;
         let instance_props = instance.properties()?;
@@ -260,9 +376,18 @@ impl OxrInitPlugin {
         let (graphics, graphics_info) = instance.init_graphics(system_id)?;
 
         let session_create_info = SessionConfigInfo {
+            view_configuration: self.view_configuration,
             blend_modes: self.blend_modes.clone(),
-            formats: self.formats.clone(),
+            format_preference: match &self.formats {
+                Some(formats) if !formats.is_empty() => {
+                    SwapchainFormatPreference::ExactList(formats.clone())
+                }
+                _ => self.format_preference.clone(),
+            },
+            view_format: self.view_format,
             resolutions: self.resolutions.clone(),
+            sample_count: self.sample_count,
+            swapchain_layout: self.swapchain_layout,
             graphics_info,
         };
 
@@ -272,17 +397,104 @@ impl OxrInitPlugin {
             graphics,
             session_create_info,
             OxrEnabledExtensions(exts),
+            OxrEnabledApiLayers(api_layers),
         ))
     }
 }
+/// Sent when the interaction profile of any top level user path changed,
+/// see [`OxrCurrentInteractionProfileChanged`](crate::interaction_profiles::OxrCurrentInteractionProfileChanged) for the changed paths.
 #[derive(Event, Clone, Copy, Debug, Default)]
 pub struct OxrInteractionProfileChanged;
 
+/// Sent whenever the runtime changes the state of the session.
+///
+/// Unlike [`XrStateChanged`] this keeps the OpenXR states apart, so it can be used to e.g. pause the game when the session loses focus.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OxrSessionStateChanged {
+    pub previous: openxr::SessionState,
+    pub current: openxr::SessionState,
+}
+
+/// Sent when the runtime is about to lose the instance, the session will exit and the app should save its state.
+///
+/// Unlike a lost session a lost instance can't be recovered from, since the render device was created from it,
+/// the app has to be restarted to use XR again.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct OxrInstanceLossPending {
+    /// The time at which the instance will be lost.
+    pub loss_time: openxr::Time,
+}
+
+/// Sent when a session was created after the previous one was lost, e.g. because the headset went to sleep.
+///
+/// All session resources like the [`OxrSwapchainImages`] were recreated,
+/// anything derived from them has to be fetched again.
+#[derive(Event, Clone, Copy, Debug, Default)]
+pub struct OxrSessionRecovered;
+
+/// The last state of the current session, removed with the session so the next one starts from `UNKNOWN`.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct OxrPreviousSessionState(pub openxr::SessionState);
+
+/// Exists while recreating a lost session, creating the session is retried until it succeeds.
+#[derive(Resource)]
+struct OxrSessionLossRecovery {
+    retry_timer: Timer,
+}
+
+impl Default for OxrSessionLossRecovery {
+    fn default() -> Self {
+        Self {
+            retry_timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+        }
+    }
+}
+
+/// Pauses the frame loop while the Android activity is suspended.
+///
+/// The runtime stops the session when the activity is paused and makes it ready again once it is resumed,
+/// which is handled like any other session state change.
+#[cfg(target_os = "android")]
+fn update_app_suspended(
+    mut events: EventReader<bevy::window::AppLifecycle>,
+    mut suspended: ResMut<OxrAppSuspended>,
+) {
+    use bevy::window::AppLifecycle;
+    for event in events.read() {
+        match event {
+            AppLifecycle::WillSuspend | AppLifecycle::Suspended => {
+                info!("app suspended, pausing the XR frame loop");
+                suspended.set_if_neq(OxrAppSuspended(true));
+            }
+            AppLifecycle::WillResume | AppLifecycle::Running => {
+                suspended.set_if_neq(OxrAppSuspended(false));
+            }
+            AppLifecycle::Idle => {}
+        }
+    }
+}
+
+/// Retries creating the session while the runtime can't create one yet, e.g. while the headset is still asleep.
+fn retry_lost_session(
+    time: Res<Time<Real>>,
+    mut recovery: ResMut<OxrSessionLossRecovery>,
+    mut create_session: EventWriter<XrCreateSessionEvent>,
+) {
+    if recovery.retry_timer.tick(time.delta()).just_finished() {
+        create_session.send_default();
+    }
+}
+
 pub fn handle_events(
     event: In<OxrEvent>,
     mut status: ResMut<XrState>,
+    previous_state: Option<Res<OxrPreviousSessionState>>,
+    mut focused: ResMut<XrSessionFocused>,
     mut changed_event: EventWriter<XrStateChanged>,
+    mut session_state_changed_event: EventWriter<OxrSessionStateChanged>,
+    mut instance_loss_pending_event: EventWriter<OxrInstanceLossPending>,
     mut interaction_profile_changed_event: EventWriter<OxrInteractionProfileChanged>,
+    mut commands: Commands,
 ) {
     use openxr::Event::*;
     // this unwrap will never panic since we are in a valid scope
@@ -304,15 +516,29 @@ pub fn handle_events(
                 SessionState::EXITING => XrState::Exiting {
                     should_restart: false,
                 },
-                SessionState::LOSS_PENDING => XrState::Exiting {
-                    should_restart: true,
-                },
+                SessionState::LOSS_PENDING => {
+                    commands.init_resource::<OxrSessionLossRecovery>();
+                    XrState::Exiting {
+                        should_restart: true,
+                    }
+                }
                 _ => unreachable!(),
             };
             changed_event.send(XrStateChanged(new_status));
             *status = new_status;
+            focused.set_if_neq(XrSessionFocused(state == SessionState::FOCUSED));
+            session_state_changed_event.send(OxrSessionStateChanged {
+                previous: previous_state.map_or(SessionState::UNKNOWN, |previous| previous.0),
+                current: state,
+            });
+            commands.insert_resource(OxrPreviousSessionState(state));
+        }
+        InstanceLossPending(e) => {
+            warn!("XR instance loss pending");
+            instance_loss_pending_event.send(OxrInstanceLossPending {
+                loss_time: e.loss_time(),
+            });
         }
-        InstanceLossPending(_) => {}
         EventsLost(e) => warn!("lost {} XR events", e.lost_event_count()),
         // we might want to check if this is the correct session?
         Event::InteractionProfileChanged(_) => {
@@ -327,10 +553,16 @@ fn init_xr_session(
     instance: &OxrInstance,
     system_id: openxr::SystemId,
     chain: &mut OxrSessionCreateNextChain,
+    swapchain_chain: &OxrSwapchainCreateNextChain,
+    passthrough_enabled: bool,
     SessionConfigInfo {
+        view_configuration,
         blend_modes,
-        formats,
+        format_preference,
+        view_format,
         resolutions,
+        sample_count,
+        swapchain_layout,
         graphics_info,
     }: SessionConfigInfo,
 ) -> Result<(
@@ -339,26 +571,68 @@ fn init_xr_session(
     OxrFrameStream,
     OxrSwapchain,
     OxrSwapchainImages,
+    OxrPerEyeSwapchains,
+    OxrPerEyeSwapchainImages,
     OxrGraphicsInfo,
+    OxrSwapchainFormatInfo,
 )> {
     let (session, frame_waiter, frame_stream) =
         unsafe { instance.create_session(system_id, graphics_info, chain)? };
 
-    // TODO!() support other view configurations
+    // view configuration selection
     let available_view_configurations = instance.enumerate_view_configurations(system_id)?;
-    if !available_view_configurations.contains(&openxr::ViewConfigurationType::PRIMARY_STEREO) {
-        return Err(OxrError::NoAvailableViewConfiguration);
-    }
-
-    let view_configuration_type = openxr::ViewConfigurationType::PRIMARY_STEREO;
+    let view_configuration_type = match view_configuration {
+        Some(view_configuration) if available_view_configurations.contains(&view_configuration) => {
+            view_configuration
+        }
+        Some(view_configuration) => {
+            return Err(OxrError::UnsupportedViewConfiguration(view_configuration))
+        }
+        None if available_view_configurations
+            .contains(&openxr::ViewConfigurationType::PRIMARY_STEREO) =>
+        {
+            openxr::ViewConfigurationType::PRIMARY_STEREO
+        }
+        None => *available_view_configurations
+            .first()
+            .ok_or(OxrError::NoAvailableViewConfiguration)?,
+    };
 
     let view_configuration_views =
         instance.enumerate_view_configuration_views(system_id, view_configuration_type)?;
+    let view_count = view_configuration_views.len() as u32;
 
-    let (resolution, _view) = if let Some(resolutions) = &resolutions {
+    // the focus views of quad views have a higher resolution than the context views,
+    // the requested resolutions only apply to the context views
+    let quad_views = view_configuration_type == openxr::ViewConfigurationType::PRIMARY_QUAD_VARJO;
+    let (context_views, focus_views) = if quad_views {
+        view_configuration_views.split_at(2.min(view_configuration_views.len()))
+    } else {
+        (&view_configuration_views[..], &[][..])
+    };
+    let focus_resolution = focus_views.first().map(|view| {
+        UVec2::new(
+            view.recommended_image_rect_width,
+            view.recommended_image_rect_height,
+        )
+    });
+    // swapchains of different resolutions can't share an array
+    let swapchain_layout = if focus_resolution.is_some() {
+        if swapchain_layout == SwapchainLayout::SingleArray {
+            info!("Using the PerEye swapchain layout for the quad views");
+        }
+        SwapchainLayout::PerEye
+    } else {
+        swapchain_layout
+    };
+
+    let (resolution, view) = if let Some(resolutions) = resolutions
+        .as_ref()
+        .filter(|resolutions| !resolutions.is_empty())
+    {
         let mut preferred = None;
         for resolution in resolutions {
-            for view_config in view_configuration_views.iter() {
+            for view_config in context_views.iter() {
                 if view_config.recommended_image_rect_height == resolution.y
                     && view_config.recommended_image_rect_width == resolution.x
                 {
@@ -369,7 +643,7 @@ fn init_xr_session(
 
         if preferred.is_none() {
             for resolution in resolutions {
-                for view_config in view_configuration_views.iter() {
+                for view_config in context_views.iter() {
                     if view_config.max_image_rect_height >= resolution.y
                         && view_config.max_image_rect_width >= resolution.x
                     {
@@ -379,9 +653,22 @@ fn init_xr_session(
             }
         }
 
-        preferred
+        Some(preferred.ok_or_else(|| {
+            OxrError::NoMatchingResolution {
+                requested: resolutions.clone(),
+                available: context_views
+                    .iter()
+                    .map(|config| {
+                        UVec2::new(
+                            config.recommended_image_rect_width,
+                            config.recommended_image_rect_height,
+                        )
+                    })
+                    .collect(),
+            }
+        })?)
     } else {
-        view_configuration_views.first().map(|config| {
+        context_views.first().map(|config| {
             (
                 UVec2::new(
                     config.recommended_image_rect_width,
@@ -395,56 +682,123 @@ fn init_xr_session(
 
     let available_formats = session.enumerate_swapchain_formats()?;
 
-    let format = if let Some(formats) = &formats {
-        let mut format = None;
-        for wanted_format in formats {
-            if available_formats.contains(wanted_format) {
-                format = Some(*wanted_format);
-            }
-        }
-        format
-    } else {
-        available_formats.first().copied()
+    let (format, reason) = format_preference.select(&available_formats)?;
+    info!("Using swapchain format {format:?}, picked as the {reason}");
+    let view_format = view_format.unwrap_or(format);
+    if view_format.remove_srgb_suffix() != format.remove_srgb_suffix() {
+        return Err(OxrError::UnsupportedTextureFormat(view_format));
     }
-    .ok_or(OxrError::NoAvailableFormat)?;
+    let format_info = OxrSwapchainFormatInfo {
+        format,
+        view_format,
+        candidates: available_formats,
+        preference: format_preference,
+        reason,
+    };
 
-    let swapchain = session.create_swapchain(SwapchainCreateInfo {
-        create_flags: SwapchainCreateFlags::EMPTY,
-        usage_flags: SwapchainUsageFlags::COLOR_ATTACHMENT | SwapchainUsageFlags::SAMPLED,
+    let mut sample_count = sample_count.clamp(1, view.max_swapchain_sample_count.max(1));
+    let array_size = swapchain_layout.array_size(view_count);
+    let create_info = |sample_count, resolution| {
+        color_swapchain_create_info(format, view_format, resolution, sample_count, array_size)
+    };
+    let swapchain = match session
+        .create_swapchain_with_next_chain(create_info(sample_count, resolution), swapchain_chain)
+    {
+        Err(err) if sample_count > 1 => {
+            warn!(
+                "Failed to create swapchain with {sample_count} samples, falling back to 1: {err}"
+            );
+            sample_count = 1;
+            session.create_swapchain_with_next_chain(
+                create_info(sample_count, resolution),
+                swapchain_chain,
+            )
+        }
+        swapchain => swapchain,
+    }?;
+
+    let images = swapchain.enumerate_images_with_view_format(
+        device,
         format,
-        // TODO() add support for multisampling
-        sample_count: 1,
-        width: resolution.x,
-        height: resolution.y,
-        face_count: 1,
-        array_size: 2,
-        mip_count: 1,
-    })?;
+        view_format,
+        resolution,
+        array_size,
+        sample_count,
+    )?;
 
-    let images = swapchain.enumerate_images(device, format, resolution)?;
+    let mut per_eye_swapchains = OxrPerEyeSwapchains::default();
+    let mut per_eye_images = OxrPerEyeSwapchainImages::default();
+    if swapchain_layout == SwapchainLayout::PerEye {
+        for index in 1..view_count {
+            let resolution = match focus_resolution {
+                Some(focus_resolution) if index >= 2 => focus_resolution,
+                _ => resolution,
+            };
+            let swapchain = session.create_swapchain_with_next_chain(
+                create_info(sample_count, resolution),
+                swapchain_chain,
+            )?;
+            per_eye_images
+                .0
+                .push(swapchain.enumerate_images_with_view_format(
+                    device,
+                    format,
+                    view_format,
+                    resolution,
+                    array_size,
+                    sample_count,
+                )?);
+            per_eye_swapchains.0.push(swapchain);
+        }
+    }
 
     let available_blend_modes =
         instance.enumerate_environment_blend_modes(system_id, view_configuration_type)?;
 
     // blend mode selection
-    let blend_mode = if let Some(wanted_blend_modes) = &blend_modes {
-        let mut blend_mode = None;
-        for wanted_blend_mode in wanted_blend_modes {
-            if available_blend_modes.contains(wanted_blend_mode) {
-                blend_mode = Some(*wanted_blend_mode);
-                break;
+    let blend_mode = if let Some(wanted_blend_modes) = blend_modes
+        .as_ref()
+        .filter(|blend_modes| !blend_modes.is_empty())
+    {
+        let mut blend_mode = wanted_blend_modes
+            .iter()
+            .find(|wanted_blend_mode| available_blend_modes.contains(wanted_blend_mode))
+            .copied();
+        // runtimes supporting passthrough usually only report opaque blending,
+        // alpha blending is achieved by submitting the passthrough layer below the projection layer instead
+        if blend_mode.is_none()
+            && passthrough_enabled
+            && wanted_blend_modes.contains(&EnvironmentBlendMode::ALPHA_BLEND)
+        {
+            if let Some(fallback) = available_blend_modes.first() {
+                info!("Alpha blending is not available, using {fallback:?} with the passthrough layer instead");
+                blend_mode = Some(*fallback);
             }
         }
-        blend_mode
+        blend_mode.ok_or_else(|| OxrError::NoMatchingBlendMode {
+            requested: wanted_blend_modes.clone(),
+            available: available_blend_modes.clone(),
+        })?
     } else {
-        available_blend_modes.first().copied()
-    }
-    .ok_or(OxrError::NoAvailableBackend)?;
+        available_blend_modes
+            .first()
+            .copied()
+            .ok_or(OxrError::NoAvailableBlendMode)?
+    };
+
+    let max_layer_count = instance.system_properties(system_id)?.max_layer_count;
 
     let graphics_info = OxrGraphicsInfo {
         blend_mode,
         resolution,
         format,
+        view_format,
+        view_configuration: view_configuration_type,
+        view_count,
+        sample_count,
+        swapchain_layout,
+        max_layer_count,
+        focus_resolution,
     };
 
     Ok((
@@ -453,47 +807,313 @@ fn init_xr_session(
         frame_stream,
         swapchain,
         images,
+        per_eye_swapchains,
+        per_eye_images,
         graphics_info,
+        format_info,
     ))
 }
 
+/// Returns the create info of the swapchains the views are rendered into.
+fn color_swapchain_create_info(
+    format: wgpu::TextureFormat,
+    view_format: wgpu::TextureFormat,
+    resolution: UVec2,
+    sample_count: u32,
+    array_size: u32,
+) -> SwapchainCreateInfo {
+    let mut usage_flags = SwapchainUsageFlags::COLOR_ATTACHMENT | SwapchainUsageFlags::SAMPLED;
+    if view_format != format {
+        usage_flags |= SwapchainUsageFlags::MUTABLE_FORMAT;
+    }
+    SwapchainCreateInfo {
+        create_flags: SwapchainCreateFlags::EMPTY,
+        usage_flags,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        format,
+        sample_count,
+        width: resolution.x,
+        height: resolution.y,
+        face_count: 1,
+        array_size,
+        mip_count: 1,
+    }
+}
+
 pub fn create_xr_session(world: &mut World) {
+    if world.resource::<OxrInstance>().backend().is_headless() {
+        create_xr_headless_session(world);
+        return;
+    }
     let mut chain = world
         .remove_non_send_resource::<OxrSessionCreateNextChain>()
         .unwrap();
     let device = world.resource::<RenderDevice>();
     let instance = world.resource::<OxrInstance>();
-    let create_info = world.non_send_resource::<SessionConfigInfo>();
+    let mut create_info = world.non_send_resource::<SessionConfigInfo>().clone();
+    if let Some(format_preference) = world.get_resource::<SwapchainFormatPreference>() {
+        create_info.format_preference = format_preference.clone();
+    }
+    let swapchain_chain = world.non_send_resource::<OxrSwapchainCreateNextChain>();
     let system_id = world.resource::<OxrSystemId>();
-    match init_xr_session(
+    let passthrough_enabled = world.contains_resource::<OxrPassthroughEnabled>();
+    let created = match init_xr_session(
         device.wgpu_device(),
         &instance,
         **system_id,
         &mut chain,
-        create_info.clone(),
+        swapchain_chain,
+        passthrough_enabled,
+        create_info,
     ) {
-        Ok((session, frame_waiter, frame_stream, swapchain, images, graphics_info)) => {
+        Ok((
+            session,
+            frame_waiter,
+            frame_stream,
+            swapchain,
+            images,
+            per_eye_swapchains,
+            per_eye_images,
+            graphics_info,
+            format_info,
+        )) => {
             world.insert_resource(session.clone());
             world.insert_resource(frame_waiter);
             world.insert_resource(images.clone());
+            world.insert_resource(per_eye_images.clone());
             world.insert_resource(graphics_info.clone());
+            world.insert_resource(format_info);
+            match OxrSessionConfigReport::new(
+                world.resource::<OxrInstance>(),
+                **world.resource::<OxrSystemId>(),
+                &session,
+                &graphics_info,
+                world.resource::<OxrEnabledExtensions>(),
+            ) {
+                Ok(report) => {
+                    report.log();
+                    world.insert_resource(report);
+                }
+                Err(e) => warn!("Failed to collect the session configuration: {e}"),
+            }
             world.insert_resource(OxrRenderResources {
                 session,
                 frame_stream,
                 swapchain,
                 images,
+                per_eye_swapchains,
+                per_eye_images,
                 graphics_info,
                 session_destroy_flag: world
                     .get_resource::<XrDestroySessionRender>()
                     .expect("added by xr session plugin")
                     .clone(),
             });
+            if world.remove_resource::<OxrSessionLossRecovery>().is_some() {
+                info!("Recovered lost XrSession");
+                world.send_event(OxrSessionRecovered);
+            }
+            true
+        }
+        Err(e) => {
+            error!("Failed to initialize XrSession: {e}");
+            false
         }
-        Err(e) => error!("Failed to initialize XrSession: {e}"),
+    };
+    world.insert_non_send_resource(chain);
+    // failed retries of a lost session keep the recovery going without a session
+    if created {
+        world.run_schedule(XrSessionCreated);
+        world.send_event(XrSessionCreatedEvent);
     }
+}
+
+/// Creates a session without graphics, which has no swapchains and renders nothing.
+///
+/// Only the [`OxrSession`], the [`OxrFrameWaiter`] and the [`OxrFrameStream`] are inserted,
+/// the frames are waited for and ended right away in the main world.
+fn create_xr_headless_session(world: &mut World) {
+    let mut chain = world
+        .remove_non_send_resource::<OxrSessionCreateNextChain>()
+        .unwrap();
+    let instance = world.resource::<OxrInstance>();
+    let system_id = world.resource::<OxrSystemId>();
+    let create_info = world.non_send_resource::<SessionConfigInfo>();
+    let created = match unsafe {
+        instance.create_session(**system_id, create_info.graphics_info.clone(), &mut chain)
+    } {
+        Ok((session, frame_waiter, frame_stream)) => {
+            world.insert_resource(session);
+            world.insert_resource(frame_waiter);
+            world.insert_resource(frame_stream);
+            if world.remove_resource::<OxrSessionLossRecovery>().is_some() {
+                info!("Recovered lost XrSession");
+                world.send_event(OxrSessionRecovered);
+            }
+            true
+        }
+        Err(e) => {
+            error!("Failed to initialize headless XrSession: {e}");
+            false
+        }
+    };
     world.insert_non_send_resource(chain);
-    world.run_schedule(XrSessionCreated);
-    world.send_event(XrSessionCreatedEvent);
+    if created {
+        world.run_schedule(XrSessionCreated);
+        world.send_event(XrSessionCreatedEvent);
+    }
+}
+
+/// Send this to recreate the [`OxrSwapchain`] at a new resolution without restarting the session,
+/// e.g. when the user changes a quality setting.
+///
+/// The new swapchains are created right away and replace the old ones in the render world in between two frames,
+/// after the images of the old swapchains were released. The [`OxrSwapchainImages`] and the
+/// [`resolution`](OxrGraphicsInfo::resolution) are extracted together with them, so layers never mix the old and new images.
+/// The resolution can't exceed the maximum resolution of the view configuration.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct OxrRecreateSwapchain {
+    pub resolution: UVec2,
+}
+
+/// Sent after the swapchain was recreated by an [`OxrRecreateSwapchain`],
+/// anything sized after the [`OxrGraphicsInfo::resolution`] has to be recreated as well.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct OxrSwapchainRecreated {
+    pub resolution: UVec2,
+}
+
+/// Transports the recreated swapchains from the main world to the render world.
+#[derive(Resource)]
+struct OxrRecreatedSwapchains {
+    swapchain: OxrSwapchain,
+    per_eye_swapchains: OxrPerEyeSwapchains,
+}
+
+fn recreate_swapchain(world: &mut World) {
+    let Some(OxrRecreateSwapchain { resolution }) = world
+        .resource_mut::<Events<OxrRecreateSwapchain>>()
+        .drain()
+        .last()
+    else {
+        return;
+    };
+    let graphics_info = *world.resource::<OxrGraphicsInfo>();
+    if graphics_info.resolution == resolution {
+        return;
+    }
+    match init_recreated_swapchains(world, graphics_info, resolution) {
+        Ok((recreated, images, per_eye_images)) => {
+            world.insert_resource(recreated);
+            world.insert_resource(images);
+            world.insert_resource(per_eye_images);
+            world.insert_resource(OxrGraphicsInfo {
+                resolution,
+                ..graphics_info
+            });
+            world.send_event(OxrSwapchainRecreated { resolution });
+        }
+        Err(e) => error!("Failed to recreate swapchain: {e}"),
+    }
+}
+
+fn init_recreated_swapchains(
+    world: &World,
+    graphics_info: OxrGraphicsInfo,
+    resolution: UVec2,
+) -> Result<(
+    OxrRecreatedSwapchains,
+    OxrSwapchainImages,
+    OxrPerEyeSwapchainImages,
+)> {
+    let device = world.resource::<RenderDevice>().wgpu_device();
+    let instance = world.resource::<OxrInstance>();
+    let session = world.resource::<OxrSession>();
+    let swapchain_chain = world.non_send_resource::<OxrSwapchainCreateNextChain>();
+
+    let view_configuration_views = instance.enumerate_view_configuration_views(
+        **world.resource::<OxrSystemId>(),
+        graphics_info.view_configuration,
+    )?;
+    // the focus views of quad views keep their resolution
+    let context_views = match graphics_info.focus_resolution {
+        Some(_) => &view_configuration_views[..2.min(view_configuration_views.len())],
+        None => &view_configuration_views[..],
+    };
+    if !context_views.iter().all(|view| {
+        view.max_image_rect_width >= resolution.x && view.max_image_rect_height >= resolution.y
+    }) {
+        return Err(OxrError::NoMatchingResolution {
+            requested: vec![resolution],
+            available: context_views
+                .iter()
+                .map(|view| UVec2::new(view.max_image_rect_width, view.max_image_rect_height))
+                .collect(),
+        });
+    }
+
+    let array_size = graphics_info
+        .swapchain_layout
+        .array_size(graphics_info.view_count);
+    let create_swapchain = |resolution: UVec2| -> Result<(OxrSwapchain, OxrSwapchainImages)> {
+        let create_info = color_swapchain_create_info(
+            graphics_info.format,
+            graphics_info.view_format,
+            resolution,
+            graphics_info.sample_count,
+            array_size,
+        );
+        let swapchain = session.recreate_swapchain(create_info, swapchain_chain)?;
+        let images = swapchain.enumerate_images_with_view_format(
+            device,
+            graphics_info.format,
+            graphics_info.view_format,
+            resolution,
+            array_size,
+            graphics_info.sample_count,
+        )?;
+        Ok((swapchain, images))
+    };
+
+    let (swapchain, images) = create_swapchain(resolution)?;
+    let mut per_eye_swapchains = OxrPerEyeSwapchains::default();
+    let mut per_eye_images = OxrPerEyeSwapchainImages::default();
+    if graphics_info.swapchain_layout == SwapchainLayout::PerEye {
+        let recreated_info = OxrGraphicsInfo {
+            resolution,
+            ..graphics_info
+        };
+        for index in 1..graphics_info.view_count {
+            let (swapchain, images) = create_swapchain(recreated_info.view_resolution(index))?;
+            per_eye_swapchains.0.push(swapchain);
+            per_eye_images.0.push(images);
+        }
+    }
+    Ok((
+        OxrRecreatedSwapchains {
+            swapchain,
+            per_eye_swapchains,
+        },
+        images,
+        per_eye_images,
+    ))
+}
+
+/// Replaces the swapchains of the render world with the ones created by [`recreate_swapchain`].
+///
+/// Extraction happens in between frames of the render world, so the images of the old swapchains are released
+/// and the old swapchains are destroyed when they are dropped.
+fn transfer_recreated_swapchains(mut commands: Commands, mut world: ResMut<MainWorld>) {
+    let Some(OxrRecreatedSwapchains {
+        swapchain,
+        per_eye_swapchains,
+    }) = world.remove_resource()
+    else {
+        return;
+    };
+
+    commands.insert_resource(swapchain);
+    commands.insert_resource(per_eye_swapchains);
 }
 
 pub fn destroy_xr_session(world: &mut World) {
@@ -503,7 +1123,13 @@ pub fn destroy_xr_session(world: &mut World) {
     world.remove_resource::<OxrFrameStream>();
     world.remove_resource::<OxrSwapchain>();
     world.remove_resource::<OxrSwapchainImages>();
+    world.remove_resource::<OxrPerEyeSwapchains>();
+    world.remove_resource::<OxrPerEyeSwapchainImages>();
     world.remove_resource::<OxrGraphicsInfo>();
+    world.remove_resource::<OxrSwapchainFormatInfo>();
+    world.remove_resource::<OxrSessionConfigReport>();
+    world.remove_resource::<OxrRecreatedSwapchains>();
+    world.remove_resource::<OxrPreviousSessionState>();
     world.insert_resource(XrState::Available);
 }
 
@@ -512,11 +1138,20 @@ pub fn begin_xr_session(
     // session: Res<OxrSession>, mut session_started: ResMut<OxrSessionStarted>
 ) {
     let _span = info_span!("xr_begin_session");
-    world
-        .get_resource::<OxrSession>()
-        .unwrap()
-        .begin(openxr::ViewConfigurationType::PRIMARY_STEREO)
-        .expect("Failed to begin session");
+    // headless sessions have no graphics info, the runtime ignores the view configuration for them
+    let view_configuration = world
+        .get_resource::<OxrGraphicsInfo>()
+        .map_or(openxr::ViewConfigurationType::PRIMARY_STEREO, |info| {
+            info.view_configuration
+        });
+    let session = world.get_resource::<OxrSession>().unwrap();
+    match world.get_resource::<OxrSecondaryViewConfiguration>() {
+        Some(secondary_view) => {
+            session.begin_with_secondary_view(view_configuration, **secondary_view)
+        }
+        None => session.begin(view_configuration).map(|_| ()),
+    }
+    .expect("Failed to begin session");
     world.get_resource_mut::<OxrSessionStarted>().unwrap().0 = true;
     world.run_schedule(XrPostSessionBegin);
 }
@@ -540,6 +1175,66 @@ pub fn request_exit_xr_session(session: Res<OxrSession>) {
     session.request_exit().expect("Failed to request exit");
 }
 
+/// How long [`delay_app_exit`] holds back the [`AppExit`] for the runtime to exit the session.
+const EXIT_SESSION_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The [`AppExit`] held back by [`delay_app_exit`] until the session is destroyed.
+#[derive(Resource)]
+struct OxrPendingAppExit {
+    exit: AppExit,
+    deadline: Instant,
+}
+
+/// Holds back the [`AppExit`] and requests the runtime to exit the session instead.
+///
+/// The session then goes through the usual state changes, so it is ended and destroyed like any other session,
+/// [`exit_app_after_session`] sends the [`AppExit`] again once that happened. Without this the session is dropped
+/// while it's still running, which can leave the runtime thinking the app is still running.
+/// Doesn't hold back the exit if the session was never started.
+fn delay_app_exit(
+    mut exits: ResMut<Events<AppExit>>,
+    started: Res<OxrSessionStarted>,
+    pending: Option<Res<OxrPendingAppExit>>,
+    mut request_exit: EventWriter<XrRequestExitEvent>,
+    mut commands: Commands,
+) {
+    if pending.is_some() {
+        // exits sent while waiting, e.g. every frame once all windows are closed
+        exits.clear();
+        return;
+    }
+    if !started.0 {
+        return;
+    }
+    let Some(exit) = exits.drain().last() else {
+        return;
+    };
+    request_exit.send_default();
+    commands.insert_resource(OxrPendingAppExit {
+        exit,
+        deadline: Instant::now() + EXIT_SESSION_TIMEOUT,
+    });
+}
+
+/// Sends the [`AppExit`] held back by [`delay_app_exit`] once the session is destroyed in both worlds,
+/// or after a few seconds if the runtime doesn't respond.
+fn exit_app_after_session(
+    pending: Res<OxrPendingAppExit>,
+    session: Option<Res<OxrSession>>,
+    destroy_render: Res<XrDestroySessionRender>,
+    mut exits: EventWriter<AppExit>,
+    mut commands: Commands,
+) {
+    let timed_out = Instant::now() >= pending.deadline;
+    if timed_out {
+        warn!("Timed out waiting for the runtime to exit the session");
+    } else if session.is_some() || destroy_render.0.load(Ordering::Relaxed) {
+        return;
+    }
+    exits.send(pending.exit.clone());
+    commands.remove_resource::<OxrPendingAppExit>();
+}
+
 /// This is used solely to transport resources from the main world to the render world.
 #[derive(Resource)]
 struct OxrRenderResources {
@@ -547,6 +1242,8 @@ struct OxrRenderResources {
     frame_stream: OxrFrameStream,
     swapchain: OxrSwapchain,
     images: OxrSwapchainImages,
+    per_eye_swapchains: OxrPerEyeSwapchains,
+    per_eye_images: OxrPerEyeSwapchainImages,
     graphics_info: OxrGraphicsInfo,
     session_destroy_flag: XrDestroySessionRender,
 }
@@ -558,6 +1255,8 @@ pub fn transfer_xr_resources(mut commands: Commands, mut world: ResMut<MainWorld
         frame_stream,
         swapchain,
         images,
+        per_eye_swapchains,
+        per_eye_images,
         graphics_info,
         session_destroy_flag,
     }) = world.remove_resource()
@@ -569,6 +1268,8 @@ pub fn transfer_xr_resources(mut commands: Commands, mut world: ResMut<MainWorld
     commands.insert_resource(frame_stream);
     commands.insert_resource(swapchain);
     commands.insert_resource(images);
+    commands.insert_resource(per_eye_swapchains);
+    commands.insert_resource(per_eye_images);
     commands.insert_resource(graphics_info);
     commands.insert_resource(session_destroy_flag);
 }
@@ -0,0 +1,123 @@
+use std::borrow::Cow;
+
+use bevy::{prelude::*, utils::HashMap};
+use bevy_mod_xr::session::XrPreDestroySession;
+
+use crate::{
+    action_set_syncing::OxrActionSetSyncSet, init::OxrInteractionProfileChanged,
+    openxr_session_running, session::OxrSession,
+};
+
+/// Keeps track of the interaction profiles bound to the top level user paths in [`OxrCurrentInteractionProfiles`]
+/// and sends an [`OxrCurrentInteractionProfileChanged`] for every path whose profile changed.
+///
+/// The profiles are checked after the first action sync of a session and whenever the runtime reports an [`OxrInteractionProfileChanged`].
+pub struct OxrInteractionProfilesPlugin;
+
+impl Plugin for OxrInteractionProfilesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<OxrCurrentInteractionProfileChanged>()
+            .init_resource::<OxrCurrentInteractionProfiles>()
+            .add_systems(
+                PreUpdate,
+                update_interaction_profiles
+                    .after(OxrActionSetSyncSet)
+                    .run_if(openxr_session_running),
+            )
+            .add_systems(XrPreDestroySession, cleanup);
+    }
+}
+
+/// Sent when the interaction profile bound to `top_level_path` changed, e.g. when the user puts down the controllers and uses hand tracking instead.
+#[derive(Event, Clone, Debug, PartialEq, Eq)]
+pub struct OxrCurrentInteractionProfileChanged {
+    /// The top level user path, e.g. `/user/hand/left`.
+    pub top_level_path: Cow<'static, str>,
+    /// The new interaction profile, e.g. `/interaction_profiles/oculus/touch_controller`.
+    ///
+    /// [`None`] if no profile is bound to the path.
+    pub profile: Option<String>,
+}
+
+/// The interaction profiles currently bound to the top level user paths.
+#[derive(Resource, Clone, Debug)]
+pub struct OxrCurrentInteractionProfiles {
+    /// The top level user paths to track.
+    pub top_level_paths: Vec<Cow<'static, str>>,
+    profiles: HashMap<Cow<'static, str>, Option<String>>,
+}
+
+impl Default for OxrCurrentInteractionProfiles {
+    fn default() -> Self {
+        Self {
+            top_level_paths: vec![
+                Cow::Borrowed("/user/hand/left"),
+                Cow::Borrowed("/user/hand/right"),
+                Cow::Borrowed("/user/head"),
+                Cow::Borrowed("/user/gamepad"),
+            ],
+            profiles: default(),
+        }
+    }
+}
+
+impl OxrCurrentInteractionProfiles {
+    /// Returns the interaction profile bound to `top_level_path`.
+    pub fn get(&self, top_level_path: &str) -> Option<&str> {
+        self.profiles.get(top_level_path)?.as_deref()
+    }
+}
+
+fn update_interaction_profiles(
+    session: Res<OxrSession>,
+    mut changed: EventReader<OxrInteractionProfileChanged>,
+    mut profiles: ResMut<OxrCurrentInteractionProfiles>,
+    mut writer: EventWriter<OxrCurrentInteractionProfileChanged>,
+) {
+    let changed = changed.read().count() != 0;
+    if !changed && !profiles.profiles.is_empty() {
+        return;
+    }
+    let profiles = profiles.as_mut();
+    for top_level_path in &profiles.top_level_paths {
+        let profile = match session.current_interaction_profile_name(top_level_path) {
+            Ok(profile) => profile,
+            Err(err) => {
+                warn!("error while getting interaction profile of {top_level_path}: {err}");
+                continue;
+            }
+        };
+        if profiles.profiles.get(top_level_path) == Some(&profile) {
+            continue;
+        }
+        profiles
+            .profiles
+            .insert(top_level_path.clone(), profile.clone());
+        writer.send(OxrCurrentInteractionProfileChanged {
+            top_level_path: top_level_path.clone(),
+            profile,
+        });
+    }
+}
+
+fn cleanup(mut profiles: ResMut<OxrCurrentInteractionProfiles>) {
+    profiles.profiles.clear();
+}
+
+impl OxrSession {
+    /// Returns the interaction profile currently bound to `top_level_path`, e.g. `/user/hand/left`, as a string.
+    ///
+    /// Returns [`None`] if no profile is bound, which is always the case before the action sets are attached.
+    pub fn current_interaction_profile_name(
+        &self,
+        top_level_path: &str,
+    ) -> openxr::Result<Option<String>> {
+        let instance = self.instance();
+        let path = instance.string_to_path(top_level_path)?;
+        let profile = self.current_interaction_profile(path)?;
+        if profile == openxr::Path::NULL {
+            return Ok(None);
+        }
+        instance.path_to_string(profile).map(Some)
+    }
+}
@@ -1,10 +1,20 @@
-use std::mem;
+use std::{ffi::c_void, marker::PhantomData, mem, ops::Deref, ptr};
 
-use bevy::ecs::world::World;
+use bevy::ecs::{system::Resource, world::World};
+use bevy::log::{warn, warn_once};
+use bevy::math::{URect, UVec2, Vec2, Vec4};
+use bevy::transform::components::Transform;
 use bevy_mod_xr::spaces::{XrPrimaryReferenceSpace, XrSpace};
-use openxr::{sys, CompositionLayerFlags, Fovf, Posef, Rect2Di};
+use openxr::{
+    sys, Color4f, CompositionLayerFlags, Extent2Df, EyeVisibility, Fovf, Posef, Quaternionf,
+    Rect2Di,
+};
 
+use crate::exts::OxrEnabledExtensions;
+use crate::features::layer_depth::OxrDepthImageIndex;
 use crate::graphics::graphics_match;
+use crate::helper_traits::{ToColor4f as _, ToPosef as _};
+use crate::reference_space::OxrViewReferenceSpace;
 use crate::resources::*;
 use crate::spaces::OxrSpaceExt as _;
 
@@ -12,53 +22,491 @@ pub trait LayerProvider {
     fn get<'a>(&'a self, world: &'a World) -> Option<Box<dyn CompositionLayer + '_>>;
 }
 
-pub struct ProjectionLayer;
+/// Composition layers of the render world submitted together with the [`OxrRenderLayers`], ordered by their `z_order`.
+///
+/// Layers are composited back to front in ascending `z_order`, the [`OxrRenderLayers`] have a `z_order` of 0
+/// and layers with the same `z_order` keep the order they were pushed in.
+/// Passthrough layers are always submitted first so they can't hide other layers, e.g. a UI panel pushed with a `z_order` of 1 is always in front of the world.
+/// Layers exceeding the [`max_layer_count`](OxrGraphicsInfo::max_layer_count) of the system are dropped from the front.
+#[derive(Resource, Default)]
+pub struct OxrLayerStack {
+    layers: Vec<(i32, Box<dyn LayerProvider + Send + Sync>)>,
+}
+
+impl OxrLayerStack {
+    /// Adds `layer` at `z_order`.
+    pub fn push(
+        &mut self,
+        z_order: i32,
+        layer: impl LayerProvider + Send + Sync + 'static,
+    ) -> &mut Self {
+        let index = self.layers.partition_point(|(z, _)| *z <= z_order);
+        self.layers.insert(index, (z_order, Box::new(layer)));
+        self
+    }
+
+    /// Removes all layers.
+    pub fn clear(&mut self) {
+        self.layers.clear();
+    }
+
+    /// Returns the layers of the stack and the `render_layers` in the order they have to be submitted in.
+    pub fn get<'a>(
+        &'a self,
+        world: &'a World,
+        render_layers: &'a OxrRenderLayers,
+        max_layer_count: u32,
+    ) -> Vec<Box<dyn CompositionLayer<'a> + 'a>> {
+        let render_layers = render_layers.iter().map(|layer| (0, layer));
+        let stack_layers = self.layers.iter().map(|(z_order, layer)| (*z_order, layer));
+        let mut layers: Vec<_> = render_layers
+            .chain(stack_layers)
+            .filter_map(|(z_order, layer)| Some((z_order, layer.get(world)?)))
+            .collect();
+        layers.sort_by_key(|(z_order, layer)| {
+            (
+                layer.header().ty != sys::StructureType::COMPOSITION_LAYER_PASSTHROUGH_FB,
+                *z_order,
+            )
+        });
+        if layers.len() > max_layer_count as usize {
+            warn!(
+                "Submitting {} composition layers, but the system only supports {max_layer_count}. Excluding the frontmost layers from frame submission.",
+                layers.len()
+            );
+            layers.truncate(max_layer_count as usize);
+        }
+        layers.into_iter().map(|(_, layer)| layer).collect()
+    }
+}
+
+/// The filtering the compositor applies to a layer when sampling it, e.g. to make text readable.
+///
+/// Requires [`XR_FB_composition_layer_settings`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#XR_FB_composition_layer_settings),
+/// layers are submitted with [`Normal`](Self::Normal) quality without it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum LayerQuality {
+    #[default]
+    Normal,
+    /// Supersamples the layer, which reduces flickering of fine detail at the cost of GPU time in the compositor.
+    SuperSample,
+    /// Sharpens the layer, which makes text crisper but can make aliasing more visible.
+    Sharpen,
+}
+
+impl LayerQuality {
+    /// Returns the settings flags for this quality, or `None` if no settings have to be chained onto the layer.
+    fn settings_flags(self, world: &World) -> Option<sys::CompositionLayerSettingsFlagsFB> {
+        let flags = match self {
+            LayerQuality::Normal => return None,
+            LayerQuality::SuperSample => {
+                sys::CompositionLayerSettingsFlagsFB::QUALITY_SUPER_SAMPLING
+            }
+            LayerQuality::Sharpen => sys::CompositionLayerSettingsFlagsFB::QUALITY_SHARPENING,
+        };
+        world
+            .get_resource::<OxrEnabledExtensions>()
+            .is_some_and(|exts| exts.composition_layer_settings())
+            .then_some(flags)
+    }
+}
+
+/// Scales and biases the colors of a layer in the compositor, e.g. to fade to black during a teleport
+/// without rendering a fullscreen quad.
+///
+/// Requires [`XR_KHR_composition_layer_color_scale_bias`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#XR_KHR_composition_layer_color_scale_bias),
+/// without it the layer is submitted unchanged and the app has to apply the fade itself, e.g. in a shader.
+/// Each texel becomes `color * scale + bias` before the layer is blended according to its flags,
+/// so the bias has to be premultiplied unless the layer uses [`UNPREMULTIPLIED_ALPHA`](CompositionLayerFlags::UNPREMULTIPLIED_ALPHA).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LayerColorScaleBias {
+    pub scale: Vec4,
+    pub bias: Vec4,
+}
+
+impl LayerColorScaleBias {
+    /// Leaves the colors unchanged.
+    pub const IDENTITY: Self = Self {
+        scale: Vec4::ONE,
+        bias: Vec4::ZERO,
+    };
+
+    /// Returns whether the scale and bias can be chained onto the layer, warns once if they can't.
+    fn supported(world: &World) -> bool {
+        let supported = world
+            .get_resource::<OxrEnabledExtensions>()
+            .is_some_and(|exts| exts.composition_layer_color_scale_bias());
+        if !supported {
+            warn_once!("XR_KHR_composition_layer_color_scale_bias isn't enabled, layer colors are submitted without scale and bias");
+        }
+        supported
+    }
+}
+
+impl Default for LayerColorScaleBias {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+/// Submits the views rendered into the [`OxrSwapchain`].
+///
+/// `flags` default to [`BLEND_TEXTURE_SOURCE_ALPHA`](CompositionLayerFlags::BLEND_TEXTURE_SOURCE_ALPHA).
+pub struct ProjectionLayer {
+    pub flags: CompositionLayerFlags,
+    pub quality: LayerQuality,
+    pub color_scale_bias: Option<LayerColorScaleBias>,
+    /// The area of the swapchain image each view is read from, indexed by view, see [`with_image_rect`](Self::with_image_rect).
+    pub image_rects: Vec<Option<URect>>,
+}
+
+impl ProjectionLayer {
+    pub fn with_flags(mut self, flags: CompositionLayerFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Submits only `rect` of the swapchain image of the view with `view_index`, e.g. to pack both eyes into one texture
+    /// or to render a foveated inset into part of it.
+    ///
+    /// The views are read from the whole [`view_resolution`](OxrGraphicsInfo::view_resolution) by default.
+    /// A rect that isn't within it is ignored with a warning.
+    pub fn with_image_rect(mut self, view_index: u32, rect: URect) -> Self {
+        set_image_rect(&mut self.image_rects, view_index, rect);
+        self
+    }
+
+    pub fn with_quality(mut self, quality: LayerQuality) -> Self {
+        self.quality = quality;
+        self
+    }
+
+    /// See [`LayerColorScaleBias`].
+    pub fn with_color_scale_bias(mut self, scale: Vec4, bias: Vec4) -> Self {
+        self.color_scale_bias = Some(LayerColorScaleBias { scale, bias });
+        self
+    }
+}
+
+impl Default for ProjectionLayer {
+    fn default() -> Self {
+        Self {
+            flags: CompositionLayerFlags::BLEND_TEXTURE_SOURCE_ALPHA,
+            quality: LayerQuality::Normal,
+            color_scale_bias: None,
+            image_rects: Vec::new(),
+        }
+    }
+}
+
+/// Submits the [`OxrPassthroughLayer`].
+///
+/// `flags` default to [`BLEND_TEXTURE_SOURCE_ALPHA`](CompositionLayerFlags::BLEND_TEXTURE_SOURCE_ALPHA).
+pub struct PassthroughLayer {
+    pub flags: CompositionLayerFlags,
+}
+
+impl PassthroughLayer {
+    pub fn with_flags(mut self, flags: CompositionLayerFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+}
+
+impl Default for PassthroughLayer {
+    fn default() -> Self {
+        Self {
+            flags: CompositionLayerFlags::BLEND_TEXTURE_SOURCE_ALPHA,
+        }
+    }
+}
+
+/// A [`ProjectionLayer`] that also submits the contents of the [`OxrDepthSwapchain`] to the runtime.
+///
+/// Requires [`XR_KHR_composition_layer_depth`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#XR_KHR_composition_layer_depth).
+/// The depth is expected to use bevy's reversed infinite depth, so `near` should match the near plane of the [`XrProjection`](bevy_mod_xr::camera::XrProjection).
+/// Each view submits the [`view_resolution`](OxrGraphicsInfo::view_resolution) of the depth swapchain.
+/// The depth is read from the same [`image_rects`](Self::image_rects) as the colors.
+/// The swapchain is created and filled by the [`OxrLayerDepthPlugin`](crate::features::layer_depth::OxrLayerDepthPlugin),
+/// the views are submitted without depth on frames it has no image for.
+pub struct ProjectionLayerDepth {
+    pub near: f32,
+    pub flags: CompositionLayerFlags,
+    pub quality: LayerQuality,
+    pub color_scale_bias: Option<LayerColorScaleBias>,
+    /// The area of the swapchain images each view is read from, indexed by view, see [`with_image_rect`](Self::with_image_rect).
+    pub image_rects: Vec<Option<URect>>,
+}
+
+impl ProjectionLayerDepth {
+    pub fn with_flags(mut self, flags: CompositionLayerFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// See [`ProjectionLayer::with_image_rect`].
+    pub fn with_image_rect(mut self, view_index: u32, rect: URect) -> Self {
+        set_image_rect(&mut self.image_rects, view_index, rect);
+        self
+    }
+
+    pub fn with_quality(mut self, quality: LayerQuality) -> Self {
+        self.quality = quality;
+        self
+    }
+
+    /// See [`LayerColorScaleBias`].
+    pub fn with_color_scale_bias(mut self, scale: Vec4, bias: Vec4) -> Self {
+        self.color_scale_bias = Some(LayerColorScaleBias { scale, bias });
+        self
+    }
+}
+
+impl Default for ProjectionLayerDepth {
+    fn default() -> Self {
+        Self {
+            near: 0.1,
+            flags: CompositionLayerFlags::BLEND_TEXTURE_SOURCE_ALPHA,
+            quality: LayerQuality::Normal,
+            color_scale_bias: None,
+            image_rects: Vec::new(),
+        }
+    }
+}
+
+fn set_image_rect(image_rects: &mut Vec<Option<URect>>, view_index: u32, rect: URect) {
+    let index = view_index as usize;
+    if image_rects.len() <= index {
+        image_rects.resize(index + 1, None);
+    }
+    image_rects[index] = Some(rect);
+}
+
+/// Returns the `image_rect` of the view with `view_index`, the override in `image_rects` if it's within
+/// the [`view_resolution`](OxrGraphicsInfo::view_resolution) and the whole view otherwise.
+fn view_image_rect(
+    graphics_info: &OxrGraphicsInfo,
+    image_rects: &[Option<URect>],
+    view_index: u32,
+) -> Rect2Di {
+    let resolution = graphics_info.view_resolution(view_index);
+    let rect = match image_rects.get(view_index as usize).copied().flatten() {
+        Some(rect) if !rect.is_empty() && rect.max.cmple(resolution).all() => rect,
+        Some(rect) => {
+            warn_once!(
+                "image rect {:?} of view {} isn't within the swapchain image of {}, submitting the whole image instead",
+                rect,
+                view_index,
+                resolution
+            );
+            URect::from_corners(UVec2::ZERO, resolution)
+        }
+        None => URect::from_corners(UVec2::ZERO, resolution),
+    };
+    Rect2Di {
+        offset: openxr::Offset2Di {
+            x: rect.min.x as _,
+            y: rect.min.y as _,
+        },
+        extent: openxr::Extent2Di {
+            width: rect.width() as _,
+            height: rect.height() as _,
+        },
+    }
+}
+
+/// Submits the image of the swapchain in the render world resource `S` as a quad, e.g. for UI panels.
+///
+/// The `pose` is relative to the [`XrPrimaryReferenceSpace`], so the quad stays in place in the world.
+/// With `head_locked` set the `pose` is relative to the [`OxrViewReferenceSpace`] instead and the quad follows the head,
+/// e.g. for reticles or cockpit style status text. Head locked quads ignore recentering and the [`XrTrackingRoot`](bevy_mod_xr::session::XrTrackingRoot).
+///
+/// Content that moves with the head can't be looked at from another angle, which many users find uncomfortable.
+/// Keep head locked quads small, place them at least a meter in front of the user and prefer world locked quads for anything the user reads for longer.
+///
+/// `flags` default to [`BLEND_TEXTURE_SOURCE_ALPHA`](CompositionLayerFlags::BLEND_TEXTURE_SOURCE_ALPHA).
+pub struct QuadLayer<S> {
+    /// The area of the swapchain image shown on the quad.
+    pub image_rect: Rect2Di,
+    /// The pose of the center of the quad.
+    pub pose: Transform,
+    /// The size of the quad in meters.
+    pub size: Vec2,
+    pub head_locked: bool,
+    pub eye_visibility: EyeVisibility,
+    pub flags: CompositionLayerFlags,
+    pub quality: LayerQuality,
+    pub color_scale_bias: Option<LayerColorScaleBias>,
+    _swapchain: PhantomData<fn() -> S>,
+}
+
+impl<S> QuadLayer<S> {
+    pub fn new(image_rect: Rect2Di, size: Vec2) -> Self {
+        Self {
+            image_rect,
+            pose: Transform::IDENTITY,
+            size,
+            head_locked: false,
+            eye_visibility: EyeVisibility::BOTH,
+            flags: CompositionLayerFlags::BLEND_TEXTURE_SOURCE_ALPHA,
+            quality: LayerQuality::Normal,
+            color_scale_bias: None,
+            _swapchain: PhantomData,
+        }
+    }
+
+    pub fn with_pose(mut self, pose: Transform) -> Self {
+        self.pose = pose;
+        self
+    }
+
+    pub fn with_head_locked(mut self, head_locked: bool) -> Self {
+        self.head_locked = head_locked;
+        self
+    }
+
+    pub fn with_eye_visibility(mut self, eye_visibility: EyeVisibility) -> Self {
+        self.eye_visibility = eye_visibility;
+        self
+    }
+
+    pub fn with_flags(mut self, flags: CompositionLayerFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    pub fn with_quality(mut self, quality: LayerQuality) -> Self {
+        self.quality = quality;
+        self
+    }
 
-pub struct PassthroughLayer;
+    /// See [`LayerColorScaleBias`].
+    pub fn with_color_scale_bias(mut self, scale: Vec4, bias: Vec4) -> Self {
+        self.color_scale_bias = Some(LayerColorScaleBias { scale, bias });
+        self
+    }
+}
 
 impl LayerProvider for ProjectionLayer {
-    fn get<'a>(&self, world: &'a World) -> Option<Box<dyn CompositionLayer<'a> + 'a>> {
+    fn get<'a>(&'a self, world: &'a World) -> Option<Box<dyn CompositionLayer + '_>> {
         let stage = world.get_resource::<XrPrimaryReferenceSpace>()?;
         let openxr_views = world.get_resource::<OxrViews>()?;
         let swapchain = world.get_resource::<OxrSwapchain>()?;
+        let per_eye_swapchains = world.get_resource::<OxrPerEyeSwapchains>();
         let graphics_info = world.get_resource::<OxrGraphicsInfo>()?;
-        let rect = openxr::Rect2Di {
-            offset: openxr::Offset2Di { x: 0, y: 0 },
-            extent: openxr::Extent2Di {
-                width: graphics_info.resolution.x as _,
-                height: graphics_info.resolution.y as _,
-            },
-        };
+        let rect = |view_index: u32| view_image_rect(graphics_info, &self.image_rects, view_index);
 
-        if openxr_views.len() < 2 {
+        if openxr_views.len() < graphics_info.view_count as usize {
             return None;
         }
 
-        Some(Box::new(
-            CompositionLayerProjection::new()
-                .layer_flags(CompositionLayerFlags::BLEND_TEXTURE_SOURCE_ALPHA)
-                .space(&stage)
-                .views(&[
-                    CompositionLayerProjectionView::new()
-                        .pose(openxr_views.0[0].pose)
-                        .fov(openxr_views.0[0].fov)
-                        .sub_image(
-                            SwapchainSubImage::new()
-                                .swapchain(&swapchain)
-                                .image_array_index(0)
-                                .image_rect(rect),
-                        ),
-                    CompositionLayerProjectionView::new()
-                        .pose(openxr_views.0[1].pose)
-                        .fov(openxr_views.0[1].fov)
+        let views = openxr_views
+            .iter()
+            .take(graphics_info.view_count as usize)
+            .enumerate()
+            .map(|(i, view)| {
+                CompositionLayerProjectionView::new()
+                    .pose(view.pose)
+                    .fov(view.fov)
+                    .sub_image(
+                        SwapchainSubImage::new()
+                            .swapchain(per_eye_swapchains.map_or(swapchain, |swapchains| {
+                                swapchains.view_swapchain(swapchain, i as u32)
+                            }))
+                            .image_array_index(graphics_info.swapchain_layout.array_layer(i as u32))
+                            .image_rect(rect(i as u32)),
+                    )
+            })
+            .collect::<Vec<_>>();
+
+        let mut layer = CompositionLayerProjection::new()
+            .layer_flags(self.flags)
+            .space(stage)
+            .views(&views);
+        if let Some(flags) = self.quality.settings_flags(world) {
+            layer = layer.settings(flags);
+        }
+        if let Some(color_scale_bias) = self.color_scale_bias {
+            if LayerColorScaleBias::supported(world) {
+                layer = layer.color_scale_bias(
+                    color_scale_bias.scale.to_color4f(),
+                    color_scale_bias.bias.to_color4f(),
+                );
+            }
+        }
+        Some(Box::new(layer))
+    }
+}
+
+impl LayerProvider for ProjectionLayerDepth {
+    fn get<'a>(&'a self, world: &'a World) -> Option<Box<dyn CompositionLayer + '_>> {
+        let stage = world.get_resource::<XrPrimaryReferenceSpace>()?;
+        let openxr_views = world.get_resource::<OxrViews>()?;
+        let swapchain = world.get_resource::<OxrSwapchain>()?;
+        let per_eye_swapchains = world.get_resource::<OxrPerEyeSwapchains>();
+        // the depth is only submitted on frames an image was acquired, waited on and released
+        let depth_swapchain = world
+            .get_resource::<OxrDepthSwapchain>()
+            .filter(|_| world.contains_resource::<OxrDepthImageIndex>());
+        let graphics_info = world.get_resource::<OxrGraphicsInfo>()?;
+        let rect = |view_index: u32| view_image_rect(graphics_info, &self.image_rects, view_index);
+
+        if openxr_views.len() < graphics_info.view_count as usize {
+            return None;
+        }
+
+        let views = openxr_views
+            .iter()
+            .take(graphics_info.view_count as usize)
+            .enumerate()
+            .map(|(i, view)| {
+                let projection_view = CompositionLayerProjectionView::new()
+                    .pose(view.pose)
+                    .fov(view.fov)
+                    .sub_image(
+                        SwapchainSubImage::new()
+                            .swapchain(per_eye_swapchains.map_or(swapchain, |swapchains| {
+                                swapchains.view_swapchain(swapchain, i as u32)
+                            }))
+                            .image_array_index(graphics_info.swapchain_layout.array_layer(i as u32))
+                            .image_rect(rect(i as u32)),
+                    );
+                let Some(depth_swapchain) = depth_swapchain else {
+                    return projection_view;
+                };
+                projection_view.depth_info(
+                    CompositionLayerDepthInfo::new()
                         .sub_image(
                             SwapchainSubImage::new()
-                                .swapchain(&swapchain)
-                                .image_array_index(1)
-                                .image_rect(rect),
-                        ),
-                ]),
-        ))
+                                .swapchain(depth_swapchain)
+                                .image_array_index(i as u32)
+                                .image_rect(rect(i as u32)),
+                        )
+                        .min_depth(0.0)
+                        .max_depth(1.0)
+                        // reversed depth, 0.0 is at infinity
+                        .near_z(f32::INFINITY)
+                        .far_z(self.near),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let mut layer = CompositionLayerProjection::new()
+            .layer_flags(self.flags)
+            .space(stage)
+            .views(&views);
+        if let Some(flags) = self.quality.settings_flags(world) {
+            layer = layer.settings(flags);
+        }
+        if let Some(color_scale_bias) = self.color_scale_bias {
+            if LayerColorScaleBias::supported(world) {
+                layer = layer.color_scale_bias(
+                    color_scale_bias.scale.to_color4f(),
+                    color_scale_bias.bias.to_color4f(),
+                );
+            }
+        }
+        Some(Box::new(layer))
     }
 }
 
@@ -67,11 +515,49 @@ impl LayerProvider for PassthroughLayer {
         Some(Box::new(
             CompositionLayerPassthrough::new()
                 .layer_handle(world.get_resource::<OxrPassthroughLayer>()?)
-                .layer_flags(CompositionLayerFlags::BLEND_TEXTURE_SOURCE_ALPHA),
+                .layer_flags(self.flags),
         ))
     }
 }
 
+impl<S: Resource + Deref<Target = OxrSwapchain>> LayerProvider for QuadLayer<S> {
+    fn get<'a>(&'a self, world: &'a World) -> Option<Box<dyn CompositionLayer + '_>> {
+        let space: &XrSpace = if self.head_locked {
+            world.get_resource::<OxrViewReferenceSpace>()?
+        } else {
+            world.get_resource::<XrPrimaryReferenceSpace>()?
+        };
+        let swapchain = world.get_resource::<S>()?;
+
+        let mut layer = CompositionLayerQuad::new()
+            .layer_flags(self.flags)
+            .space(space)
+            .eye_visibility(self.eye_visibility)
+            .sub_image(
+                SwapchainSubImage::new()
+                    .swapchain(swapchain)
+                    .image_rect(self.image_rect),
+            )
+            .pose(self.pose.to_posef())
+            .size(Extent2Df {
+                width: self.size.x,
+                height: self.size.y,
+            });
+        if let Some(flags) = self.quality.settings_flags(world) {
+            layer = layer.settings(flags);
+        }
+        if let Some(color_scale_bias) = self.color_scale_bias {
+            if LayerColorScaleBias::supported(world) {
+                layer = layer.color_scale_bias(
+                    color_scale_bias.scale.to_color4f(),
+                    color_scale_bias.bias.to_color4f(),
+                );
+            }
+        }
+        Some(Box::new(layer))
+    }
+}
+
 #[derive(Copy, Clone)]
 pub struct SwapchainSubImage<'a> {
     inner: sys::SwapchainSubImage,
@@ -127,6 +613,8 @@ impl<'a> Default for SwapchainSubImage<'a> {
 pub struct CompositionLayerProjectionView<'a> {
     inner: sys::CompositionLayerProjectionView,
     swapchain: Option<&'a OxrSwapchain>,
+    depth_info: Option<sys::CompositionLayerDepthInfoKHR>,
+    space_warp_info: Option<sys::CompositionLayerSpaceWarpInfoFB>,
 }
 
 impl<'a> CompositionLayerProjectionView<'a> {
@@ -138,6 +626,8 @@ impl<'a> CompositionLayerProjectionView<'a> {
                 ..unsafe { mem::zeroed() }
             },
             swapchain: None,
+            depth_info: None,
+            space_warp_info: None,
         }
     }
     #[inline]
@@ -164,12 +654,158 @@ impl<'a> CompositionLayerProjectionView<'a> {
         self.swapchain = value.swapchain;
         self
     }
+    /// Chains depth info onto this view.
+    ///
+    /// Requires [`XR_KHR_composition_layer_depth`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#XR_KHR_composition_layer_depth).
+    #[inline]
+    pub fn depth_info(mut self, value: CompositionLayerDepthInfo<'a>) -> Self {
+        self.depth_info = Some(value.inner);
+        self
+    }
+    /// Requires [`XR_FB_space_warp`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#XR_FB_space_warp).
+    #[inline]
+    pub fn space_warp_info(mut self, value: CompositionLayerSpaceWarpInfo<'a>) -> Self {
+        self.space_warp_info = Some(value.inner);
+        self
+    }
 }
 impl<'a> Default for CompositionLayerProjectionView<'a> {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[derive(Copy, Clone)]
+pub struct CompositionLayerDepthInfo<'a> {
+    inner: sys::CompositionLayerDepthInfoKHR,
+    swapchain: Option<&'a OxrSwapchain>,
+}
+
+impl<'a> CompositionLayerDepthInfo<'a> {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            inner: sys::CompositionLayerDepthInfoKHR {
+                ty: sys::CompositionLayerDepthInfoKHR::TYPE,
+                ..unsafe { mem::zeroed() }
+            },
+            swapchain: None,
+        }
+    }
+    #[inline]
+    pub fn into_raw(self) -> sys::CompositionLayerDepthInfoKHR {
+        self.inner
+    }
+    #[inline]
+    pub fn as_raw(&self) -> &sys::CompositionLayerDepthInfoKHR {
+        &self.inner
+    }
+    #[inline]
+    pub fn sub_image(mut self, value: SwapchainSubImage<'a>) -> Self {
+        self.inner.sub_image = value.inner;
+        self.swapchain = value.swapchain;
+        self
+    }
+    #[inline]
+    pub fn min_depth(mut self, value: f32) -> Self {
+        self.inner.min_depth = value;
+        self
+    }
+    #[inline]
+    pub fn max_depth(mut self, value: f32) -> Self {
+        self.inner.max_depth = value;
+        self
+    }
+    #[inline]
+    pub fn near_z(mut self, value: f32) -> Self {
+        self.inner.near_z = value;
+        self
+    }
+    #[inline]
+    pub fn far_z(mut self, value: f32) -> Self {
+        self.inner.far_z = value;
+        self
+    }
+}
+impl<'a> Default for CompositionLayerDepthInfo<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+#[derive(Copy, Clone)]
+pub struct CompositionLayerSpaceWarpInfo<'a> {
+    inner: sys::CompositionLayerSpaceWarpInfoFB,
+    swapchains: [Option<&'a OxrSwapchain>; 2],
+}
+
+impl<'a> CompositionLayerSpaceWarpInfo<'a> {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            inner: sys::CompositionLayerSpaceWarpInfoFB {
+                ty: sys::CompositionLayerSpaceWarpInfoFB::TYPE,
+                ..unsafe { mem::zeroed() }
+            },
+            swapchains: [None; 2],
+        }
+    }
+    #[inline]
+    pub fn into_raw(self) -> sys::CompositionLayerSpaceWarpInfoFB {
+        self.inner
+    }
+    #[inline]
+    pub fn as_raw(&self) -> &sys::CompositionLayerSpaceWarpInfoFB {
+        &self.inner
+    }
+    #[inline]
+    pub fn layer_flags(mut self, value: openxr::CompositionLayerSpaceWarpInfoFlagsFB) -> Self {
+        self.inner.layer_flags = value;
+        self
+    }
+    #[inline]
+    pub fn motion_vector_sub_image(mut self, value: SwapchainSubImage<'a>) -> Self {
+        self.inner.motion_vector_sub_image = value.inner;
+        self.swapchains[0] = value.swapchain;
+        self
+    }
+    /// The motion of the app space since the last frame, e.g. caused by moving the tracking root.
+    #[inline]
+    pub fn app_space_delta_pose(mut self, value: Posef) -> Self {
+        self.inner.app_space_delta_pose = value;
+        self
+    }
+    #[inline]
+    pub fn depth_sub_image(mut self, value: SwapchainSubImage<'a>) -> Self {
+        self.inner.depth_sub_image = value.inner;
+        self.swapchains[1] = value.swapchain;
+        self
+    }
+    #[inline]
+    pub fn min_depth(mut self, value: f32) -> Self {
+        self.inner.min_depth = value;
+        self
+    }
+    #[inline]
+    pub fn max_depth(mut self, value: f32) -> Self {
+        self.inner.max_depth = value;
+        self
+    }
+    #[inline]
+    pub fn near_z(mut self, value: f32) -> Self {
+        self.inner.near_z = value;
+        self
+    }
+    #[inline]
+    pub fn far_z(mut self, value: f32) -> Self {
+        self.inner.far_z = value;
+        self
+    }
+}
+impl<'a> Default for CompositionLayerSpaceWarpInfo<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 pub unsafe trait CompositionLayer<'a> {
     fn swapchain(&self) -> Option<&'a OxrSwapchain>;
     fn header(&self) -> &sys::CompositionLayerBaseHeader;
@@ -179,6 +815,14 @@ pub struct CompositionLayerProjection<'a> {
     inner: sys::CompositionLayerProjection,
     swapchain: Option<&'a OxrSwapchain>,
     views: Vec<sys::CompositionLayerProjectionView>,
+    /// Kept alive here since the views point into this.
+    depth_infos: Vec<sys::CompositionLayerDepthInfoKHR>,
+    /// Kept alive here since the views or their depth infos point into this.
+    space_warp_infos: Vec<sys::CompositionLayerSpaceWarpInfoFB>,
+    /// Boxed so the layer can point to it while being moved.
+    settings: Option<Box<sys::CompositionLayerSettingsFB>>,
+    /// Boxed so the layer can point to it while being moved, chained before the settings.
+    color_scale_bias: Option<Box<sys::CompositionLayerColorScaleBiasKHR>>,
 }
 impl<'a> CompositionLayerProjection<'a> {
     #[inline]
@@ -190,6 +834,10 @@ impl<'a> CompositionLayerProjection<'a> {
             },
             swapchain: None,
             views: Vec::new(),
+            depth_infos: Vec::new(),
+            space_warp_infos: Vec::new(),
+            settings: None,
+            color_scale_bias: None,
         }
     }
     #[inline]
@@ -212,11 +860,55 @@ impl<'a> CompositionLayerProjection<'a> {
     }
     #[inline]
     pub fn views(mut self, value: &[CompositionLayerProjectionView<'a>]) -> Self {
-        self.views = value.iter().map(|view| view.inner).collect();
+        self.depth_infos = value.iter().filter_map(|view| view.depth_info).collect();
+        self.space_warp_infos = value
+            .iter()
+            .filter_map(|view| view.space_warp_info)
+            .collect();
+        let mut depth_infos = self.depth_infos.iter_mut();
+        let mut space_warp_infos = self.space_warp_infos.iter();
+        self.views = value
+            .iter()
+            .map(|view| {
+                let mut inner = view.inner;
+                // the space warp info is chained after the depth info
+                let space_warp_info = match view.space_warp_info {
+                    Some(_) => space_warp_infos.next().unwrap() as *const _ as _,
+                    None => ptr::null(),
+                };
+                match view.depth_info {
+                    Some(_) => {
+                        let depth_info = depth_infos.next().unwrap();
+                        depth_info.next = space_warp_info;
+                        inner.next = depth_info as *const _ as _;
+                    }
+                    None => inner.next = space_warp_info,
+                }
+                inner
+            })
+            .collect();
         self.inner.views = self.views.as_slice().as_ptr() as *const _ as _;
         self.inner.view_count = self.views.len() as u32;
         self
     }
+    /// Chains [`CompositionLayerSettingsFB`](sys::CompositionLayerSettingsFB) onto the layer, e.g. to supersample it.
+    ///
+    /// Requires [`XR_FB_composition_layer_settings`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#XR_FB_composition_layer_settings).
+    #[inline]
+    pub fn settings(mut self, value: sys::CompositionLayerSettingsFlagsFB) -> Self {
+        self.settings = Some(Box::new(layer_settings(value)));
+        self.inner.next = layer_next(&self.settings, &mut self.color_scale_bias);
+        self
+    }
+    /// Chains [`CompositionLayerColorScaleBiasKHR`](sys::CompositionLayerColorScaleBiasKHR) onto the layer, see [`LayerColorScaleBias`].
+    ///
+    /// Requires [`XR_KHR_composition_layer_color_scale_bias`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#XR_KHR_composition_layer_color_scale_bias).
+    #[inline]
+    pub fn color_scale_bias(mut self, scale: Color4f, bias: Color4f) -> Self {
+        self.color_scale_bias = Some(Box::new(layer_color_scale_bias(scale, bias)));
+        self.inner.next = layer_next(&self.settings, &mut self.color_scale_bias);
+        self
+    }
 }
 unsafe impl<'a> CompositionLayer<'a> for CompositionLayerProjection<'a> {
     fn swapchain(&self) -> Option<&'a OxrSwapchain> {
@@ -232,23 +924,446 @@ impl<'a> Default for CompositionLayerProjection<'a> {
         Self::new()
     }
 }
-pub struct CompositionLayerPassthrough {
-    inner: sys::CompositionLayerPassthroughFB,
+/// A quad placed in a space, useful for world locked UI.
+///
+/// Use [`CompositionLayerFlags::BLEND_TEXTURE_SOURCE_ALPHA`] for transparent panels,
+/// and additionally [`CompositionLayerFlags::UNPREMULTIPLIED_ALPHA`] if the swapchain contents are not premultiplied.
+#[derive(Clone)]
+pub struct CompositionLayerQuad<'a> {
+    inner: sys::CompositionLayerQuad,
+    swapchain: Option<&'a OxrSwapchain>,
+    /// Boxed so the layer can point to it while being moved.
+    settings: Option<Box<sys::CompositionLayerSettingsFB>>,
+    /// Boxed so the layer can point to it while being moved, chained before the settings.
+    color_scale_bias: Option<Box<sys::CompositionLayerColorScaleBiasKHR>>,
 }
-impl CompositionLayerPassthrough {
+impl<'a> CompositionLayerQuad<'a> {
     #[inline]
     pub fn new() -> Self {
         Self {
-            inner: openxr::sys::CompositionLayerPassthroughFB {
-                ty: openxr::sys::CompositionLayerPassthroughFB::TYPE,
+            inner: sys::CompositionLayerQuad {
+                ty: sys::CompositionLayerQuad::TYPE,
                 ..unsafe { mem::zeroed() }
             },
+            swapchain: None,
+            settings: None,
+            color_scale_bias: None,
         }
     }
     #[inline]
-    pub fn layer_handle(mut self, layer_handle: &OxrPassthroughLayer) -> Self {
-        self.inner.layer_handle = *layer_handle.inner();
-        self
+    pub fn into_raw(self) -> sys::CompositionLayerQuad {
+        self.inner
+    }
+    #[inline]
+    pub fn as_raw(&self) -> &sys::CompositionLayerQuad {
+        &self.inner
+    }
+    #[inline]
+    pub fn layer_flags(mut self, value: CompositionLayerFlags) -> Self {
+        self.inner.layer_flags = value;
+        self
+    }
+    #[inline]
+    pub fn space(mut self, value: &XrSpace) -> Self {
+        self.inner.space = value.as_raw_openxr_space();
+        self
+    }
+    #[inline]
+    pub fn eye_visibility(mut self, value: EyeVisibility) -> Self {
+        self.inner.eye_visibility = value;
+        self
+    }
+    #[inline]
+    pub fn sub_image(mut self, value: SwapchainSubImage<'a>) -> Self {
+        self.inner.sub_image = value.inner;
+        self.swapchain = value.swapchain;
+        self
+    }
+    /// The pose of the center of the quad, relative to the [`space`](Self::space).
+    #[inline]
+    pub fn pose(mut self, value: Posef) -> Self {
+        self.inner.pose = value;
+        self
+    }
+    /// The size of the quad in meters.
+    #[inline]
+    pub fn size(mut self, value: Extent2Df) -> Self {
+        self.inner.size = value;
+        self
+    }
+    /// Chains [`CompositionLayerSettingsFB`](sys::CompositionLayerSettingsFB) onto the layer, e.g. to sharpen text.
+    ///
+    /// Requires [`XR_FB_composition_layer_settings`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#XR_FB_composition_layer_settings).
+    #[inline]
+    pub fn settings(mut self, value: sys::CompositionLayerSettingsFlagsFB) -> Self {
+        self.settings = Some(Box::new(layer_settings(value)));
+        self.inner.next = layer_next(&self.settings, &mut self.color_scale_bias);
+        self
+    }
+    /// Chains [`CompositionLayerColorScaleBiasKHR`](sys::CompositionLayerColorScaleBiasKHR) onto the layer, see [`LayerColorScaleBias`].
+    ///
+    /// Requires [`XR_KHR_composition_layer_color_scale_bias`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#XR_KHR_composition_layer_color_scale_bias).
+    #[inline]
+    pub fn color_scale_bias(mut self, scale: Color4f, bias: Color4f) -> Self {
+        self.color_scale_bias = Some(Box::new(layer_color_scale_bias(scale, bias)));
+        self.inner.next = layer_next(&self.settings, &mut self.color_scale_bias);
+        self
+    }
+}
+unsafe impl<'a> CompositionLayer<'a> for CompositionLayerQuad<'a> {
+    fn swapchain(&self) -> Option<&'a OxrSwapchain> {
+        self.swapchain
+    }
+
+    fn header(&self) -> &sys::CompositionLayerBaseHeader {
+        unsafe { mem::transmute(&self.inner) }
+    }
+}
+impl<'a> Default for CompositionLayerQuad<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+/// A section of a cylinder placed in a space, useful for curved UI wrapped around the user.
+///
+/// Requires [`XR_KHR_composition_layer_cylinder`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#XR_KHR_composition_layer_cylinder).
+#[derive(Clone)]
+pub struct CompositionLayerCylinder<'a> {
+    inner: sys::CompositionLayerCylinderKHR,
+    swapchain: Option<&'a OxrSwapchain>,
+    /// Boxed so the layer can point to it while being moved.
+    color_scale_bias: Option<Box<sys::CompositionLayerColorScaleBiasKHR>>,
+}
+impl<'a> CompositionLayerCylinder<'a> {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            inner: sys::CompositionLayerCylinderKHR {
+                ty: sys::CompositionLayerCylinderKHR::TYPE,
+                ..unsafe { mem::zeroed() }
+            },
+            swapchain: None,
+            color_scale_bias: None,
+        }
+    }
+    #[inline]
+    pub fn into_raw(self) -> sys::CompositionLayerCylinderKHR {
+        self.inner
+    }
+    #[inline]
+    pub fn as_raw(&self) -> &sys::CompositionLayerCylinderKHR {
+        &self.inner
+    }
+    #[inline]
+    pub fn layer_flags(mut self, value: CompositionLayerFlags) -> Self {
+        self.inner.layer_flags = value;
+        self
+    }
+    #[inline]
+    pub fn space(mut self, value: &XrSpace) -> Self {
+        self.inner.space = value.as_raw_openxr_space();
+        self
+    }
+    #[inline]
+    pub fn eye_visibility(mut self, value: EyeVisibility) -> Self {
+        self.inner.eye_visibility = value;
+        self
+    }
+    #[inline]
+    pub fn sub_image(mut self, value: SwapchainSubImage<'a>) -> Self {
+        self.inner.sub_image = value.inner;
+        self.swapchain = value.swapchain;
+        self
+    }
+    /// The pose of the center of the cylinder, relative to the [`space`](Self::space).
+    /// The image is placed on the side of the cylinder facing towards negative Z.
+    #[inline]
+    pub fn pose(mut self, value: Posef) -> Self {
+        self.inner.pose = value;
+        self
+    }
+    /// The radius of the cylinder in meters.
+    #[inline]
+    pub fn radius(mut self, value: f32) -> Self {
+        self.inner.radius = value;
+        self
+    }
+    /// The angle of the visible section of the cylinder in radians.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the angle is not within `(0, 2π]`.
+    #[inline]
+    pub fn central_angle(mut self, value: f32) -> Self {
+        assert!(
+            value > 0.0 && value <= std::f32::consts::TAU,
+            "central angle of a cylinder layer has to be within (0, 2π], got {value}"
+        );
+        self.inner.central_angle = value;
+        self
+    }
+    /// The ratio of the width of the visible section of the cylinder to its height.
+    #[inline]
+    pub fn aspect_ratio(mut self, value: f32) -> Self {
+        self.inner.aspect_ratio = value;
+        self
+    }
+    /// Chains [`CompositionLayerColorScaleBiasKHR`](sys::CompositionLayerColorScaleBiasKHR) onto the layer, see [`LayerColorScaleBias`].
+    ///
+    /// Requires [`XR_KHR_composition_layer_color_scale_bias`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#XR_KHR_composition_layer_color_scale_bias).
+    #[inline]
+    pub fn color_scale_bias(mut self, scale: Color4f, bias: Color4f) -> Self {
+        let color_scale_bias = self
+            .color_scale_bias
+            .insert(Box::new(layer_color_scale_bias(scale, bias)));
+        self.inner.next = &**color_scale_bias as *const _ as _;
+        self
+    }
+}
+unsafe impl<'a> CompositionLayer<'a> for CompositionLayerCylinder<'a> {
+    fn swapchain(&self) -> Option<&'a OxrSwapchain> {
+        self.swapchain
+    }
+
+    fn header(&self) -> &sys::CompositionLayerBaseHeader {
+        unsafe { mem::transmute(&self.inner) }
+    }
+}
+impl<'a> Default for CompositionLayerCylinder<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+/// An equirectangular image mapped onto a sphere placed in a space, useful for 360° images and videos.
+///
+/// Submit it before the projection layer to show it behind the rendered scene.
+/// Requires [`XR_KHR_composition_layer_equirect2`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#XR_KHR_composition_layer_equirect2).
+#[derive(Clone)]
+pub struct CompositionLayerEquirect2<'a> {
+    inner: sys::CompositionLayerEquirect2KHR,
+    swapchain: Option<&'a OxrSwapchain>,
+    /// Boxed so the layer can point to it while being moved.
+    color_scale_bias: Option<Box<sys::CompositionLayerColorScaleBiasKHR>>,
+}
+impl<'a> CompositionLayerEquirect2<'a> {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            inner: sys::CompositionLayerEquirect2KHR {
+                ty: sys::CompositionLayerEquirect2KHR::TYPE,
+                ..unsafe { mem::zeroed() }
+            },
+            swapchain: None,
+            color_scale_bias: None,
+        }
+    }
+    #[inline]
+    pub fn into_raw(self) -> sys::CompositionLayerEquirect2KHR {
+        self.inner
+    }
+    #[inline]
+    pub fn as_raw(&self) -> &sys::CompositionLayerEquirect2KHR {
+        &self.inner
+    }
+    #[inline]
+    pub fn layer_flags(mut self, value: CompositionLayerFlags) -> Self {
+        self.inner.layer_flags = value;
+        self
+    }
+    #[inline]
+    pub fn space(mut self, value: &XrSpace) -> Self {
+        self.inner.space = value.as_raw_openxr_space();
+        self
+    }
+    #[inline]
+    pub fn eye_visibility(mut self, value: EyeVisibility) -> Self {
+        self.inner.eye_visibility = value;
+        self
+    }
+    #[inline]
+    pub fn sub_image(mut self, value: SwapchainSubImage<'a>) -> Self {
+        self.inner.sub_image = value.inner;
+        self.swapchain = value.swapchain;
+        self
+    }
+    /// The pose of the center of the sphere, relative to the [`space`](Self::space).
+    #[inline]
+    pub fn pose(mut self, value: Posef) -> Self {
+        self.inner.pose = value;
+        self
+    }
+    /// The radius of the sphere in meters.
+    ///
+    /// A radius of `0.0` or [`f32::INFINITY`] is treated as an infinitely large sphere enclosing the user,
+    /// see [`infinite_radius`](Self::infinite_radius).
+    #[inline]
+    pub fn radius(mut self, value: f32) -> Self {
+        self.inner.radius = value;
+        self
+    }
+    /// Makes the sphere infinitely large, so the image is always drawn behind everything else regardless of the position of the user.
+    #[inline]
+    pub fn infinite_radius(self) -> Self {
+        self.radius(f32::INFINITY)
+    }
+    /// The visible horizontal angle of the sphere in radians, centered around the forward direction of the [`pose`](Self::pose).
+    /// Use [`TAU`](std::f32::consts::TAU) to cover the whole sphere.
+    #[inline]
+    pub fn central_horizontal_angle(mut self, value: f32) -> Self {
+        self.inner.central_horizontal_angle = value;
+        self
+    }
+    /// The upper visible angle of the sphere in radians, in the range `[-π/2, π/2]`.
+    #[inline]
+    pub fn upper_vertical_angle(mut self, value: f32) -> Self {
+        self.inner.upper_vertical_angle = value;
+        self
+    }
+    /// The lower visible angle of the sphere in radians, in the range `[-π/2, π/2]`.
+    #[inline]
+    pub fn lower_vertical_angle(mut self, value: f32) -> Self {
+        self.inner.lower_vertical_angle = value;
+        self
+    }
+    /// Chains [`CompositionLayerColorScaleBiasKHR`](sys::CompositionLayerColorScaleBiasKHR) onto the layer, see [`LayerColorScaleBias`].
+    ///
+    /// Requires [`XR_KHR_composition_layer_color_scale_bias`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#XR_KHR_composition_layer_color_scale_bias).
+    #[inline]
+    pub fn color_scale_bias(mut self, scale: Color4f, bias: Color4f) -> Self {
+        let color_scale_bias = self
+            .color_scale_bias
+            .insert(Box::new(layer_color_scale_bias(scale, bias)));
+        self.inner.next = &**color_scale_bias as *const _ as _;
+        self
+    }
+}
+unsafe impl<'a> CompositionLayer<'a> for CompositionLayerEquirect2<'a> {
+    fn swapchain(&self) -> Option<&'a OxrSwapchain> {
+        self.swapchain
+    }
+
+    fn header(&self) -> &sys::CompositionLayerBaseHeader {
+        unsafe { mem::transmute(&self.inner) }
+    }
+}
+impl<'a> Default for CompositionLayerEquirect2<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+/// A cube map surrounding the user at infinite distance, useful for skyboxes.
+///
+/// The swapchain has to be created with a [`face_count`](crate::types::SwapchainCreateInfo::face_count) of 6.
+/// Its images can be enumerated with [`SwapchainImageLayout::cube`](crate::types::SwapchainImageLayout::cube).
+/// The faces are stored in the order +X, -X, +Y, -Y, +Z, -Z, following the convention of the graphics api.
+/// Requires [`XR_KHR_composition_layer_cube`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#XR_KHR_composition_layer_cube).
+#[derive(Clone)]
+pub struct CompositionLayerCube<'a> {
+    inner: sys::CompositionLayerCubeKHR,
+    swapchain: Option<&'a OxrSwapchain>,
+    /// Boxed so the layer can point to it while being moved.
+    color_scale_bias: Option<Box<sys::CompositionLayerColorScaleBiasKHR>>,
+}
+impl<'a> CompositionLayerCube<'a> {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            inner: sys::CompositionLayerCubeKHR {
+                ty: sys::CompositionLayerCubeKHR::TYPE,
+                orientation: Quaternionf::IDENTITY,
+                ..unsafe { mem::zeroed() }
+            },
+            swapchain: None,
+            color_scale_bias: None,
+        }
+    }
+    #[inline]
+    pub fn into_raw(self) -> sys::CompositionLayerCubeKHR {
+        self.inner
+    }
+    #[inline]
+    pub fn as_raw(&self) -> &sys::CompositionLayerCubeKHR {
+        &self.inner
+    }
+    #[inline]
+    pub fn layer_flags(mut self, value: CompositionLayerFlags) -> Self {
+        self.inner.layer_flags = value;
+        self
+    }
+    #[inline]
+    pub fn space(mut self, value: &XrSpace) -> Self {
+        self.inner.space = value.as_raw_openxr_space();
+        self
+    }
+    #[inline]
+    pub fn eye_visibility(mut self, value: EyeVisibility) -> Self {
+        self.inner.eye_visibility = value;
+        self
+    }
+    #[inline]
+    pub fn swapchain(mut self, value: &'a OxrSwapchain) -> Self {
+        graphics_match!(
+            &value.0;
+            swap => self.inner.swapchain = swap.as_raw()
+        );
+        self.swapchain = Some(value);
+        self
+    }
+    /// The index of the cube map in the array of the swapchain.
+    #[inline]
+    pub fn image_array_index(mut self, value: u32) -> Self {
+        self.inner.image_array_index = value;
+        self
+    }
+    /// The orientation of the cube map, relative to the [`space`](Self::space).
+    #[inline]
+    pub fn orientation(mut self, value: Quaternionf) -> Self {
+        self.inner.orientation = value;
+        self
+    }
+    /// Chains [`CompositionLayerColorScaleBiasKHR`](sys::CompositionLayerColorScaleBiasKHR) onto the layer, see [`LayerColorScaleBias`].
+    ///
+    /// Requires [`XR_KHR_composition_layer_color_scale_bias`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#XR_KHR_composition_layer_color_scale_bias).
+    #[inline]
+    pub fn color_scale_bias(mut self, scale: Color4f, bias: Color4f) -> Self {
+        let color_scale_bias = self
+            .color_scale_bias
+            .insert(Box::new(layer_color_scale_bias(scale, bias)));
+        self.inner.next = &**color_scale_bias as *const _ as _;
+        self
+    }
+}
+unsafe impl<'a> CompositionLayer<'a> for CompositionLayerCube<'a> {
+    fn swapchain(&self) -> Option<&'a OxrSwapchain> {
+        self.swapchain
+    }
+
+    fn header(&self) -> &sys::CompositionLayerBaseHeader {
+        unsafe { mem::transmute(&self.inner) }
+    }
+}
+impl<'a> Default for CompositionLayerCube<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+pub struct CompositionLayerPassthrough {
+    inner: sys::CompositionLayerPassthroughFB,
+}
+impl CompositionLayerPassthrough {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            inner: openxr::sys::CompositionLayerPassthroughFB {
+                ty: openxr::sys::CompositionLayerPassthroughFB::TYPE,
+                ..unsafe { mem::zeroed() }
+            },
+        }
+    }
+    #[inline]
+    pub fn layer_handle(mut self, layer_handle: &OxrPassthroughLayer) -> Self {
+        self.inner.layer_handle = *layer_handle.inner();
+        self
     }
     #[inline]
     pub fn layer_flags(mut self, value: CompositionLayerFlags) -> Self {
@@ -265,3 +1380,44 @@ unsafe impl<'a> CompositionLayer<'a> for CompositionLayerPassthrough {
         unsafe { mem::transmute(&self.inner) }
     }
 }
+
+#[inline]
+fn layer_settings(
+    layer_flags: sys::CompositionLayerSettingsFlagsFB,
+) -> sys::CompositionLayerSettingsFB {
+    sys::CompositionLayerSettingsFB {
+        ty: sys::CompositionLayerSettingsFB::TYPE,
+        next: ptr::null(),
+        layer_flags,
+    }
+}
+
+#[inline]
+fn layer_color_scale_bias(
+    color_scale: Color4f,
+    color_bias: Color4f,
+) -> sys::CompositionLayerColorScaleBiasKHR {
+    sys::CompositionLayerColorScaleBiasKHR {
+        ty: sys::CompositionLayerColorScaleBiasKHR::TYPE,
+        next: ptr::null(),
+        color_scale,
+        color_bias,
+    }
+}
+
+/// Links the structs chained onto a layer and returns the start of the chain for its `next` pointer.
+fn layer_next(
+    settings: &Option<Box<sys::CompositionLayerSettingsFB>>,
+    color_scale_bias: &mut Option<Box<sys::CompositionLayerColorScaleBiasKHR>>,
+) -> *const c_void {
+    let settings = settings
+        .as_deref()
+        .map_or(ptr::null(), |settings| settings as *const _ as _);
+    match color_scale_bias {
+        Some(color_scale_bias) => {
+            color_scale_bias.next = settings;
+            &**color_scale_bias as *const _ as _
+        }
+        None => settings,
+    }
+}
@@ -22,12 +22,15 @@ use self::{
 pub mod action_binding;
 pub mod action_set_attaching;
 pub mod action_set_syncing;
+pub mod actions;
 pub mod error;
 pub mod exts;
 pub mod features;
+pub mod frame_submission;
 pub mod graphics;
 pub mod helper_traits;
 pub mod init;
+pub mod interaction_profiles;
 pub mod layer_builder;
 pub mod next_chain;
 pub mod poll_events;
@@ -36,6 +39,8 @@ pub mod render;
 pub mod resources;
 pub mod session;
 pub mod spaces;
+pub mod swapchain_blit;
+pub mod time;
 pub mod types;
 
 /// A [`Condition`](bevy::ecs::schedule::Condition) system that says if the OpenXR session is available.
@@ -71,7 +76,10 @@ pub fn add_xr_plugins<G: PluginGroup>(plugins: G) -> PluginGroupBuilder {
         .add(action_set_attaching::OxrActionAttachingPlugin)
         .add(action_binding::OxrActionBindingPlugin)
         .add(action_set_syncing::OxrActionSyncingPlugin)
+        .add(actions::OxrActionsPlugin)
+        .add(interaction_profiles::OxrInteractionProfilesPlugin)
         .add(features::overlay::OxrOverlayPlugin)
+        .add(features::refresh_rate::OxrRefreshRatePlugin)
         .add(spaces::OxrSpatialPlugin)
         .add(spaces::OxrSpacePatchingPlugin)
         // .add(XrActionPlugin)
@@ -1,14 +1,21 @@
-use std::{cell::RefCell, mem, ops::Deref, rc::Rc};
+use std::{cell::RefCell, mem, mem::MaybeUninit, ops::Deref, ptr, rc::Rc};
 
 use bevy::{ecs::system::SystemId, prelude::*};
 use bevy_mod_xr::session::{XrFirst, XrHandleEvents};
-use openxr::{Event, EventDataBuffer};
+use openxr::{sys, Event};
 
+/// Drains the OpenXR event queue once per frame in [`poll_events`].
+///
+/// Every event is passed to the handlers added with [`add_oxr_event_handler`](OxrEventHandlerExt::add_oxr_event_handler),
+/// which turn the events the crate knows into typed events, e.g. [`OxrSessionStateChanged`](crate::init::OxrSessionStateChanged).
+/// Events [`openxr`] can't decode, e.g. of extensions it doesn't know yet, are sent as an [`OxrRawEvent`] instead.
+/// Nothing else may poll events, any event polled elsewhere never reaches the handlers.
 pub struct OxrEventsPlugin;
 
 impl Plugin for OxrEventsPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<OxrEventHandlers>();
+        app.add_event::<OxrRawEvent>();
         app.add_systems(
             XrFirst,
             poll_events
@@ -22,11 +29,24 @@ pub fn poll_events(world: &mut World) {
     let _span = info_span!("xr_poll_events");
     let instance = world.resource::<OxrInstance>().clone();
     let handlers = world.remove_resource::<OxrEventHandlers>().unwrap();
-    let mut buffer = EventDataBuffer::default();
-    while let Some(event) = instance
-        .poll_event(&mut buffer)
-        .expect("Failed to poll event")
-    {
+    let mut buffer = MaybeUninit::<sys::EventDataBuffer>::uninit();
+    loop {
+        unsafe {
+            ptr::addr_of_mut!((*buffer.as_mut_ptr()).ty).write(sys::EventDataBuffer::TYPE);
+            ptr::addr_of_mut!((*buffer.as_mut_ptr()).next).write(ptr::null());
+        }
+        let result = unsafe { (instance.fp().poll_event)(instance.as_raw(), buffer.as_mut_ptr()) };
+        if result == sys::Result::EVENT_UNAVAILABLE {
+            break;
+        }
+        if result.into_raw() < 0 {
+            panic!("Failed to poll event: {result}");
+        }
+        // the buffer is initialized by the runtime if polling succeeded
+        let Some(event) = (unsafe { Event::from_raw(&buffer) }) else {
+            world.send_event(OxrRawEvent(Box::new(unsafe { buffer.assume_init_read() })));
+            continue;
+        };
         let event = Rc::new(RefCell::new(Some(event)));
         for handler in handlers.handlers.iter() {
             if let Err(err) =
@@ -47,6 +67,35 @@ pub struct OxrEventHandlers {
 }
 pub type OxrEventHandler = SystemId<OxrEvent, ()>;
 
+/// An event [`openxr`] can't decode, sent by [`poll_events`] so it can be handled downstream.
+#[derive(Event, Clone)]
+pub struct OxrRawEvent(Box<sys::EventDataBuffer>);
+
+// SAFETY: the next chain of polled events is never dereferenced by the crate and may only be read by the runtime
+unsafe impl Send for OxrRawEvent {}
+unsafe impl Sync for OxrRawEvent {}
+
+impl OxrRawEvent {
+    /// The structure type of the event, e.g. [`EVENT_DATA_SPATIAL_ANCHOR_CREATE_COMPLETE_FB`](sys::StructureType::EVENT_DATA_SPATIAL_ANCHOR_CREATE_COMPLETE_FB).
+    pub fn ty(&self) -> sys::StructureType {
+        self.0.ty
+    }
+
+    /// The raw event buffer.
+    pub fn as_raw(&self) -> &sys::EventDataBuffer {
+        &self.0
+    }
+
+    /// Reinterprets the event as `T`, or returns [`None`] if the [`ty`](Self::ty) doesn't match.
+    ///
+    /// # Safety
+    ///
+    /// `T` has to be the event struct for `ty`.
+    pub unsafe fn cast<T>(&self, ty: sys::StructureType) -> Option<&T> {
+        (self.0.ty == ty).then(|| &*(&*self.0 as *const sys::EventDataBuffer as *const T))
+    }
+}
+
 pub struct OxrEvent {
     event: Rc<RefCell<Option<Event<'static>>>>,
 }
@@ -1,33 +1,114 @@
 use bevy::{
     prelude::*,
-    render::{extract_resource::ExtractResourcePlugin, RenderApp},
+    render::{
+        extract_resource::{ExtractResource, ExtractResourcePlugin},
+        RenderApp,
+    },
 };
 use bevy_mod_xr::{
     session::{XrPreDestroySession, XrSessionCreated},
-    spaces::{XrPrimaryReferenceSpace, XrReferenceSpace},
+    spaces::{XrDestroySpace, XrPrimaryReferenceSpace, XrReferenceSpace},
 };
+use openxr::{Event, ReferenceSpaceType, SpaceLocationFlags};
 
-use crate::session::OxrSession;
+use crate::{
+    exts::OxrEnabledExtensions,
+    helper_traits::ToTransform,
+    openxr_session_running,
+    poll_events::{OxrEvent, OxrEventHandlerExt},
+    resources::OxrFrameState,
+    session::OxrSession,
+    spaces::OxrSpaceSyncSet,
+};
 
 pub struct OxrReferenceSpacePlugin {
     pub default_primary_ref_space: openxr::ReferenceSpaceType,
+    /// Height of the user's head above the floor, used to emulate `LOCAL_FLOOR_EXT` with a `LOCAL` space
+    /// if `XR_EXT_local_floor` is unavailable and the floor height can't be read from the `STAGE` space.
+    pub fallback_floor_height: f32,
 }
 impl Default for OxrReferenceSpacePlugin {
     fn default() -> Self {
         Self {
             default_primary_ref_space: openxr::ReferenceSpaceType::STAGE,
+            fallback_floor_height: 1.6,
         }
     }
 }
 
 /// Resource specifying what the type should be for [`OxrPrimaryReferenceSpace`]. Set through [`OxrReferenceSpacePlugin`].
 #[derive(Resource)]
-struct OxrDefaultPrimaryReferenceSpaceType(openxr::ReferenceSpaceType);
+struct OxrDefaultPrimaryReferenceSpaceType {
+    ty: openxr::ReferenceSpaceType,
+    fallback_floor_height: f32,
+}
+
+/// Describes how the current [`XrPrimaryReferenceSpace`] was created.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct OxrPrimaryReferenceSpaceInfo {
+    /// The reference space type the primary reference space is based on.
+    ///
+    /// This is `LOCAL` if `LOCAL_FLOOR_EXT` was requested but had to be emulated.
+    pub ty: openxr::ReferenceSpaceType,
+    /// The pose of the primary reference space relative to the natural origin of `ty`.
+    pub offset: Transform,
+    /// If `LOCAL_FLOOR_EXT` is emulated using the `LOCAL` space.
+    pub emulated_local_floor: bool,
+}
+
+/// Send this event to recenter the [`XrPrimaryReferenceSpace`] on the current position and facing direction of the user's head.
+///
+/// The height of the space is kept, so a floor level space stays on the floor.
+/// The recentered space is used starting with the next frame, the frame currently being rendered still uses the previous space.
+/// An [`OxrRecenterPerformed`] event is sent once the space was recentered.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct OxrRecenterReferenceSpace {
+    /// Only use the yaw of the head, keeping the space level.
+    /// Otherwise the pitch and roll of the head are applied to the space too.
+    pub yaw_only: bool,
+}
+
+impl Default for OxrRecenterReferenceSpace {
+    fn default() -> Self {
+        Self { yaw_only: true }
+    }
+}
+
+/// Sent after the [`XrPrimaryReferenceSpace`] was recentered because of an [`OxrRecenterReferenceSpace`] event.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct OxrRecenterPerformed {
+    /// The pose of the new origin relative to the previous one.
+    pub pose_in_previous_space: Transform,
+}
+
+/// Sent when a reference space changed, either because the runtime is about to change it
+/// (e.g. after the user recentered or redefined the play area) or because the [`XrPrimaryReferenceSpace`] was recentered.
+///
+/// Things depending on the reference space, like the [stage bounds](OxrSession::stage_bounds), should be queried again.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct OxrReferenceSpaceChanged {
+    pub ty: openxr::ReferenceSpaceType,
+    /// The time at which the change takes effect.
+    pub change_time: openxr::Time,
+    /// The pose of the new origin relative to the previous one, if known.
+    pub pose_in_previous_space: Option<Transform>,
+}
+
+/// The emulated `LOCAL_FLOOR_EXT` space still uses the fallback floor height.
+#[derive(Resource)]
+struct OxrEstimateFloorHeight;
 
 /// The Default Reference space used for locating things
 // #[derive(Resource, Deref, ExtrctResource, Clone)]
 // pub struct OxrPrimaryReferenceSpace(pub Arc<openxr::Space>);
 
+/// A `VIEW` reference space created together with the session, following the head of the user.
+///
+/// Layers submitted relative to this space are head locked, e.g. a [`QuadLayer`](crate::layer_builder::QuadLayer) with `head_locked` set.
+/// Unlike the [`XrPrimaryReferenceSpace`] it isn't affected by recentering.
+#[derive(Resource, Clone, Copy, Debug, Deref, ExtractResource)]
+pub struct OxrViewReferenceSpace(pub XrReferenceSpace);
+
 /// The Reference space used for locating spaces on this entity
 #[derive(Component)]
 pub struct OxrReferenceSpace(pub openxr::Space);
@@ -35,10 +116,30 @@ pub struct OxrReferenceSpace(pub openxr::Space);
 impl Plugin for OxrReferenceSpacePlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(ExtractResourcePlugin::<XrPrimaryReferenceSpace>::default())
-            .insert_resource(OxrDefaultPrimaryReferenceSpaceType(
-                self.default_primary_ref_space,
-            ))
-            .add_systems(XrSessionCreated, set_primary_ref_space)
+            .add_plugins(ExtractResourcePlugin::<OxrViewReferenceSpace>::default())
+            .add_event::<OxrRecenterReferenceSpace>()
+            .add_event::<OxrRecenterPerformed>()
+            .add_event::<OxrReferenceSpaceChanged>()
+            .add_oxr_event_handler(handle_reference_space_event)
+            .insert_resource(OxrDefaultPrimaryReferenceSpaceType {
+                ty: self.default_primary_ref_space,
+                fallback_floor_height: self.fallback_floor_height,
+            })
+            .add_systems(
+                XrSessionCreated,
+                (set_primary_ref_space, create_view_ref_space),
+            )
+            .add_systems(
+                PreUpdate,
+                (
+                    estimate_floor_height.run_if(resource_exists::<OxrEstimateFloorHeight>),
+                    recenter_primary_ref_space.run_if(on_event::<OxrRecenterReferenceSpace>()),
+                )
+                    .chain()
+                    .before(OxrSpaceSyncSet)
+                    .run_if(openxr_session_running)
+                    .run_if(resource_exists::<OxrPrimaryReferenceSpaceInfo>),
+            )
             .add_systems(XrPreDestroySession, cleanup);
 
         let render_app = app.sub_app_mut(RenderApp);
@@ -47,8 +148,40 @@ impl Plugin for OxrReferenceSpacePlugin {
     }
 }
 
+fn handle_reference_space_event(
+    event: In<OxrEvent>,
+    mut writer: EventWriter<OxrReferenceSpaceChanged>,
+) {
+    // this unwrap will never panic since we are in a valid scope
+    if let Event::ReferenceSpaceChangePending(event) = unsafe { event.get() }.unwrap() {
+        writer.send(OxrReferenceSpaceChanged {
+            ty: event.reference_space_type(),
+            change_time: event.change_time(),
+            pose_in_previous_space: event
+                .pose_valid()
+                .then(|| event.pose_in_previous_space().to_transform()),
+        });
+    }
+}
+
+impl OxrSession {
+    /// Returns the width and depth of the play area in meters, or [`None`] if the runtime doesn't know them.
+    ///
+    /// The bounds can change at runtime, query them again after receiving an [`OxrReferenceSpaceChanged`] event.
+    ///
+    /// Calls [`reference_space_bounds_rect`](openxr::Session::reference_space_bounds_rect) internally.
+    pub fn stage_bounds(&self) -> openxr::Result<Option<Vec2>> {
+        Ok(self
+            .reference_space_bounds_rect(ReferenceSpaceType::STAGE)?
+            .map(|bounds| Vec2::new(bounds.width, bounds.height)))
+    }
+}
+
 fn cleanup(query: Query<Entity, With<XrReferenceSpace>>, mut cmds: Commands) {
     cmds.remove_resource::<XrPrimaryReferenceSpace>();
+    cmds.remove_resource::<OxrViewReferenceSpace>();
+    cmds.remove_resource::<OxrPrimaryReferenceSpaceInfo>();
+    cmds.remove_resource::<OxrEstimateFloorHeight>();
     for e in &query {
         cmds.entity(e).remove::<XrReferenceSpace>();
     }
@@ -57,11 +190,28 @@ fn cleanup(query: Query<Entity, With<XrReferenceSpace>>, mut cmds: Commands) {
 fn set_primary_ref_space(
     session: Res<OxrSession>,
     space_type: Res<OxrDefaultPrimaryReferenceSpaceType>,
+    exts: Res<OxrEnabledExtensions>,
     mut cmds: Commands,
 ) {
-    match session.create_reference_space(space_type.0, Transform::IDENTITY) {
+    let info = if space_type.ty == ReferenceSpaceType::LOCAL_FLOOR_EXT && !exts.ext_local_floor {
+        info!("XR_EXT_local_floor not enabled, emulating the local floor space");
+        cmds.insert_resource(OxrEstimateFloorHeight);
+        OxrPrimaryReferenceSpaceInfo {
+            ty: ReferenceSpaceType::LOCAL,
+            offset: Transform::from_xyz(0.0, -space_type.fallback_floor_height, 0.0),
+            emulated_local_floor: true,
+        }
+    } else {
+        OxrPrimaryReferenceSpaceInfo {
+            ty: space_type.ty,
+            offset: Transform::IDENTITY,
+            emulated_local_floor: false,
+        }
+    };
+    match session.create_reference_space(info.ty, info.offset) {
         Ok(space) => {
             cmds.insert_resource(XrPrimaryReferenceSpace(space));
+            cmds.insert_resource(info);
         }
         Err(openxr::sys::Result::ERROR_EXTENSION_NOT_PRESENT) => {
             error!("Required Extension for Reference Space not loaded");
@@ -69,3 +219,159 @@ fn set_primary_ref_space(
         Err(err) => error!("Error while creating reference space: {}", err.to_string()),
     };
 }
+
+fn create_view_ref_space(session: Res<OxrSession>, mut cmds: Commands) {
+    match session.create_reference_space(ReferenceSpaceType::VIEW, Transform::IDENTITY) {
+        Ok(space) => cmds.insert_resource(OxrViewReferenceSpace(space)),
+        Err(err) => error!("Error while creating view space: {err}"),
+    }
+}
+
+/// Replaces the emulated floor height with the floor of the `STAGE` space, once the `STAGE` space can be located.
+fn estimate_floor_height(
+    session: Res<OxrSession>,
+    frame_state: Res<OxrFrameState>,
+    ref_space: Res<XrPrimaryReferenceSpace>,
+    mut info: ResMut<OxrPrimaryReferenceSpaceInfo>,
+    mut destroy_space: EventWriter<XrDestroySpace>,
+    mut changed: EventWriter<OxrReferenceSpaceChanged>,
+    mut cmds: Commands,
+) {
+    let stage = match session.create_reference_space(ReferenceSpaceType::STAGE, Transform::IDENTITY)
+    {
+        Ok(stage) => stage,
+        Err(err) => {
+            info!("Unable to create stage space, using the fallback floor height: {err}");
+            cmds.remove_resource::<OxrEstimateFloorHeight>();
+            return;
+        }
+    };
+    let location = session.locate_space(&stage, &ref_space, frame_state.predicted_display_time);
+    if let Err(err) = session.destroy_space(stage.0) {
+        warn!("error while destroying space: {}", err);
+    }
+    let location = match location {
+        Ok(location) => location,
+        Err(err) => {
+            warn!("Unable to locate stage space, using the fallback floor height: {err}");
+            cmds.remove_resource::<OxrEstimateFloorHeight>();
+            return;
+        }
+    };
+    // the stage might not be tracked yet, try again next frame
+    if !location
+        .location_flags
+        .contains(SpaceLocationFlags::POSITION_VALID)
+    {
+        return;
+    }
+    cmds.remove_resource::<OxrEstimateFloorHeight>();
+
+    let floor = Transform::from_xyz(0.0, location.pose.to_transform().translation.y, 0.0);
+    let offset = info.offset.mul_transform(floor);
+    if replace_primary_ref_space(
+        &session,
+        &ref_space,
+        &mut info,
+        offset,
+        &mut destroy_space,
+        &mut cmds,
+    ) {
+        changed.send(OxrReferenceSpaceChanged {
+            ty: ReferenceSpaceType::LOCAL_FLOOR_EXT,
+            change_time: frame_state.predicted_display_time,
+            pose_in_previous_space: Some(floor),
+        });
+    }
+}
+
+fn recenter_primary_ref_space(
+    session: Res<OxrSession>,
+    frame_state: Res<OxrFrameState>,
+    ref_space: Res<XrPrimaryReferenceSpace>,
+    mut info: ResMut<OxrPrimaryReferenceSpaceInfo>,
+    mut events: EventReader<OxrRecenterReferenceSpace>,
+    mut destroy_space: EventWriter<XrDestroySpace>,
+    mut changed: EventWriter<OxrReferenceSpaceChanged>,
+    mut performed: EventWriter<OxrRecenterPerformed>,
+    mut cmds: Commands,
+) {
+    let Some(event) = events.read().last().copied() else {
+        return;
+    };
+    let view = match session.create_reference_space(ReferenceSpaceType::VIEW, Transform::IDENTITY) {
+        Ok(view) => view,
+        Err(err) => {
+            error!("Error while creating view space: {err}");
+            return;
+        }
+    };
+    let location = session.locate_space(&view, &ref_space, frame_state.predicted_display_time);
+    if let Err(err) = session.destroy_space(view.0) {
+        warn!("error while destroying space: {}", err);
+    }
+    let location = match location {
+        Ok(location) => location,
+        Err(err) => {
+            error!("Error while locating view space: {err}");
+            return;
+        }
+    };
+    if !location
+        .location_flags
+        .contains(SpaceLocationFlags::POSITION_VALID | SpaceLocationFlags::ORIENTATION_VALID)
+    {
+        warn!("Can't recenter the reference space while the head isn't tracked");
+        return;
+    }
+
+    let head = location.pose.to_transform();
+    let rotation = if event.yaw_only {
+        let (yaw, _, _) = head.rotation.to_euler(EulerRot::YXZ);
+        Quat::from_rotation_y(yaw)
+    } else {
+        head.rotation
+    };
+    let recentered =
+        Transform::from_xyz(head.translation.x, 0.0, head.translation.z).with_rotation(rotation);
+    let offset = info.offset.mul_transform(recentered);
+    if replace_primary_ref_space(
+        &session,
+        &ref_space,
+        &mut info,
+        offset,
+        &mut destroy_space,
+        &mut cmds,
+    ) {
+        changed.send(OxrReferenceSpaceChanged {
+            ty: info.ty,
+            change_time: frame_state.predicted_display_time,
+            pose_in_previous_space: Some(recentered),
+        });
+        performed.send(OxrRecenterPerformed {
+            pose_in_previous_space: recentered,
+        });
+    }
+}
+
+fn replace_primary_ref_space(
+    session: &OxrSession,
+    old: &XrPrimaryReferenceSpace,
+    info: &mut OxrPrimaryReferenceSpaceInfo,
+    offset: Transform,
+    destroy_space: &mut EventWriter<XrDestroySpace>,
+    cmds: &mut Commands,
+) -> bool {
+    match session.create_reference_space(info.ty, offset) {
+        Ok(space) => {
+            info.offset = offset;
+            cmds.insert_resource(XrPrimaryReferenceSpace(space));
+            destroy_space.send(XrDestroySpace(old.0 .0));
+            true
+        }
+        Err(err) => {
+            error!("Error while creating reference space: {}", err.to_string());
+            false
+        }
+    }
+}
@@ -2,7 +2,10 @@ use bevy::{
     ecs::query::QuerySingleError,
     prelude::*,
     render::{
-        camera::{ManualTextureView, ManualTextureViewHandle, ManualTextureViews, RenderTarget},
+        camera::{
+            CameraUpdateSystem, ManualTextureView, ManualTextureViewHandle, ManualTextureViews,
+            RenderTarget,
+        },
         extract_resource::ExtractResourcePlugin,
         pipelined_rendering::PipelinedRenderingPlugin,
         view::ExtractedView,
@@ -11,16 +14,34 @@ use bevy::{
     transform::TransformSystem,
 };
 use bevy_mod_xr::{
-    camera::{XrCamera, XrCameraBundle, XrProjection},
+    camera::{XrCamera, XrCameraBundle, XrFov, XrProjection, XrTrackingState, XrView, XrViewData},
     session::{
-        XrFirst, XrHandleEvents, XrPreDestroySession, XrRenderSet, XrRootTransform, XrTrackingRoot,
+        XrFirst, XrFixedTimestep, XrFrameStats, XrHandleEvents, XrPreDestroySession,
+        XrPreSessionEnd, XrRenderSet, XrRootTransform, XrShouldRender, XrTrackingRoot,
     },
     spaces::XrPrimaryReferenceSpace,
 };
 use openxr::ViewStateFlags;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+use std::time::Duration;
 
-use crate::{init::should_run_frame_loop, resources::*};
-use crate::{layer_builder::ProjectionLayer, session::OxrSession};
+use crate::features::secondary_view::{
+    end_secondary_frame, OxrSecondaryViewConfiguration, OxrSecondaryViewState,
+};
+use crate::frame_submission::OxrFrameSubmitter;
+use crate::helper_traits::{ToQuat, ToVec3};
+use crate::swapchain_blit::OxrSwapchainBlitSources;
+use crate::{
+    init::{should_render, should_run_frame_loop},
+    resources::*,
+};
+use crate::{
+    layer_builder::{OxrLayerStack, ProjectionLayer},
+    session::OxrSession,
+};
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Copy, SystemSet)]
 pub struct OxrRenderBegin;
@@ -40,58 +61,101 @@ impl Plugin for OxrRenderPlugin {
             // }
         }
 
+        let counters = OxrRenderFrameCounters::default();
+
         app.add_plugins((
             ExtractResourcePlugin::<OxrFrameState>::default(),
             ExtractResourcePlugin::<OxrGraphicsInfo>::default(),
             ExtractResourcePlugin::<OxrSwapchainImages>::default(),
+            ExtractResourcePlugin::<OxrPerEyeSwapchainImages>::default(),
             ExtractResourcePlugin::<OxrViews>::default(),
+            ExtractResourcePlugin::<OxrPosePredictionOffset>::default(),
+            ExtractResourcePlugin::<OxrWaitImagePolicy>::default(),
         ))
         .add_systems(XrPreDestroySession, clean_views)
+        .add_systems(
+            XrPreSessionEnd,
+            (
+                reset_should_render,
+                reset_last_display_time,
+                reset_tracking_state,
+            ),
+        )
         .add_systems(
             XrFirst,
             (
                 wait_frame.run_if(should_run_frame_loop),
+                // only headless sessions keep the frame stream in the main world
+                end_headless_frame
+                    .run_if(should_run_frame_loop)
+                    .run_if(resource_exists::<OxrFrameStream>),
                 update_cameras.run_if(should_run_frame_loop),
-                init_views.run_if(resource_added::<OxrSession>),
+                init_views
+                    .run_if(resource_added::<OxrSession>)
+                    .run_if(resource_exists::<OxrSwapchainImages>),
             )
                 .chain()
                 .in_set(XrHandleEvents::FrameLoop),
         )
         .add_systems(
             PostUpdate,
-            (locate_views, update_views)
+            update_clear_color.run_if(resource_exists::<OxrGraphicsInfo>),
+        )
+        .add_systems(PostUpdate, init_xr_cameras.before(CameraUpdateSystem))
+        .add_systems(
+            PostUpdate,
+            (locate_views, update_views, update_view_data)
                 .before(TransformSystem::TransformPropagate)
                 .chain()
                 // .run_if(should_render)
-                .run_if(should_run_frame_loop),
+                .run_if(should_run_frame_loop)
+                .run_if(resource_exists::<OxrGraphicsInfo>),
         )
-        .init_resource::<OxrViews>();
+        .init_resource::<OxrViews>()
+        .init_resource::<OxrViewStateFlags>()
+        .init_resource::<XrTrackingState>()
+        .init_resource::<OxrPosePredictionOffset>()
+        .init_resource::<OxrWaitImagePolicy>()
+        .init_resource::<XrViewData>()
+        .init_resource::<OxrLastDisplayTime>()
+        .insert_resource(counters.clone());
 
         let render_app = app.sub_app_mut(RenderApp);
 
         render_app
-            .add_systems(XrPreDestroySession, clean_views)
+            .add_systems(XrPreDestroySession, (clean_views, clean_image_index))
             .add_systems(
                 Render,
                 (
                     begin_frame,
-                    insert_texture_views,
+                    // no images are acquired while the frame isn't shown, the frame is ended without layers
+                    insert_texture_views.run_if(should_render),
+                    clean_image_index.run_if(not(should_render)),
                     locate_views,
                     update_views_render_world,
-                    wait_image,
+                    wait_image.run_if(should_render),
                 )
                     .chain()
                     .in_set(XrRenderSet::PreRender)
-                    .run_if(should_run_frame_loop),
+                    .run_if(should_run_frame_loop)
+                    .run_if(resource_exists::<OxrFrameStream>),
             )
             .add_systems(
                 Render,
-                (release_image, end_frame)
+                // frames whose images timed out release nothing, the images are waited for again next frame
+                (
+                    release_image.run_if(resource_exists::<OxrSwapchainImageIndex>),
+                    end_frame,
+                )
                     .chain()
                     .run_if(should_run_frame_loop)
+                    .run_if(resource_exists::<OxrFrameStream>)
                     .in_set(XrRenderSet::PostRender),
             )
-            .insert_resource(OxrRenderLayers(vec![Box::new(ProjectionLayer)]));
+            .insert_resource(counters)
+            .init_resource::<OxrViewStateFlags>()
+            .insert_resource(OxrRenderLayers(vec![Box::new(ProjectionLayer::default())]))
+            .init_resource::<OxrLayerStack>();
     }
 }
 
@@ -136,25 +200,39 @@ pub fn clean_views(
     }
 }
 
+/// Returns the render target of the [`XrCamera`] of the view with `view_index`.
+///
+/// The texture view behind it is replaced with the acquired swapchain image every frame.
+pub fn xr_view_target(view_index: u32) -> RenderTarget {
+    RenderTarget::TextureView(ManualTextureViewHandle(XR_TEXTURE_INDEX + view_index))
+}
+
+/// Spawns an [`XrCameraBundle`] for every view the app didn't spawn a camera for itself.
 pub fn init_views(
     graphics_info: Res<OxrGraphicsInfo>,
     mut manual_texture_views: ResMut<ManualTextureViews>,
     swapchain_images: Res<OxrSwapchainImages>,
+    per_eye_images: Option<Res<OxrPerEyeSwapchainImages>>,
     root: Query<Entity, With<XrTrackingRoot>>,
+    cameras: Query<&XrCamera>,
     mut commands: Commands,
 ) {
     let _span = info_span!("xr_init_views");
-    let temp_tex = swapchain_images.first().unwrap();
-    // this for loop is to easily add support for quad or mono views in the future.
-    for index in 0..2 {
+    for index in 0..graphics_info.view_count {
         info!("{}", graphics_info.resolution);
-        let view_handle =
-            add_texture_view(&mut manual_texture_views, temp_tex, &graphics_info, index);
+        let temp_tex = per_eye_images
+            .as_ref()
+            .and_then(|images| images.get(index.checked_sub(1)? as usize)?.first())
+            .unwrap_or_else(|| swapchain_images.first().unwrap());
+        add_texture_view(&mut manual_texture_views, temp_tex, &graphics_info, index);
+        if cameras.iter().any(|camera| camera.0 == index) {
+            continue;
+        }
 
         let cam = commands
             .spawn((XrCameraBundle {
                 camera: Camera {
-                    target: RenderTarget::TextureView(view_handle),
+                    target: xr_view_target(index),
                     ..Default::default()
                 },
                 view: XrCamera(index),
@@ -175,12 +253,130 @@ pub fn init_views(
     }
 }
 
-pub fn wait_frame(mut frame_waiter: ResMut<OxrFrameWaiter>, mut commands: Commands) {
+/// Points the [`XrCamera`]s spawned by the app at the swapchain image of their view
+/// and parents them to the [`XrTrackingRoot`] if they don't have a parent,
+/// so spawning an [`XrCameraBundle`] is enough to render into the headset.
+///
+/// The pose and projection of the cameras are updated by [`update_views`].
+/// Like the cameras spawned by [`init_views`], they are despawned when the session is destroyed.
+pub fn init_xr_cameras(
+    mut cameras: Query<(Entity, &mut Camera, &XrCamera, Has<Parent>), Added<XrCamera>>,
+    root: Query<Entity, With<XrTrackingRoot>>,
+    mut commands: Commands,
+) {
+    for (entity, mut camera, view, has_parent) in &mut cameras {
+        let target = xr_view_target(view.0);
+        if camera.target != target {
+            camera.target = target;
+        }
+        if has_parent {
+            continue;
+        }
+        match root.get_single() {
+            Ok(root) => {
+                commands.entity(root).add_child(entity);
+            }
+            Err(QuerySingleError::NoEntities(_)) => {
+                warn!("No XrTrackingRoot!");
+            }
+            Err(QuerySingleError::MultipleEntities(_)) => {
+                warn!("Multiple XrTrackingRoots! this is not allowed");
+            }
+        }
+    }
+}
+
+/// Counters updated by the render world, they are read into the [`XrFrameStats`] when waiting for the next frame.
+#[derive(Resource, Clone, Default)]
+pub(crate) struct OxrRenderFrameCounters(Arc<OxrRenderFrameCountersInner>);
+
+impl OxrRenderFrameCounters {
+    /// Counts `count` swapchain image waits that timed out with the [`OxrWaitImagePolicy`].
+    pub(crate) fn add_image_wait_timeouts(&self, count: u64) {
+        self.0
+            .image_wait_timeouts
+            .fetch_add(count, Ordering::Relaxed);
+    }
+}
+
+#[derive(Default)]
+struct OxrRenderFrameCountersInner {
+    frames_submitted: AtomicU64,
+    image_wait_timeouts: AtomicU64,
+}
+
+/// The predicted display time of the previous frame, reset when the session ends so the gap isn't counted as dropped frames.
+#[derive(Resource, Default)]
+struct OxrLastDisplayTime(Option<openxr::Time>);
+
+#[allow(clippy::too_many_arguments)]
+pub fn wait_frame(
+    mut frame_waiter: ResMut<OxrFrameWaiter>,
+    session: Res<OxrSession>,
+    secondary_view: Option<Res<OxrSecondaryViewConfiguration>>,
+    mut xr_should_render: ResMut<XrShouldRender>,
+    mut stats: ResMut<XrFrameStats>,
+    mut last_display_time: ResMut<OxrLastDisplayTime>,
+    counters: Res<OxrRenderFrameCounters>,
+    fixed_timestep: Option<ResMut<XrFixedTimestep>>,
+    mut commands: Commands,
+) {
     let _span = info_span!("xr_wait_frame");
-    let state = frame_waiter.wait().expect("Failed to wait frame");
+    let state = match secondary_view {
+        Some(secondary_view) => {
+            let (state, active) = session
+                .wait_frame_with_secondary_view(**secondary_view)
+                .expect("Failed to wait frame");
+            commands.insert_resource(OxrSecondaryViewState { active });
+            frame_waiter.waited_externally();
+            state
+        }
+        None => frame_waiter.wait().expect("Failed to wait frame"),
+    };
+    xr_should_render.set_if_neq(XrShouldRender(state.should_render));
+
+    stats.frames_submitted = counters.0.frames_submitted.load(Ordering::Relaxed);
+    stats.image_wait_timeouts = counters.0.image_wait_timeouts.load(Ordering::Relaxed);
+    if !state.should_render {
+        stats.frames_not_rendered += 1;
+    }
+    if let Some(last) = last_display_time.0.replace(state.predicted_display_time) {
+        let interval = state.predicted_display_time.as_nanos() - last.as_nanos();
+        let period = state.predicted_display_period.as_nanos();
+        if interval > 0 && period > 0 {
+            stats.record_display_interval(
+                Duration::from_nanos(interval as u64),
+                Duration::from_nanos(period as u64),
+            );
+        }
+    }
+
+    if let Some(mut fixed_timestep) = fixed_timestep {
+        let period = Duration::from_nanos(state.predicted_display_period.as_nanos().max(0) as u64);
+        if !period.is_zero() && fixed_timestep.display_period != Some(period) {
+            fixed_timestep.display_period = Some(period);
+        }
+    }
+
     commands.insert_resource(OxrFrameState(state));
 }
 
+fn reset_should_render(mut xr_should_render: ResMut<XrShouldRender>) {
+    xr_should_render.set_if_neq(XrShouldRender(false));
+}
+
+fn reset_last_display_time(mut last_display_time: ResMut<OxrLastDisplayTime>) {
+    last_display_time.0 = None;
+}
+
+fn reset_tracking_state(
+    mut view_state_flags: ResMut<OxrViewStateFlags>,
+    mut tracking_state: ResMut<XrTrackingState>,
+) {
+    view_state_flags.set_if_neq(OxrViewStateFlags::default());
+    tracking_state.set_if_neq(XrTrackingState::default());
+}
+
 pub fn update_cameras(
     frame_state: Res<OxrFrameState>,
     mut cameras: Query<&mut Camera, With<XrCamera>>,
@@ -192,29 +388,52 @@ pub fn update_cameras(
     }
 }
 
+/// Sets the clear color of new [`XrCamera`]s, and of all of them when the [`OxrClearColorOverride`] changes.
+pub fn update_clear_color(
+    graphics_info: Res<OxrGraphicsInfo>,
+    clear_color: Option<Res<OxrClearColorOverride>>,
+    mut had_override: Local<bool>,
+    mut cameras: Query<(Ref<XrCamera>, &mut Camera)>,
+) {
+    let changed = clear_color
+        .as_ref()
+        .map_or(*had_override, |clear_color| clear_color.is_changed());
+    *had_override = clear_color.is_some();
+    let config = clear_color.map_or_else(
+        || graphics_info.clear_color(),
+        |clear_color| clear_color.0.clone(),
+    );
+    for (view, mut camera) in &mut cameras {
+        if changed || view.is_added() {
+            camera.clear_color = config.clone();
+        }
+    }
+}
+
 pub fn locate_views(
     session: Res<OxrSession>,
     ref_space: Res<XrPrimaryReferenceSpace>,
     frame_state: Res<OxrFrameState>,
+    graphics_info: Res<OxrGraphicsInfo>,
     mut openxr_views: ResMut<OxrViews>,
+    mut view_state_flags: ResMut<OxrViewStateFlags>,
+    tracking_state: Option<ResMut<XrTrackingState>>,
     pipelined: Option<Res<Pipelined>>,
+    offset: Option<Res<OxrPosePredictionOffset>>,
 ) {
-    let time = if pipelined.is_some() {
-        openxr::Time::from_nanos(
-            frame_state.predicted_display_time.as_nanos()
-                + frame_state.predicted_display_period.as_nanos(),
-        )
-    } else {
-        frame_state.predicted_display_time
-    };
+    let time = frame_state.pose_time(pipelined.is_some(), offset.as_deref());
     let (flags, xr_views) = session
-        .locate_views(
-            openxr::ViewConfigurationType::PRIMARY_STEREO,
-            time,
-            &ref_space,
-        )
+        .locate_views(graphics_info.view_configuration, time, &ref_space)
         .expect("Failed to locate views");
 
+    view_state_flags.set_if_neq(OxrViewStateFlags(flags));
+    // only exists in the main world
+    if let Some(mut tracking_state) = tracking_state {
+        tracking_state.set_if_neq(XrTrackingState {
+            head_tracked: view_state_flags.rot_tracked() && view_state_flags.pos_tracked(),
+        });
+    }
+
     match (
         flags & ViewStateFlags::ORIENTATION_VALID == ViewStateFlags::ORIENTATION_VALID,
         flags & ViewStateFlags::POSITION_VALID == ViewStateFlags::POSITION_VALID,
@@ -261,6 +480,22 @@ pub fn update_views(
     }
 }
 
+pub fn update_view_data(views: Res<OxrViews>, mut view_data: ResMut<XrViewData>) {
+    view_data.0 = views
+        .iter()
+        .map(|view| XrView {
+            pose: Transform::from_translation(view.pose.position.to_vec3())
+                .with_rotation(view.pose.orientation.to_quat()),
+            fov: XrFov {
+                left: view.fov.angle_left,
+                right: view.fov.angle_right,
+                up: view.fov.angle_up,
+                down: view.fov.angle_down,
+            },
+        })
+        .collect();
+}
+
 pub fn update_views_render_world(
     views: Res<OxrViews>,
     root: Res<XrRootTransform>,
@@ -281,7 +516,7 @@ pub fn update_views_render_world(
     }
 }
 
-fn calculate_projection(near_z: f32, fov: openxr::Fovf) -> Mat4 {
+pub(crate) fn calculate_projection(near_z: f32, fov: openxr::Fovf) -> Mat4 {
     //  symmetric perspective for debugging
     // let x_fov = (self.fov.angle_left.abs() + self.fov.angle_right.abs());
     // let y_fov = (self.fov.angle_up.abs() + self.fov.angle_down.abs());
@@ -377,27 +612,106 @@ fn calculate_projection(near_z: f32, fov: openxr::Fovf) -> Mat4 {
     Mat4::from_cols_array(&cols)
 }
 
+/// The index of the swapchain image acquired for the current frame, inserted into the render world by [`insert_texture_views`].
+#[derive(Resource, Clone, Copy, Debug, Deref)]
+pub struct OxrSwapchainImageIndex(pub u32);
+
+/// The indices of the images acquired from the [`OxrPerEyeSwapchains`] for the current frame, in the same order.
+/// Inserted into the render world by [`insert_texture_views`].
+#[derive(Resource, Clone, Debug, Default, Deref)]
+pub struct OxrPerEyeSwapchainImageIndices(pub Vec<u32>);
+
 /// # Safety
 /// Images inserted into texture views here should not be written to until [`wait_image`] is ran
 pub fn insert_texture_views(
     swapchain_images: Res<OxrSwapchainImages>,
     mut swapchain: ResMut<OxrSwapchain>,
+    per_eye_images: Option<Res<OxrPerEyeSwapchainImages>>,
+    per_eye_swapchains: Option<ResMut<OxrPerEyeSwapchains>>,
     mut manual_texture_views: ResMut<ManualTextureViews>,
     graphics_info: Res<OxrGraphicsInfo>,
+    blit_sources: Option<Res<OxrSwapchainBlitSources>>,
+    mut commands: Commands,
 ) {
     let _span = info_span!("xr_insert_texture_views");
-    let index = swapchain.acquire_image().expect("Failed to acquire image");
-    let image = &swapchain_images[index as usize];
+    let index = swapchain
+        .acquire_next_image()
+        .expect("Failed to acquire image");
+    commands.insert_resource(OxrSwapchainImageIndex(index));
+    let per_eye_indices = per_eye_swapchains.map_or_else(Vec::new, |mut swapchains| {
+        swapchains
+            .iter_mut()
+            .map(|swapchain| {
+                swapchain
+                    .acquire_next_image()
+                    .expect("Failed to acquire image")
+            })
+            .collect()
+    });
+    commands.insert_resource(OxrPerEyeSwapchainImageIndices(per_eye_indices.clone()));
+    // the cameras render into the blit sources, which are copied into the image later
+    if blit_sources.is_some() {
+        return;
+    }
+    let image = swapchain_images.image(index);
 
-    for i in 0..2 {
+    for i in 0..graphics_info.view_count {
+        let image = per_eye_images
+            .as_ref()
+            .and_then(|images| images.view_image(i, &per_eye_indices))
+            .unwrap_or(image);
         add_texture_view(&mut manual_texture_views, image, &graphics_info, i);
     }
 }
 
-pub fn wait_image(mut swapchain: ResMut<OxrSwapchain>) {
-    swapchain
-        .wait_image(openxr::Duration::INFINITE)
-        .expect("Failed to wait image");
+fn clean_image_index(mut commands: Commands) {
+    commands.remove_resource::<OxrSwapchainImageIndex>();
+    commands.remove_resource::<OxrPerEyeSwapchainImageIndices>();
+}
+
+/// Waits for the images acquired by [`insert_texture_views`] with the [`OxrWaitImagePolicy`].
+///
+/// If any wait times out the frame is skipped: the texture views and image indices are removed so nothing is rendered
+/// or submitted, the images that were waited for are released and the others are waited for again next frame.
+pub fn wait_image(
+    mut swapchain: ResMut<OxrSwapchain>,
+    mut per_eye_swapchains: Option<ResMut<OxrPerEyeSwapchains>>,
+    policy: Res<OxrWaitImagePolicy>,
+    counters: Res<OxrRenderFrameCounters>,
+    graphics_info: Res<OxrGraphicsInfo>,
+    mut manual_texture_views: ResMut<ManualTextureViews>,
+    mut commands: Commands,
+) {
+    let mut swapchains: Vec<&mut OxrSwapchain> = std::iter::once(&mut *swapchain)
+        .chain(
+            per_eye_swapchains
+                .iter_mut()
+                .flat_map(|swapchains| swapchains.iter_mut()),
+        )
+        .collect();
+    let ready: Vec<bool> = swapchains
+        .iter_mut()
+        .map(|swapchain| {
+            swapchain
+                .wait_acquired_image(**policy)
+                .expect("Failed to wait image")
+        })
+        .collect();
+    let timeouts = ready.iter().filter(|ready| !**ready).count();
+    if timeouts == 0 {
+        return;
+    }
+    counters.add_image_wait_timeouts(timeouts as u64);
+    for (swapchain, ready) in swapchains.iter_mut().zip(ready) {
+        if ready {
+            swapchain.release_image().expect("Failed to release image");
+        }
+    }
+    commands.remove_resource::<OxrSwapchainImageIndex>();
+    commands.remove_resource::<OxrPerEyeSwapchainImageIndices>();
+    for i in 0..graphics_info.view_count {
+        manual_texture_views.remove(&ManualTextureViewHandle(XR_TEXTURE_INDEX + i));
+    }
 }
 
 pub fn add_texture_view(
@@ -409,25 +723,52 @@ pub fn add_texture_view(
     let view = texture.create_view(&wgpu::TextureViewDescriptor {
         dimension: Some(wgpu::TextureViewDimension::D2),
         array_layer_count: Some(1),
-        base_array_layer: index,
+        base_array_layer: info.swapchain_layout.array_layer(index),
+        format: Some(info.view_format),
         ..default()
     });
     let view = ManualTextureView {
         texture_view: view.into(),
-        size: info.resolution,
-        format: info.format,
+        size: info.view_resolution(index),
+        format: info.view_format,
     };
     let handle = ManualTextureViewHandle(XR_TEXTURE_INDEX + index);
     manual_texture_views.insert(handle, view);
     handle
 }
 
-pub fn begin_frame(mut frame_stream: ResMut<OxrFrameStream>) {
+pub fn begin_frame(
+    mut frame_stream: ResMut<OxrFrameStream>,
+    submitter: Option<Res<OxrFrameSubmitter>>,
+) {
     let _span = info_span!("xr_begin_frame");
+    if let Some(submitter) = submitter {
+        submitter.wait();
+    }
     frame_stream.begin().expect("Failed to begin frame");
 }
 
-pub fn release_image(mut swapchain: ResMut<OxrSwapchain>) {
+/// Ends the frames of headless sessions right after waiting for them, since they have nothing to render.
+pub fn end_headless_frame(
+    mut frame_stream: ResMut<OxrFrameStream>,
+    frame_state: Res<OxrFrameState>,
+) {
+    let _span = info_span!("xr_end_headless_frame");
+    if let Err(e) = frame_stream.begin().and_then(|()| {
+        frame_stream.end(
+            frame_state.predicted_display_time,
+            openxr::EnvironmentBlendMode::OPAQUE,
+            &[],
+        )
+    }) {
+        error!("Failed to end headless frame: {e}");
+    }
+}
+
+pub fn release_image(
+    mut swapchain: ResMut<OxrSwapchain>,
+    per_eye_swapchains: Option<ResMut<OxrPerEyeSwapchains>>,
+) {
     let _span = info_span!("xr_release_image");
     #[cfg(target_os = "android")]
     {
@@ -436,6 +777,11 @@ pub fn release_image(mut swapchain: ResMut<OxrSwapchain>) {
         let env = vm.attach_current_thread_as_daemon();
     }
     swapchain.release_image().unwrap();
+    if let Some(mut swapchains) = per_eye_swapchains {
+        for swapchain in swapchains.iter_mut() {
+            swapchain.release_image().unwrap();
+        }
+    }
 }
 
 pub fn end_frame(world: &mut World) {
@@ -447,22 +793,55 @@ pub fn end_frame(world: &mut World) {
         let env = vm.attach_current_thread_as_daemon();
     }
     world.resource_scope::<OxrFrameStream, ()>(|world, mut frame_stream| {
-        let mut layers = vec![];
         let frame_state = world.resource::<OxrFrameState>();
-        if frame_state.should_render {
-            for layer in world.resource::<OxrRenderLayers>().iter() {
-                if let Some(layer) = layer.get(world) {
-                    layers.push(layer);
-                }
-            }
-        }
+        // nothing was rendered if waiting for the swapchain images timed out
+        let layers =
+            if frame_state.should_render && world.contains_resource::<OxrSwapchainImageIndex>() {
+                world.resource::<OxrLayerStack>().get(
+                    world,
+                    world.resource::<OxrRenderLayers>(),
+                    world.resource::<OxrGraphicsInfo>().max_layer_count,
+                )
+            } else {
+                vec![]
+            };
         let layers: Vec<_> = layers.iter().map(Box::as_ref).collect();
-        if let Err(e) = frame_stream.end(
-            frame_state.predicted_display_time,
-            world.resource::<OxrGraphicsInfo>().blend_mode,
-            &layers,
-        ) {
+        let display_time = frame_state.predicted_display_time;
+        let blend_mode = world.resource::<OxrGraphicsInfo>().blend_mode;
+        if world
+            .get_resource::<OxrSecondaryViewState>()
+            .is_some_and(|state| state.active)
+        {
+            if let Err(e) = frame_stream.check_end() {
+                error!("Failed to end frame stream: {e}");
+                return;
+            }
+            frame_stream.ended_externally();
+            if let Err(e) = end_secondary_frame(world, display_time, blend_mode, &layers) {
+                error!("Failed to end frame stream: {e}");
+                return;
+            }
+        } else if frame_stream.check_end().is_ok()
+            && world
+                .get_resource::<OxrFrameSubmitter>()
+                .is_some_and(|submitter| {
+                    submitter.submit(
+                        world.resource::<OxrSession>(),
+                        display_time,
+                        blend_mode,
+                        &layers,
+                    )
+                })
+        {
+            frame_stream.ended_externally();
+        } else if let Err(e) = frame_stream.end(display_time, blend_mode, &layers) {
             error!("Failed to end frame stream: {e}");
+            return;
         }
+        world
+            .resource::<OxrRenderFrameCounters>()
+            .0
+            .frames_submitted
+            .fetch_add(1, Ordering::Relaxed);
     });
 }
@@ -1,8 +1,16 @@
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+
 use bevy::prelude::*;
 use bevy::render::extract_resource::ExtractResource;
+use bevy::utils::HashMap;
+
+use bevy_mod_xr::camera::XrFov;
 
 use crate::error::OxrError;
 use crate::graphics::*;
+use crate::helper_traits::ToPosef as _;
 use crate::layer_builder::{CompositionLayer, LayerProvider};
 use crate::session::{OxrSession, OxrSessionCreateNextChain};
 use crate::types::*;
@@ -19,6 +27,13 @@ impl OxrEntry {
         Ok(self.0.enumerate_extensions().map(Into::into)?)
     }
 
+    /// Enumerate the API layers available to instances of this OpenXR runtime.
+    ///
+    /// Calls [`enumerate_layers`](openxr::Entry::enumerate_layers) internally.
+    pub fn enumerate_api_layers(&self) -> Result<Vec<openxr::ApiLayerProperties>> {
+        Ok(self.0.enumerate_layers()?)
+    }
+
     /// Creates an [`OxrInstance`].
     ///
     /// Calls [`create_instance`](openxr::Entry::create_instance) internally.
@@ -36,13 +51,21 @@ impl OxrEntry {
         }
 
         let required_exts = exts | backend.required_exts();
+        // the openxr crate chains `XrInstanceCreateInfoAndroidKHR` with the JNI VM and activity from `ndk_context`
+        // if this extension is enabled, the runtime needs them to attach to the activity
+        #[cfg(target_os = "android")]
+        let required_exts = {
+            let mut required_exts = required_exts;
+            required_exts.khr_android_create_instance |= available_exts.khr_android_create_instance;
+            required_exts
+        };
 
         let instance = self.0.create_instance(
             &openxr::ApplicationInfo {
                 application_name: &app_info.name,
                 application_version: app_info.version.to_u32(),
-                engine_name: "Bevy",
-                engine_version: Version::BEVY.to_u32(),
+                engine_name: app_info.engine_name(),
+                engine_version: app_info.engine_version().to_u32(),
             },
             &required_exts.into(),
             layers,
@@ -59,6 +82,10 @@ impl OxrEntry {
     }
 }
 
+/// The API layers enabled on the [`OxrInstance`], inserted by the [`OxrInitPlugin`](crate::init::OxrInitPlugin).
+#[derive(Resource, Clone, Debug, Default, PartialEq, Eq, Deref)]
+pub struct OxrEnabledApiLayers(pub Vec<String>);
+
 /// Wrapper around [`openxr::Instance`] with additional data for safety and some methods overriden to use bevy types.
 ///
 /// See [`openxr::Instance`] for other available methods.
@@ -100,6 +127,72 @@ impl OxrInstance {
         &self.2
     }
 
+    /// Returns the properties of `system_id`, including the capabilities of the enabled hand tracking,
+    /// eye gaze interaction and passthrough extensions.
+    ///
+    /// Overrides [`openxr::Instance::system_properties`].
+    pub fn system_properties(
+        &self,
+        system_id: openxr::SystemId,
+    ) -> openxr::Result<OxrSystemProperties> {
+        use openxr::sys;
+
+        let exts = self.exts();
+        let mut next: *mut sys::BaseOutStructure = std::ptr::null_mut();
+        let mut hand_tracking = sys::SystemHandTrackingPropertiesEXT {
+            ty: sys::SystemHandTrackingPropertiesEXT::TYPE,
+            next: std::ptr::null_mut(),
+            supports_hand_tracking: sys::FALSE,
+        };
+        if exts.ext_hand_tracking.is_some() {
+            hand_tracking.next = next as _;
+            next = &mut hand_tracking as *mut _ as _;
+        }
+        let mut eye_gaze = sys::SystemEyeGazeInteractionPropertiesEXT {
+            ty: sys::SystemEyeGazeInteractionPropertiesEXT::TYPE,
+            next: std::ptr::null_mut(),
+            supports_eye_gaze_interaction: sys::FALSE,
+        };
+        if exts.ext_eye_gaze_interaction.is_some() {
+            eye_gaze.next = next as _;
+            next = &mut eye_gaze as *mut _ as _;
+        }
+        let mut passthrough = sys::SystemPassthroughProperties2FB {
+            ty: sys::SystemPassthroughProperties2FB::TYPE,
+            next: std::ptr::null(),
+            capabilities: sys::PassthroughCapabilityFlagsFB::EMPTY,
+        };
+        if exts.fb_passthrough.is_some() {
+            passthrough.next = next as _;
+            next = &mut passthrough as *mut _ as _;
+        }
+        let props = unsafe {
+            let mut props = sys::SystemProperties::out(next);
+            let result =
+                (self.fp().get_system_properties)(self.as_raw(), system_id, props.as_mut_ptr());
+            if result.into_raw() < 0 {
+                return Err(result);
+            }
+            props.assume_init()
+        };
+        let system_name = unsafe { std::ffi::CStr::from_ptr(props.system_name.as_ptr()) }
+            .to_string_lossy()
+            .into_owned();
+        Ok(OxrSystemProperties {
+            system_id: props.system_id,
+            vendor_id: props.vendor_id,
+            system_name,
+            max_swapchain_image_width: props.graphics_properties.max_swapchain_image_width,
+            max_swapchain_image_height: props.graphics_properties.max_swapchain_image_height,
+            max_layer_count: props.graphics_properties.max_layer_count,
+            orientation_tracking: props.tracking_properties.orientation_tracking.into(),
+            position_tracking: props.tracking_properties.position_tracking.into(),
+            supports_hand_tracking: hand_tracking.supports_hand_tracking.into(),
+            supports_eye_gaze_interaction: eye_gaze.supports_eye_gaze_interaction.into(),
+            passthrough_capabilities: passthrough.capabilities,
+        })
+    }
+
     /// Initialize graphics. This is used to create [WgpuGraphics] for the bevy app and to get the [SessionCreateInfo] needed to make an XR session.
     pub fn init_graphics(
         &self,
@@ -139,7 +232,9 @@ impl OxrInstance {
             info.0;
             info => {
                 let (session, frame_waiter, frame_stream) = Api::create_session(self,system_id, &info,chain)?;
-                Ok((session.into(), OxrFrameWaiter(frame_waiter), OxrFrameStream(Api::wrap(frame_stream))))
+                let frame_waiter = OxrFrameWaiter::from_inner(frame_waiter);
+                let frame_stream = OxrFrameStream::from_inner(frame_stream, &frame_waiter);
+                Ok((session.into(), frame_waiter, frame_stream))
             }
         )
     }
@@ -147,27 +242,51 @@ impl OxrInstance {
 
 /// Graphics agnostic wrapper around [openxr::FrameStream]
 #[derive(Resource)]
-pub struct OxrFrameStream(pub GraphicsWrap<Self>);
+pub struct OxrFrameStream(pub GraphicsWrap<Self>, pub(crate) OxrFrameLoopGuard);
 
 impl GraphicsType for OxrFrameStream {
     type Inner<G: GraphicsExt> = openxr::FrameStream<G>;
 }
 
 impl OxrFrameStream {
-    /// Creates a new [`OxrFrameStream`] from an [`openxr::FrameStream`].
+    /// Creates a new [`OxrFrameStream`] from an [`openxr::FrameStream`] and the [`OxrFrameWaiter`] of the same session.
     /// In the majority of cases, you should use [`create_session`](OxrInstance::create_session) instead.
-    pub fn from_inner<G: GraphicsExt>(frame_stream: openxr::FrameStream<G>) -> Self {
-        Self(G::wrap(frame_stream))
+    pub fn from_inner<G: GraphicsExt>(
+        frame_stream: openxr::FrameStream<G>,
+        frame_waiter: &OxrFrameWaiter,
+    ) -> Self {
+        Self(G::wrap(frame_stream), frame_waiter.1.clone())
+    }
+
+    /// Returns the state of the frame loop.
+    pub fn frame_loop_state(&self) -> OxrFrameLoopState {
+        self.1.state()
     }
 
     /// Indicate that graphics device work is beginning.
     ///
     /// Calls [`begin`](openxr::FrameStream::begin) internally.
-    pub fn begin(&mut self) -> openxr::Result<()> {
+    /// Fails with [`OxrError::FrameLoopOutOfOrder`] if no frame was waited for or the previous frame wasn't ended.
+    pub fn begin(&mut self) -> Result<()> {
+        self.1.check(OxrFrameLoopState::WaitingToBegin, "begin")?;
+        #[cfg(feature = "trace")]
+        let _span = info_span!("xr::begin_frame").entered();
         graphics_match!(
             &mut self.0;
             stream => stream.begin()
-        )
+        )?;
+        self.1.began();
+        Ok(())
+    }
+
+    /// Records that the frame was ended without [`end`](Self::end), e.g. by submitting it on another thread.
+    pub(crate) fn ended_externally(&mut self) {
+        self.1.ended();
+    }
+
+    /// Fails with [`OxrError::FrameLoopOutOfOrder`] if no frame was begun.
+    pub(crate) fn check_end(&self) -> Result<()> {
+        self.1.check(OxrFrameLoopState::WaitingToEnd, "end")
     }
 
     /// Indicate that all graphics work for the frame has been submitted
@@ -180,6 +299,16 @@ impl OxrFrameStream {
         environment_blend_mode: openxr::EnvironmentBlendMode,
         layers: &[&dyn CompositionLayer],
     ) -> Result<()> {
+        self.check_end()?;
+        #[cfg(feature = "trace")]
+        let _span = info_span!(
+            "xr::end_frame",
+            display_time = display_time.as_nanos(),
+            layer_count = layers.len()
+        )
+        .entered();
+        // the frame is over even if the runtime rejects it, the next one can be begun either way
+        self.1.ended();
         graphics_match!(
             &mut self.0;
             stream => {
@@ -209,11 +338,133 @@ impl OxrFrameStream {
 ///
 /// See [`FrameWaiter`](openxr::FrameWaiter) for available methods.
 #[derive(Resource, Deref, DerefMut)]
-pub struct OxrFrameWaiter(pub openxr::FrameWaiter);
+pub struct OxrFrameWaiter(
+    #[deref] pub openxr::FrameWaiter,
+    pub(crate) OxrFrameLoopGuard,
+);
+
+impl OxrFrameWaiter {
+    /// Creates a new [`OxrFrameWaiter`] from an [`openxr::FrameWaiter`].
+    /// In the majority of cases, you should use [`create_session`](OxrInstance::create_session) instead.
+    pub fn from_inner(frame_waiter: openxr::FrameWaiter) -> Self {
+        Self(frame_waiter, default())
+    }
+
+    /// Blocks until rendering should begin.
+    ///
+    /// Calls [`wait`](openxr::FrameWaiter::wait) internally.
+    pub fn wait(&mut self) -> openxr::Result<openxr::FrameState> {
+        #[cfg(feature = "trace")]
+        let span = info_span!(
+            "xr::wait_frame",
+            predicted_display_time = bevy::utils::tracing::field::Empty
+        )
+        .entered();
+        let state = self.0.wait()?;
+        #[cfg(feature = "trace")]
+        span.record(
+            "predicted_display_time",
+            state.predicted_display_time.as_nanos(),
+        );
+        self.1.waited();
+        Ok(state)
+    }
+
+    /// Records that a frame was waited for without [`wait`](Self::wait), e.g. with a secondary view state in the chain.
+    pub(crate) fn waited_externally(&mut self) {
+        self.1.waited();
+    }
+}
+
+/// Tracks the frame loop calls shared by an [`OxrFrameWaiter`] and its [`OxrFrameStream`],
+/// which usually live in different worlds.
+///
+/// A frame may be waited for while the previous one is still being rendered,
+/// so waits are counted instead of being part of the state.
+#[derive(Clone, Default)]
+pub(crate) struct OxrFrameLoopGuard(Arc<OxrFrameLoopCounters>);
+
+#[derive(Default)]
+struct OxrFrameLoopCounters {
+    /// Frames waited for but not begun yet.
+    waited: AtomicU32,
+    begun: AtomicBool,
+}
+
+impl OxrFrameLoopGuard {
+    fn state(&self) -> OxrFrameLoopState {
+        if self.0.begun.load(Ordering::Acquire) {
+            OxrFrameLoopState::WaitingToEnd
+        } else if self.0.waited.load(Ordering::Acquire) != 0 {
+            OxrFrameLoopState::WaitingToBegin
+        } else {
+            OxrFrameLoopState::WaitingToWait
+        }
+    }
+
+    fn check(&self, expected: OxrFrameLoopState, call: &'static str) -> Result<()> {
+        let state = self.state();
+        if state == expected {
+            Ok(())
+        } else {
+            Err(OxrError::FrameLoopOutOfOrder { call, state })
+        }
+    }
+
+    fn waited(&self) {
+        self.0.waited.fetch_add(1, Ordering::AcqRel);
+    }
+
+    fn began(&self) {
+        self.0.waited.fetch_sub(1, Ordering::AcqRel);
+        self.0.begun.store(true, Ordering::Release);
+    }
+
+    fn ended(&self) {
+        self.0.begun.store(false, Ordering::Release);
+    }
+}
 
 /// Graphics agnostic wrapper around [openxr::Swapchain]
 #[derive(Resource)]
-pub struct OxrSwapchain(pub GraphicsWrap<Self>);
+pub struct OxrSwapchain(
+    pub GraphicsWrap<Self>,
+    /// The images returned by the last call to [`enumerate_images`](OxrSwapchain::enumerate_images).
+    pub(crate) Mutex<Option<OxrSwapchainImageCache>>,
+    /// The usage of the textures created by [`enumerate_images`](OxrSwapchain::enumerate_images).
+    pub(crate) wgpu::TextureUsages,
+    /// The image acquired by [`acquire_next_image`](OxrSwapchain::acquire_next_image) that wasn't waited for yet.
+    pub(crate) Option<u32>,
+);
+
+/// An image acquired with [`OxrSwapchain::acquire`], released when dropped.
+pub struct OxrAcquiredImage<'a> {
+    swapchain: &'a mut OxrSwapchain,
+    index: u32,
+}
+
+impl OxrAcquiredImage<'_> {
+    /// The index of the image in the swapchain images, see [`OxrSwapchain::enumerate_images`].
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+}
+
+impl Drop for OxrAcquiredImage<'_> {
+    fn drop(&mut self) {
+        if let Err(err) = self.swapchain.release_image() {
+            warn!("error while releasing swapchain image: {err}");
+        }
+    }
+}
+
+pub(crate) struct OxrSwapchainImageCache {
+    format: wgpu::TextureFormat,
+    view_format: wgpu::TextureFormat,
+    layout: SwapchainImageLayout,
+    sample_count: u32,
+    images: OxrSwapchainImages,
+}
 
 impl GraphicsType for OxrSwapchain {
     type Inner<G: GraphicsExt> = openxr::Swapchain<G>;
@@ -222,41 +473,141 @@ impl GraphicsType for OxrSwapchain {
 impl OxrSwapchain {
     /// Creates a new [`OxrSwapchain`] from an [`openxr::Swapchain`].
     /// In the majority of cases, you should use [`create_swapchain`](OxrSession::create_swapchain) instead.
+    ///
+    /// The images are created with the [`RENDER_ATTACHMENT`](wgpu::TextureUsages::RENDER_ATTACHMENT) usage,
+    /// use [`with_usage`](OxrSwapchain::with_usage) if the swapchain was created with other usage flags.
     pub fn from_inner<G: GraphicsExt>(swapchain: openxr::Swapchain<G>) -> Self {
-        Self(G::wrap(swapchain))
+        Self(
+            G::wrap(swapchain),
+            Mutex::default(),
+            wgpu::TextureUsages::RENDER_ATTACHMENT,
+            None,
+        )
+    }
+
+    /// Sets the usage of the textures created by [`enumerate_images`](OxrSwapchain::enumerate_images).
+    ///
+    /// The swapchain has to be created with the [`required_usage_flags`](SwapchainCreateInfo::required_usage_flags) of `usage`.
+    pub fn with_usage(mut self, usage: wgpu::TextureUsages) -> Self {
+        self.2 = usage;
+        self.invalidate_images();
+        self
+    }
+
+    /// The usage of the textures created by [`enumerate_images`](OxrSwapchain::enumerate_images).
+    pub fn usage(&self) -> wgpu::TextureUsages {
+        self.2
+    }
+
+    /// Acquires the next image and waits until it can be rendered to, the image is released when the returned guard is dropped.
+    ///
+    /// Fails if either [`acquire_image`](OxrSwapchain::acquire_image) or [`wait_image`](OxrSwapchain::wait_image) fails,
+    /// or with [`SwapchainWaitTimedOut`](OxrError::SwapchainWaitTimedOut) if waiting times out.
+    /// If waiting fails the image stays acquired and has to be waited for and released manually.
+    pub fn acquire(&mut self, policy: WaitImagePolicy) -> Result<OxrAcquiredImage<'_>> {
+        let index = self.acquire_image()?;
+        if !self.wait_image(policy)? {
+            return Err(OxrError::SwapchainWaitTimedOut);
+        }
+        Ok(OxrAcquiredImage {
+            swapchain: self,
+            index,
+        })
     }
 
     /// Determine the index of the next image to render to in the swapchain image array.
     ///
     /// Calls [`acquire_image`](openxr::Swapchain::acquire_image) internally.
     pub fn acquire_image(&mut self) -> Result<u32> {
-        graphics_match!(
-            &mut self.0;
-            swap => Ok(swap.acquire_image()?)
+        #[cfg(feature = "trace")]
+        let span = info_span!(
+            "xr::acquire_image",
+            index = bevy::utils::tracing::field::Empty
         )
+        .entered();
+        let index = graphics_match!(
+            &mut self.0;
+            swap => swap.acquire_image()?
+        );
+        #[cfg(feature = "trace")]
+        span.record("index", index);
+        Ok(index)
+    }
+
+    /// Acquires the next image like [`acquire_image`](OxrSwapchain::acquire_image), unless waiting for the image
+    /// returned by the last call timed out, in which case that image is returned again.
+    ///
+    /// Together with [`wait_acquired_image`](OxrSwapchain::wait_acquired_image) this lets frames be skipped
+    /// while the compositor still reads from the image, without acquiring more images than the swapchain has.
+    pub fn acquire_next_image(&mut self) -> Result<u32> {
+        if let Some(index) = self.3 {
+            return Ok(index);
+        }
+        let index = self.acquire_image()?;
+        self.3 = Some(index);
+        Ok(index)
+    }
+
+    /// Waits for the image returned by [`acquire_next_image`](OxrSwapchain::acquire_next_image), see [`wait_image`](OxrSwapchain::wait_image).
+    ///
+    /// Returns `true` right away if the image was already waited for.
+    /// Returns `false` if the timeout of `policy` expired, the image then stays acquired and has to be waited for again
+    /// before it can be rendered to and released, the next call to `acquire_next_image` returns it again.
+    pub fn wait_acquired_image(&mut self, policy: WaitImagePolicy) -> Result<bool> {
+        if self.3.is_none() {
+            return Ok(true);
+        }
+        let ready = self.wait_image(policy)?;
+        if ready {
+            self.3 = None;
+        }
+        Ok(ready)
     }
 
     /// Wait for the compositor to finish reading from the oldest unwaited acquired image.
     ///
-    /// Calls [`wait_image`](openxr::Swapchain::wait_image) internally.
-    pub fn wait_image(&mut self, timeout: openxr::Duration) -> Result<()> {
-        graphics_match!(
+    /// Returns `false` if the timeout of `policy` expired before the image became available,
+    /// in which case the image has to be waited for again before it can be rendered to and released.
+    /// With [`NonBlocking`](WaitImagePolicy::NonBlocking) the caller can skip the frame instead of stalling the render thread.
+    ///
+    /// Calls `xrWaitSwapchainImage` directly, since [`wait_image`](openxr::Swapchain::wait_image) doesn't report timeouts.
+    pub fn wait_image(&mut self, policy: WaitImagePolicy) -> Result<bool> {
+        #[cfg(feature = "trace")]
+        let _span = info_span!("xr::wait_image").entered();
+        let info = openxr::sys::SwapchainImageWaitInfo {
+            ty: openxr::sys::SwapchainImageWaitInfo::TYPE,
+            next: std::ptr::null(),
+            timeout: policy.timeout(),
+        };
+        let result = graphics_match!(
             &mut self.0;
-            swap => Ok(swap.wait_image(timeout)?)
-        )
+            swap => unsafe { (swap.instance().fp().wait_swapchain_image)(swap.as_raw(), &info) }
+        );
+        match result {
+            openxr::sys::Result::TIMEOUT_EXPIRED => Ok(false),
+            result if result.into_raw() >= 0 => Ok(true),
+            err => Err(err.into()),
+        }
     }
 
     /// Release the oldest acquired image.
     ///
     /// Calls [`release_image`](openxr::Swapchain::release_image) internally.
     pub fn release_image(&mut self) -> Result<()> {
+        #[cfg(feature = "trace")]
+        let _span = info_span!("xr::release_image").entered();
         graphics_match!(
             &mut self.0;
             swap => Ok(swap.release_image()?)
         )
     }
 
-    /// Enumerates swapchain images and converts them to wgpu [`Texture`](wgpu::Texture)s.
+    /// Enumerates swapchain images and converts them to 2D wgpu [`Texture`](wgpu::Texture)s.
+    ///
+    /// `array_size` has to match the array size the swapchain was created with,
+    /// use [`enumerate_images_with_layout`](OxrSwapchain::enumerate_images_with_layout) for cube map swapchains.
+    /// The images are cached, calling this again with the same arguments returns the same images
+    /// until [`invalidate_images`](OxrSwapchain::invalidate_images) is called.
     ///
     /// Calls [`enumerate_images`](openxr::Swapchain::enumerate_images) internally.
     pub fn enumerate_images(
@@ -264,26 +615,277 @@ impl OxrSwapchain {
         device: &wgpu::Device,
         format: wgpu::TextureFormat,
         resolution: UVec2,
+        array_size: u32,
     ) -> Result<OxrSwapchainImages> {
-        graphics_match!(
+        self.enumerate_images_with_view_format(device, format, format, resolution, array_size, 1)
+    }
+
+    /// Like [`enumerate_images`](OxrSwapchain::enumerate_images), but allows creating views of the images with `view_format`.
+    ///
+    /// If `view_format` differs from `format`, it may only differ in being the sRGB variant of it (or the other way around)
+    /// and the swapchain has to be created with [`MUTABLE_FORMAT`](openxr::SwapchainUsageFlags::MUTABLE_FORMAT).
+    /// The textures still have `format` as their format, views have to be created with `view_format` explicitly.
+    /// `sample_count` has to match the sample count the swapchain was created with.
+    ///
+    /// Fails with [`UnsupportedTextureUsage`](OxrError::UnsupportedTextureUsage) if `format` doesn't support the [`usage`](OxrSwapchain::usage) on `device`.
+    pub fn enumerate_images_with_view_format(
+        &self,
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        view_format: wgpu::TextureFormat,
+        resolution: UVec2,
+        array_size: u32,
+        sample_count: u32,
+    ) -> Result<OxrSwapchainImages> {
+        self.enumerate_images_with_layout(
+            device,
+            format,
+            view_format,
+            SwapchainImageLayout::array(resolution, array_size),
+            sample_count,
+        )
+    }
+
+    /// Like [`enumerate_images_with_view_format`](OxrSwapchain::enumerate_images_with_view_format),
+    /// but takes the full `layout` of the swapchain, e.g. [`SwapchainImageLayout::cube`] for cube map swapchains.
+    ///
+    /// Views of the whole images can be created with [`view_descriptor`](SwapchainImageLayout::view_descriptor).
+    pub fn enumerate_images_with_layout(
+        &self,
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        view_format: wgpu::TextureFormat,
+        layout: SwapchainImageLayout,
+        sample_count: u32,
+    ) -> Result<OxrSwapchainImages> {
+        if view_format.remove_srgb_suffix() != format.remove_srgb_suffix() {
+            return Err(OxrError::UnsupportedTextureFormat(view_format));
+        }
+        validate_texture_usage(self.2, format, device.features())?;
+        let mut cache = self.1.lock().unwrap();
+        if let Some(cache) = cache.as_ref().filter(|cache| {
+            cache.format == format
+                && cache.view_format == view_format
+                && cache.layout == layout
+                && cache.sample_count == sample_count
+        }) {
+            return Ok(cache.images);
+        }
+        let view_formats: &[wgpu::TextureFormat] = if view_format != format {
+            &[view_format]
+        } else {
+            &[]
+        };
+        let images = graphics_match!(
             &self.0;
             swap => {
                 let mut images = vec![];
                 for image in swap.enumerate_images()? {
                     unsafe {
-                        images.push(Api::to_wgpu_img(image, device, format, resolution)?);
+                        images.push(Api::to_wgpu_img(image, device, format, view_formats, self.2, layout, sample_count)?);
                     }
                 }
-                Ok(OxrSwapchainImages(images.leak()))
+                OxrSwapchainImages(images.leak())
             }
-        )
+        );
+        *cache = Some(OxrSwapchainImageCache {
+            format,
+            view_format,
+            layout,
+            sample_count,
+            images,
+        });
+        Ok(images)
+    }
+
+    /// Clears the images cached by [`enumerate_images`](OxrSwapchain::enumerate_images),
+    /// so the next call enumerates them again.
+    pub fn invalidate_images(&self) {
+        *self.1.lock().unwrap() = None;
     }
 }
 
+/// A set of named swapchains that are acquired, waited on and released together, e.g. one swapchain per composition layer.
+///
+/// The swapchains go through acquire, wait and release in this order each frame,
+/// calling the methods out of order skips the swapchains that aren't in the expected state.
+/// Errors are wrapped in [`OxrError::SwapchainSetError`], naming the swapchain that failed.
+#[derive(Resource, Default)]
+pub struct OxrSwapchainSet {
+    swapchains: Vec<(Cow<'static, str>, OxrSwapchain, OxrSwapchainSetState)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum OxrSwapchainSetState {
+    Released,
+    Acquired,
+    Waited,
+}
+
+impl OxrSwapchainSet {
+    /// Adds `swapchain` to the set, replacing the swapchain previously added as `name`.
+    pub fn insert(&mut self, name: impl Into<Cow<'static, str>>, swapchain: OxrSwapchain) {
+        let name = name.into();
+        self.remove(&name);
+        self.swapchains
+            .push((name, swapchain, OxrSwapchainSetState::Released));
+    }
+
+    /// Removes the swapchain added as `name` from the set.
+    pub fn remove(&mut self, name: &str) -> Option<OxrSwapchain> {
+        let index = self.swapchains.iter().position(|(n, ..)| n == name)?;
+        Some(self.swapchains.remove(index).1)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&OxrSwapchain> {
+        self.swapchains
+            .iter()
+            .find(|(n, ..)| n == name)
+            .map(|(_, swapchain, _)| swapchain)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &OxrSwapchain)> {
+        self.swapchains
+            .iter()
+            .map(|(name, swapchain, _)| (name.as_ref(), swapchain))
+    }
+
+    /// Acquires the next image of every released swapchain and returns the acquired image indices by swapchain name.
+    pub fn acquire_all(&mut self) -> Result<HashMap<Cow<'static, str>, u32>> {
+        let mut indices = HashMap::new();
+        for (name, swapchain, state) in &mut self.swapchains {
+            if *state != OxrSwapchainSetState::Released {
+                continue;
+            }
+            let index = swapchain
+                .acquire_image()
+                .map_err(|err| OxrError::SwapchainSetError {
+                    swapchain: name.clone(),
+                    source: Box::new(err),
+                })?;
+            *state = OxrSwapchainSetState::Acquired;
+            indices.insert(name.clone(), index);
+        }
+        Ok(indices)
+    }
+
+    /// Waits for the images of every acquired swapchain, after this the images can be rendered to.
+    ///
+    /// Returns `false` if any swapchain timed out, those stay acquired and are waited for again by the next call.
+    pub fn wait_all(&mut self, policy: WaitImagePolicy) -> Result<bool> {
+        let mut all_waited = true;
+        for (name, swapchain, state) in &mut self.swapchains {
+            if *state != OxrSwapchainSetState::Acquired {
+                continue;
+            }
+            let waited =
+                swapchain
+                    .wait_image(policy)
+                    .map_err(|err| OxrError::SwapchainSetError {
+                        swapchain: name.clone(),
+                        source: Box::new(err),
+                    })?;
+            if waited {
+                *state = OxrSwapchainSetState::Waited;
+            } else {
+                all_waited = false;
+            }
+        }
+        Ok(all_waited)
+    }
+
+    /// Releases the images of every waited swapchain, this has to happen after rendering and before the frame is ended.
+    pub fn release_all(&mut self) -> Result<()> {
+        for (name, swapchain, state) in &mut self.swapchains {
+            if *state != OxrSwapchainSetState::Waited {
+                continue;
+            }
+            swapchain
+                .release_image()
+                .map_err(|err| OxrError::SwapchainSetError {
+                    swapchain: name.clone(),
+                    source: Box::new(err),
+                })?;
+            *state = OxrSwapchainSetState::Released;
+        }
+        Ok(())
+    }
+}
+
+/// A swapchain storing the depth for the [`ProjectionLayerDepth`](crate::layer_builder::ProjectionLayerDepth).
+///
+/// Created and inserted into the render world by the [`OxrLayerDepthPlugin`](crate::features::layer_depth::OxrLayerDepthPlugin),
+/// which also acquires, waits on and releases its images every frame.
+#[derive(Resource, Deref, DerefMut)]
+pub struct OxrDepthSwapchain(pub OxrSwapchain);
+
 /// Stores the generated swapchain images.
+///
+/// The runtime decides how many images a swapchain has, this is often 2 or 3 but can be more,
+/// so the images have to be indexed by the index returned by [`acquire_image`](OxrSwapchain::acquire_image).
 #[derive(Debug, Deref, Resource, Clone, Copy, ExtractResource)]
 pub struct OxrSwapchainImages(pub &'static [wgpu::Texture]);
 
+impl OxrSwapchainImages {
+    /// Returns the number of images the runtime allocated for the swapchain.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the image with the `index` returned by [`acquire_image`](OxrSwapchain::acquire_image).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of range, which means the images don't belong to the swapchain the index was acquired from.
+    pub fn image(&self, index: u32) -> &wgpu::Texture {
+        debug_assert!(
+            (index as usize) < self.0.len(),
+            "acquired swapchain image index {index} is out of range for {} images",
+            self.0.len()
+        );
+        &self.0[index as usize]
+    }
+}
+
+/// The swapchains of the views after the first one, empty unless the [`SwapchainLayout::PerEye`] layout is used.
+///
+/// The first view renders into the [`OxrSwapchain`], the view with index `i` into the swapchain at index `i - 1`.
+/// Only exists in the render world.
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct OxrPerEyeSwapchains(pub Vec<OxrSwapchain>);
+
+impl OxrPerEyeSwapchains {
+    /// Returns the swapchain the view with `view_index` renders into, `swapchain` is the [`OxrSwapchain`].
+    pub fn view_swapchain<'a>(
+        &'a self,
+        swapchain: &'a OxrSwapchain,
+        view_index: u32,
+    ) -> &'a OxrSwapchain {
+        view_index
+            .checked_sub(1)
+            .and_then(|index| self.0.get(index as usize))
+            .unwrap_or(swapchain)
+    }
+}
+
+/// The images of the [`OxrPerEyeSwapchains`], in the same order.
+#[derive(Debug, Default, Deref, Resource, Clone, ExtractResource)]
+pub struct OxrPerEyeSwapchainImages(pub Vec<OxrSwapchainImages>);
+
+impl OxrPerEyeSwapchainImages {
+    /// Returns the image with the index in `image_indices` of the swapchain the view with `view_index` renders into.
+    ///
+    /// Returns [`None`] for the first view, which renders into the [`OxrSwapchainImages`].
+    pub fn view_image(&self, view_index: u32, image_indices: &[u32]) -> Option<&wgpu::Texture> {
+        let index = view_index.checked_sub(1)? as usize;
+        Some(self.0.get(index)?.image(*image_indices.get(index)?))
+    }
+}
+
 /// Thread safe wrapper around [openxr::Space] representing the stage.
 // #[derive(Deref, Clone, Resource)]
 // pub struct OxrStage(pub Arc<openxr::Space>);
@@ -292,6 +894,49 @@ pub struct OxrSwapchainImages(pub &'static [wgpu::Texture]);
 #[derive(Clone, Resource, ExtractResource, Deref, DerefMut, Default)]
 pub struct OxrViews(pub Vec<openxr::View>);
 
+/// The flags of the latest located [`OxrViews`], they tell whether the poses of the views are valid and tracked.
+/// The render world locates the views again and has its own flags.
+///
+/// The [`XrTrackingState`](bevy_mod_xr::camera::XrTrackingState) is updated from these flags.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Resource, Deref)]
+pub struct OxrViewStateFlags(pub openxr::ViewStateFlags);
+
+impl OxrViewStateFlags {
+    pub fn pos_valid(&self) -> bool {
+        self.0.contains(openxr::ViewStateFlags::POSITION_VALID)
+    }
+    pub fn pos_tracked(&self) -> bool {
+        self.0.contains(openxr::ViewStateFlags::POSITION_TRACKED)
+    }
+    pub fn rot_valid(&self) -> bool {
+        self.0.contains(openxr::ViewStateFlags::ORIENTATION_VALID)
+    }
+    pub fn rot_tracked(&self) -> bool {
+        self.0.contains(openxr::ViewStateFlags::ORIENTATION_TRACKED)
+    }
+}
+
+impl OxrViews {
+    /// Creates views from poses relative to the [`XrPrimaryReferenceSpace`](bevy_mod_xr::spaces::XrPrimaryReferenceSpace)
+    /// and their fields of view, e.g. to test systems reading the views without a session.
+    pub fn from_poses(views: Vec<(Transform, XrFov)>) -> Self {
+        Self(
+            views
+                .into_iter()
+                .map(|(pose, fov)| openxr::View {
+                    pose: pose.to_posef(),
+                    fov: openxr::Fovf {
+                        angle_left: fov.left,
+                        angle_right: fov.right,
+                        angle_up: fov.up,
+                        angle_down: fov.down,
+                    },
+                })
+                .collect(),
+        )
+    }
+}
+
 /// Wrapper around [openxr::SystemId] to allow it to be stored as a resource.
 #[derive(Debug, Copy, Clone, Deref, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Resource)]
 pub struct OxrSystemId(pub openxr::SystemId);
@@ -332,18 +977,207 @@ pub struct OxrRenderLayers(pub Vec<Box<dyn LayerProvider + Send + Sync>>);
 pub struct OxrGraphicsInfo {
     pub blend_mode: EnvironmentBlendMode,
     pub resolution: UVec2,
+    /// The format of the swapchain, this is how the runtime interprets the rendered images.
     pub format: wgpu::TextureFormat,
+    /// The format of the texture views bevy renders into.
+    ///
+    /// This is the same as [`format`](Self::format) unless a different [`view_format`](crate::init::OxrInitPlugin::view_format) was requested,
+    /// in which case the swapchain was created with [`MUTABLE_FORMAT`](openxr::SwapchainUsageFlags::MUTABLE_FORMAT).
+    /// If this is an sRGB format the GPU encodes the rendered colors when writing them,
+    /// so shaders should output linear colors and not apply gamma correction themselves.
+    pub view_format: wgpu::TextureFormat,
+    /// The view configuration the session was started with.
+    pub view_configuration: openxr::ViewConfigurationType,
+    /// The number of views of the [`view_configuration`](Self::view_configuration),
+    /// this is also the array size of the swapchain unless the [`swapchain_layout`](Self::swapchain_layout) is [`PerEye`](SwapchainLayout::PerEye).
+    pub view_count: u32,
+    /// The sample count of the swapchain images.
+    pub sample_count: u32,
+    /// How the images of the views are split across swapchains.
+    pub swapchain_layout: SwapchainLayout,
+    /// The maximum number of composition layers the system supports per frame.
+    pub max_layer_count: u32,
+    /// The resolution of the focus views of the [`PRIMARY_QUAD_VARJO`](openxr::ViewConfigurationType::PRIMARY_QUAD_VARJO) view configuration,
+    /// the views with index 2 and 3 which cover the area the user looks at in a higher resolution.
+    ///
+    /// [`None`] for other view configurations, all views use the [`resolution`](Self::resolution) then.
+    pub focus_resolution: Option<UVec2>,
+}
+
+impl OxrGraphicsInfo {
+    /// Creates graphics info for a stereo session rendering into a single swapchain,
+    /// e.g. to test systems reading the graphics info without a session.
+    ///
+    /// The [`max_layer_count`](Self::max_layer_count) is 16, the minimum every runtime has to support.
+    pub fn new_for_test(
+        blend_mode: EnvironmentBlendMode,
+        resolution: UVec2,
+        format: wgpu::TextureFormat,
+    ) -> Self {
+        Self {
+            blend_mode,
+            resolution,
+            format,
+            view_format: format,
+            view_configuration: openxr::ViewConfigurationType::PRIMARY_STEREO,
+            view_count: 2,
+            sample_count: 1,
+            swapchain_layout: SwapchainLayout::SingleArray,
+            max_layer_count: 16,
+            focus_resolution: None,
+        }
+    }
+
+    /// Returns the resolution of the view with `view_index`,
+    /// this is the [`focus_resolution`](Self::focus_resolution) for the focus views of quad view sessions.
+    pub fn view_resolution(&self, view_index: u32) -> UVec2 {
+        match self.focus_resolution {
+            Some(focus_resolution) if view_index >= 2 => focus_resolution,
+            _ => self.resolution,
+        }
+    }
+}
+
+impl OxrGraphicsInfo {
+    /// Returns the clear color of the [`XrCamera`](bevy_mod_xr::camera::XrCamera)s for the [`blend_mode`](Self::blend_mode).
+    ///
+    /// With the [`ADDITIVE`](EnvironmentBlendMode::ADDITIVE) and [`ALPHA_BLEND`](EnvironmentBlendMode::ALPHA_BLEND) blend modes
+    /// anything opaque the views are cleared to covers the real world, so they are cleared to [`Color::NONE`].
+    /// With the [`OPAQUE`](EnvironmentBlendMode::OPAQUE) blend mode the [`ClearColor`] resource is used.
+    pub fn clear_color(&self) -> ClearColorConfig {
+        match self.blend_mode {
+            EnvironmentBlendMode::ADDITIVE | EnvironmentBlendMode::ALPHA_BLEND => {
+                ClearColorConfig::Custom(Color::NONE)
+            }
+            _ => ClearColorConfig::Default,
+        }
+    }
+}
+
+/// Overrides the clear color of the [`XrCamera`](bevy_mod_xr::camera::XrCamera)s,
+/// which is picked based on the blend mode by [`OxrGraphicsInfo::clear_color`] otherwise.
+///
+/// Removing the resource switches the cameras back to the clear color of the blend mode.
+#[derive(Clone, Debug, Resource)]
+pub struct OxrClearColorOverride(pub ClearColorConfig);
+
+/// How the swapchain format of the current session was picked, for debugging.
+#[derive(Clone, Debug, Resource)]
+pub struct OxrSwapchainFormatInfo {
+    /// The format of the swapchain.
+    pub format: wgpu::TextureFormat,
+    /// The format of the texture views rendered into, see [`OxrGraphicsInfo::view_format`].
+    pub view_format: wgpu::TextureFormat,
+    /// The formats supported by the runtime, in the order of preference of the runtime.
+    pub candidates: Vec<wgpu::TextureFormat>,
+    /// The preference the format was picked with.
+    pub preference: SwapchainFormatPreference,
+    /// Why the format was picked.
+    pub reason: &'static str,
+}
+
+/// Summary of the configuration the session was negotiated with, logged when the session is created.
+///
+/// Useful to check whether the requested configuration took effect and to include in bug reports.
+/// Removed when the session is destroyed, headless sessions have no report.
+#[derive(Clone, Debug, Resource)]
+pub struct OxrSessionConfigReport {
+    pub runtime_name: String,
+    pub runtime_version: openxr::Version,
+    pub system_name: String,
+    /// The name of the graphics API, see [`GraphicsWrap::graphics_name`].
+    pub backend: &'static str,
+    pub view_configuration: openxr::ViewConfigurationType,
+    pub blend_mode: EnvironmentBlendMode,
+    pub format: wgpu::TextureFormat,
+    pub view_format: wgpu::TextureFormat,
+    pub resolution: UVec2,
+    /// The resolution of the focus views, see [`OxrGraphicsInfo::focus_resolution`].
+    pub focus_resolution: Option<UVec2>,
+    pub view_count: u32,
+    pub sample_count: u32,
+    pub swapchain_layout: SwapchainLayout,
+    /// The refresh rate of the display in Hz, [`None`] if `XR_FB_display_refresh_rate` isn't enabled.
+    pub refresh_rate: Option<f32>,
+    pub enabled_extensions: Vec<Cow<'static, str>>,
+}
+
+impl OxrSessionConfigReport {
+    /// Collects the report of a session created with `graphics_info`.
+    pub fn new(
+        instance: &OxrInstance,
+        system_id: openxr::SystemId,
+        session: &OxrSession,
+        graphics_info: &OxrGraphicsInfo,
+        exts: &OxrEnabledExtensions,
+    ) -> Result<Self> {
+        let instance_props = instance.properties()?;
+        let system_props = instance.system_properties(system_id)?;
+        let refresh_rate = if exts.fb_display_refresh_rate {
+            Some(session.get_refresh_rate()?)
+        } else {
+            None
+        };
+        Ok(Self {
+            runtime_name: instance_props.runtime_name,
+            runtime_version: instance_props.runtime_version,
+            system_name: system_props.system_name,
+            backend: instance.backend().graphics_name(),
+            view_configuration: graphics_info.view_configuration,
+            blend_mode: graphics_info.blend_mode,
+            format: graphics_info.format,
+            view_format: graphics_info.view_format,
+            resolution: graphics_info.resolution,
+            focus_resolution: graphics_info.focus_resolution,
+            view_count: graphics_info.view_count,
+            sample_count: graphics_info.sample_count,
+            swapchain_layout: graphics_info.swapchain_layout,
+            refresh_rate,
+            enabled_extensions: exts.names(),
+        })
+    }
+
+    /// Logs the report as a single event with one field per setting.
+    pub fn log(&self) {
+        info!(
+            runtime = %format!("{} {}", self.runtime_name, self.runtime_version),
+            system = %self.system_name,
+            backend = self.backend,
+            view_configuration = ?self.view_configuration,
+            blend_mode = ?self.blend_mode,
+            format = ?self.format,
+            srgb = self.format.is_srgb(),
+            view_format = ?self.view_format,
+            resolution = %self.resolution,
+            focus_resolution = ?self.focus_resolution,
+            view_count = self.view_count,
+            sample_count = self.sample_count,
+            swapchain_layout = ?self.swapchain_layout,
+            refresh_rate = ?self.refresh_rate,
+            extensions = ?self.enabled_extensions,
+            "Created XrSession"
+        );
+    }
 }
 
 #[derive(Clone)]
 /// This is used to store information from startup that is needed to create the session after the instance has been created.
 pub struct SessionConfigInfo {
+    /// The view configuration the session should use. If [None], use [`PRIMARY_STEREO`](openxr::ViewConfigurationType::PRIMARY_STEREO)
+    /// if available, otherwise pick the first available view configuration.
+    pub view_configuration: Option<openxr::ViewConfigurationType>,
     /// List of blend modes the openxr session can use. If [None], pick the first available blend mode.
     pub blend_modes: Option<Vec<EnvironmentBlendMode>>,
-    /// List of formats the openxr session can use. If [None], pick the first available format
-    pub formats: Option<Vec<wgpu::TextureFormat>>,
+    /// How the swapchain format is picked from the formats the runtime supports.
+    pub format_preference: SwapchainFormatPreference,
+    /// Format of the texture views rendered into. If [None], use the swapchain format.
+    pub view_format: Option<wgpu::TextureFormat>,
     /// List of resolutions that the openxr swapchain can use. If [None] pick the first available resolution.
     pub resolutions: Option<Vec<UVec2>>,
+    /// Sample count of the swapchain images, falls back to 1 if unsupported.
+    pub sample_count: u32,
+    /// How the images of the views are split across swapchains.
+    pub swapchain_layout: SwapchainLayout,
     /// Graphics info used to create a session.
     pub graphics_info: SessionCreateInfo,
 }
@@ -351,10 +1185,88 @@ pub struct SessionConfigInfo {
 #[derive(ExtractResource, Resource, Clone, Default)]
 pub struct OxrSessionStarted(pub bool);
 
+/// Whether the app is suspended by the OS, e.g. because the Android activity was paused.
+///
+/// The frame loop doesn't run while the app is suspended, since waiting for a frame would block until it is resumed.
+#[derive(ExtractResource, Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OxrAppSuspended(pub bool);
+
 /// The frame state returned from [FrameWaiter::wait_frame](openxr::FrameWaiter::wait)
+///
+/// Updated in [`XrFirst`](bevy_mod_xr::session::XrFirst) right after waiting for the frame.
+/// If [`should_render`](openxr::FrameState::should_render) is false, the cameras are disabled, but the frame is still begun and ended without any layers.
 #[derive(Clone, Deref, DerefMut, Resource, ExtractResource)]
 pub struct OxrFrameState(pub openxr::FrameState);
 
+impl OxrFrameState {
+    /// Creates the state of a frame that should be rendered, e.g. to test systems reading the frame state without a session.
+    pub fn from_nanos(predicted_display_time: i64, predicted_display_period: i64) -> Self {
+        Self(openxr::FrameState {
+            predicted_display_time: openxr::Time::from_nanos(predicted_display_time),
+            predicted_display_period: openxr::Duration::from_nanos(predicted_display_period),
+            should_render: true,
+        })
+    }
+
+    /// Returns the time spaces should be located at for the frame that is currently being processed.
+    ///
+    /// This is the [`predicted_display_time`](openxr::FrameState::predicted_display_time) of the frame.
+    /// With [`Pipelined`] rendering the frame is displayed one [`predicted_display_period`](openxr::FrameState::predicted_display_period) later,
+    /// so `pipelined` should be true in the main world if [`Pipelined`] exists.
+    pub fn locate_time(&self, pipelined: bool) -> openxr::Time {
+        if pipelined {
+            openxr::Time::from_nanos(
+                self.predicted_display_time.as_nanos() + self.predicted_display_period.as_nanos(),
+            )
+        } else {
+            self.predicted_display_time
+        }
+    }
+
+    /// Returns the time views and action spaces should be located at, the [`locate_time`](Self::locate_time)
+    /// moved forward by the [`OxrPosePredictionOffset`].
+    pub fn pose_time(
+        &self,
+        pipelined: bool,
+        offset: Option<&OxrPosePredictionOffset>,
+    ) -> openxr::Time {
+        let time = self.locate_time(pipelined);
+        match offset {
+            Some(offset) => {
+                openxr::Time::from_nanos(time.as_nanos() + offset.clamped().as_nanos() as i64)
+            }
+            None => time,
+        }
+    }
+}
+
+/// Added to the predicted display time when locating the views and action spaces, to late latch the poses
+/// closer to when the frame is actually shown.
+///
+/// A small offset can reduce the swimming of the world on some headsets, but too much makes the poses overshoot
+/// whenever the head changes direction, so it should be tuned per headset. The offset is clamped to [`MAX`](Self::MAX).
+/// The frame itself is still submitted for its predicted display time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Resource, ExtractResource)]
+pub struct OxrPosePredictionOffset(pub std::time::Duration);
+
+impl OxrPosePredictionOffset {
+    /// The largest offset that is applied.
+    pub const MAX: std::time::Duration = std::time::Duration::from_millis(50);
+
+    /// Returns the offset clamped to [`MAX`](Self::MAX).
+    pub fn clamped(&self) -> std::time::Duration {
+        self.0.min(Self::MAX)
+    }
+}
+
+/// How long the render world waits for each swapchain image acquired for a frame, [`Infinite`](WaitImagePolicy::Infinite) by default.
+///
+/// With a finite policy a frame whose images aren't available in time isn't rendered and submits no views,
+/// instead of stalling the render thread. The images stay acquired and are waited for again on the next frame.
+/// Every wait that timed out is counted in the [`image_wait_timeouts`](bevy_mod_xr::session::XrFrameStats::image_wait_timeouts).
+#[derive(Resource, ExtractResource, Clone, Copy, Debug, Default, Deref, DerefMut)]
+pub struct OxrWaitImagePolicy(pub WaitImagePolicy);
+
 /// Instructs systems to add display period
 #[derive(Clone, Copy, Default, Resource)]
 pub struct Pipelined;
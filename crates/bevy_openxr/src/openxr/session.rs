@@ -1,16 +1,22 @@
 use std::ffi::c_void;
 
+use crate::error::{cvt, OxrError};
 use crate::next_chain::{OxrNextChain, OxrNextChainStructBase, OxrNextChainStructProvider};
-use crate::resources::{OxrPassthrough, OxrPassthroughLayer, OxrSwapchain};
-use crate::types::{Result, SwapchainCreateInfo};
+use crate::resources::{OxrDepthSwapchain, OxrPassthrough, OxrPassthroughLayer, OxrSwapchain};
+use crate::types::{Result, SwapchainCreateFlags, SwapchainCreateInfo, SwapchainUsageFlags};
 use bevy::prelude::*;
-use openxr::AnyGraphics;
+use openxr::{sys, AnyGraphics};
 
-use crate::graphics::{graphics_match, GraphicsExt, GraphicsType, GraphicsWrap};
+use crate::graphics::{graphics_match, GraphicsBackend, GraphicsExt, GraphicsType, GraphicsWrap};
 
 /// Graphics agnostic wrapper around [openxr::Session].
 ///
 /// See [`openxr::Session`] for other available methods.
+///
+/// To call functions of extensions this crate doesn't wrap, use [`as_raw`](openxr::Session::as_raw) for the raw handle,
+/// [`instance`](openxr::Session::instance) for the function pointers and [`backend`](Self::backend) to know which graphics API the session uses.
+/// The raw handle is only valid while this session or one of its clones is alive, it is destroyed together with the last one,
+/// so it must not be destroyed manually or kept around after the session was destroyed, e.g. in [`XrPreDestroySession`](bevy_mod_xr::session::XrPreDestroySession).
 #[derive(Resource, Deref, Clone)]
 pub struct OxrSession(
     /// A session handle with [`AnyGraphics`].
@@ -39,6 +45,29 @@ impl OxrSession {
         Self(session.clone().into_any_graphics(), G::wrap(session))
     }
 
+    /// Returns the session with the graphics API erased.
+    ///
+    /// [`OxrSession`] already derefs to it, this is useful to pass the session to code expecting an [`openxr::Session<AnyGraphics>`].
+    pub fn as_any_graphics(&self) -> &openxr::Session<AnyGraphics> {
+        &self.0
+    }
+
+    /// Returns the graphics backend the session was created with.
+    pub fn backend(&self) -> GraphicsBackend {
+        graphics_match!(
+            &self.1;
+            _ => () => ()
+        )
+    }
+
+    /// Returns whether the session was created without graphics using `XR_MND_headless`.
+    ///
+    /// Headless sessions can sync actions and locate spaces, creating swapchains fails with
+    /// [`HeadlessSessionNoGraphics`](OxrError::HeadlessSessionNoGraphics).
+    pub fn is_headless(&self) -> bool {
+        self.1.is_headless()
+    }
+
     /// Returns [`GraphicsWrap`] with [`openxr::Session<G>`] as the inner type.
     ///
     /// This can be useful if you need access to the original [`openxr::Session`] with the graphics API still specified.
@@ -56,14 +85,123 @@ impl OxrSession {
         )
     }
 
+    /// Enumerates all available swapchain formats that have a depth aspect.
+    ///
+    /// Calls [`enumerate_swapchain_formats`](OxrSession::enumerate_swapchain_formats) internally.
+    pub fn enumerate_depth_swapchain_formats(&self) -> Result<Vec<wgpu::TextureFormat>> {
+        Ok(self
+            .enumerate_swapchain_formats()?
+            .into_iter()
+            .filter(|format| format.has_depth_aspect())
+            .collect())
+    }
+
+    /// Creates an [OxrDepthSwapchain] with one array layer per view.
+    ///
+    /// Calls [`create_swapchain`](OxrSession::create_swapchain) internally.
+    pub fn create_depth_swapchain(
+        &self,
+        format: wgpu::TextureFormat,
+        resolution: UVec2,
+        view_count: u32,
+    ) -> Result<OxrDepthSwapchain> {
+        Ok(OxrDepthSwapchain(self.create_swapchain(
+            SwapchainCreateInfo {
+                create_flags: SwapchainCreateFlags::EMPTY,
+                usage_flags: SwapchainUsageFlags::DEPTH_STENCIL_ATTACHMENT
+                    | SwapchainUsageFlags::TRANSFER_DST,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_DST,
+                format,
+                sample_count: 1,
+                width: resolution.x,
+                height: resolution.y,
+                face_count: 1,
+                array_size: view_count,
+                mip_count: 1,
+            },
+        )?))
+    }
+
     /// Creates an [OxrSwapchain].
     ///
+    /// The info is checked with [`validate`](SwapchainCreateInfo::validate) first,
+    /// fails with [`HeadlessSessionNoGraphics`](OxrError::HeadlessSessionNoGraphics) for headless sessions.
     /// Calls [`create_swapchain`](openxr::Session::create_swapchain) internally.
     pub fn create_swapchain(&self, info: SwapchainCreateInfo) -> Result<OxrSwapchain> {
-        Ok(OxrSwapchain(graphics_match!(
-            &self.1;
-            session => session.create_swapchain(&info.try_into()?)? => OxrSwapchain
-        )))
+        if self.is_headless() {
+            return Err(OxrError::HeadlessSessionNoGraphics);
+        }
+        info.validate(&self.enumerate_swapchain_formats()?)?;
+        Ok(OxrSwapchain(
+            graphics_match!(
+                &self.1;
+                session => session.create_swapchain(&info.try_into()?)? => OxrSwapchain
+            ),
+            default(),
+            info.usage,
+            None,
+        ))
+    }
+
+    /// Creates an [OxrSwapchain] replacing an existing one, e.g. at a new resolution without restarting the session.
+    ///
+    /// The old swapchain is destroyed when it's dropped, which may only happen after all of its acquired images were released.
+    /// Use [`OxrRecreateSwapchain`](crate::init::OxrRecreateSwapchain) to replace the swapchain the views are rendered into.
+    ///
+    /// `chain` has to contain the same structs the old swapchain was created with, e.g. to keep its foveation.
+    ///
+    /// Calls [`create_swapchain_with_next_chain`](OxrSession::create_swapchain_with_next_chain) internally.
+    pub fn recreate_swapchain(
+        &self,
+        new_info: SwapchainCreateInfo,
+        chain: &OxrSwapchainCreateNextChain,
+    ) -> Result<OxrSwapchain> {
+        self.create_swapchain_with_next_chain(new_info, chain)
+    }
+
+    /// Creates an [OxrSwapchain] with the structs in `chain` passed as the next chain of the create info.
+    ///
+    /// The info is checked with [`validate`](SwapchainCreateInfo::validate) first,
+    /// fails with [`HeadlessSessionNoGraphics`](OxrError::HeadlessSessionNoGraphics) for headless sessions.
+    /// Calls [`xrCreateSwapchain`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#xrCreateSwapchain) internally.
+    pub fn create_swapchain_with_next_chain(
+        &self,
+        info: SwapchainCreateInfo,
+        chain: &OxrSwapchainCreateNextChain,
+    ) -> Result<OxrSwapchain> {
+        if self.is_headless() {
+            return Err(OxrError::HeadlessSessionNoGraphics);
+        }
+        info.validate(&self.enumerate_swapchain_formats()?)?;
+        Ok(OxrSwapchain(
+            graphics_match!(
+                &self.1;
+                session => {
+                    let info = openxr::SwapchainCreateInfo::<Api>::try_from(info)?;
+                    let raw_info = sys::SwapchainCreateInfo {
+                        ty: sys::SwapchainCreateInfo::TYPE,
+                        next: chain.chain_pointer(),
+                        create_flags: info.create_flags,
+                        usage_flags: info.usage_flags,
+                        format: <Api as openxr::Graphics>::lower_format(info.format),
+                        sample_count: info.sample_count,
+                        width: info.width,
+                        height: info.height,
+                        face_count: info.face_count,
+                        array_size: info.array_size,
+                        mip_count: info.mip_count,
+                    };
+                    let mut out = sys::Swapchain::NULL;
+                    cvt(unsafe {
+                        (session.instance().fp().create_swapchain)(session.as_raw(), &raw_info, &mut out)
+                    })?;
+                    unsafe { openxr::Swapchain::from_raw(session.clone(), out) }
+                } => OxrSwapchain
+            ),
+            default(),
+            info.usage,
+            None,
+        ))
     }
 
     /// Creates a passthrough.
@@ -100,6 +238,24 @@ impl OxrSession {
 
 pub trait OxrSessionCreateNextProvider: OxrNextChainStructProvider {}
 
+pub trait OxrSwapchainCreateNextProvider: OxrNextChainStructProvider {}
+
+/// NonSend Resource
+#[derive(Default)]
+pub struct OxrSwapchainCreateNextChain(OxrNextChain);
+
+impl OxrSwapchainCreateNextChain {
+    pub fn push<T: OxrSwapchainCreateNextProvider>(&mut self, info_struct: T) {
+        self.0.push(info_struct)
+    }
+    pub fn chain(&self) -> Option<&OxrNextChainStructBase> {
+        self.0.chain()
+    }
+    pub fn chain_pointer(&self) -> *const c_void {
+        self.0.chain_pointer()
+    }
+}
+
 /// NonSend Resource
 #[derive(Default)]
 pub struct OxrSessionCreateNextChain(OxrNextChain);
@@ -1,8 +1,17 @@
-use std::{mem::MaybeUninit, ptr, sync::Mutex};
+use std::{
+    ffi::{c_char, c_void},
+    mem::MaybeUninit,
+    ptr,
+    sync::Mutex,
+    time::Duration,
+};
 
-use bevy::{prelude::*, utils::hashbrown::HashSet};
+use bevy::{
+    prelude::*,
+    utils::{hashbrown::HashSet, HashMap},
+};
 use bevy_mod_xr::{
-    session::{XrFirst, XrHandleEvents},
+    session::{XrFirst, XrHandleEvents, XrPreDestroySession, XrSessionCreated},
     spaces::{
         XrDestroySpace, XrPrimaryReferenceSpace, XrReferenceSpace, XrSpace, XrSpaceLocationFlags,
         XrSpaceVelocityFlags, XrVelocity,
@@ -15,10 +24,13 @@ use openxr::{
 };
 
 use crate::{
+    error::{cvt, OxrError},
+    exts::OxrEnabledExtensions,
     helper_traits::{ToPosef, ToQuat, ToVec3},
     openxr_session_available, openxr_session_running,
-    resources::{OxrFrameState, OxrInstance, Pipelined},
+    resources::{OxrFrameState, OxrInstance, OxrPosePredictionOffset, Pipelined},
     session::OxrSession,
+    types::Result,
 };
 
 #[derive(SystemSet, Hash, Debug, Clone, Copy, PartialEq, Eq)]
@@ -45,6 +57,8 @@ impl Plugin for OxrSpatialPlugin {
                     .before(XrHandleEvents::Poll)
                     .run_if(openxr_session_available),
             )
+            .add_systems(XrSessionCreated, init_locate_spaces)
+            .add_systems(XrPreDestroySession, cleanup_locate_spaces)
             .add_systems(
                 PreUpdate,
                 update_space_transforms
@@ -56,13 +70,37 @@ impl Plugin for OxrSpatialPlugin {
     }
 }
 
+/// `xrLocateSpacesKHR`, used by [`locate_spaces`](OxrSession::locate_spaces) to locate all spaces in a single call.
+///
+/// Only exists while a session exists and [`XR_KHR_locate_spaces`](https://registry.khronos.org/OpenXR/specs/1.1/html/xrspec.html#XR_KHR_locate_spaces) is enabled.
+#[derive(Resource, Clone, Copy)]
+pub struct OxrLocateSpaces(LocateSpacesKHR);
+
+fn init_locate_spaces(
+    exts: Res<OxrEnabledExtensions>,
+    instance: Res<OxrInstance>,
+    mut commands: Commands,
+) {
+    if !exts.locate_spaces() {
+        return;
+    }
+    match locate_spaces_fn(&instance) {
+        Some(function) => commands.insert_resource(OxrLocateSpaces(function)),
+        None => warn!("XR_KHR_locate_spaces is enabled, but xrLocateSpacesKHR isn't available"),
+    }
+}
+
+fn cleanup_locate_spaces(mut commands: Commands) {
+    commands.remove_resource::<OxrLocateSpaces>();
+}
+
 fn add_velocity_flags(event: Trigger<OnAdd, XrVelocity>, mut cmds: Commands) {
     if event.entity() == Entity::PLACEHOLDER {
-        error!("called add_location_flags observer without entity");
+        error!("called add_velocity_flags observer without entity");
         return;
     }
     cmds.entity(event.entity())
-        .insert(OxrSpaceLocationFlags(openxr::SpaceLocationFlags::default()));
+        .insert(OxrSpaceVelocityFlags(openxr::SpaceVelocityFlags::default()));
 }
 fn add_location_flags(event: Trigger<OnAdd, XrSpace>, mut cmds: Commands) {
     if event.entity() == Entity::PLACEHOLDER {
@@ -135,6 +173,44 @@ impl OxrSpaceLocationFlags {
         self.0.contains(SpaceLocationFlags::ORIENTATION_TRACKED)
     }
 }
+
+/// A location returned by [`OxrSession::locate_at`].
+#[derive(Clone, Copy, Debug)]
+pub struct OxrPredictedLocation {
+    pub location: openxr::SpaceLocation,
+    /// The time the space was located at.
+    pub time: openxr::Time,
+    /// Whether the time is after the predicted display time, so the pose is extrapolated by the runtime.
+    pub predicted: bool,
+}
+
+impl OxrPredictedLocation {
+    /// Returns the flags of the location.
+    pub fn flags(&self) -> OxrSpaceLocationFlags {
+        OxrSpaceLocationFlags(self.location.location_flags)
+    }
+    /// Whether the position and orientation are based on tracking data, otherwise they are inferred by the runtime,
+    /// e.g. from the last known pose of a controller that lost tracking.
+    pub fn is_tracked(&self) -> bool {
+        let flags = self.flags();
+        flags.pos_tracked() && flags.rot_tracked()
+    }
+}
+
+fn prediction_time(
+    frame_state: &OxrFrameState,
+    time_offset: openxr::Duration,
+) -> Result<openxr::Time> {
+    let max = OxrSession::MAX_PREDICTION_OFFSET;
+    let offset = Duration::from_nanos(time_offset.as_nanos().unsigned_abs());
+    if offset > max {
+        return Err(OxrError::PredictionOffsetOutOfRange { offset, max });
+    }
+    Ok(openxr::Time::from_nanos(
+        frame_state.predicted_display_time.as_nanos() + time_offset.as_nanos(),
+    ))
+}
+
 #[derive(Clone, Copy, Component)]
 pub struct OxrSpaceVelocityFlags(pub openxr::SpaceVelocityFlags);
 impl OxrSpaceVelocityFlags {
@@ -152,6 +228,8 @@ fn update_space_transforms(
     default_ref_space: Res<XrPrimaryReferenceSpace>,
     pipelined: Option<Res<Pipelined>>,
     frame_state: Res<OxrFrameState>,
+    offset: Option<Res<OxrPosePredictionOffset>>,
+    locate_spaces: Option<Res<OxrLocateSpaces>>,
     mut query: Query<(
         &mut Transform,
         &XrSpace,
@@ -163,69 +241,98 @@ fn update_space_transforms(
         Option<&mut XrSpaceVelocityFlags>,
     )>,
 ) {
-    for (
-        mut transform,
-        space,
-        velocity,
-        ref_space,
-        mut oxr_space_location_flags,
-        mut xr_space_location_flags,
-        oxr_space_velocity_flags,
-        xr_space_velocity_flags,
-    ) in &mut query
-    {
-        let ref_space = ref_space.unwrap_or(&default_ref_space);
-        let time = if pipelined.is_some() {
-            openxr::Time::from_nanos(
-                frame_state.predicted_display_time.as_nanos()
-                    + frame_state.predicted_display_period.as_nanos(),
-            )
-        } else {
-            frame_state.predicted_display_time
-        };
-        let space_location = if let Some(mut velocity) = velocity {
-            match session.locate_space_with_velocity(space, ref_space, time) {
-                Ok((location, space_velocity)) => {
-                    let flags = OxrSpaceVelocityFlags(space_velocity.velocity_flags);
-                    if flags.linear_valid() {
-                        velocity.linear = space_velocity.linear_velocity.to_vec3();
-                    }
-                    if flags.linear_valid() {
-                        velocity.linear = space_velocity.linear_velocity.to_vec3();
-                    }
-                    let Some(mut vel_flags) = oxr_space_velocity_flags else {
-                        error!("XrVelocity without OxrSpaceVelocityFlags");
-                        return;
-                    };
-                    let Some(mut xr_vel_flags) = xr_space_velocity_flags else {
-                        error!("XrVelocity without XrSpaceVelocityFlags");
-                        return;
-                    };
-                    *vel_flags = flags;
-                    xr_vel_flags.linear_valid = vel_flags.linear_valid();
-                    xr_vel_flags.angular_valid = vel_flags.angular_valid();
-                    Ok(location)
+    let time = frame_state.pose_time(pipelined.is_some(), offset.as_deref());
+    let mut items: Vec<_> = query.iter_mut().collect();
+    // spaces with the same base space are located in a single call, separately for the ones that need velocities
+    let mut batches = HashMap::<(XrSpace, bool), Vec<usize>>::new();
+    for (index, (_, _, velocity, ref_space, ..)) in items.iter().enumerate() {
+        let base = ref_space.map_or(***default_ref_space, |ref_space| **ref_space);
+        batches
+            .entry((base, velocity.is_some()))
+            .or_default()
+            .push(index);
+    }
+    for ((base, with_velocity), indices) in batches {
+        let spaces: Vec<XrSpace> = indices.iter().map(|&index| *items[index].1).collect();
+        if with_velocity {
+            let Ok(locations) =
+                session.locate_spaces_with_velocity(locate_spaces.as_deref(), &spaces, &base, time)
+            else {
+                continue;
+            };
+            for (index, (location, space_velocity)) in indices.into_iter().zip(locations) {
+                let (
+                    transform,
+                    _,
+                    velocity,
+                    _,
+                    oxr_space_location_flags,
+                    xr_space_location_flags,
+                    oxr_space_velocity_flags,
+                    xr_space_velocity_flags,
+                ) = &mut items[index];
+                let (Some(vel_flags), Some(xr_vel_flags)) =
+                    (oxr_space_velocity_flags, xr_space_velocity_flags)
+                else {
+                    error!("XrVelocity without OxrSpaceVelocityFlags or XrSpaceVelocityFlags");
+                    continue;
+                };
+                let velocity = velocity.as_mut().unwrap();
+                let flags = OxrSpaceVelocityFlags(space_velocity.velocity_flags);
+                if flags.linear_valid() {
+                    velocity.linear = space_velocity.linear_velocity.to_vec3();
+                }
+                if flags.angular_valid() {
+                    velocity.angular = space_velocity.angular_velocity.to_vec3();
                 }
-                Err(err) => Err(err),
+                **vel_flags = flags;
+                xr_vel_flags.linear_valid = flags.linear_valid();
+                xr_vel_flags.angular_valid = flags.angular_valid();
+                apply_space_location(
+                    &location,
+                    transform,
+                    oxr_space_location_flags,
+                    xr_space_location_flags,
+                );
             }
         } else {
-            session.locate_space(space, ref_space, time)
-        };
-        if let Ok(space_location) = space_location {
-            let flags = OxrSpaceLocationFlags(space_location.location_flags);
-            if flags.pos_valid() {
-                transform.translation = space_location.pose.position.to_vec3();
+            let Ok(locations) =
+                session.locate_spaces(locate_spaces.as_deref(), &spaces, &base, time)
+            else {
+                continue;
+            };
+            for (index, location) in indices.into_iter().zip(locations) {
+                let (transform, _, _, _, oxr_space_location_flags, xr_space_location_flags, ..) =
+                    &mut items[index];
+                apply_space_location(
+                    &location,
+                    transform,
+                    oxr_space_location_flags,
+                    xr_space_location_flags,
+                );
             }
-            if flags.rot_valid() {
-                transform.rotation = space_location.pose.orientation.to_quat();
-            }
-            *oxr_space_location_flags = flags;
-            xr_space_location_flags.position_tracked = flags.pos_valid() && flags.pos_tracked();
-            xr_space_location_flags.rotation_tracked = flags.rot_valid() && flags.rot_tracked();
         }
     }
 }
 
+fn apply_space_location(
+    location: &openxr::SpaceLocation,
+    transform: &mut Transform,
+    oxr_space_location_flags: &mut OxrSpaceLocationFlags,
+    xr_space_location_flags: &mut XrSpaceLocationFlags,
+) {
+    let flags = OxrSpaceLocationFlags(location.location_flags);
+    if flags.pos_valid() {
+        transform.translation = location.pose.position.to_vec3();
+    }
+    if flags.rot_valid() {
+        transform.rotation = location.pose.orientation.to_quat();
+    }
+    *oxr_space_location_flags = flags;
+    xr_space_location_flags.position_tracked = flags.pos_valid() && flags.pos_tracked();
+    xr_space_location_flags.rotation_tracked = flags.rot_valid() && flags.rot_tracked();
+}
+
 impl OxrSession {
     pub fn create_action_space<T: openxr::ActionTy>(
         &self,
@@ -456,6 +563,9 @@ impl OxrSession {
                 .collect(),
         ))
     }
+    /// Locates `space` relative to `base` at `time`, both can be any space of this session.
+    ///
+    /// Only the parts of the pose marked as valid in the returned location flags are initialized.
     pub fn locate_space(
         &self,
         space: &XrSpace,
@@ -464,6 +574,7 @@ impl OxrSession {
     ) -> openxr::Result<openxr::SpaceLocation> {
         locate_space(self.instance(), space, base, time)
     }
+    /// Same as [`locate_space`](OxrSession::locate_space), additionally returns the velocity of `space` relative to `base`.
     pub fn locate_space_with_velocity(
         &self,
         space: &XrSpace,
@@ -472,6 +583,175 @@ impl OxrSession {
     ) -> openxr::Result<(openxr::SpaceLocation, openxr::SpaceVelocity)> {
         locate_space_with_velocity(self.instance(), space, base, time)
     }
+    /// Locates all `spaces` relative to `base` at `time` in a single call, the locations are in the order of `spaces`.
+    ///
+    /// Uses `locate_spaces`, the [`OxrLocateSpaces`] resource, if it exists,
+    /// otherwise every space is located with [`locate_space`](OxrSession::locate_space).
+    pub fn locate_spaces(
+        &self,
+        locate_spaces: Option<&OxrLocateSpaces>,
+        spaces: &[XrSpace],
+        base: &XrSpace,
+        time: openxr::Time,
+    ) -> openxr::Result<Vec<openxr::SpaceLocation>> {
+        match self.locate_spaces_khr(locate_spaces, spaces, base, time, false)? {
+            Some((locations, _)) => Ok(locations),
+            None => spaces
+                .iter()
+                .map(|space| self.locate_space(space, base, time))
+                .collect(),
+        }
+    }
+    /// Same as [`locate_spaces`](OxrSession::locate_spaces), additionally returns the velocities of `spaces` relative to `base`.
+    pub fn locate_spaces_with_velocity(
+        &self,
+        locate_spaces: Option<&OxrLocateSpaces>,
+        spaces: &[XrSpace],
+        base: &XrSpace,
+        time: openxr::Time,
+    ) -> openxr::Result<Vec<(openxr::SpaceLocation, openxr::SpaceVelocity)>> {
+        match self.locate_spaces_khr(locate_spaces, spaces, base, time, true)? {
+            Some((locations, velocities)) => Ok(locations.into_iter().zip(velocities).collect()),
+            None => spaces
+                .iter()
+                .map(|space| self.locate_space_with_velocity(space, base, time))
+                .collect(),
+        }
+    }
+    /// Locates `space` relative to `base` at `time_offset` after the predicted display time of the current frame,
+    /// e.g. to extrapolate a throw or to compensate network latency. Negative offsets locate in the past.
+    ///
+    /// Runtimes only predict a short time ahead, so offsets with a magnitude above [`MAX_PREDICTION_OFFSET`](Self::MAX_PREDICTION_OFFSET)
+    /// are rejected with [`PredictionOffsetOutOfRange`](OxrError::PredictionOffsetOutOfRange).
+    pub fn locate_at(
+        &self,
+        space: &XrSpace,
+        base: &XrSpace,
+        frame_state: &OxrFrameState,
+        time_offset: openxr::Duration,
+    ) -> Result<OxrPredictedLocation> {
+        let time = prediction_time(frame_state, time_offset)?;
+        let location = self.locate_space(space, base, time)?;
+        Ok(OxrPredictedLocation {
+            location,
+            time,
+            predicted: time_offset.as_nanos() > 0,
+        })
+    }
+    /// Same as [`locate_at`](OxrSession::locate_at), additionally returns the velocity of `space` relative to `base`.
+    pub fn locate_at_with_velocity(
+        &self,
+        space: &XrSpace,
+        base: &XrSpace,
+        frame_state: &OxrFrameState,
+        time_offset: openxr::Duration,
+    ) -> Result<(OxrPredictedLocation, openxr::SpaceVelocity)> {
+        let time = prediction_time(frame_state, time_offset)?;
+        let (location, velocity) = self.locate_space_with_velocity(space, base, time)?;
+        Ok((
+            OxrPredictedLocation {
+                location,
+                time,
+                predicted: time_offset.as_nanos() > 0,
+            },
+            velocity,
+        ))
+    }
+    /// The largest magnitude of the time offset accepted by [`locate_at`](OxrSession::locate_at).
+    pub const MAX_PREDICTION_OFFSET: Duration = Duration::from_millis(500);
+    /// Returns [`None`] if `xrLocateSpacesKHR` isn't available.
+    fn locate_spaces_khr(
+        &self,
+        locate_spaces: Option<&OxrLocateSpaces>,
+        spaces: &[XrSpace],
+        base: &XrSpace,
+        time: openxr::Time,
+        with_velocity: bool,
+    ) -> openxr::Result<Option<(Vec<openxr::SpaceLocation>, Vec<openxr::SpaceVelocity>)>> {
+        let Some(&OxrLocateSpaces(locate_spaces)) = locate_spaces else {
+            return Ok(None);
+        };
+        let raw_spaces: Vec<_> = spaces
+            .iter()
+            .map(|space| space.as_raw_openxr_space())
+            .collect();
+        let info = SpacesLocateInfoKHR {
+            ty: sys::StructureType::from_raw(TYPE_SPACES_LOCATE_INFO_KHR),
+            next: ptr::null(),
+            base_space: base.as_raw_openxr_space(),
+            time,
+            space_count: spaces.len() as u32,
+            spaces: raw_spaces.as_ptr(),
+        };
+        let mut location_data = vec![
+            SpaceLocationDataKHR {
+                location_flags: SpaceLocationFlags::EMPTY,
+                pose: openxr::Posef::IDENTITY,
+            };
+            spaces.len()
+        ];
+        let mut velocity_data = vec![
+            SpaceVelocityDataKHR {
+                velocity_flags: SpaceVelocityFlags::EMPTY,
+                linear_velocity: Default::default(),
+                angular_velocity: Default::default(),
+            };
+            if with_velocity { spaces.len() } else { 0 }
+        ];
+        let mut velocities = SpaceVelocitiesKHR {
+            ty: sys::StructureType::from_raw(TYPE_SPACE_VELOCITIES_KHR),
+            next: ptr::null_mut(),
+            velocity_count: velocity_data.len() as u32,
+            velocities: velocity_data.as_mut_ptr(),
+        };
+        let mut locations = SpaceLocationsKHR {
+            ty: sys::StructureType::from_raw(TYPE_SPACE_LOCATIONS_KHR),
+            next: if with_velocity {
+                &mut velocities as *mut _ as _
+            } else {
+                ptr::null_mut()
+            },
+            location_count: location_data.len() as u32,
+            locations: location_data.as_mut_ptr(),
+        };
+        cvt(unsafe { locate_spaces(self.as_raw(), &info, &mut locations) })?;
+        // Applications *must* not read invalid parts of a pose or velocity, i.e. they may be uninitialized
+        let locations = location_data
+            .into_iter()
+            .map(|data| openxr::SpaceLocation {
+                location_flags: data.location_flags,
+                pose: openxr::Posef {
+                    orientation: data
+                        .location_flags
+                        .contains(SpaceLocationFlags::ORIENTATION_VALID)
+                        .then_some(data.pose.orientation)
+                        .unwrap_or_default(),
+                    position: data
+                        .location_flags
+                        .contains(SpaceLocationFlags::POSITION_VALID)
+                        .then_some(data.pose.position)
+                        .unwrap_or_default(),
+                },
+            })
+            .collect();
+        let velocities = velocity_data
+            .into_iter()
+            .map(|data| openxr::SpaceVelocity {
+                velocity_flags: data.velocity_flags,
+                linear_velocity: data
+                    .velocity_flags
+                    .contains(SpaceVelocityFlags::LINEAR_VALID)
+                    .then_some(data.linear_velocity)
+                    .unwrap_or_default(),
+                angular_velocity: data
+                    .velocity_flags
+                    .contains(SpaceVelocityFlags::ANGULAR_VALID)
+                    .then_some(data.angular_velocity)
+                    .unwrap_or_default(),
+            })
+            .collect();
+        Ok(Some((locations, velocities)))
+    }
     pub fn locate_hand_joints(
         &self,
         tracker: &openxr::HandTracker,
@@ -594,13 +874,76 @@ unsafe impl OxrSpaceExt for XrSpace {
     }
 }
 
-fn cvt(x: sys::Result) -> openxr::Result<sys::Result> {
-    if x.into_raw() >= 0 {
-        Ok(x)
-    } else {
-        Err(x)
-    }
+// `XR_KHR_locate_spaces` is newer than the bindings of the openxr crate, so its types are declared here.
+const TYPE_SPACES_LOCATE_INFO_KHR: i32 = 1000471000;
+const TYPE_SPACE_LOCATIONS_KHR: i32 = 1000471001;
+const TYPE_SPACE_VELOCITIES_KHR: i32 = 1000471002;
+
+type LocateSpacesKHR = unsafe extern "system" fn(
+    session: sys::Session,
+    locate_info: *const SpacesLocateInfoKHR,
+    space_locations: *mut SpaceLocationsKHR,
+) -> sys::Result;
+
+#[repr(C)]
+struct SpacesLocateInfoKHR {
+    ty: sys::StructureType,
+    next: *const c_void,
+    base_space: sys::Space,
+    time: openxr::Time,
+    space_count: u32,
+    spaces: *const sys::Space,
 }
+
+#[repr(C)]
+struct SpaceLocationsKHR {
+    ty: sys::StructureType,
+    next: *mut c_void,
+    location_count: u32,
+    locations: *mut SpaceLocationDataKHR,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct SpaceLocationDataKHR {
+    location_flags: SpaceLocationFlags,
+    pose: openxr::Posef,
+}
+
+#[repr(C)]
+struct SpaceVelocitiesKHR {
+    ty: sys::StructureType,
+    next: *mut c_void,
+    velocity_count: u32,
+    velocities: *mut SpaceVelocityDataKHR,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct SpaceVelocityDataKHR {
+    velocity_flags: SpaceVelocityFlags,
+    linear_velocity: openxr::Vector3f,
+    angular_velocity: openxr::Vector3f,
+}
+
+/// Returns `xrLocateSpacesKHR`, or [`None`] if the runtime doesn't provide it.
+fn locate_spaces_fn(instance: &openxr::Instance) -> Option<LocateSpacesKHR> {
+    let mut function = None;
+    let result = unsafe {
+        (instance.fp().get_instance_proc_addr)(
+            instance.as_raw(),
+            b"xrLocateSpacesKHR\0".as_ptr() as *const c_char,
+            &mut function,
+        )
+    };
+    if result.into_raw() < 0 {
+        return None;
+    }
+    function.map(|function| unsafe {
+        std::mem::transmute::<sys::pfn::VoidFunction, LocateSpacesKHR>(function)
+    })
+}
+
 unsafe fn create_view(flags: openxr::ViewStateFlags, raw: &MaybeUninit<sys::View>) -> openxr::View {
     // Applications *must* not read invalid parts of a poses, i.e. they may be uninitialized
     let ptr = raw.as_ptr();
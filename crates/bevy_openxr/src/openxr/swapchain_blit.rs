@@ -0,0 +1,270 @@
+use bevy::{
+    core_pipeline::blit::{BlitPipeline, BlitPipelineKey},
+    prelude::*,
+    render::{
+        camera::{ManualTextureView, ManualTextureViewHandle, ManualTextureViews},
+        extract_resource::{ExtractResource, ExtractResourcePlugin},
+        graph::CameraDriverLabel,
+        render_graph::{Node, NodeRunError, RenderGraph, RenderGraphContext, RenderLabel},
+        render_resource::{
+            BindGroup, BindGroupEntries, CachedRenderPipelineId, LoadOp, Operations, PipelineCache,
+            RenderPassColorAttachment, RenderPassDescriptor, SpecializedRenderPipelines, StoreOp,
+            TextureView,
+        },
+        renderer::{RenderContext, RenderDevice},
+        Render, RenderApp, RenderSet,
+    },
+};
+use bevy_mod_xr::session::{XrFirst, XrHandleEvents, XrPreDestroySession};
+
+use crate::{
+    init::should_run_frame_loop,
+    render::{
+        init_views, OxrPerEyeSwapchainImageIndices, OxrSwapchainImageIndex, XR_TEXTURE_INDEX,
+    },
+    resources::{OxrGraphicsInfo, OxrPerEyeSwapchainImages, OxrSwapchainImages},
+    session::OxrSession,
+};
+
+/// Renders the [`XrCamera`](bevy_mod_xr::camera::XrCamera)s into intermediate textures
+/// and copies them into the acquired swapchain image with the [`OxrSwapchainBlitNode`].
+///
+/// The intermediate textures have the swapchain resolution scaled by the [`OxrRenderScale`],
+/// so the views can be rendered at a lower resolution than the swapchain and upscaled when copied.
+/// The copy samples the nearest texel, it doesn't filter.
+pub struct OxrSwapchainBlitPlugin;
+
+impl Plugin for OxrSwapchainBlitPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<OxrRenderScale>()
+            .add_plugins(ExtractResourcePlugin::<OxrSwapchainBlitSources>::default())
+            .add_systems(
+                XrFirst,
+                create_blit_sources
+                    .after(init_views)
+                    .run_if(resource_exists::<OxrGraphicsInfo>)
+                    .run_if(
+                        resource_added::<OxrSession>.or_else(resource_changed::<OxrRenderScale>),
+                    )
+                    .in_set(XrHandleEvents::FrameLoop),
+            )
+            .add_systems(XrPreDestroySession, cleanup_blit_sources);
+
+        let render_app = app.sub_app_mut(RenderApp);
+        render_app
+            .add_systems(
+                Render,
+                prepare_blit_targets
+                    .in_set(RenderSet::Prepare)
+                    .run_if(should_run_frame_loop)
+                    .run_if(resource_exists::<OxrSwapchainBlitSources>)
+                    .run_if(resource_exists::<OxrSwapchainImageIndex>),
+            )
+            .add_systems(XrPreDestroySession, cleanup_blit_targets);
+
+        let mut graph = render_app.world_mut().resource_mut::<RenderGraph>();
+        graph.add_node(OxrSwapchainBlitLabel, OxrSwapchainBlitNode);
+        graph.add_node_edge(CameraDriverLabel, OxrSwapchainBlitLabel);
+    }
+}
+
+/// Scale of the resolution the views are rendered at relative to the swapchain resolution, used by the [`OxrSwapchainBlitPlugin`].
+///
+/// Defaults to 1, values below 1 render at a lower resolution and upscale the views when copying them into the swapchain.
+/// The scale is clamped to [`MIN`](Self::MIN)..=[`MAX`](Self::MAX).
+/// It can be changed at any time, e.g. to keep the frame rate up. The textures the views are rendered into are resized
+/// in the next frame if the render resolution changed, the swapchain stays the same.
+#[derive(Resource, Clone, Copy, Debug, Deref, DerefMut)]
+pub struct OxrRenderScale(pub f32);
+
+impl Default for OxrRenderScale {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+impl OxrRenderScale {
+    pub const MIN: f32 = 0.5;
+    pub const MAX: f32 = 1.0;
+
+    /// Returns the resolution the views are rendered at for the `swapchain_resolution`.
+    pub fn render_resolution(&self, swapchain_resolution: UVec2) -> UVec2 {
+        (swapchain_resolution.as_vec2() * self.0.clamp(Self::MIN, Self::MAX))
+            .round()
+            .as_uvec2()
+            .max(UVec2::ONE)
+    }
+}
+
+/// The textures the [`XrCamera`](bevy_mod_xr::camera::XrCamera)s render into, indexed by the view index.
+#[derive(Resource, Clone, ExtractResource)]
+pub struct OxrSwapchainBlitSources {
+    pub views: Vec<TextureView>,
+    /// The resolution of the textures.
+    pub resolution: UVec2,
+}
+
+#[derive(Resource)]
+struct OxrSwapchainBlitTargets {
+    pipeline: CachedRenderPipelineId,
+    /// The bind group of the source texture and the view of the swapchain image layer for every view.
+    views: Vec<(BindGroup, TextureView)>,
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct OxrSwapchainBlitLabel;
+
+/// Copies the textures the views were rendered into to their array layer of the acquired swapchain image.
+///
+/// Runs after all cameras have been rendered.
+pub struct OxrSwapchainBlitNode;
+
+impl Node for OxrSwapchainBlitNode {
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        // the targets are only prepared on frames an image was acquired for, they are stale otherwise
+        if !world.contains_resource::<OxrSwapchainImageIndex>() {
+            return Ok(());
+        }
+        let Some(targets) = world.get_resource::<OxrSwapchainBlitTargets>() else {
+            return Ok(());
+        };
+        let Some(pipeline) = world
+            .resource::<PipelineCache>()
+            .get_render_pipeline(targets.pipeline)
+        else {
+            return Ok(());
+        };
+        for (bind_group, destination) in &targets.views {
+            let mut pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+                label: Some("xr_swapchain_blit"),
+                color_attachments: &[Some(RenderPassColorAttachment {
+                    view: destination,
+                    resolve_target: None,
+                    ops: Operations {
+                        load: LoadOp::Clear(default()),
+                        store: StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            pass.set_render_pipeline(pipeline);
+            pass.set_bind_group(0, bind_group, &[]);
+            pass.draw(0..3, 0..1);
+        }
+        Ok(())
+    }
+}
+
+fn create_blit_sources(
+    device: Res<RenderDevice>,
+    graphics_info: Res<OxrGraphicsInfo>,
+    scale: Res<OxrRenderScale>,
+    current: Option<Res<OxrSwapchainBlitSources>>,
+    mut manual_texture_views: ResMut<ManualTextureViews>,
+    mut commands: Commands,
+) {
+    let size = scale.render_resolution(graphics_info.resolution);
+    if current.is_some_and(|current| current.resolution == size) {
+        return;
+    }
+    let mut sources = vec![];
+    for index in 0..graphics_info.view_count {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("xr_render_target"),
+            size: wgpu::Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: graphics_info.view_format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&default());
+        // replaces the view of the swapchain image inserted by `init_views`
+        manual_texture_views.insert(
+            ManualTextureViewHandle(XR_TEXTURE_INDEX + index),
+            ManualTextureView {
+                texture_view: view.clone(),
+                size,
+                format: graphics_info.view_format,
+            },
+        );
+        sources.push(view);
+    }
+    commands.insert_resource(OxrSwapchainBlitSources {
+        views: sources,
+        resolution: size,
+    });
+}
+
+fn cleanup_blit_sources(mut commands: Commands) {
+    commands.remove_resource::<OxrSwapchainBlitSources>();
+}
+
+#[allow(clippy::too_many_arguments)]
+fn prepare_blit_targets(
+    device: Res<RenderDevice>,
+    pipeline_cache: Res<PipelineCache>,
+    blit_pipeline: Res<BlitPipeline>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<BlitPipeline>>,
+    sources: Res<OxrSwapchainBlitSources>,
+    images: Res<OxrSwapchainImages>,
+    image_index: Res<OxrSwapchainImageIndex>,
+    per_eye_images: Option<Res<OxrPerEyeSwapchainImages>>,
+    per_eye_indices: Option<Res<OxrPerEyeSwapchainImageIndices>>,
+    graphics_info: Res<OxrGraphicsInfo>,
+    mut commands: Commands,
+) {
+    let pipeline = pipelines.specialize(
+        &pipeline_cache,
+        &blit_pipeline,
+        BlitPipelineKey {
+            texture_format: graphics_info.view_format,
+            blend_state: None,
+            samples: graphics_info.sample_count,
+        },
+    );
+    let image = images.image(image_index.0);
+    let views = sources
+        .views
+        .iter()
+        .enumerate()
+        .map(|(index, source)| {
+            let bind_group = device.create_bind_group(
+                "xr_swapchain_blit_bind_group",
+                &blit_pipeline.texture_bind_group,
+                &BindGroupEntries::sequential((source, &blit_pipeline.sampler)),
+            );
+            let image = per_eye_images
+                .as_ref()
+                .zip(per_eye_indices.as_ref())
+                .and_then(|(images, indices)| images.view_image(index as u32, indices))
+                .unwrap_or(image);
+            let destination = image.create_view(&wgpu::TextureViewDescriptor {
+                dimension: Some(wgpu::TextureViewDimension::D2),
+                array_layer_count: Some(1),
+                base_array_layer: graphics_info.swapchain_layout.array_layer(index as u32),
+                format: Some(graphics_info.view_format),
+                ..default()
+            });
+            (bind_group, destination.into())
+        })
+        .collect();
+    commands.insert_resource(OxrSwapchainBlitTargets { pipeline, views });
+}
+
+fn cleanup_blit_targets(mut commands: Commands) {
+    commands.remove_resource::<OxrSwapchainBlitSources>();
+    commands.remove_resource::<OxrSwapchainBlitTargets>();
+}
@@ -0,0 +1,94 @@
+//! Conversions between [`openxr::Time`] and the clocks of the platform.
+//!
+//! [`openxr::Time`] is a number of nanoseconds in the time domain of the runtime, see [`openxr::Time::as_nanos`] and [`openxr::Time::from_nanos`].
+//! To compare it to [`Instant`]s, e.g. the ones in Bevy's [`Time`](bevy::time::Time), use [`OxrInstance::instant`].
+
+use std::time::{Duration, Instant};
+
+use openxr::sys;
+
+use crate::error::cvt;
+use crate::resources::OxrInstance;
+
+/// Returns the time elapsed from `earlier` to `later`, or zero if `later` is before `earlier`.
+pub fn elapsed(earlier: openxr::Time, later: openxr::Time) -> Duration {
+    Duration::from_nanos(later.as_nanos().saturating_sub(earlier.as_nanos()).max(0) as u64)
+}
+
+impl OxrInstance {
+    /// Returns the current time of the runtime.
+    ///
+    /// Requires [`XR_KHR_convert_timespec_time`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#XR_KHR_convert_timespec_time) on Linux and Android
+    /// and [`XR_KHR_win32_convert_performance_counter_time`](https://registry.khronos.org/OpenXR/specs/1.0/html/xrspec.html#XR_KHR_win32_convert_performance_counter_time) on Windows.
+    /// Fails with [`ERROR_FUNCTION_UNSUPPORTED`](sys::Result::ERROR_FUNCTION_UNSUPPORTED) on other platforms.
+    pub fn now(&self) -> openxr::Result<openxr::Time> {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        {
+            extern "C" {
+                fn clock_gettime(clock: i32, time: *mut sys::platform::timespec) -> i32;
+            }
+            const CLOCK_MONOTONIC: i32 = 1;
+
+            let fns = self
+                .exts()
+                .khr_convert_timespec_time
+                .as_ref()
+                .ok_or(sys::Result::ERROR_EXTENSION_NOT_PRESENT)?;
+            let mut timespec = std::mem::MaybeUninit::<sys::platform::timespec>::uninit();
+            if unsafe { clock_gettime(CLOCK_MONOTONIC, timespec.as_mut_ptr()) } != 0 {
+                return Err(sys::Result::ERROR_RUNTIME_FAILURE);
+            }
+            let mut time = openxr::Time::from_nanos(0);
+            unsafe {
+                cvt((fns.convert_timespec_time_to_time)(
+                    self.as_raw(),
+                    timespec.as_ptr(),
+                    &mut time,
+                ))?;
+            }
+            Ok(time)
+        }
+        #[cfg(windows)]
+        {
+            extern "system" {
+                fn QueryPerformanceCounter(count: *mut i64) -> i32;
+            }
+
+            let fns = self
+                .exts()
+                .khr_win32_convert_performance_counter_time
+                .as_ref()
+                .ok_or(sys::Result::ERROR_EXTENSION_NOT_PRESENT)?;
+            let mut count = 0i64;
+            if unsafe { QueryPerformanceCounter(&mut count) } == 0 {
+                return Err(sys::Result::ERROR_RUNTIME_FAILURE);
+            }
+            let mut time = openxr::Time::from_nanos(0);
+            unsafe {
+                cvt((fns.convert_win32_performance_counter_to_time)(
+                    self.as_raw(),
+                    &count as *const i64 as *const _,
+                    &mut time,
+                ))?;
+            }
+            Ok(time)
+        }
+        #[cfg(not(any(target_os = "linux", target_os = "android", windows)))]
+        {
+            Err(sys::Result::ERROR_FUNCTION_UNSUPPORTED)
+        }
+    }
+
+    /// Converts `time` to an [`Instant`], e.g. to compare the predicted display time to Bevy's [`Time`](bevy::time::Time).
+    ///
+    /// This has the same requirements as [`now`](OxrInstance::now).
+    pub fn instant(&self, time: openxr::Time) -> openxr::Result<Instant> {
+        let now = self.now()?;
+        let instant = Instant::now();
+        Ok(if time.as_nanos() >= now.as_nanos() {
+            instant + elapsed(now, time)
+        } else {
+            instant.checked_sub(elapsed(time, now)).unwrap_or(instant)
+        })
+    }
+}
@@ -1,5 +1,8 @@
 use std::borrow::Cow;
 
+use bevy::ecs::system::Resource;
+use bevy::math::UVec2;
+
 use crate::error::OxrError;
 use crate::graphics::{GraphicsExt, GraphicsType, GraphicsWrap};
 
@@ -16,8 +19,33 @@ pub struct WgpuGraphics(
     pub wgpu::AdapterInfo,
     pub wgpu::Adapter,
     pub wgpu::Instance,
+    pub OxrGraphicsAdapter,
 );
 
+/// The GPU adapter the OpenXR runtime requires the session to be created on.
+///
+/// Inserted as a resource by the [`OxrInitPlugin`](crate::init::OxrInitPlugin). On systems with multiple GPUs the session
+/// has to run on the adapter the headset is connected to, [`matches`](Self::matches) can be used to pick
+/// the same adapter for anything created outside of the XR render device.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OxrGraphicsAdapter {
+    /// The PCI vendor id, compared against [`wgpu::AdapterInfo::vendor`].
+    pub vendor: u32,
+    /// The PCI device id, compared against [`wgpu::AdapterInfo::device`].
+    pub device: u32,
+    /// The locally unique id of the adapter, only available on Windows.
+    pub luid: Option<[u8; 8]>,
+    /// The universally unique id of the adapter, only available with Vulkan.
+    pub uuid: Option<[u8; 16]>,
+}
+
+impl OxrGraphicsAdapter {
+    /// Returns whether the wgpu adapter described by `info` is the adapter required by the runtime.
+    pub fn matches(&self, info: &wgpu::AdapterInfo) -> bool {
+        info.vendor == self.vendor && info.device == self.device
+    }
+}
+
 /// A version number that can be stored inside of a u32
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct Version(pub u8, pub u8, pub u16);
@@ -38,6 +66,10 @@ impl Version {
 pub struct AppInfo {
     pub name: Cow<'static, str>,
     pub version: Version,
+    /// Overrides the engine name reported to the runtime, defaults to `"Bevy"`.
+    pub engine_name: Option<Cow<'static, str>>,
+    /// Overrides the engine version reported to the runtime, defaults to [`Version::BEVY`].
+    pub engine_version: Option<Version>,
 }
 
 impl AppInfo {
@@ -45,7 +77,19 @@ impl AppInfo {
     pub const BEVY: Self = Self {
         name: Cow::Borrowed("Bevy"),
         version: Version::BEVY,
+        engine_name: None,
+        engine_version: None,
     };
+
+    /// Returns the engine name reported to the runtime.
+    pub fn engine_name(&self) -> &str {
+        self.engine_name.as_deref().unwrap_or("Bevy")
+    }
+
+    /// Returns the engine version reported to the runtime.
+    pub fn engine_version(&self) -> Version {
+        self.engine_version.unwrap_or(Version::BEVY)
+    }
 }
 
 impl Default for AppInfo {
@@ -54,25 +98,393 @@ impl Default for AppInfo {
     }
 }
 
+/// The position in the frame loop of an [`OxrFrameStream`](crate::resources::OxrFrameStream).
+///
+/// Every frame has to be waited for, begun and ended in this order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OxrFrameLoopState {
+    /// The previous frame was ended, or no frame was started yet.
+    WaitingToWait,
+    /// A frame was waited for and can be begun.
+    WaitingToBegin,
+    /// A frame was begun and has to be ended.
+    WaitingToEnd,
+}
+
+/// Properties of an OpenXR system, returned by [`OxrInstance::system_properties`](crate::resources::OxrInstance::system_properties).
+///
+/// Can be inspected before the session is created, e.g. to cap the number of submitted layers at [`max_layer_count`](Self::max_layer_count).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OxrSystemProperties {
+    pub system_id: openxr::SystemId,
+    pub vendor_id: u32,
+    pub system_name: String,
+    pub max_swapchain_image_width: u32,
+    pub max_swapchain_image_height: u32,
+    /// The maximum number of composition layers per frame, including the projection layer.
+    pub max_layer_count: u32,
+    pub orientation_tracking: bool,
+    pub position_tracking: bool,
+    /// Always `false` if `XR_EXT_hand_tracking` isn't enabled.
+    pub supports_hand_tracking: bool,
+    /// Always `false` if `XR_EXT_eye_gaze_interaction` isn't enabled.
+    pub supports_eye_gaze_interaction: bool,
+    /// Empty if `XR_FB_passthrough` isn't enabled.
+    pub passthrough_capabilities: openxr::sys::PassthroughCapabilityFlagsFB,
+}
+
+/// How the images of the views are split across swapchains.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SwapchainLayout {
+    /// One swapchain with an array layer per view.
+    #[default]
+    SingleArray,
+    /// One swapchain with a single array layer per view.
+    ///
+    /// The first view renders into the [`OxrSwapchain`](crate::resources::OxrSwapchain),
+    /// the other views into the [`OxrPerEyeSwapchains`](crate::resources::OxrPerEyeSwapchains).
+    /// Useful on runtimes with buggy array layer handling.
+    PerEye,
+}
+
+impl SwapchainLayout {
+    /// Returns the array layer of the swapchain image the view with `view_index` renders into.
+    pub fn array_layer(self, view_index: u32) -> u32 {
+        match self {
+            SwapchainLayout::SingleArray => view_index,
+            SwapchainLayout::PerEye => 0,
+        }
+    }
+
+    /// Returns the array size of the swapchains for a view configuration with `view_count` views.
+    pub fn array_size(self, view_count: u32) -> u32 {
+        match self {
+            SwapchainLayout::SingleArray => view_count,
+            SwapchainLayout::PerEye => 1,
+        }
+    }
+}
+
+/// How the swapchain format is picked from the formats supported by the runtime.
+///
+/// Set through [`OxrInitPlugin::format_preference`](crate::init::OxrInitPlugin::format_preference),
+/// inserting it as a resource overrides that for the sessions created afterwards.
+/// The chosen format is stored in the [`OxrSwapchainFormatInfo`](crate::resources::OxrSwapchainFormatInfo).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Resource)]
+pub enum SwapchainFormatPreference {
+    /// The first sRGB format the runtime supports, or the first supported format if there is none.
+    #[default]
+    PreferSrgb,
+    /// The first linear format the runtime supports, or the first supported format if there is none.
+    PreferLinear,
+    /// The first format of the list the runtime supports, in the order of the list.
+    ///
+    /// Fails with [`NoMatchingSwapchainFormat`](OxrError::NoMatchingSwapchainFormat) if none of them are supported.
+    ExactList(Vec<wgpu::TextureFormat>),
+    /// The first format the runtime supports, which is the one the runtime prefers.
+    RuntimePreferred,
+}
+
+impl SwapchainFormatPreference {
+    /// Picks a format from `available`, which is in the order of preference of the runtime.
+    ///
+    /// Returns the format together with a short description of why it was picked.
+    pub fn select(
+        &self,
+        available: &[wgpu::TextureFormat],
+    ) -> Result<(wgpu::TextureFormat, &'static str)> {
+        let first = || {
+            available
+                .first()
+                .map(|format| (*format, "first format preferred by the runtime"))
+                .ok_or(OxrError::NoAvailableFormat)
+        };
+        match self {
+            SwapchainFormatPreference::PreferSrgb => {
+                match available.iter().find(|format| format.is_srgb()) {
+                    Some(format) => Ok((*format, "first sRGB format preferred by the runtime")),
+                    None => first(),
+                }
+            }
+            SwapchainFormatPreference::PreferLinear => {
+                match available.iter().find(|format| !format.is_srgb()) {
+                    Some(format) => Ok((*format, "first linear format preferred by the runtime")),
+                    None => first(),
+                }
+            }
+            SwapchainFormatPreference::ExactList(formats) => formats
+                .iter()
+                .find(|format| available.contains(format))
+                .map(|format| (*format, "first requested format supported by the runtime"))
+                .ok_or_else(|| OxrError::NoMatchingSwapchainFormat {
+                    requested: formats.clone(),
+                    available: available.to_vec(),
+                }),
+            SwapchainFormatPreference::RuntimePreferred => first(),
+        }
+    }
+}
+
+/// How long [`OxrSwapchain::wait_image`](crate::resources::OxrSwapchain::wait_image) blocks
+/// until the compositor is done reading from the image.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum WaitImagePolicy {
+    /// Blocks until the image is available.
+    #[default]
+    Infinite,
+    /// Blocks for at most the given duration.
+    Timeout(std::time::Duration),
+    /// Returns immediately, so the frame can be skipped if the image isn't available yet.
+    NonBlocking,
+}
+
+impl WaitImagePolicy {
+    /// Returns the timeout passed to `xrWaitSwapchainImage`.
+    pub fn timeout(self) -> openxr::Duration {
+        match self {
+            WaitImagePolicy::Infinite => openxr::Duration::INFINITE,
+            WaitImagePolicy::Timeout(timeout) => {
+                openxr::Duration::from_nanos(timeout.as_nanos().try_into().unwrap_or(i64::MAX))
+            }
+            WaitImagePolicy::NonBlocking => openxr::Duration::from_nanos(0),
+        }
+    }
+}
+
 /// Info needed to create a swapchain.
 /// This is an API agnostic version of [openxr::SwapchainCreateInfo] used for some of this library's functions
 #[derive(Debug, Copy, Clone)]
 pub struct SwapchainCreateInfo {
     pub create_flags: SwapchainCreateFlags,
+    /// Has to contain the [`required_usage_flags`](SwapchainCreateInfo::required_usage_flags) of `usage`.
     pub usage_flags: SwapchainUsageFlags,
+    /// The usage of the textures created by [`enumerate_images`](crate::resources::OxrSwapchain::enumerate_images),
+    /// e.g. [`STORAGE_BINDING`](wgpu::TextureUsages::STORAGE_BINDING) to write to the images from a compute shader.
+    pub usage: wgpu::TextureUsages,
     pub format: wgpu::TextureFormat,
     pub sample_count: u32,
     pub width: u32,
     pub height: u32,
+    /// Either 1, or 6 for cube map swapchains used by [`CompositionLayerCube`](crate::layer_builder::CompositionLayerCube).
     pub face_count: u32,
     pub array_size: u32,
     pub mip_count: u32,
 }
 
+impl SwapchainCreateInfo {
+    /// Returns the usage flags a swapchain with `format` needs to be created with so its images can be used with `usage`.
+    pub fn required_usage_flags(
+        usage: wgpu::TextureUsages,
+        format: wgpu::TextureFormat,
+    ) -> SwapchainUsageFlags {
+        let mut usage_flags = SwapchainUsageFlags::EMPTY;
+        if usage.contains(wgpu::TextureUsages::RENDER_ATTACHMENT) {
+            usage_flags |= if format.is_depth_stencil_format() {
+                SwapchainUsageFlags::DEPTH_STENCIL_ATTACHMENT
+            } else {
+                SwapchainUsageFlags::COLOR_ATTACHMENT
+            };
+        }
+        if usage.contains(wgpu::TextureUsages::TEXTURE_BINDING) {
+            usage_flags |= SwapchainUsageFlags::SAMPLED;
+        }
+        if usage.contains(wgpu::TextureUsages::STORAGE_BINDING) {
+            usage_flags |= SwapchainUsageFlags::UNORDERED_ACCESS;
+        }
+        if usage.contains(wgpu::TextureUsages::COPY_SRC) {
+            usage_flags |= SwapchainUsageFlags::TRANSFER_SRC;
+        }
+        if usage.contains(wgpu::TextureUsages::COPY_DST) {
+            usage_flags |= SwapchainUsageFlags::TRANSFER_DST;
+        }
+        usage_flags
+    }
+
+    /// Checks that `usage` is covered by the `usage_flags` and that the format supports it on a device with `features`.
+    pub fn validate_usage(&self, features: wgpu::Features) -> Result<()> {
+        validate_texture_usage(self.usage, self.format, features)?;
+        self.validate_usage_flags()
+    }
+
+    /// Checks that a swapchain can be created with this info, so invalid infos fail with an
+    /// [`InvalidSwapchainCreateInfo`](OxrError::InvalidSwapchainCreateInfo) instead of an opaque runtime error.
+    ///
+    /// `available_formats` are the formats returned by [`enumerate_swapchain_formats`](crate::session::OxrSession::enumerate_swapchain_formats).
+    pub fn validate(&self, available_formats: &[wgpu::TextureFormat]) -> Result<()> {
+        if self.width == 0 || self.height == 0 {
+            return Err(OxrError::InvalidSwapchainCreateInfo(
+                format!("resolution {}x{} is empty", self.width, self.height).into(),
+            ));
+        }
+        if self.array_size == 0 {
+            return Err(OxrError::InvalidSwapchainCreateInfo(
+                "array size is 0".into(),
+            ));
+        }
+        if !available_formats.contains(&self.format) {
+            return Err(OxrError::InvalidSwapchainCreateInfo(
+                format!(
+                    "format {:?} is not supported by the runtime, available formats: {available_formats:?}",
+                    self.format
+                )
+                .into(),
+            ));
+        }
+        let max_mip_count = u32::BITS - self.width.max(self.height).leading_zeros();
+        if self.mip_count == 0 || self.mip_count > max_mip_count {
+            return Err(OxrError::InvalidSwapchainCreateInfo(
+                format!(
+                    "mip count {} is not in 1..={max_mip_count} for the resolution {}x{}",
+                    self.mip_count, self.width, self.height
+                )
+                .into(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn validate_usage_flags(&self) -> Result<()> {
+        let required = Self::required_usage_flags(self.usage, self.format);
+        if !self.usage_flags.contains(required) {
+            return Err(OxrError::MissingSwapchainUsageFlags {
+                usage: self.usage,
+                required,
+                usage_flags: self.usage_flags,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Checks that textures with `format` can be created with `usage` on a device with `features`.
+pub(crate) fn validate_texture_usage(
+    usage: wgpu::TextureUsages,
+    format: wgpu::TextureFormat,
+    features: wgpu::Features,
+) -> Result<()> {
+    if !format
+        .guaranteed_format_features(features)
+        .allowed_usages
+        .contains(usage)
+    {
+        return Err(OxrError::UnsupportedTextureUsage { usage, format });
+    }
+    Ok(())
+}
+
+/// The layout of the images of a swapchain, used to convert them to wgpu [`Texture`](wgpu::Texture)s.
+///
+/// OpenXR swapchain images are always 2D textures, array and cube map swapchains only differ in their array layers,
+/// a cube map swapchain has 6 layers per array element, one for each face.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SwapchainImageLayout {
+    pub resolution: UVec2,
+    /// The array size the swapchain was created with.
+    pub array_size: u32,
+    /// Either 1, or 6 for cube map swapchains.
+    pub face_count: u32,
+}
+
+impl SwapchainImageLayout {
+    /// The layout of a swapchain with a single 2D image.
+    pub const fn d2(resolution: UVec2) -> Self {
+        Self::array(resolution, 1)
+    }
+
+    /// The layout of a swapchain with `array_size` 2D images, e.g. one per view for multiview rendering.
+    pub const fn array(resolution: UVec2, array_size: u32) -> Self {
+        Self {
+            resolution,
+            array_size,
+            face_count: 1,
+        }
+    }
+
+    /// The layout of a cube map swapchain with `array_size` cubes.
+    pub const fn cube(resolution: UVec2, array_size: u32) -> Self {
+        Self {
+            resolution,
+            array_size,
+            face_count: 6,
+        }
+    }
+
+    pub const fn is_cube(&self) -> bool {
+        self.face_count == 6
+    }
+
+    /// The number of array layers of the textures, the array size multiplied by the face count.
+    pub const fn array_layer_count(&self) -> u32 {
+        self.array_size * self.face_count
+    }
+
+    /// The dimension of the textures, always [`D2`](wgpu::TextureDimension::D2).
+    pub const fn texture_dimension(&self) -> wgpu::TextureDimension {
+        wgpu::TextureDimension::D2
+    }
+
+    pub const fn extent(&self) -> wgpu::Extent3d {
+        wgpu::Extent3d {
+            width: self.resolution.x,
+            height: self.resolution.y,
+            depth_or_array_layers: self.array_layer_count(),
+        }
+    }
+
+    /// The dimension of a view covering all layers of the textures.
+    pub const fn view_dimension(&self) -> wgpu::TextureViewDimension {
+        match (self.is_cube(), self.array_size > 1) {
+            (true, true) => wgpu::TextureViewDimension::CubeArray,
+            (true, false) => wgpu::TextureViewDimension::Cube,
+            (false, true) => wgpu::TextureViewDimension::D2Array,
+            (false, false) => wgpu::TextureViewDimension::D2,
+        }
+    }
+
+    /// Returns the descriptor of a view covering all layers of the textures, created with `format` if it's [`Some`].
+    pub fn view_descriptor<'a>(
+        &self,
+        format: Option<wgpu::TextureFormat>,
+    ) -> wgpu::TextureViewDescriptor<'a> {
+        wgpu::TextureViewDescriptor {
+            format,
+            dimension: Some(self.view_dimension()),
+            array_layer_count: Some(self.array_layer_count()),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the descriptor of a 2D view of a single layer, e.g. one face of a cube map.
+    pub fn layer_view_descriptor<'a>(
+        &self,
+        format: Option<wgpu::TextureFormat>,
+        layer: u32,
+    ) -> wgpu::TextureViewDescriptor<'a> {
+        wgpu::TextureViewDescriptor {
+            format,
+            dimension: Some(wgpu::TextureViewDimension::D2),
+            base_array_layer: layer,
+            array_layer_count: Some(1),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&SwapchainCreateInfo> for SwapchainImageLayout {
+    fn from(info: &SwapchainCreateInfo) -> Self {
+        Self {
+            resolution: UVec2::new(info.width, info.height),
+            array_size: info.array_size,
+            face_count: info.face_count,
+        }
+    }
+}
+
 impl<G: GraphicsExt> TryFrom<SwapchainCreateInfo> for openxr::SwapchainCreateInfo<G> {
     type Error = OxrError;
 
     fn try_from(value: SwapchainCreateInfo) -> Result<Self> {
+        value.validate_usage_flags()?;
         Ok(openxr::SwapchainCreateInfo {
             create_flags: value.create_flags,
             usage_flags: value.usage_flags,
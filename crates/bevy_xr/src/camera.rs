@@ -6,6 +6,7 @@ use bevy::ecs::bundle::Bundle;
 use bevy::ecs::component::Component;
 use bevy::ecs::reflect::ReflectComponent;
 use bevy::ecs::schedule::IntoSystemConfigs;
+use bevy::ecs::system::Resource;
 use bevy::math::{Mat4, Vec3A};
 use bevy::pbr::{build_directional_light_cascades, clear_directional_light_cascades, SimulationLightSystems};
 use bevy::reflect::std_traits::ReflectDefault;
@@ -40,7 +41,8 @@ impl Plugin for XrCameraPlugin {
         app.add_plugins((
             ExtractComponentPlugin::<XrProjection>::default(),
             ExtractComponentPlugin::<XrCamera>::default(),
-        ));
+        ))
+        .init_resource::<XrTrackingState>();
     }
 }
 
@@ -60,6 +62,90 @@ impl Default for XrProjection {
     }
 }
 
+/// The field of view of an [`XrView`], as the angles in radians of its sides relative to the view direction.
+///
+/// `left` and `down` are usually negative, the angles don't have to be symmetric.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Reflect)]
+pub struct XrFov {
+    pub left: f32,
+    pub right: f32,
+    pub up: f32,
+    pub down: f32,
+}
+
+impl XrFov {
+    /// Returns the asymmetric perspective projection of this field of view with bevy's reversed depth,
+    /// pass [`f32::INFINITY`] as `far` for an infinite projection.
+    pub fn projection_matrix(&self, near: f32, far: f32) -> Mat4 {
+        let tan_left = self.left.tan();
+        let tan_right = self.right.tan();
+        let tan_up = self.up.tan();
+        let tan_down = self.down.tan();
+        let tan_width = tan_right - tan_left;
+        let tan_height = tan_up - tan_down;
+        // maps near to a depth of 1 and far to 0
+        let (depth_scale, depth_offset) = if far.is_finite() {
+            (near / (far - near), near * far / (far - near))
+        } else {
+            (0.0, near)
+        };
+        Mat4::from_cols_array(&[
+            2.0 / tan_width,
+            0.0,
+            0.0,
+            0.0,
+            0.0,
+            2.0 / tan_height,
+            0.0,
+            0.0,
+            (tan_right + tan_left) / tan_width,
+            (tan_up + tan_down) / tan_height,
+            depth_scale,
+            -1.0,
+            0.0,
+            0.0,
+            depth_offset,
+            0.0,
+        ])
+    }
+}
+
+/// The pose and field of view of a view, e.g. an eye.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Reflect)]
+pub struct XrView {
+    /// The pose relative to the [`XrTrackingRoot`](crate::session::XrTrackingRoot).
+    pub pose: Transform,
+    pub fov: XrFov,
+}
+
+impl XrView {
+    /// Returns the projection of this view with bevy's reversed depth and the far plane at infinity,
+    /// like [`Mat4::perspective_infinite_reverse_rh`] but with the asymmetric [`fov`](Self::fov).
+    pub fn perspective_rh_reverse_z(&self, near: f32) -> Mat4 {
+        self.fov.projection_matrix(near, f32::INFINITY)
+    }
+
+    /// Returns the projection of this view with bevy's reversed depth, mapping `near` to a depth of 1 and `far` to 0.
+    pub fn perspective_rh_reverse_z_finite(&self, near: f32, far: f32) -> Mat4 {
+        self.fov.projection_matrix(near, far)
+    }
+}
+
+/// The latest located views, indexed like the [`XrCamera`]s. It is the backends responsibility to update this.
+#[derive(Clone, Debug, Default, Resource)]
+pub struct XrViewData(pub Vec<XrView>);
+
+/// Whether the head of the user is currently tracked, aggregated from the flags of the located views.
+/// It is the backends responsibility to update this.
+///
+/// The views keep their last or an inferred pose while tracking is lost, apps can use this to show a
+/// "tracking lost" indicator or to pause the game.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Resource)]
+pub struct XrTrackingState {
+    /// Both the orientation and the position of the views are tracked.
+    pub head_tracked: bool,
+}
+
 /// Marker component for an XR view. It is the backends responsibility to update this.
 #[derive(Clone, Copy, Component, ExtractComponent, Debug, Default)]
 pub struct XrCamera(pub u32);
@@ -101,6 +187,10 @@ impl CameraProjection for XrProjection {
     }
 }
 
+/// A camera rendering one of the XR views, which is selected by the [`XrCamera`].
+///
+/// The backend spawns one for every view the app didn't spawn one for itself,
+/// and updates the render target, pose and projection of all of them.
 #[derive(Bundle)]
 pub struct XrCameraBundle {
     pub camera: Camera,
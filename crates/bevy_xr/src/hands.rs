@@ -143,6 +143,38 @@ impl HandBone {
         )
     }
 
+    /// Returns the name of the bone in snake case, e.g. `index_metacarpal`.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            HandBone::Palm => "palm",
+            HandBone::Wrist => "wrist",
+            HandBone::ThumbMetacarpal => "thumb_metacarpal",
+            HandBone::ThumbProximal => "thumb_proximal",
+            HandBone::ThumbDistal => "thumb_distal",
+            HandBone::ThumbTip => "thumb_tip",
+            HandBone::IndexMetacarpal => "index_metacarpal",
+            HandBone::IndexProximal => "index_proximal",
+            HandBone::IndexIntermediate => "index_intermediate",
+            HandBone::IndexDistal => "index_distal",
+            HandBone::IndexTip => "index_tip",
+            HandBone::MiddleMetacarpal => "middle_metacarpal",
+            HandBone::MiddleProximal => "middle_proximal",
+            HandBone::MiddleIntermediate => "middle_intermediate",
+            HandBone::MiddleDistal => "middle_distal",
+            HandBone::MiddleTip => "middle_tip",
+            HandBone::RingMetacarpal => "ring_metacarpal",
+            HandBone::RingProximal => "ring_proximal",
+            HandBone::RingIntermediate => "ring_intermediate",
+            HandBone::RingDistal => "ring_distal",
+            HandBone::RingTip => "ring_tip",
+            HandBone::LittleMetacarpal => "little_metacarpal",
+            HandBone::LittleProximal => "little_proximal",
+            HandBone::LittleIntermediate => "little_intermediate",
+            HandBone::LittleDistal => "little_distal",
+            HandBone::LittleTip => "little_tip",
+        }
+    }
+
     pub const fn get_all_bones() -> [HandBone; 26] {
         [
             HandBone::Palm,
@@ -1,8 +1,10 @@
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
+use std::time::Duration;
 
 use bevy::app::{AppExit, MainScheduleOrder};
 use bevy::ecs::schedule::ScheduleLabel;
+use bevy::ecs::world::Command;
 use bevy::prelude::*;
 use bevy::render::extract_resource::{ExtractResource, ExtractResourcePlugin};
 use bevy::render::{Render, RenderApp, RenderSet};
@@ -82,17 +84,145 @@ pub enum XrRenderSet {
 #[derive(ExtractResource, Resource, Clone, Copy, Default)]
 pub struct XrRootTransform(pub GlobalTransform);
 
+/// Whether the frames of the session are shown to the user, e.g. `false` while the headset is taken off or the app is hidden.
+///
+/// Backends skip rendering the views while this is `false` but keep the frame loop running,
+/// gameplay systems can check it to throttle expensive work.
+#[derive(Resource, Clone, Copy, Debug, Default, Deref, PartialEq, Eq)]
+pub struct XrShouldRender(pub bool);
+
+/// Whether the session has input focus, e.g. `false` while a system menu is shown on top of the app.
+///
+/// Frames are still submitted while the session is only visible or synchronized, but actions aren't updated,
+/// so gameplay reacting to input should pause until the session is focused again.
+#[derive(Resource, Clone, Copy, Debug, Default, Deref, PartialEq, Eq)]
+pub struct XrSessionFocused(pub bool);
+
+/// Statistics about the frame loop, updated by the backend every frame, e.g. to show them in a debug overlay.
+///
+/// Helps telling hitches apart: dropped frames without image wait timeouts usually mean the app missed the compositor,
+/// while image wait timeouts point at the GPU.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq)]
+pub struct XrFrameStats {
+    /// Frames ended by the backend, including frames submitted without layers.
+    pub frames_submitted: u64,
+    /// Frames the runtime didn't want to be rendered, see [`XrShouldRender`].
+    pub frames_not_rendered: u64,
+    /// How often waiting for a swapchain image timed out.
+    /// Only increases if the backend is configured to wait with a timeout, frames whose images timed out aren't rendered.
+    pub image_wait_timeouts: u64,
+    /// Frames the compositor most likely dropped, inferred from gaps between the predicted display times.
+    pub dropped_frames: u64,
+    /// Rolling average of the time between the predicted display times of consecutive frames.
+    pub average_display_interval: Duration,
+}
+
+impl XrFrameStats {
+    /// Weight of the newest interval in [`average_display_interval`](Self::average_display_interval).
+    const AVERAGE_WEIGHT: f64 = 0.1;
+
+    /// Records the time between the predicted display times of two consecutive frames.
+    ///
+    /// Every display `period` that fits into the `interval` after the first one counts as a dropped frame.
+    pub fn record_display_interval(&mut self, interval: Duration, period: Duration) {
+        self.average_display_interval = if self.average_display_interval.is_zero() {
+            interval
+        } else {
+            let average = self.average_display_interval.as_secs_f64();
+            Duration::from_secs_f64(
+                average + (interval.as_secs_f64() - average) * Self::AVERAGE_WEIGHT,
+            )
+        };
+        if !period.is_zero() {
+            let periods = (interval.as_secs_f64() / period.as_secs_f64()).round() as u64;
+            self.dropped_frames += periods.saturating_sub(1);
+        }
+    }
+}
+
+/// Steps [`FixedUpdate`] in lockstep with the display, e.g. for physics,
+/// which avoids the judder of a fixed timestep that doesn't match the refresh rate.
+///
+/// Insert this resource to enable it. The backend keeps the [`display_period`](Self::display_period) up to date,
+/// including when the refresh rate changes, and the timestep of [`Time<Fixed>`] is set to [`timestep`](Self::timestep).
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct XrFixedTimestep {
+    /// Fixed steps per displayed frame, e.g. 2 to step twice as often as the display refreshes.
+    pub steps_per_frame: u32,
+    /// The time between two displayed frames, [`None`] until the first frame was waited for.
+    pub display_period: Option<Duration>,
+}
+
+impl Default for XrFixedTimestep {
+    fn default() -> Self {
+        Self {
+            steps_per_frame: 1,
+            display_period: None,
+        }
+    }
+}
+
+impl XrFixedTimestep {
+    /// Returns the timestep of [`FixedUpdate`], or [`None`] if the display period isn't known yet.
+    pub fn timestep(&self) -> Option<Duration> {
+        self.display_period
+            .filter(|period| !period.is_zero())
+            .map(|period| period / self.steps_per_frame.max(1))
+    }
+}
+
+fn apply_fixed_timestep(fixed_timestep: Res<XrFixedTimestep>, mut time: ResMut<Time<Fixed>>) {
+    if let Some(timestep) = fixed_timestep.timestep() {
+        if time.timestep() != timestep {
+            time.set_timestep(timestep);
+        }
+    }
+}
+
 /// Component used to specify the entity we should use as the tracking root.
+///
+/// The cameras, hands and tracked spaces are children of this entity, so moving it moves the user through the world
+/// while everything tracked stays aligned, e.g. to teleport or to offset a seated experience.
 #[derive(Component)]
 pub struct XrTrackingRoot;
 
+/// Sets the [`Transform`] of the [`XrTrackingRoot`].
+///
+/// The offset is used by the views rendered in the same frame, so setting it every frame moves the user smoothly.
+#[derive(Clone, Copy, Debug)]
+pub struct XrSetRootOffset(pub Transform);
+
+impl Command for XrSetRootOffset {
+    fn apply(self, world: &mut World) {
+        let mut query = world.query_filtered::<&mut Transform, With<XrTrackingRoot>>();
+        match query.get_single_mut(world) {
+            Ok(mut transform) => *transform = self.0,
+            Err(err) => warn!("unable to set root offset: {err}"),
+        }
+    }
+}
+
+pub trait XrRootOffsetCommandsExt {
+    /// Sets the [`Transform`] of the [`XrTrackingRoot`], see [`XrSetRootOffset`].
+    fn set_root_offset(&mut self, offset: Transform);
+}
+
+impl XrRootOffsetCommandsExt for Commands<'_, '_> {
+    fn set_root_offset(&mut self, offset: Transform) {
+        self.add(XrSetRootOffset(offset));
+    }
+}
+
 pub struct XrSessionPlugin {
     pub auto_handle: bool,
 }
 
 impl Plugin for XrSessionPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<XrDestroySessionRender>();
+        app.init_resource::<XrDestroySessionRender>()
+            .init_resource::<XrShouldRender>()
+            .init_resource::<XrSessionFocused>()
+            .init_resource::<XrFrameStats>();
         let mut xr_first = Schedule::new(XrFirst);
         xr_first.set_executor_kind(bevy::ecs::schedule::ExecutorKind::Simple);
         app.add_event::<XrCreateSessionEvent>()
@@ -124,6 +254,12 @@ impl Plugin for XrSessionPlugin {
                     .run_if(on_event::<AppExit>())
                     .run_if(session_created)
                     .in_set(XrHandleEvents::ExitEvents),
+            )
+            .add_systems(
+                XrFirst,
+                apply_fixed_timestep
+                    .after(XrHandleEvents::FrameLoop)
+                    .run_if(resource_exists_and_changed::<XrFixedTimestep>),
             );
         app.world_mut()
             .resource_mut::<MainScheduleOrder>()
@@ -289,6 +425,11 @@ pub fn session_running(status: Option<Res<XrState>>) -> bool {
     matches!(status.as_deref(), Some(XrState::Running))
 }
 
+/// A [`Condition`](bevy::ecs::schedule::Condition) system that says if the XR session has input focus, see [`XrSessionFocused`].
+pub fn session_focused(focused: Option<Res<XrSessionFocused>>) -> bool {
+    focused.is_some_and(|focused| focused.0)
+}
+
 /// A function that returns a [`Condition`](bevy::ecs::schedule::Condition) system that says if the [`XrState`] is in a specific state
 pub fn state_equals(status: XrState) -> impl FnMut(Option<Res<XrState>>) -> bool {
     move |state: Option<Res<XrState>>| state.is_some_and(|s| *s == status)
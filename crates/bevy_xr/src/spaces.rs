@@ -4,7 +4,11 @@ use bevy::{
     render::{extract_component::ExtractComponent, extract_resource::ExtractResource},
 };
 
-/// Any Spaces will be invalid after the owning session exits
+/// Handle to any space of the session, e.g. a reference space, an action space or an anchor.
+///
+/// Any space can be located relative to any other space, the [`XrPrimaryReferenceSpace`] is simply the one entities are located in by default.
+/// The handle is [`Copy`] so it can be shared with the render world, it is destroyed by sending an [`XrDestroySpace`] event
+/// or together with the owning session.
 #[repr(transparent)]
 #[derive(Clone, Copy, Hash, PartialEq, Eq, Reflect, Debug, ExtractComponent)]
 pub struct XrSpace(u64);
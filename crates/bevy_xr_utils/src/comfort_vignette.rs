@@ -0,0 +1,359 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::{
+    asset::load_internal_asset,
+    core_pipeline::{
+        core_3d::graph::{Core3d, Node3d},
+        fullscreen_vertex_shader::fullscreen_shader_vertex_state,
+    },
+    ecs::query::QueryItem,
+    prelude::*,
+    render::{
+        extract_resource::{ExtractResource, ExtractResourcePlugin},
+        render_graph::{
+            NodeRunError, RenderGraphApp, RenderGraphContext, RenderLabel, ViewNode, ViewNodeRunner,
+        },
+        render_resource::{
+            binding_types::{sampler, texture_2d, uniform_buffer},
+            BindGroupEntries, BindGroupLayout, BindGroupLayoutEntries, CachedRenderPipelineId,
+            ColorTargetState, ColorWrites, FragmentState, MultisampleState, Operations,
+            PipelineCache, PrimitiveState, RenderPassColorAttachment, RenderPassDescriptor,
+            RenderPipelineDescriptor, Sampler, SamplerBindingType, SamplerDescriptor, ShaderStages,
+            ShaderType, SpecializedRenderPipeline, SpecializedRenderPipelines, TextureFormat,
+            TextureSampleType, UniformBuffer,
+        },
+        renderer::{RenderContext, RenderDevice, RenderQueue},
+        texture::BevyDefault,
+        view::{ExtractedView, ViewTarget, ViewUniform, ViewUniformOffset, ViewUniforms},
+        Render, RenderApp, RenderSet,
+    },
+};
+use bevy_mod_xr::camera::XrCamera;
+
+/// Handle of the shader drawing the [`XrComfortVignette`].
+pub const COMFORT_VIGNETTE_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(0x91c4_3a7e_5d02_4b6f_8e19_c2f5_06ad_7b3e);
+
+/// Draws a tunneling vignette over the views of the [`XrCamera`]s that closes in while the user moves,
+/// which reduces motion sickness during artificial locomotion.
+///
+/// Write the current speed of the locomotion to the [`XrLocomotionSpeed`] every frame,
+/// the vignette is configured with the [`XrComfortVignette`].
+/// The vignette is centered on the direction of each view and covers the same angles in all views,
+/// so both eyes see it at the same place and it doesn't cause a depth mismatch.
+pub struct XrComfortVignettePlugin;
+
+impl Plugin for XrComfortVignettePlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            COMFORT_VIGNETTE_SHADER_HANDLE,
+            "comfort_vignette.wgsl",
+            Shader::from_wgsl
+        );
+
+        app.init_resource::<XrComfortVignette>()
+            .init_resource::<XrLocomotionSpeed>()
+            .init_resource::<ComfortVignetteState>()
+            .add_plugins(ExtractResourcePlugin::<ComfortVignetteState>::default())
+            .add_systems(PostUpdate, update_vignette_state);
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app
+            .init_resource::<SpecializedRenderPipelines<ComfortVignettePipeline>>()
+            .add_systems(
+                Render,
+                (
+                    prepare_vignette_pipelines.in_set(RenderSet::Prepare),
+                    prepare_vignette_uniform
+                        .in_set(RenderSet::PrepareResources)
+                        .run_if(resource_exists::<ComfortVignetteState>),
+                ),
+            )
+            .add_render_graph_node::<ViewNodeRunner<ComfortVignetteNode>>(
+                Core3d,
+                ComfortVignetteLabel,
+            )
+            .add_render_graph_edges(
+                Core3d,
+                (
+                    Node3d::Tonemapping,
+                    ComfortVignetteLabel,
+                    Node3d::EndMainPassPostProcessing,
+                ),
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app.init_resource::<ComfortVignettePipeline>();
+    }
+}
+
+/// Configures the vignette drawn by the [`XrComfortVignettePlugin`].
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct XrComfortVignette {
+    /// How far the vignette closes in at the [`full_speed`](Self::full_speed), 0 disables it.
+    pub strength: f32,
+    /// The angle from the view direction in radians that stays visible when the vignette is fully closed.
+    pub inner_radius: f32,
+    /// The color of the covered area, its alpha makes the vignette translucent.
+    pub color: Color,
+    /// The [`XrLocomotionSpeed`] at which the vignette is fully closed.
+    pub full_speed: f32,
+}
+
+impl Default for XrComfortVignette {
+    fn default() -> Self {
+        Self {
+            strength: 1.0,
+            inner_radius: 0.5,
+            color: Color::BLACK,
+            full_speed: 3.0,
+        }
+    }
+}
+
+/// The current speed of the artificial locomotion of the user, e.g. in meters per second.
+///
+/// Only movement that doesn't come from tracking should be included, the vignette would close in
+/// whenever the user walks around in the real world otherwise.
+#[derive(Resource, Clone, Copy, Debug, Default, Deref, DerefMut)]
+pub struct XrLocomotionSpeed(pub f32);
+
+/// The smoothed radius and color of the vignette, extracted to the render world.
+#[derive(Resource, Clone, Copy, Debug, ExtractResource)]
+struct ComfortVignetteState {
+    /// How far the vignette is closed, from 0 to 1.
+    amount: f32,
+    radius: f32,
+    color: LinearRgba,
+}
+
+impl Default for ComfortVignetteState {
+    fn default() -> Self {
+        Self {
+            amount: 0.0,
+            radius: FRAC_PI_2,
+            color: LinearRgba::BLACK,
+        }
+    }
+}
+
+/// How quickly the vignette follows changes of the speed, higher is faster.
+const VIGNETTE_RESPONSE: f32 = 8.0;
+
+/// The angle in radians over which the vignette fades in at its edge.
+const VIGNETTE_FEATHER: f32 = 0.15;
+
+fn update_vignette_state(
+    time: Res<Time>,
+    vignette: Res<XrComfortVignette>,
+    speed: Res<XrLocomotionSpeed>,
+    mut state: ResMut<ComfortVignetteState>,
+) {
+    let target = if vignette.full_speed > 0.0 {
+        (speed.0.abs() / vignette.full_speed).min(1.0) * vignette.strength.clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    // ease towards the target so the vignette doesn't pop in when the user starts moving
+    let response = 1.0 - (-VIGNETTE_RESPONSE * time.delta_seconds()).exp();
+    let amount = state.amount + (target - state.amount) * response;
+    let amount = if amount < 0.001 { 0.0 } else { amount };
+    let inner_radius = vignette.inner_radius.clamp(0.0, FRAC_PI_2);
+    *state = ComfortVignetteState {
+        amount,
+        radius: FRAC_PI_2 + (inner_radius - FRAC_PI_2) * amount,
+        color: vignette.color.into(),
+    };
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+struct ComfortVignetteLabel;
+
+/// Matches `XrComfortVignette` in the shader.
+#[derive(ShaderType, Clone, Copy, Debug, Default)]
+struct ComfortVignetteUniform {
+    color: Vec4,
+    radius: f32,
+    feather: f32,
+}
+
+#[derive(Resource, Default)]
+struct ComfortVignetteUniformBuffer(UniformBuffer<ComfortVignetteUniform>);
+
+fn prepare_vignette_uniform(
+    state: Res<ComfortVignetteState>,
+    device: Res<RenderDevice>,
+    queue: Res<RenderQueue>,
+    buffer: Option<ResMut<ComfortVignetteUniformBuffer>>,
+    mut commands: Commands,
+) {
+    let color = state.color;
+    let uniform = ComfortVignetteUniform {
+        color: Vec4::new(color.red, color.green, color.blue, color.alpha),
+        radius: state.radius,
+        feather: VIGNETTE_FEATHER,
+    };
+    match buffer {
+        Some(mut buffer) => {
+            buffer.0.set(uniform);
+            buffer.0.write_buffer(&device, &queue);
+        }
+        None => {
+            let mut buffer = UniformBuffer::from(uniform);
+            buffer.set_label(Some("xr_comfort_vignette"));
+            buffer.write_buffer(&device, &queue);
+            commands.insert_resource(ComfortVignetteUniformBuffer(buffer));
+        }
+    }
+}
+
+#[derive(Resource)]
+struct ComfortVignettePipeline {
+    layout: BindGroupLayout,
+    sampler: Sampler,
+}
+
+impl FromWorld for ComfortVignettePipeline {
+    fn from_world(world: &mut World) -> Self {
+        let device = world.resource::<RenderDevice>();
+        let layout = device.create_bind_group_layout(
+            "xr_comfort_vignette_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::FRAGMENT,
+                (
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                    sampler(SamplerBindingType::Filtering),
+                    uniform_buffer::<ViewUniform>(true),
+                    uniform_buffer::<ComfortVignetteUniform>(false),
+                ),
+            ),
+        );
+        let sampler = device.create_sampler(&SamplerDescriptor::default());
+        Self { layout, sampler }
+    }
+}
+
+impl SpecializedRenderPipeline for ComfortVignettePipeline {
+    /// Whether the view is hdr.
+    type Key = bool;
+
+    fn specialize(&self, hdr: Self::Key) -> RenderPipelineDescriptor {
+        let format = if hdr {
+            ViewTarget::TEXTURE_FORMAT_HDR
+        } else {
+            TextureFormat::bevy_default()
+        };
+        RenderPipelineDescriptor {
+            label: Some("xr_comfort_vignette_pipeline".into()),
+            layout: vec![self.layout.clone()],
+            vertex: fullscreen_shader_vertex_state(),
+            fragment: Some(FragmentState {
+                shader: COMFORT_VIGNETTE_SHADER_HANDLE,
+                shader_defs: vec![],
+                entry_point: "fragment".into(),
+                targets: vec![Some(ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            push_constant_ranges: vec![],
+        }
+    }
+}
+
+#[derive(Component)]
+struct ComfortVignettePipelineId(CachedRenderPipelineId);
+
+fn prepare_vignette_pipelines(
+    mut commands: Commands,
+    pipeline_cache: Res<PipelineCache>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<ComfortVignettePipeline>>,
+    pipeline: Res<ComfortVignettePipeline>,
+    views: Query<(Entity, &ExtractedView), With<XrCamera>>,
+) {
+    for (entity, view) in &views {
+        let id = pipelines.specialize(&pipeline_cache, &pipeline, view.hdr);
+        commands
+            .entity(entity)
+            .insert(ComfortVignettePipelineId(id));
+    }
+}
+
+#[derive(Default)]
+struct ComfortVignetteNode;
+
+impl ViewNode for ComfortVignetteNode {
+    type ViewQuery = (
+        &'static ViewTarget,
+        &'static ViewUniformOffset,
+        &'static ComfortVignettePipelineId,
+    );
+
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (view_target, view_uniform_offset, pipeline_id): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        if world
+            .get_resource::<ComfortVignetteState>()
+            .map_or(true, |state| state.amount == 0.0)
+        {
+            return Ok(());
+        }
+        let pipeline = world.resource::<ComfortVignettePipeline>();
+        let Some(render_pipeline) = world
+            .resource::<PipelineCache>()
+            .get_render_pipeline(pipeline_id.0)
+        else {
+            return Ok(());
+        };
+        let (Some(view_binding), Some(vignette_binding)) = (
+            world.resource::<ViewUniforms>().uniforms.binding(),
+            world
+                .get_resource::<ComfortVignetteUniformBuffer>()
+                .and_then(|buffer| buffer.0.binding()),
+        ) else {
+            return Ok(());
+        };
+
+        let post_process = view_target.post_process_write();
+        let bind_group = render_context.render_device().create_bind_group(
+            "xr_comfort_vignette_bind_group",
+            &pipeline.layout,
+            &BindGroupEntries::sequential((
+                post_process.source,
+                &pipeline.sampler,
+                view_binding,
+                vignette_binding,
+            )),
+        );
+        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("xr_comfort_vignette_pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: post_process.destination,
+                resolve_target: None,
+                ops: Operations::default(),
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_render_pipeline(render_pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[view_uniform_offset.offset]);
+        render_pass.draw(0..3, 0..1);
+        Ok(())
+    }
+}
@@ -0,0 +1,28 @@
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_render::view::View
+
+// Matches `ComfortVignetteUniform`.
+struct XrComfortVignette {
+    color: vec4<f32>,
+    // The angle from the view direction in radians where the vignette is fully faded in.
+    radius: f32,
+    feather: f32,
+}
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var screen_sampler: sampler;
+@group(0) @binding(2) var<uniform> view: View;
+@group(0) @binding(3) var<uniform> vignette: XrComfortVignette;
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(screen_texture, screen_sampler, in.uv);
+    // the angle is measured in view space, so the vignette covers the same directions in every view
+    // even if the fovs are asymmetric
+    let ndc = vec2(in.uv.x * 2.0 - 1.0, 1.0 - in.uv.y * 2.0);
+    let view_position = view.view_from_clip * vec4(ndc, 1.0, 1.0);
+    let direction = normalize(view_position.xyz / view_position.w);
+    let angle = acos(clamp(-direction.z, -1.0, 1.0));
+    let coverage = smoothstep(vignette.radius - vignette.feather, vignette.radius, angle) * vignette.color.a;
+    return vec4(mix(color.rgb, vignette.color.rgb, coverage), color.a);
+}
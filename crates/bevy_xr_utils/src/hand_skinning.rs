@@ -0,0 +1,192 @@
+use std::borrow::Cow;
+
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::{
+            skinning::{SkinnedMesh, SkinnedMeshInverseBindposes},
+            Indices, PrimitiveTopology, VertexAttributeValues,
+        },
+        render_asset::RenderAssetUsages,
+    },
+    transform::TransformSystem,
+};
+use bevy_mod_xr::hands::{HandBone, XrHandBoneEntities, HAND_JOINT_COUNT};
+
+/// Drives skinned hand meshes with the tracked [`HandBone`]s.
+///
+/// Add [`XrHandSkin`] to the root of a hand model, e.g. a glTF scene, to bind its joints to the bones of a hand tracker,
+/// or use [`hand_mesh`] and [`hand_skinned_mesh`] for a low-poly hand without any assets.
+/// The backends only update the bones with valid poses, so untracked joints keep their last valid pose instead of popping.
+pub struct HandSkinningPlugin;
+
+impl Plugin for HandSkinningPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            bind_hand_skins.before(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+/// Binds the joints of the [`SkinnedMesh`]es of this entity and its descendants to the bones of `hand`,
+/// the entity with the [`XrHandBoneEntities`], once the meshes are spawned.
+///
+/// Joints are matched by their [`Name`] and joints without a matching bone are left as they are.
+/// The joints of the model need the same orientation as the tracked bones,
+/// -Z pointing towards the finger tips and +Y out of the back of the hand, otherwise the mesh is distorted.
+#[derive(Component, Clone, Debug)]
+pub struct XrHandSkin {
+    pub hand: Entity,
+    /// The joint name of every bone, indexed by [`HandBone`]. Defaults to [`HandBone::name`].
+    pub joint_names: [Cow<'static, str>; HAND_JOINT_COUNT],
+}
+
+impl XrHandSkin {
+    pub fn new(hand: Entity) -> Self {
+        Self {
+            hand,
+            joint_names: HandBone::get_all_bones().map(|bone| Cow::Borrowed(bone.name())),
+        }
+    }
+}
+
+/// Added to an [`XrHandSkin`] once its meshes are bound.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct XrHandSkinBound;
+
+fn bind_hand_skins(
+    skins: Query<(Entity, &XrHandSkin), Without<XrHandSkinBound>>,
+    hands: Query<&XrHandBoneEntities>,
+    children: Query<&Children>,
+    names: Query<&Name>,
+    mut meshes: Query<&mut SkinnedMesh>,
+    mut cmds: Commands,
+) {
+    for (entity, skin) in &skins {
+        let Ok(bones) = hands.get(skin.hand) else {
+            continue;
+        };
+        let mut bound = false;
+        for descendant in std::iter::once(entity).chain(children.iter_descendants(entity)) {
+            let Ok(mut mesh) = meshes.get_mut(descendant) else {
+                continue;
+            };
+            for joint in &mut mesh.joints {
+                let Some(bone) = names.get(*joint).ok().and_then(|name| {
+                    skin.joint_names
+                        .iter()
+                        .position(|joint_name| joint_name == name.as_str())
+                }) else {
+                    continue;
+                };
+                *joint = bones[bone];
+            }
+            bound = true;
+        }
+        if bound {
+            cmds.entity(entity).insert(XrHandSkinBound);
+        }
+    }
+}
+
+/// Returns a low-poly hand made of one box per bone, each skinned to its [`HandBone`] with an identity inverse bind pose.
+///
+/// Use [`hand_skinned_mesh`] to drive it with a hand tracker.
+/// The mesh is built around the origin, so entities using it need [`NoFrustumCulling`](bevy::render::view::NoFrustumCulling).
+pub fn hand_mesh() -> Mesh {
+    let mut positions = vec![];
+    let mut normals = vec![];
+    let mut joint_indices = vec![];
+    let mut joint_weights = vec![];
+    let mut indices = vec![];
+    for bone in HandBone::get_all_bones() {
+        let size = bone_size(bone);
+        // bones point along -Z towards the finger tips, the palm is centered on its joint
+        let offset = match bone {
+            HandBone::Palm => Vec3::ZERO,
+            _ => Vec3::new(0.0, 0.0, -size.z / 2.0),
+        };
+        let cuboid = Mesh::from(Cuboid::from_size(size));
+        let (
+            Some(VertexAttributeValues::Float32x3(cuboid_positions)),
+            Some(VertexAttributeValues::Float32x3(cuboid_normals)),
+            Some(cuboid_indices),
+        ) = (
+            cuboid.attribute(Mesh::ATTRIBUTE_POSITION),
+            cuboid.attribute(Mesh::ATTRIBUTE_NORMAL),
+            cuboid.indices(),
+        )
+        else {
+            continue;
+        };
+        let start = positions.len() as u32;
+        indices.extend(cuboid_indices.iter().map(|index| start + index as u32));
+        positions.extend(
+            cuboid_positions
+                .iter()
+                .map(|position| (Vec3::from(*position) + offset).to_array()),
+        );
+        normals.extend_from_slice(cuboid_normals);
+        joint_indices.extend(cuboid_positions.iter().map(|_| [bone as u16, 0, 0, 0]));
+        joint_weights.extend(cuboid_positions.iter().map(|_| [1.0, 0.0, 0.0, 0.0]));
+    }
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_attribute(
+        Mesh::ATTRIBUTE_JOINT_INDEX,
+        VertexAttributeValues::Uint16x4(joint_indices),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_JOINT_WEIGHT, joint_weights)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+/// Returns a [`SkinnedMesh`] driving a [`hand_mesh`] with `bones`.
+pub fn hand_skinned_mesh(
+    bones: &XrHandBoneEntities,
+    inverse_bindposes: &mut Assets<SkinnedMeshInverseBindposes>,
+) -> SkinnedMesh {
+    SkinnedMesh {
+        inverse_bindposes: inverse_bindposes.add(SkinnedMeshInverseBindposes::from(vec![
+            Mat4::IDENTITY;
+            HAND_JOINT_COUNT
+        ])),
+        joints: bones.to_vec(),
+    }
+}
+
+/// The size of the box of `bone` in meters, its length is along Z.
+fn bone_size(bone: HandBone) -> Vec3 {
+    match bone {
+        HandBone::Palm => Vec3::new(0.08, 0.02, 0.08),
+        HandBone::Wrist => Vec3::new(0.06, 0.025, 0.03),
+        HandBone::ThumbMetacarpal => Vec3::new(0.022, 0.02, 0.04),
+        HandBone::ThumbProximal => Vec3::new(0.02, 0.018, 0.032),
+        HandBone::ThumbDistal => Vec3::new(0.018, 0.016, 0.028),
+        HandBone::IndexMetacarpal
+        | HandBone::MiddleMetacarpal
+        | HandBone::RingMetacarpal
+        | HandBone::LittleMetacarpal => Vec3::new(0.016, 0.014, 0.05),
+        HandBone::IndexProximal | HandBone::MiddleProximal | HandBone::RingProximal => {
+            Vec3::new(0.017, 0.015, 0.04)
+        }
+        HandBone::LittleProximal => Vec3::new(0.014, 0.013, 0.03),
+        HandBone::IndexIntermediate | HandBone::MiddleIntermediate | HandBone::RingIntermediate => {
+            Vec3::new(0.016, 0.014, 0.025)
+        }
+        HandBone::LittleIntermediate => Vec3::new(0.013, 0.012, 0.02),
+        HandBone::IndexDistal | HandBone::MiddleDistal | HandBone::RingDistal => {
+            Vec3::new(0.015, 0.013, 0.02)
+        }
+        HandBone::LittleDistal => Vec3::new(0.012, 0.011, 0.018),
+        HandBone::ThumbTip
+        | HandBone::IndexTip
+        | HandBone::MiddleTip
+        | HandBone::RingTip
+        | HandBone::LittleTip => Vec3::splat(0.008),
+    }
+}
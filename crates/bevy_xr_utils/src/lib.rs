@@ -1,4 +1,8 @@
+pub mod comfort_vignette;
 pub mod hand_gizmos;
+pub mod hand_skinning;
+#[cfg(not(target_family = "wasm"))]
+pub mod raycast;
 #[cfg(not(target_family = "wasm"))]
 pub mod tracking_utils;
 #[cfg(not(target_family = "wasm"))]
@@ -0,0 +1,136 @@
+use bevy::{
+    ecs::system::SystemParam,
+    math::bounding::{Aabb3d, BoundingSphere, RayCast3d},
+    prelude::*,
+    render::primitives::Aabb,
+};
+use bevy_mod_openxr::{
+    helper_traits::ToTransform,
+    resources::{OxrFrameState, Pipelined},
+    session::OxrSession,
+    spaces::OxrSpaceLocationFlags,
+};
+use bevy_mod_xr::{
+    session::XrRootTransform,
+    spaces::{XrPrimaryReferenceSpace, XrReferenceSpace, XrSpace},
+};
+
+/// Builds world space rays from tracked spaces, e.g. the aim space of a controller or the eye gaze space.
+///
+/// The rays start at the origin of the space and point along its forward direction,
+/// the [`XrRootTransform`] is applied so they are in the same space as the rest of the world.
+#[derive(SystemParam)]
+pub struct XrRaycaster<'w> {
+    session: Option<Res<'w, OxrSession>>,
+    frame_state: Option<Res<'w, OxrFrameState>>,
+    primary_ref_space: Option<Res<'w, XrPrimaryReferenceSpace>>,
+    root: Res<'w, XrRootTransform>,
+    pipelined: Option<Res<'w, Pipelined>>,
+}
+
+impl XrRaycaster<'_> {
+    /// Returns the ray along `space` located in `ref_space`, or the [`XrPrimaryReferenceSpace`] if [`None`].
+    ///
+    /// Returns [`None`] if the session isn't running or the position or orientation of `space` isn't valid.
+    pub fn ray(&self, space: &XrSpace, ref_space: Option<&XrReferenceSpace>) -> Option<Ray3d> {
+        let session = self.session.as_ref()?;
+        let frame_state = self.frame_state.as_ref()?;
+        let ref_space = ref_space.or(self.primary_ref_space.as_deref().map(|space| &space.0))?;
+        let time = frame_state.locate_time(self.pipelined.is_some());
+        let location = match session.locate_space(space, ref_space, time) {
+            Ok(location) => location,
+            Err(err) => {
+                warn!("error while locating raycast space: {}", err);
+                return None;
+            }
+        };
+        let flags = OxrSpaceLocationFlags(location.location_flags);
+        if !flags.pos_valid() || !flags.rot_valid() {
+            return None;
+        }
+        self.ray_from_pose(location.pose.to_transform())
+    }
+
+    /// Returns the ray along `pose`, which is relative to the reference space.
+    ///
+    /// Returns [`None`] if the root transform is degenerate, e.g. scaled to zero.
+    pub fn ray_from_pose(&self, pose: Transform) -> Option<Ray3d> {
+        let transform = self.root.0.mul_transform(pose);
+        Some(Ray3d {
+            origin: transform.translation(),
+            direction: Dir3::new(transform.affine().transform_vector3(Vec3::NEG_Z)).ok()?,
+        })
+    }
+}
+
+/// A world space volume that can be hit by [`raycast_colliders`].
+#[derive(Clone, Copy, Debug)]
+pub enum XrRayCollider {
+    Aabb(Aabb3d),
+    Sphere(BoundingSphere),
+}
+
+/// The nearest hit returned by [`raycast_colliders`] and [`raycast_mesh_aabbs`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct XrRayHit<T> {
+    /// What was hit, e.g. the entity of the mesh.
+    pub target: T,
+    /// The distance from the ray origin to the hit.
+    pub distance: f32,
+    /// The world space position of the hit.
+    pub point: Vec3,
+}
+
+/// Returns the nearest of `colliders` hit by `ray` within `max_distance`.
+pub fn raycast_colliders<T>(
+    ray: Ray3d,
+    max_distance: f32,
+    colliders: impl IntoIterator<Item = (T, XrRayCollider)>,
+) -> Option<XrRayHit<T>> {
+    let cast = RayCast3d::from_ray(ray, max_distance);
+    colliders
+        .into_iter()
+        .filter_map(|(target, collider)| {
+            let distance = match collider {
+                XrRayCollider::Aabb(aabb) => cast.aabb_intersection_at(&aabb),
+                XrRayCollider::Sphere(sphere) => cast.sphere_intersection_at(&sphere),
+            }?;
+            Some(XrRayHit {
+                target,
+                distance,
+                point: ray.get_point(distance),
+            })
+        })
+        .min_by(|a, b| a.distance.total_cmp(&b.distance))
+}
+
+/// Returns the nearest of the mesh [`Aabb`]s hit by `ray` within `max_distance`.
+///
+/// The [`Aabb`]s are in the local space of the entities, e.g. `Query<(Entity, &Aabb, &GlobalTransform)>`,
+/// so the hits are only as accurate as the bounding boxes of the meshes.
+pub fn raycast_mesh_aabbs<'a>(
+    ray: Ray3d,
+    max_distance: f32,
+    meshes: impl IntoIterator<Item = (Entity, &'a Aabb, &'a GlobalTransform)>,
+) -> Option<XrRayHit<Entity>> {
+    meshes
+        .into_iter()
+        .filter_map(|(entity, aabb, transform)| {
+            let world_to_local = transform.affine().inverse();
+            let local_ray = Ray3d {
+                origin: world_to_local.transform_point3(ray.origin),
+                direction: Dir3::new(world_to_local.transform_vector3(*ray.direction)).ok()?,
+            };
+            let local_aabb = Aabb3d::new(Vec3::from(aabb.center), Vec3::from(aabb.half_extents));
+            let local_distance =
+                RayCast3d::from_ray(local_ray, f32::MAX).aabb_intersection_at(&local_aabb)?;
+            let point = transform.transform_point(local_ray.get_point(local_distance));
+            let distance = point.distance(ray.origin);
+            (distance <= max_distance).then_some(XrRayHit {
+                target: entity,
+                distance,
+                point,
+            })
+        })
+        .min_by(|a, b| a.distance.total_cmp(&b.distance))
+}
@@ -1,10 +1,18 @@
+use std::time::Duration;
+
 use bevy::prelude::*;
 use bevy_mod_openxr::{
-    action_binding::{OxrSendActionBindings, OxrSuggestActionBinding}, action_set_attaching::OxrAttachActionSet, action_set_syncing::{OxrActionSetSyncSet, OxrSyncActionSet}, helper_traits::{ToQuat, ToVec3}, openxr_session_available, openxr_session_running, resources::{OxrFrameState, OxrInstance, Pipelined}, session::OxrSession, spaces::{OxrSpaceLocationFlags, OxrSpaceSyncSet}
+    action_binding::{OxrSendActionBindings, OxrSuggestActionBinding}, action_set_attaching::OxrAttachActionSet, action_set_syncing::{OxrActionSetSyncSet, OxrSyncActionSet}, helper_traits::{ToQuat, ToVec3}, interaction_profiles::OxrCurrentInteractionProfiles, openxr_session_available, openxr_session_running, resources::{OxrFrameState, OxrInstance, Pipelined}, session::OxrSession, spaces::{OxrSpaceLocationFlags, OxrSpaceSyncSet}
 };
 use bevy_mod_xr::{
-    session::{session_available, session_running, XrSessionCreated, XrTrackingRoot},
-    spaces::{XrPrimaryReferenceSpace, XrReferenceSpace},
+    hands::HandSide,
+    session::{
+        session_available, session_running, XrPreDestroySession, XrSessionCreated, XrTrackingRoot,
+    },
+    spaces::{
+        XrPrimaryReferenceSpace, XrReferenceSpace, XrSpaceLocationFlags, XrSpaceVelocityFlags,
+        XrVelocity,
+    },
     types::XrPose,
 };
 use openxr::Posef;
@@ -19,12 +27,83 @@ pub struct XrTrackedLocalFloor;
 #[derive(Component)]
 pub struct XrTrackedView;
 
+/// Tracks the left grip pose.
+///
+/// Add an [`XrVelocity`] to the entity to also track the velocity of the grip,
+/// the [`XrSpaceVelocityFlags`] state if the velocity is valid.
 #[derive(Component)]
 pub struct XrTrackedLeftGrip;
 
+/// Tracks the right grip pose.
+///
+/// Add an [`XrVelocity`] to the entity to also track the velocity of the grip,
+/// the [`XrSpaceVelocityFlags`] state if the velocity is valid.
 #[derive(Component)]
 pub struct XrTrackedRightGrip;
 
+/// Tracks the left aim pose, pointing forward from the controller.
+///
+/// Attach meshes of held objects to the [`XrTrackedLeftGrip`] and pointer rays to the aim,
+/// the two poses differ in position and rotation. Add [`XrSpaceLocationFlags`] to the entity to know if the pose is tracked.
+#[derive(Component)]
+pub struct XrTrackedLeftAim;
+
+/// Tracks the right aim pose, pointing forward from the controller.
+///
+/// Attach meshes of held objects to the [`XrTrackedRightGrip`] and pointer rays to the aim,
+/// the two poses differ in position and rotation. Add [`XrSpaceLocationFlags`] to the entity to know if the pose is tracked.
+#[derive(Component)]
+pub struct XrTrackedRightAim;
+
+/// Sent when a controller is connected, e.g. when it is turned on or picked up again.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct XrControllerConnected(pub HandSide);
+
+/// Sent when a controller is disconnected, e.g. when it is turned off or the runtime switched to hand tracking.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct XrControllerDisconnected(pub HandSide);
+
+/// Whether the controllers are connected.
+///
+/// A controller is connected while its grip pose action is active, an interaction profile is bound to its hand
+/// and its grip pose is tracked. It only counts as disconnected after the [`disconnect_delay`](Self::disconnect_delay),
+/// so brief tracking dropouts don't send an [`XrControllerDisconnected`].
+/// The tracked grip and aim entities of disconnected controllers are hidden.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct XrControllerConnections {
+    /// How long a controller has to be missing before it counts as disconnected.
+    pub disconnect_delay: Duration,
+    left: ControllerConnection,
+    right: ControllerConnection,
+}
+
+impl Default for XrControllerConnections {
+    fn default() -> Self {
+        Self {
+            disconnect_delay: Duration::from_millis(500),
+            left: default(),
+            right: default(),
+        }
+    }
+}
+
+impl XrControllerConnections {
+    /// Returns whether the controller of `hand` is connected.
+    pub fn is_connected(&self, hand: HandSide) -> bool {
+        match hand {
+            HandSide::Left => self.left.connected,
+            HandSide::Right => self.right.connected,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct ControllerConnection {
+    connected: bool,
+    /// When the controller went missing while still connected.
+    missing_since: Option<Duration>,
+}
+
 pub struct TrackingUtilitiesPlugin;
 
 impl Plugin for TrackingUtilitiesPlugin {
@@ -69,6 +148,47 @@ impl Plugin for TrackingUtilitiesPlugin {
 
         app.add_systems(PreUpdate, update_left_grip.after(OxrSpaceSyncSet));
         app.add_systems(PreUpdate, update_right_grip.after(OxrSpaceSyncSet));
+        app.add_systems(
+            PreUpdate,
+            (
+                update_aim::<LeftAim, XrTrackedLeftAim>,
+                update_aim::<RightAim, XrTrackedRightAim>,
+            )
+                .after(OxrSpaceSyncSet),
+        );
+
+        //controller connections
+        app.add_event::<XrControllerConnected>()
+            .add_event::<XrControllerDisconnected>()
+            .init_resource::<XrControllerConnections>();
+        app.add_systems(
+            PreUpdate,
+            (update_controller_connections, update_controller_visibility)
+                .chain()
+                .after(OxrSpaceSyncSet)
+                .after(OxrActionSetSyncSet)
+                .run_if(openxr_session_running)
+                .run_if(resource_exists::<ControllerActions>),
+        );
+        app.add_systems(XrPreDestroySession, reset_controller_connections);
+
+        //velocities are only located if requested by a tracked entity
+        app.add_systems(
+            PreUpdate,
+            (
+                enable_velocity::<XrTrackedLeftGrip, LeftGrip>,
+                enable_velocity::<XrTrackedRightGrip, RightGrip>,
+            )
+                .before(OxrSpaceSyncSet),
+        );
+        app.add_systems(
+            PreUpdate,
+            (
+                copy_velocity::<LeftGrip, XrTrackedLeftGrip>,
+                copy_velocity::<RightGrip, XrTrackedRightGrip>,
+            )
+                .after(OxrSpaceSyncSet),
+        );
     }
 }
 
@@ -101,14 +221,7 @@ fn update_head_transforms(
 ) {
     for (mut transform, space, ref_space) in &mut query {
         let ref_space = ref_space.unwrap_or(&default_ref_space);
-        let time = if pipelined.is_some() {
-            openxr::Time::from_nanos(
-                frame_state.predicted_display_time.as_nanos()
-                    + frame_state.predicted_display_period.as_nanos(),
-            )
-        } else {
-            frame_state.predicted_display_time
-        };
+        let time = frame_state.locate_time(pipelined.is_some());
         let space_location = session.locate_space(&space.0, ref_space, time);
 
         if let Ok(space_location) = space_location {
@@ -198,12 +311,171 @@ fn update_right_grip(
     }
 }
 
+//aim
+#[derive(Component)]
+struct LeftAim;
+
+#[derive(Component)]
+struct RightAim;
+
+fn update_aim<Source: Component, Tracked: Component>(
+    source: Query<(&Transform, &XrSpaceLocationFlags), (With<Source>, Without<Tracked>)>,
+    mut tracked: Query<
+        (&mut Transform, Option<&mut XrSpaceLocationFlags>),
+        (With<Tracked>, Without<Source>),
+    >,
+) {
+    let Ok((source_transform, source_flags)) = source.get_single() else {
+        return;
+    };
+    for (mut transform, flags) in &mut tracked {
+        *transform = *source_transform;
+        if let Some(mut flags) = flags {
+            *flags = *source_flags;
+        }
+    }
+}
+
+//velocity
+fn enable_velocity<Tracked: Component, Source: Component>(
+    tracked: Query<(), (With<Tracked>, With<XrVelocity>)>,
+    source: Query<Entity, (With<Source>, Without<XrVelocity>)>,
+    mut cmds: Commands,
+) {
+    if tracked.is_empty() {
+        return;
+    }
+    for entity in &source {
+        cmds.entity(entity).insert(XrVelocity::new());
+    }
+}
+
+fn copy_velocity<Source: Component, Tracked: Component>(
+    source: Query<(&XrVelocity, &XrSpaceVelocityFlags), (With<Source>, Without<Tracked>)>,
+    mut tracked: Query<
+        (&mut XrVelocity, Option<&mut XrSpaceVelocityFlags>),
+        (With<Tracked>, Without<Source>),
+    >,
+) {
+    let Ok((source_velocity, source_flags)) = source.get_single() else {
+        return;
+    };
+    for (mut velocity, flags) in &mut tracked {
+        *velocity = *source_velocity;
+        if let Some(mut flags) = flags {
+            *flags = *source_flags;
+        }
+    }
+}
+
+//controller connections
+fn update_controller_connections(
+    session: Res<OxrSession>,
+    actions: Res<ControllerActions>,
+    profiles: Option<Res<OxrCurrentInteractionProfiles>>,
+    time: Res<Time<Real>>,
+    grips: Query<(&XrSpaceLocationFlags, Has<LeftGrip>), Or<(With<LeftGrip>, With<RightGrip>)>>,
+    mut connections: ResMut<XrControllerConnections>,
+    mut connected: EventWriter<XrControllerConnected>,
+    mut disconnected: EventWriter<XrControllerDisconnected>,
+) {
+    let now = time.elapsed();
+    let delay = connections.disconnect_delay;
+    for hand in [HandSide::Left, HandSide::Right] {
+        let (action, top_level_path) = match hand {
+            HandSide::Left => (&actions.left, "/user/hand/left"),
+            HandSide::Right => (&actions.right, "/user/hand/right"),
+        };
+        let active = match action.is_active(&session, openxr::Path::NULL) {
+            Ok(active) => active,
+            Err(err) => {
+                warn!("error while checking if the {hand:?} grip action is active: {err}");
+                false
+            }
+        };
+        // without the interaction profiles plugin only the action and the tracking are checked
+        let bound = profiles
+            .as_ref()
+            .map_or(true, |profiles| profiles.get(top_level_path).is_some());
+        let tracked = grips
+            .iter()
+            .find(|(_, is_left)| *is_left == matches!(hand, HandSide::Left))
+            .is_some_and(|(flags, _)| flags.position_tracked || flags.rotation_tracked);
+
+        let connection = match hand {
+            HandSide::Left => &mut connections.left,
+            HandSide::Right => &mut connections.right,
+        };
+        if active && bound && tracked {
+            connection.missing_since = None;
+            if !connection.connected {
+                connection.connected = true;
+                connected.send(XrControllerConnected(hand));
+            }
+        } else if connection.connected {
+            let missing_since = *connection.missing_since.get_or_insert(now);
+            if now.saturating_sub(missing_since) >= delay {
+                connection.connected = false;
+                connection.missing_since = None;
+                disconnected.send(XrControllerDisconnected(hand));
+            }
+        }
+    }
+}
+
+fn update_controller_visibility(
+    connections: Res<XrControllerConnections>,
+    mut tracked: Query<
+        (
+            &mut Visibility,
+            Has<XrTrackedLeftGrip>,
+            Has<XrTrackedLeftAim>,
+        ),
+        Or<(
+            With<XrTrackedLeftGrip>,
+            With<XrTrackedLeftAim>,
+            With<XrTrackedRightGrip>,
+            With<XrTrackedRightAim>,
+        )>,
+    >,
+) {
+    for (mut visibility, left_grip, left_aim) in &mut tracked {
+        let hand = if left_grip || left_aim {
+            HandSide::Left
+        } else {
+            HandSide::Right
+        };
+        visibility.set_if_neq(if connections.is_connected(hand) {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+}
+
+fn reset_controller_connections(
+    mut connections: ResMut<XrControllerConnections>,
+    mut disconnected: EventWriter<XrControllerDisconnected>,
+) {
+    for hand in [HandSide::Left, HandSide::Right] {
+        if connections.is_connected(hand) {
+            disconnected.send(XrControllerDisconnected(hand));
+        }
+    }
+    connections.left = default();
+    connections.right = default();
+}
+
 //tracking rig
 #[derive(Resource)]
 pub struct ControllerActions {
     pub set: openxr::ActionSet,
+    /// The left grip pose.
     pub left: openxr::Action<Posef>,
+    /// The right grip pose.
     pub right: openxr::Action<Posef>,
+    pub left_aim: openxr::Action<Posef>,
+    pub right_aim: openxr::Action<Posef>,
 }
 
 fn spawn_tracking_rig(
@@ -234,8 +506,21 @@ fn spawn_tracking_rig(
         .spawn((SpatialBundle::default(), right_space, RightGrip))
         .id();
 
+    let left_aim_space = session
+        .create_action_space(&actions.left_aim, openxr::Path::NULL, XrPose::IDENTITY)
+        .unwrap();
+    let right_aim_space = session
+        .create_action_space(&actions.right_aim, openxr::Path::NULL, XrPose::IDENTITY)
+        .unwrap();
+    let left_aim = cmds
+        .spawn((SpatialBundle::default(), left_aim_space, LeftAim))
+        .id();
+    let right_aim = cmds
+        .spawn((SpatialBundle::default(), right_aim_space, RightAim))
+        .id();
+
     cmds.entity(root.single())
-        .push_children(&[head, left, right]);
+        .push_children(&[head, left, right, left_aim, right_aim]);
 }
 
 //bindings
@@ -254,6 +539,16 @@ pub fn suggest_action_bindings(
         interaction_profile: "/interaction_profiles/oculus/touch_controller".into(),
         bindings: vec!["/user/hand/right/input/grip/pose".into()],
     });
+    bindings.send(OxrSuggestActionBinding {
+        action: actions.left_aim.as_raw(),
+        interaction_profile: "/interaction_profiles/oculus/touch_controller".into(),
+        bindings: vec!["/user/hand/left/input/aim/pose".into()],
+    });
+    bindings.send(OxrSuggestActionBinding {
+        action: actions.right_aim.as_raw(),
+        interaction_profile: "/interaction_profiles/oculus/touch_controller".into(),
+        bindings: vec!["/user/hand/right/input/aim/pose".into()],
+    });
 }
 
 fn sync_actions(actions: Res<ControllerActions>, mut sync: EventWriter<OxrSyncActionSet>) {
@@ -272,6 +567,18 @@ fn create_actions(instance: Res<OxrInstance>, mut cmds: Commands) {
     let right = set
         .create_action("right_pose", "Right Hand Grip Pose", &[])
         .unwrap();
+    let left_aim = set
+        .create_action("left_aim_pose", "Left Hand Aim Pose", &[])
+        .unwrap();
+    let right_aim = set
+        .create_action("right_aim_pose", "Right Hand Aim Pose", &[])
+        .unwrap();
 
-    cmds.insert_resource(ControllerActions { set, left, right })
+    cmds.insert_resource(ControllerActions {
+        set,
+        left,
+        right,
+        left_aim,
+        right_aim,
+    })
 }